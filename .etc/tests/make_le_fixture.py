@@ -0,0 +1,37 @@
+"""Writes le_fixture.nbt, little-endian NBT for testing the Rust encoder against.
+
+Only uses the struct module, so the bytes don't depend on the code under test.
+"""
+
+import struct
+
+
+def string(val):
+    data = val.encode("utf-8")
+    return struct.pack("<H", len(data)) + data
+
+
+def entry(tag, name, payload):
+    return struct.pack("<b", tag) + string(name) + payload
+
+
+ints = [0x01020304, -2, 2**31 - 1, -(2**31)] + list(range(-8, 13))
+longs = [0x0102030405060708, -2, 2**63 - 1, -(2**63)] + list(range(-3, 3))
+
+root = b"".join([
+    entry(1, "byte", struct.pack("<b", -7)),
+    entry(2, "short", struct.pack("<h", 0x1234)),
+    entry(3, "int", struct.pack("<i", -123456789)),
+    entry(4, "long", struct.pack("<q", 0x0102030405060708)),
+    entry(5, "float", struct.pack("<f", 1.5)),
+    entry(6, "double", struct.pack("<d", -2.25)),
+    entry(7, "bytes", struct.pack("<i3b", 3, 1, 2, -1)),
+    entry(8, "string", string("héllo")),
+    entry(9, "list", struct.pack("<bi", 8, 2) + string("a") + string("b")),
+    entry(10, "nested", entry(8, "name", string("inner")) + b"\x00"),
+    entry(11, "ints", struct.pack("<i%di" % len(ints), len(ints), *ints)),
+    entry(12, "longs", struct.pack("<i%dq" % len(longs), len(longs), *longs)),
+]) + b"\x00"
+
+with open("le_fixture.nbt", "wb") as file:
+    file.write(entry(10, "python", root))
@@ -0,0 +1,10 @@
+#![no_main]
+
+use ferrumc_nbt::{Nbt, NbtValue};
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = data;
+    let _ = Nbt::<NbtValue>::decode(&mut reader, &NetDecodeOpts::None);
+});
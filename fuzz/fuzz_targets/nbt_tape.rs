@@ -0,0 +1,9 @@
+#![no_main]
+
+use ferrumc_nbt::NbtTape;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut tape = NbtTape::new(data);
+    let _ = tape.parse();
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+//! Decodes every `#[packet]` struct registered in `ferrumc-net`.
+//!
+//! Input layout: `[connection state][packet id][payload...]`
+
+use ferrumc_net::connection::ConnectionState;
+use ferrumc_net::debug_packet;
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let [state, packet_id, payload @ ..] = data else {
+        return;
+    };
+
+    let Ok(state) = ConnectionState::try_from(*state) else {
+        return;
+    };
+
+    let _ = debug_packet(*packet_id, &state, &mut Cursor::new(payload));
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use ferrumc_net::packets::incoming::packet_skeleton::PacketSkeleton;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = data;
+    let _ = futures::executor::block_on(PacketSkeleton::new(&mut reader, false));
+});
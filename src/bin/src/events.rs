@@ -13,6 +13,8 @@ pub use ferrumc_net::packets::incoming::server_bound_plugin_message::LoginPlugin
 pub use ferrumc_net::packets::incoming::chat_message::PlayerAsyncChatEvent;
 pub use ferrumc_net::connection::PlayerDisconnectEvent;
 
+pub use ferrumc_core::transform::{position::Position, movement::MovementViolation};
+
 use std::sync::{Arc, RwLock};
 
 /// A event that you can read or write and access it after a event has triggered.
@@ -108,8 +110,28 @@ pub struct PlayerStartLoginEvent {
 /// This event is triggered right after the client acknowledges the configuration state.
 ///
 /// This event takes place after the [LoginGamePacket](ferrumc_net::packets::outgoing::login_play::LoginPlayPacket) is sent and the [Profile](crate::Profile) component is initialized.
+/// It's triggered again whenever the player finishes a re-configuration, since the client re-joins the game afterwards.
 #[derive(Event, Clone)]
 pub struct PlayerJoinGameEvent {
     /// The entity that this event was fired for.
     pub entity: Entity,
 }
+
+/// This event is triggered when a movement sent by a player fails server-side validation.
+///
+/// The movement is never applied. By default the player is also moved back to `last_valid`,
+/// cancelling this event (by returning [EventsError::Cancelled]) skips that, e.g. for a listener that teleports the player somewhere else.
+#[derive(Event, Clone)]
+pub struct PlayerMovementViolationEvent {
+    /// The entity that this event was fired for.
+    pub entity: Entity,
+
+    /// Why the movement was rejected.
+    pub violation: MovementViolation,
+
+    /// The position the player tried to move to.
+    pub attempted: Position,
+
+    /// The last position of the player that was accepted.
+    pub last_valid: Position,
+}
@@ -1,6 +1,9 @@
 // Security
 #![forbid(unsafe_code)]
 
+// The packet handlers and systems refer to this crate by name, like plugins would.
+extern crate self as ferrumc;
+
 use std::sync::Arc;
 use lazy_static::lazy_static;
 
@@ -36,6 +39,13 @@ pub mod text {
 /// Event API
 pub mod events;
 
+pub mod errors;
+mod packet_handlers;
+pub mod systems;
+mod velocity;
+
+pub type Result<T> = std::result::Result<T, errors::BinaryError>;
+
 /// INTERNAL
 pub mod internal {
     use super::*;
@@ -4,22 +4,15 @@
 
 #![feature(slice_as_chunks)]
 
-use ferrumc::{ServerState, get_global_config};
+use ferrumc::{ServerState, get_global_config, Result};
+use ferrumc::systems::definition;
 use ferrumc_ecs::Universe;
 use ferrumc_net::server::create_server_listener;
 use std::sync::Arc;
-use systems::definition;
 use tracing::{error, info, trace};
 use std::time::Duration;
 use rand::seq::IndexedRandom;
 
-pub(crate) mod errors;
-mod packet_handlers;
-mod systems;
-mod velocity;
-
-pub type Result<T> = std::result::Result<T, errors::BinaryError>;
-
 #[tokio::main]
 async fn main() {
     ferrumc_logging::init_logging();
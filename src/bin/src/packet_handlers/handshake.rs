@@ -1,5 +1,5 @@
 use ferrumc_macros::event_handler;
-use ferrumc_net::connection::ConnectionState;
+use ferrumc_net::connection::{ConnectionState, StreamWriter};
 use ferrumc_net::errors::NetError::{Packet};
 use ferrumc_net::errors::{NetError, PacketError};
 use ferrumc_net::packets::incoming::handshake::HandshakeEvent;
@@ -18,24 +18,24 @@ async fn handle_handshake(
 
     // set connection state to handshake
     let entity = handshake_event.conn_id;
-    let Ok(mut connection_state) = entity
-        .get_mut::<ConnectionState>(state) else {
+    let Ok(mut writer) = entity
+        .get_mut::<StreamWriter>(state.clone()) else {
         error!("Failed to get connection state");
         return Err(NetError::ECSError(ECSError::ComponentNotFound));
     };
 
     trace!(
         "conn state: {} -> {}",
-        connection_state.as_str(),
+        writer.state().as_str(),
         handshake.next_state.val
     );
 
     let next_state = handshake.next_state.val as u8;
-    *connection_state = match next_state {
+    writer.set_state(match next_state {
         1 => ConnectionState::Status,
         2 => ConnectionState::Login,
         s => return Err(Packet(PacketError::InvalidState(s))),
-    };
+    });
 
     Ok(handshake_event)
 }
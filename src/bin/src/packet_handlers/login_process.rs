@@ -3,6 +3,7 @@ use ferrumc::events::{event_handler, Event, PlayerStartLoginEvent, PlayerJoinGam
 use ferrumc_net::errors::NetError;
 use ferrumc::{ConnectionState, StreamWriter, GameProfile, Profile};
 use ferrumc_net::packets::incoming::ack_finish_configuration::AckFinishConfigurationEvent;
+use ferrumc_net::packets::incoming::acknowledge_configuration::AcknowledgeConfigurationEvent;
 use ferrumc_net::packets::incoming::login_acknowledged::LoginAcknowledgedEvent;
 use ferrumc_net::packets::incoming::login_start::LoginStartEvent;
 use ferrumc_net::packets::incoming::server_bound_known_packs::ServerBoundKnownPacksEvent;
@@ -20,6 +21,14 @@ use ferrumc_net::packets::outgoing::synchronize_player_position::SynchronizePlay
 use ferrumc_net::packets::outgoing::finish_configuration::FinishConfigurationPacket;
 use ferrumc_net::packets::outgoing::player_info_update::{PlayerInfoUpdatePacket, PlayerInfo};
 use ferrumc::events::EventsError;
+use ferrumc_core::transform::movement::{MovementAbilities, MovementState};
+use ferrumc_core::transform::position::Position;
+use ferrumc_net::packets::outgoing::set_default_spawn_position::DEFAULT_SPAWN_POSITION;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use ferrumc_net::packets::generated::outgoing::SetCenterChunkPacket;
+use ferrumc_net::utils::chunk_sender::{chunks_around, ChunkSender};
+use ferrumc_net::utils::chunk_tracker::{view_distance, ChunkTracker};
+use ferrumc_net::packets::incoming::client_information::ClientInformation;
 use std::sync::Arc;
 
 #[event_handler]
@@ -64,16 +73,32 @@ async fn handle_login_acknowledged(
 ) -> Result<LoginAcknowledgedEvent, NetError> {
     trace!("Handling Login Acknowledged event");
 
-    //Set the connection State to Configuration
-    let mut connection_state = state
-        .universe
-        .get_mut::<ConnectionState>(login_acknowledged_event.conn_id)?;
+    enter_configuration(login_acknowledged_event.conn_id, &state).await?;
+
+    Ok(login_acknowledged_event)
+}
+
+#[event_handler]
+async fn handle_acknowledge_configuration(
+    acknowledge_configuration_event: AcknowledgeConfigurationEvent,
+    state: GlobalState,
+) -> Result<AcknowledgeConfigurationEvent, NetError> {
+    trace!("Handling Acknowledge Configuration event");
+
+    // Started with `StreamWriter::start_reconfiguration`, the client goes through the whole
+    // configuration again and re-joins the game once it's finished.
+    enter_configuration(acknowledge_configuration_event.conn_id, &state).await?;
 
-    *connection_state = ConnectionState::Configuration;
+    Ok(acknowledge_configuration_event)
+}
 
+/// Switches the connection to the configuration state and starts the configuration.
+async fn enter_configuration(conn_id: usize, state: &GlobalState) -> Result<(), NetError> {
+    //Set the connection State to Configuration
     let mut writer = state
         .universe
-        .get_mut::<StreamWriter>(login_acknowledged_event.conn_id)?;
+        .get_mut::<StreamWriter>(conn_id)?;
+    writer.set_state(ConnectionState::Configuration);
 
     let server_brand = ConfigurationPluginMessagePacket::new(String::from("minecraft:brand"), String::from("FerrumC"));
     writer.send_packet(&server_brand, &NetEncodeOpts::WithLength).await?;
@@ -82,7 +107,7 @@ async fn handle_login_acknowledged(
     let client_bound_known_packs = ClientBoundKnownPacksPacket::new();
     writer.send_packet(&client_bound_known_packs, &NetEncodeOpts::WithLength).await?;
 
-    Ok(login_acknowledged_event)
+    Ok(())
 }
 
 #[event_handler]
@@ -97,7 +122,7 @@ async fn handle_server_bound_known_packs(
         .get_mut::<StreamWriter>(server_bound_known_packs_event.conn_id)?;
 
     let registry_packets = get_registry_packets();
-    writer.send_packet(&registry_packets, &NetEncodeOpts::None).await?;
+    writer.send_raw(&registry_packets, &NetEncodeOpts::None).await?;
     
     writer.send_packet(&FinishConfigurationPacket::new(), &NetEncodeOpts::WithLength).await?;
 
@@ -113,20 +138,16 @@ async fn handle_ack_finish_configuration(
 
     let conn_id = ack_finish_configuration_event.conn_id;
 
-    let mut conn_state = state
-        .universe
-        .get_mut::<ConnectionState>(conn_id)?;
-
-    *conn_state = ConnectionState::Play;
-
     let mut writer = state
         .universe
         .get_mut::<StreamWriter>(conn_id)?;
+    writer.set_state(ConnectionState::Play);
 
     writer.send_packet(&LoginPlayPacket::new(conn_id), &NetEncodeOpts::WithLength).await?;
     writer.send_packet(&SetDefaultSpawnPositionPacket::default(), &NetEncodeOpts::WithLength).await?;
-    writer.send_packet(&SynchronizePlayerPositionPacket::default(), &NetEncodeOpts::WithLength).await?;
+    send_spawn_position(conn_id, Arc::clone(&state), &mut writer).await?;
     writer.send_packet(&GameEventPacket::start_waiting_for_level_chunks(), &NetEncodeOpts::WithLength).await?;
+    queue_initial_chunks(conn_id, Arc::clone(&state), &mut writer).await?;
 
     if let Some(profile) = &state
         .universe
@@ -150,6 +171,64 @@ async fn handle_ack_finish_configuration(
     Ok(ack_finish_configuration_event)
 }
 
+async fn send_spawn_position(conn_id: usize, state: GlobalState, writer: &mut ComponentRefMut<'_, StreamWriter>) -> Result<(), NetError> {
+    // Players coming back from a re-configuration keep their position.
+    let spawn = match state.universe.get::<Position>(conn_id) {
+        Ok(position) => *position,
+        Err(_) => Position::new(
+            DEFAULT_SPAWN_POSITION.x as f64,
+            DEFAULT_SPAWN_POSITION.y as f64,
+            DEFAULT_SPAWN_POSITION.z as f64,
+        ),
+    };
+
+    // The spawn is a teleport as well, movements are ignored until the client confirms it.
+    let mut movement_state = MovementState::new(spawn);
+    let teleport_id = movement_state.teleport(spawn);
+
+    state.universe.add_component::<Position>(conn_id, spawn)?;
+    state.universe.add_component::<MovementState>(conn_id, movement_state)?;
+    // LoginPlayPacket puts every player in creative mode, so everyone may fly.
+    state.universe.add_component::<MovementAbilities>(conn_id, MovementAbilities {
+        may_fly: true,
+        ..Default::default()
+    })?;
+
+    let sync_position = SynchronizePlayerPositionPacket::new(
+        spawn.x,
+        spawn.y,
+        spawn.z,
+        0.0,
+        0.0,
+        0,
+        VarInt::new(teleport_id),
+    );
+    writer.send_packet(&sync_position, &NetEncodeOpts::WithLength).await?;
+
+    Ok(())
+}
+
+/// Centers the client's view on the player and queues the chunks they can see, which are sent in
+/// batches every tick.
+async fn queue_initial_chunks(conn_id: usize, state: GlobalState, writer: &mut ComponentRefMut<'_, StreamWriter>) -> Result<(), NetError> {
+    let (chunk_x, chunk_z) = state.universe.get::<Position>(conn_id)?.chunk();
+    writer.send_packet(&SetCenterChunkPacket { chunk_x, chunk_z }, &NetEncodeOpts::WithLength).await?;
+
+    let client_view_distance = state
+        .universe
+        .get::<ClientInformation>(conn_id)
+        .ok()
+        .map(|information| information.view_distance);
+    let view_distance = view_distance(client_view_distance);
+
+    let mut chunk_sender = ChunkSender::new();
+    chunk_sender.queue(chunks_around((chunk_x, chunk_z), view_distance));
+    state.universe.add_component::<ChunkSender>(conn_id, chunk_sender)?;
+    state.universe.add_component::<ChunkTracker>(conn_id, ChunkTracker::new((chunk_x, chunk_z), view_distance))?;
+
+    Ok(())
+}
+
 async fn send_keep_alive(conn_id: usize, state: GlobalState, writer: &mut ComponentRefMut<'_, StreamWriter>) -> Result<(), NetError> {
     let keep_alive_packet = KeepAlivePacket::default();
     writer.send_packet(&keep_alive_packet, &NetEncodeOpts::WithLength).await?;
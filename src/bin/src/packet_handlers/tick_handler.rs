@@ -1,12 +1,22 @@
 use ferrumc_macros::event_handler;
-use ferrumc_net::connection::ConnectionState;
 use ferrumc_net::connection::StreamWriter;
 use ferrumc_net::errors::NetError;
+use ferrumc_core::transform::position::Position;
+use ferrumc_ecs::ECSResult;
+use ferrumc_net::packets::generated::outgoing::{
+    ChunkBatchFinishedPacket, ChunkBatchStartPacket, SetCenterChunkPacket, UnloadChunkPacket,
+};
+use ferrumc_net::packets::incoming::client_information::ClientInformation;
 use ferrumc_net::packets::outgoing::update_time::TickEvent;
 use ferrumc_net::packets::outgoing::update_time::UpdateTimePacket;
+use ferrumc_net::utils::chunk_cache::{ChunkCache, EncodedChunks};
+use ferrumc_net::utils::chunk_sender::ChunkSender;
+use ferrumc_net::utils::chunk_tracker::{view_distance, ChunkTracker};
 use ferrumc_net::GlobalState;
-use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::encode::pool::BufferPool;
+use ferrumc_net_codec::encode::NetEncodeOpts;
 use futures::StreamExt;
+use std::collections::HashSet;
 use tracing::error;
 
 #[event_handler]
@@ -18,20 +28,16 @@ async fn handle_tick(event: TickEvent, state: GlobalState) -> Result<TickEvent,
     ///////
 
     let packet = UpdateTimePacket::new(event.tick, event.tick % 24000);
-    let packet = {
-        let mut buffer = Vec::new();
-        packet.encode(&mut buffer, &NetEncodeOpts::WithLength)?;
-        buffer
-    };
+    let packet = BufferPool::global().encode(&packet, &NetEncodeOpts::WithLength)?;
 
     let query = state
         .universe
-        .query::<(&mut StreamWriter, &ConnectionState)>()
+        .query::<&mut StreamWriter>()
         .into_entities()
         .into_iter()
         .filter_map(|entity| {
-            let conn_state = state.universe.get::<ConnectionState>(entity).ok()?;
-            if matches!(*conn_state, ConnectionState::Play) {
+            let writer = state.universe.get::<StreamWriter>(entity).ok()?;
+            if writer.is_playing() {
                 Some(entity)
             } else {
                 None
@@ -39,15 +45,12 @@ async fn handle_tick(event: TickEvent, state: GlobalState) -> Result<TickEvent,
         })
         .collect::<Vec<_>>();
 
-    tokio::spawn(
-        futures::stream::iter(query.into_iter())
+    tokio::spawn(async move {
+        let (_, packet) = futures::stream::iter(query.into_iter())
             .fold((state, packet), move |(state, packet), entity| {
                 async move {
                     if let Ok(mut writer) = state.universe.get_mut::<StreamWriter>(entity) {
-                        if let Err(e) = writer
-                            .send_packet(&packet.as_slice(), &NetEncodeOpts::None)
-                            .await
-                        {
+                        if let Err(e) = writer.send_bytes(packet.clone()).await {
                             error!("Error sending update_time packet: {}", e);
                         }
                     }
@@ -55,6 +58,141 @@ async fn handle_tick(event: TickEvent, state: GlobalState) -> Result<TickEvent,
                     (state, packet)
                 }
             })
-    );
+            .await;
+
+        BufferPool::global().recycle(packet);
+    });
+    Ok(event)
+}
+
+#[event_handler]
+async fn handle_chunk_batches(event: TickEvent, state: GlobalState) -> Result<TickEvent, NetError> {
+    let players = state
+        .universe
+        .query::<(&mut ChunkSender, &mut StreamWriter)>()
+        .into_entities();
+
+    let mut updates = Vec::new();
+    for entity in players {
+        let playing = state
+            .universe
+            .get::<StreamWriter>(entity)
+            .is_ok_and(|writer| writer.is_playing());
+        if !playing {
+            continue;
+        }
+
+        let moved = match follow_player(entity, &state) {
+            Ok(moved) => moved,
+            Err(_) => continue,
+        };
+        let batch = match state.universe.get_mut::<ChunkSender>(entity) {
+            Ok(mut chunk_sender) => chunk_sender.next_batch(),
+            Err(_) => continue,
+        };
+        if moved.is_none() && batch.is_empty() {
+            continue;
+        }
+        updates.push((entity, moved, batch));
+    }
+    if updates.is_empty() {
+        return Ok(event);
+    }
+
+    // Every chunk is generated and encoded once for all players that need it, on the blocking
+    // thread pool, and cached chunks aren't generated again.
+    let chunks = updates.iter().flat_map(|(_, _, batch)| batch.iter().copied());
+    let packets = ChunkCache::global().get_or_generate(chunks).await?;
+
+    // Sent from here rather than a task, so every player gets their packets in the order of
+    // the ticks: a newer center never arrives before an older one, nor an unload before the
+    // data of the chunk it unloads.
+    for (entity, moved, batch) in updates {
+        if let Err(e) = send_chunks(entity, moved, &batch, &packets, &state).await {
+            error!("Error sending chunks to {}: {}", entity, e);
+        }
+    }
+
     Ok(event)
 }
+
+/// The player entered another chunk, or their view distance changed.
+struct ViewMove {
+    center: (i32, i32),
+    /// The chunks out of view that the client has been sent.
+    unload: Vec<(i32, i32)>,
+}
+
+/// Moves the chunks the player can see along with them.
+fn follow_player(entity: usize, state: &GlobalState) -> ECSResult<Option<ViewMove>> {
+    let center = state.universe.get::<Position>(entity)?.chunk();
+    let client_view_distance = state
+        .universe
+        .get::<ClientInformation>(entity)
+        .ok()
+        .map(|information| information.view_distance);
+
+    let Some(changes) = state
+        .universe
+        .get_mut::<ChunkTracker>(entity)?
+        .update(center, view_distance(client_view_distance))
+    else {
+        return Ok(None);
+    };
+
+    let mut chunk_sender = state.universe.get_mut::<ChunkSender>(entity)?;
+    // Chunks that are still queued don't have to be unloaded, they just aren't sent. Chunks in
+    // batches that aren't acknowledged yet are in flight and always unloaded, unloading a chunk
+    // the client doesn't have is harmless but keeping one it does have isn't.
+    let in_flight = chunk_sender.in_flight().copied().collect::<HashSet<_>>();
+    let unsent = chunk_sender
+        .pending()
+        .filter(|chunk| !in_flight.contains(chunk))
+        .copied()
+        .collect::<HashSet<_>>();
+    let out_of_view = changes.unload.iter().copied().collect::<HashSet<_>>();
+    chunk_sender.retain(|chunk| !out_of_view.contains(chunk));
+    chunk_sender.queue(changes.load);
+    chunk_sender.sort_nearest_first(center);
+
+    Ok(Some(ViewMove {
+        center,
+        unload: changes
+            .unload
+            .into_iter()
+            .filter(|chunk| !unsent.contains(chunk))
+            .collect(),
+    }))
+}
+
+async fn send_chunks(
+    entity: usize,
+    moved: Option<ViewMove>,
+    batch: &[(i32, i32)],
+    packets: &EncodedChunks,
+    state: &GlobalState,
+) -> Result<(), NetError> {
+    let mut writer = state.universe.get_mut::<StreamWriter>(entity)?;
+    if let Some(ViewMove { center: (chunk_x, chunk_z), unload }) = moved {
+        writer.send_packet(&SetCenterChunkPacket { chunk_x, chunk_z }, &NetEncodeOpts::WithLength).await?;
+        for (chunk_x, chunk_z) in unload {
+            writer.send_packet(&UnloadChunkPacket { chunk_z, chunk_x }, &NetEncodeOpts::WithLength).await?;
+        }
+    }
+
+    if batch.is_empty() {
+        return Ok(());
+    }
+    writer.send_packet(&ChunkBatchStartPacket {}, &NetEncodeOpts::WithLength).await?;
+    for chunk in batch {
+        if let Some(packet) = packets.get(chunk) {
+            writer.send_bytes(packet.clone()).await?;
+        }
+    }
+    let finished = ChunkBatchFinishedPacket {
+        batch_size: batch.len() as i32,
+    };
+    writer.send_packet(&finished, &NetEncodeOpts::WithLength).await?;
+
+    Ok(())
+}
@@ -1,9 +1,36 @@
+use std::sync::Arc;
+use ferrumc::events::{Event, PlayerMovementViolationEvent};
+use ferrumc_config::statics::get_global_config;
+use ferrumc_core::transform::movement::{MovementAbilities, MovementLimits, MovementState, MovementViolation};
 use ferrumc_core::transform::position::Position;
+use ferrumc_events::errors::EventsError;
 use ferrumc_macros::event_handler;
+use ferrumc_net::connection::StreamWriter;
 use ferrumc_net::errors::NetError;
+use ferrumc_net::packets::incoming::confirm_teleportation::ConfirmTeleportationEvent;
 use ferrumc_net::packets::incoming::set_player_position::SetPlayerPositionEvent;
+use ferrumc_net::packets::outgoing::synchronize_player_position::SynchronizePlayerPositionPacket;
 use ferrumc_net::GlobalState;
 use ferrumc_net::utils::ecs_helpers::EntityExt;
+use ferrumc_net_codec::encode::NetEncodeOpts;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use tracing::{debug, trace};
+
+fn movement_limits() -> MovementLimits {
+    let config = &get_global_config().movement;
+
+    MovementLimits {
+        max_horizontal_speed: config.max_horizontal_speed,
+        max_vertical_speed: config.max_vertical_speed,
+        max_fall_speed: config.max_fall_speed,
+        flying_multiplier: config.flying_multiplier,
+        elytra_multiplier: config.elytra_multiplier,
+        max_buffered_ticks: config.max_buffered_ticks,
+        teleport_grace_ticks: config.teleport_grace_ticks,
+        teleport_grace_margin: config.teleport_grace_margin,
+        world_border: config.world_border,
+    }
+}
 
 #[event_handler]
 async fn handle_player_move(
@@ -13,13 +40,89 @@ async fn handle_player_move(
     let new_position = &event.data;
     let conn_id = event.conn_id;
 
-    let mut position = conn_id.get_mut::<Position>(state)?;
-    
-    *position = Position::new(
+    let attempted = Position::new(
         new_position.x,
         new_position.feet_y,
         new_position.z
     );
-    
+
+    if get_global_config().movement.enabled {
+        let result = {
+            let mut abilities = conn_id.get_mut::<MovementAbilities>(Arc::clone(&state))?;
+            if new_position.on_ground {
+                abilities.gliding = false;
+            }
+
+            let mut movement = conn_id.get_mut::<MovementState>(Arc::clone(&state))?;
+            movement
+                .validate(&attempted, &abilities, &movement_limits())
+                .map_err(|violation| (violation, movement.last_valid))
+        };
+
+        if let Err((violation, last_valid)) = result {
+            // The client is still moving from before the teleport, just drop the movement.
+            if violation == MovementViolation::AwaitingTeleport {
+                trace!("Ignoring movement from {} while awaiting a teleport", conn_id);
+                return Ok(event);
+            }
+
+            debug!("Player {} failed movement validation: {:?}", conn_id, violation);
+
+            match PlayerMovementViolationEvent::trigger(PlayerMovementViolationEvent {
+                entity: conn_id,
+                violation,
+                attempted,
+                last_valid,
+            }, Arc::clone(&state)).await {
+                Ok(()) => rubber_band(conn_id, last_valid, state).await?,
+                // A listener handles correcting the player itself.
+                Err(NetError::EventsError(EventsError::Cancelled)) => {}
+                Err(e) => return Err(e),
+            }
+
+            return Ok(event);
+        }
+    }
+
+    let mut position = conn_id.get_mut::<Position>(state)?;
+    *position = attempted;
+
+    Ok(event)
+}
+
+/// Teleports the player back to `position`.
+async fn rubber_band(conn_id: usize, position: Position, state: GlobalState) -> Result<(), NetError> {
+    let teleport_id = conn_id
+        .get_mut::<MovementState>(Arc::clone(&state))?
+        .teleport(position);
+
+    let packet = SynchronizePlayerPositionPacket::new(
+        position.x,
+        position.y,
+        position.z,
+        0.0,
+        0.0,
+        // keep the client's rotation
+        0b11000,
+        VarInt::new(teleport_id),
+    );
+
+    conn_id
+        .get_mut::<StreamWriter>(state)?
+        .send_packet(&packet, &NetEncodeOpts::WithLength)
+        .await
+}
+
+#[event_handler]
+async fn handle_confirm_teleportation(
+    event: ConfirmTeleportationEvent,
+    state: GlobalState,
+) -> Result<ConfirmTeleportationEvent, NetError> {
+    let teleport_id = event.data.teleport_id.val;
+
+    if !event.conn_id.get_mut::<MovementState>(state)?.confirm_teleport(teleport_id) {
+        trace!("Player {} confirmed unknown teleport {}", event.conn_id, teleport_id);
+    }
+
     Ok(event)
-}
\ No newline at end of file
+}
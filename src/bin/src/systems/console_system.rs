@@ -0,0 +1,95 @@
+use std::io::BufRead;
+use std::net::IpAddr;
+use std::sync::Arc;
+use async_trait::async_trait;
+use ferrumc_net::capture::{start_capturing, stop_capturing, CaptureFilter};
+use ferrumc_net::{GlobalState, ServerState};
+use tokio::sync::{mpsc, Notify};
+use tracing::{debug, info, warn};
+use crate::systems::definition::System;
+
+const USAGE: &str = "Commands: capture <start|stop> <all|entity id|ip address>";
+
+/// Runs the commands typed into the server console.
+#[derive(Default)]
+pub struct ConsoleSystem {
+    shutdown: Notify,
+}
+
+#[async_trait]
+impl System for ConsoleSystem {
+    async fn start(self: Arc<Self>, state: GlobalState) {
+        // Reading stdin can't be cancelled, so it's done on a thread of its own that the runtime
+        // doesn't wait for when shutting down.
+        let (sender, mut lines) = mpsc::unbounded_channel();
+        let thread = std::thread::Builder::new()
+            .name("console".to_string())
+            .spawn(move || {
+                for line in std::io::stdin().lock().lines() {
+                    let Ok(line) = line else { break };
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+        if let Err(e) = thread {
+            warn!("Failed to start reading the console: {}", e);
+            return;
+        }
+
+        tokio::select! {
+            _ = async move {
+                while let Some(line) = lines.recv().await {
+                    if !line.trim().is_empty() {
+                        info!("{}", run_command(&state, &line));
+                    }
+                }
+            } => {},
+            _ = self.shutdown.notified() => {}
+        }
+    }
+
+    async fn stop(self: Arc<Self>, _state: GlobalState) {
+        debug!("Stopping console system...");
+        self.shutdown.notify_one();
+    }
+
+    fn name(&self) -> &'static str {
+        "console"
+    }
+}
+
+/// Runs a console command and returns what to tell the operator.
+///
+/// `capture start <filter>` and `capture stop <filter>` start and stop packet captures at runtime,
+/// the filter is `all`, the entity id of a connection or the ip address of a client.
+pub fn run_command(state: &ServerState, line: &str) -> String {
+    let args = line.split_whitespace().collect::<Vec<_>>();
+    match args.as_slice() {
+        ["capture", action @ ("start" | "stop"), filter] => {
+            let Some(filter) = parse_capture_filter(filter) else {
+                return format!("Unknown connection {filter}, expected all, an entity id or an ip address");
+            };
+
+            if *action == "start" {
+                let paths = start_capturing(state, filter);
+                format!("Started capturing {} connection(s) matching {:?}: {:?}", paths.len(), filter, paths)
+            } else {
+                let paths = stop_capturing(state, filter);
+                format!("Stopped capturing {} connection(s) matching {:?}: {:?}", paths.len(), filter, paths)
+            }
+        }
+        _ => USAGE.to_string(),
+    }
+}
+
+fn parse_capture_filter(filter: &str) -> Option<CaptureFilter> {
+    if filter == "all" {
+        return Some(CaptureFilter::All);
+    }
+    filter
+        .parse::<usize>()
+        .map(CaptureFilter::Entity)
+        .or_else(|_| filter.parse::<IpAddr>().map(CaptureFilter::Address))
+        .ok()
+}
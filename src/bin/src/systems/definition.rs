@@ -1,3 +1,4 @@
+use crate::systems::console_system::ConsoleSystem;
 use crate::systems::keep_alive_system::KeepAliveSystem;
 use crate::systems::tcp_listener_system::TcpListenerSystem;
 use crate::systems::ticking_system::TickingSystem;
@@ -27,6 +28,7 @@ pub fn create_systems() -> Vec<Arc<dyn System>> {
         Arc::new(TcpListenerSystem::new()),
         Arc::new(KeepAliveSystem::new()),
         Arc::new(TickingSystem::new()),
+        Arc::new(ConsoleSystem::default()),
     ]
 }
 
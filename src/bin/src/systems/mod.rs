@@ -1,4 +1,5 @@
-pub(crate) mod definition;
+pub mod definition;
+pub mod console_system;
 
 mod tcp_listener_system;
 mod keep_alive_system;
@@ -6,7 +6,7 @@ use ferrumc_net::GlobalState;
 use crate::systems::definition::System;
 use crate::Result;
 use tokio::sync::Notify;
-use ferrumc::{ConnectionState, StreamWriter};
+use ferrumc::StreamWriter;
 use futures::StreamExt;
 
 pub struct TcpListenerSystem {
@@ -31,9 +31,9 @@ impl System for TcpListenerSystem {
         debug!("Stopping TCP listener system...");
 
         tokio::spawn(async move {
-            futures::stream::iter(state.universe.query::<(&mut StreamWriter, &ConnectionState)>())
-                .for_each_concurrent(None, |(mut writer, conn_state)| async move {
-                    writer.kick(&conn_state, "Server Closed").await.unwrap();
+            futures::stream::iter(state.universe.query::<&mut StreamWriter>())
+                .for_each_concurrent(None, |mut writer| async move {
+                    writer.kick("Server Closed").await.unwrap();
                 })
                 .await;
 
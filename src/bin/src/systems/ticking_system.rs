@@ -2,12 +2,17 @@ use std::sync::Arc;
 use crate::systems::definition::System;
 use async_trait::async_trait;
 use ferrumc_events::infrastructure::Event;
+use ferrumc_config::statics::get_global_config;
+use ferrumc_net::connection::StreamWriter;
+use ferrumc_net::packets::outgoing::tick_times::{TickTimes, TickTimesSubscriber, TICK_TIMES_BATCH};
 use ferrumc_net::packets::outgoing::update_time::TickEvent;
 use ferrumc_net::GlobalState;
+use ferrumc_net_codec::encode::pool::BufferPool;
+use ferrumc_net_codec::encode::NetEncodeOpts;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::time::Instant;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 pub struct TickingSystem {
     killed: AtomicBool,
@@ -24,8 +29,10 @@ impl System for TickingSystem {
     async fn start(self: Arc<Self>, state: GlobalState) {
         // TODO game time must be loaded from a file
         let mut tick = 0;
+        let mut tick_times = Vec::with_capacity(TICK_TIMES_BATCH);
         while !self.killed.load(Ordering::Relaxed) {
-            let required_end = Instant::now() + Duration::from_millis(50);
+            let start = Instant::now();
+            let required_end = start + Duration::from_millis(50);
             // TODO handle error
             let res = TickEvent::trigger(TickEvent::new(tick), state.clone()).await;
 
@@ -34,6 +41,13 @@ impl System for TickingSystem {
             if res.is_err() {
                 debug!("error: {:?}", res);
             }
+
+            if get_global_config().debug.tick_times {
+                tick_times.push(start.elapsed().as_micros() as u64);
+                if tick_times.len() >= TICK_TIMES_BATCH {
+                    send_tick_times(&state, std::mem::take(&mut tick_times));
+                }
+            }
             let now = Instant::now();
             if required_end > now {
                 tokio::time::sleep(required_end - now).await;
@@ -55,3 +69,39 @@ impl System for TickingSystem {
         "ticking"
     }
 }
+
+/// Sends the durations of the last ticks to everyone that registered the tick times channel.
+fn send_tick_times(state: &GlobalState, samples: Vec<u64>) {
+    let packet = match BufferPool::global().encode(&TickTimes::new(samples).into_packet(), &NetEncodeOpts::WithLength) {
+        Ok(packet) => packet,
+        Err(e) => {
+            error!("Error encoding tick times: {}", e);
+            return;
+        }
+    };
+
+    let subscribers = state
+        .universe
+        .query::<(&mut StreamWriter, &TickTimesSubscriber)>()
+        .into_entities()
+        .into_iter()
+        // Players register the channel during the configuration
+        .filter(|entity| {
+            state.universe.get::<StreamWriter>(*entity)
+                .is_ok_and(|writer| writer.is_playing())
+        })
+        .collect::<Vec<_>>();
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        for entity in subscribers {
+            if let Ok(mut writer) = state.universe.get_mut::<StreamWriter>(entity) {
+                if let Err(e) = writer.send_bytes(packet.clone()).await {
+                    error!("Error sending tick times: {}", e);
+                }
+            }
+        }
+
+        BufferPool::global().recycle(packet);
+    });
+}
@@ -0,0 +1,221 @@
+//! Inspects and replays packet captures written by the server.
+//!
+//! ```text
+//! ferrumc-capture decode <capture>
+//! ferrumc-capture replay <capture> [address] [--speed <factor>]
+//! ferrumc-capture corpus <capture> <fuzz directory>
+//! ```
+//!
+//! `decode` pretty-prints every packet of a capture, incoming packets are decoded with the
+//! packet structs registered in `ferrumc-net`.
+//!
+//! `replay` connects to a server and sends it all incoming packets of a capture with the original
+//! timing, divided by `--speed`. A speed of `0` sends everything at once.
+//!
+//! `corpus` turns the incoming packets of a capture into seed inputs for the fuzz targets in `fuzz/`.
+
+use anyhow::{anyhow, bail, Context};
+use ferrumc_net::capture::{CaptureReader, CapturedPacket, PacketDirection};
+use ferrumc_net::debug_packet;
+use ferrumc_net::packets::incoming::packet_skeleton::PacketSkeleton;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+
+const USAGE: &str = "Usage:
+    ferrumc-capture decode <capture>
+    ferrumc-capture replay <capture> [address] [--speed <factor>]
+    ferrumc-capture corpus <capture> <fuzz directory>";
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:25565";
+
+/// How many bytes of a packet are shown when it can't be decoded.
+const HEX_PREVIEW_LEN: usize = 64;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        Some("decode") => {
+            let path = args.get(1).ok_or_else(|| anyhow!(USAGE))?;
+            decode(path)
+        }
+        Some("replay") => {
+            let path = args.get(1).ok_or_else(|| anyhow!(USAGE))?;
+
+            let mut address = DEFAULT_ADDRESS.to_string();
+            let mut speed = 1.0;
+            let mut rest = args[2..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--speed" => {
+                        speed = rest
+                            .next()
+                            .ok_or_else(|| anyhow!("--speed needs a value"))?
+                            .parse::<f64>()
+                            .context("invalid --speed")?;
+                    }
+                    _ => address.clone_from(arg),
+                }
+            }
+
+            replay(path, &address, speed).await
+        }
+        Some("corpus") => {
+            let (Some(path), Some(fuzz_dir)) = (args.get(1), args.get(2)) else {
+                bail!(USAGE);
+            };
+            corpus(path, Path::new(fuzz_dir))
+        }
+        _ => bail!(USAGE),
+    }
+}
+
+fn read_capture(path: &str) -> anyhow::Result<(u64, Vec<CapturedPacket>)> {
+    let file = File::open(path).with_context(|| format!("failed to open {path}"))?;
+    let reader = CaptureReader::new(BufReader::new(file))?;
+    let started_at = reader.started_at();
+    let packets = reader.collect::<Result<Vec<_>, _>>()?;
+
+    Ok((started_at, packets))
+}
+
+fn decode(path: &str) -> anyhow::Result<()> {
+    let (started_at, packets) = read_capture(path)?;
+
+    println!("Capture started at {started_at} (unix ms), {} packets", packets.len());
+
+    for packet in packets {
+        let direction = match packet.direction {
+            PacketDirection::Inbound => "C->S",
+            PacketDirection::Outbound => "S->C",
+        };
+
+        println!(
+            "[{:>10.3}s] {} {:<13} 0x{:02X} ({} bytes)",
+            Duration::from_micros(packet.timestamp).as_secs_f64(),
+            direction,
+            packet.state.as_str(),
+            packet.packet_id,
+            packet.payload.len()
+        );
+
+        // Only incoming packets have decodable structs.
+        let decoded = match packet.direction {
+            PacketDirection::Inbound => {
+                debug_packet(packet.packet_id, &packet.state, &mut Cursor::new(&packet.payload))
+            }
+            PacketDirection::Outbound => Ok(None),
+        };
+
+        match decoded {
+            Ok(Some(decoded)) => {
+                for line in decoded.lines() {
+                    println!("    {line}");
+                }
+            }
+            Ok(None) => println!("    {}", hex_preview(&packet.payload)),
+            Err(e) => println!("    failed to decode: {e}\n    {}", hex_preview(&packet.payload)),
+        }
+    }
+
+    Ok(())
+}
+
+fn corpus(path: &str, fuzz_dir: &Path) -> anyhow::Result<()> {
+    let (_, packets) = read_capture(path)?;
+    let name = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let decode_dir = fuzz_dir.join("corpus").join("packet_decode");
+    let skeleton_dir = fuzz_dir.join("corpus").join("packet_skeleton_uncompressed");
+    std::fs::create_dir_all(&decode_dir)?;
+    std::fs::create_dir_all(&skeleton_dir)?;
+
+    let mut count = 0;
+    for (i, packet) in packets.iter().enumerate() {
+        if packet.direction != PacketDirection::Inbound {
+            continue;
+        }
+
+        // See fuzz/fuzz_targets/packet_decode.rs for the layout
+        let mut input = vec![packet.state.clone() as u8, packet.packet_id];
+        input.extend_from_slice(&packet.payload);
+        std::fs::write(decode_dir.join(format!("{name}-{i}")), input)?;
+        std::fs::write(skeleton_dir.join(format!("{name}-{i}")), packet.to_frame()?)?;
+
+        count += 1;
+    }
+
+    println!("Wrote {count} seeds to {}", fuzz_dir.join("corpus").display());
+
+    Ok(())
+}
+
+fn hex_preview(payload: &[u8]) -> String {
+    let mut hex = payload
+        .iter()
+        .take(HEX_PREVIEW_LEN)
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if payload.len() > HEX_PREVIEW_LEN {
+        hex.push_str(" ...");
+    }
+
+    hex
+}
+
+async fn replay(path: &str, address: &str, speed: f64) -> anyhow::Result<()> {
+    if !speed.is_finite() || speed < 0.0 {
+        bail!("--speed has to be a positive number");
+    }
+
+    let (_, packets) = read_capture(path)?;
+    let packets = packets
+        .into_iter()
+        .filter(|packet| packet.direction == PacketDirection::Inbound)
+        .collect::<Vec<_>>();
+
+    let stream = TcpStream::connect(address)
+        .await
+        .with_context(|| format!("failed to connect to {address}"))?;
+    let (mut reader, mut writer) = stream.into_split();
+
+    println!("Replaying {} packets to {address}", packets.len());
+
+    // Keep reading what the server sends, otherwise it'll eventually block on a full socket.
+    let responses = tokio::spawn(async move {
+        while let Ok(packet) = PacketSkeleton::new(&mut reader, false).await {
+            println!("    <- 0x{:02X} ({} bytes)", packet.id, packet.length);
+        }
+    });
+
+    let start = Instant::now();
+    for packet in &packets {
+        if speed > 0.0 {
+            let at = Duration::from_micros(packet.timestamp).div_f64(speed);
+            tokio::time::sleep_until(start + at).await;
+        }
+
+        println!("-> {} 0x{:02X} ({} bytes)", packet.state.as_str(), packet.packet_id, packet.payload.len());
+        writer.write_all(&packet.to_frame()?).await?;
+    }
+
+    // Give the server some time to respond to the last packets.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    writer.shutdown().await?;
+    responses.abort();
+
+    println!("Replay finished");
+
+    Ok(())
+}
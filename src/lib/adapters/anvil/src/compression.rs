@@ -0,0 +1,272 @@
+use std::io::{Read, Write};
+use crate::errors::AnvilError;
+
+/// How the data of a chunk is compressed, the id is stored before the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Compression {
+    Gzip = 1,
+    /// What vanilla uses unless configured otherwise.
+    #[default]
+    Zlib = 2,
+    None = 3,
+    /// The block stream format of lz4-java, which vanilla uses since 1.20.5.
+    Lz4 = 4,
+}
+
+impl Compression {
+    pub fn from_id(id: u8) -> Result<Self, AnvilError> {
+        match id {
+            1 => Ok(Self::Gzip),
+            2 => Ok(Self::Zlib),
+            3 => Ok(Self::None),
+            4 => Ok(Self::Lz4),
+            _ => Err(AnvilError::UnknownCompression(id)),
+        }
+    }
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, AnvilError> {
+        match self {
+            Self::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).map_err(|e| AnvilError::CompressionFailed(e.to_string()))?;
+                encoder.finish().map_err(|e| AnvilError::CompressionFailed(e.to_string()))
+            }
+            Self::Zlib => {
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).map_err(|e| AnvilError::CompressionFailed(e.to_string()))?;
+                encoder.finish().map_err(|e| AnvilError::CompressionFailed(e.to_string()))
+            }
+            Self::None => Ok(data.to_vec()),
+            Self::Lz4 => lz4_block::compress(data),
+        }
+    }
+
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, AnvilError> {
+        match self {
+            Self::Gzip => {
+                let mut decompressed = Vec::new();
+                flate2::read::GzDecoder::new(data)
+                    .read_to_end(&mut decompressed)
+                    .map_err(|e| AnvilError::DecompressionFailed(e.to_string()))?;
+                Ok(decompressed)
+            }
+            Self::Zlib => {
+                let mut decompressed = Vec::new();
+                flate2::read::ZlibDecoder::new(data)
+                    .read_to_end(&mut decompressed)
+                    .map_err(|e| AnvilError::DecompressionFailed(e.to_string()))?;
+                Ok(decompressed)
+            }
+            Self::None => Ok(data.to_vec()),
+            Self::Lz4 => lz4_block::decompress(data),
+        }
+    }
+}
+
+/// The format of lz4-java's `LZ4BlockOutputStream`: blocks of up to 64 KiB, each with a header
+/// holding its lengths and a checksum, and an empty block at the end.
+mod lz4_block {
+    use crate::errors::AnvilError;
+
+    const MAGIC: &[u8; 8] = b"LZ4Block";
+    const HEADER_LENGTH: usize = MAGIC.len() + 1 + 4 + 4 + 4;
+    const BLOCK_SIZE: usize = 1 << 16;
+    const METHOD_RAW: u8 = 0x10;
+    const METHOD_LZ4: u8 = 0x20;
+    /// Derived from the block size, lz4-java stores it in the token but doesn't read it.
+    const COMPRESSION_LEVEL: u8 = 6;
+    const CHECKSUM_SEED: u32 = 0x9747_B28C;
+
+    fn checksum(data: &[u8]) -> u32 {
+        super::xxh32(data, CHECKSUM_SEED) & 0x0FFF_FFFF
+    }
+
+    fn write_header(out: &mut Vec<u8>, method: u8, compressed_length: usize, length: usize, checksum: u32) {
+        out.extend_from_slice(MAGIC);
+        out.push(method | COMPRESSION_LEVEL);
+        out.extend_from_slice(&(compressed_length as u32).to_le_bytes());
+        out.extend_from_slice(&(length as u32).to_le_bytes());
+        out.extend_from_slice(&checksum.to_le_bytes());
+    }
+
+    pub fn compress(data: &[u8]) -> Result<Vec<u8>, AnvilError> {
+        let mut out = Vec::new();
+
+        for block in data.chunks(BLOCK_SIZE) {
+            let mut compressed = Vec::new();
+            lzzzz::lz4::compress_to_vec(block, &mut compressed, lzzzz::lz4::ACC_LEVEL_DEFAULT)
+                .map_err(|e| AnvilError::CompressionFailed(e.to_string()))?;
+
+            // Blocks that don't get smaller are stored as they are.
+            if compressed.len() < block.len() {
+                write_header(&mut out, METHOD_LZ4, compressed.len(), block.len(), checksum(block));
+                out.extend_from_slice(&compressed);
+            } else {
+                write_header(&mut out, METHOD_RAW, block.len(), block.len(), checksum(block));
+                out.extend_from_slice(block);
+            }
+        }
+
+        write_header(&mut out, METHOD_RAW, 0, 0, 0);
+        Ok(out)
+    }
+
+    pub fn decompress(mut data: &[u8]) -> Result<Vec<u8>, AnvilError> {
+        let invalid = |reason: &str| AnvilError::DecompressionFailed(format!("Invalid LZ4 block: {reason}"));
+        let mut out = Vec::new();
+
+        loop {
+            let Some((header, rest)) = data.split_at_checked(HEADER_LENGTH) else {
+                return Err(invalid("missing end block"));
+            };
+            if &header[..MAGIC.len()] != MAGIC {
+                return Err(invalid("wrong magic"));
+            }
+            let method = header[8] & 0xF0;
+            let read_u32 = |at: usize| u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]]);
+            let compressed_length = read_u32(9) as usize;
+            let length = read_u32(13) as usize;
+            let expected_checksum = read_u32(17);
+
+            if length == 0 {
+                return Ok(out);
+            }
+            if length > BLOCK_SIZE {
+                return Err(invalid("block too large"));
+            }
+            let Some((block, rest)) = rest.split_at_checked(compressed_length) else {
+                return Err(invalid("truncated block"));
+            };
+
+            let start = out.len();
+            match method {
+                METHOD_RAW if compressed_length == length => out.extend_from_slice(block),
+                METHOD_LZ4 => {
+                    out.resize(start + length, 0);
+                    let written = lzzzz::lz4::decompress(block, &mut out[start..])
+                        .map_err(|e| AnvilError::DecompressionFailed(e.to_string()))?;
+                    if written != length {
+                        return Err(invalid("wrong length"));
+                    }
+                }
+                _ => return Err(invalid("unknown method")),
+            }
+
+            if checksum(&out[start..]) != expected_checksum {
+                return Err(invalid("checksum does not match"));
+            }
+            data = rest;
+        }
+    }
+}
+
+const PRIME_1: u32 = 0x9E37_79B1;
+const PRIME_2: u32 = 0x85EB_CA77;
+const PRIME_3: u32 = 0xC2B2_AE3D;
+const PRIME_4: u32 = 0x27D4_EB2F;
+const PRIME_5: u32 = 0x1656_67B1;
+
+/// The 32-bit xxHash of `data`, which lz4-java checksums blocks with.
+fn xxh32(data: &[u8], seed: u32) -> u32 {
+    fn round(acc: u32, input: u32) -> u32 {
+        acc.wrapping_add(input.wrapping_mul(PRIME_2))
+            .rotate_left(13)
+            .wrapping_mul(PRIME_1)
+    }
+    fn read(bytes: &[u8]) -> u32 {
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    let stripes = data.chunks_exact(16);
+    let rest = stripes.remainder();
+
+    let mut hash = if data.len() >= 16 {
+        let mut acc = [
+            seed.wrapping_add(PRIME_1).wrapping_add(PRIME_2),
+            seed.wrapping_add(PRIME_2),
+            seed,
+            seed.wrapping_sub(PRIME_1),
+        ];
+        for stripe in stripes {
+            for (lane, acc) in acc.iter_mut().enumerate() {
+                *acc = round(*acc, read(&stripe[lane * 4..]));
+            }
+        }
+        acc[0]
+            .rotate_left(1)
+            .wrapping_add(acc[1].rotate_left(7))
+            .wrapping_add(acc[2].rotate_left(12))
+            .wrapping_add(acc[3].rotate_left(18))
+    } else {
+        seed.wrapping_add(PRIME_5)
+    };
+    hash = hash.wrapping_add(data.len() as u32);
+
+    let words = rest.chunks_exact(4);
+    let bytes = words.remainder();
+    for word in words {
+        hash = hash
+            .wrapping_add(read(word).wrapping_mul(PRIME_3))
+            .rotate_left(17)
+            .wrapping_mul(PRIME_4);
+    }
+    for byte in bytes {
+        hash = hash
+            .wrapping_add(u32::from(*byte).wrapping_mul(PRIME_5))
+            .rotate_left(11)
+            .wrapping_mul(PRIME_1);
+    }
+
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(PRIME_2);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(PRIME_3);
+    hash ^ (hash >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xxh32() {
+        assert_eq!(xxh32(b"", 0), 0x02CC_5D05);
+        assert_eq!(xxh32(b"abc", 0), 0x32D1_53FF);
+        assert_eq!(xxh32(b"Nobody inspects the spammish repetition", 0), 0xE229_3B2F);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // Several LZ4 blocks, some of which don't compress.
+        let data = (0..200_000u32)
+            .map(|i| if i < 100_000 { (i % 7) as u8 } else { i.wrapping_mul(2_654_435_761).to_be_bytes()[0] })
+            .collect::<Vec<_>>();
+
+        for compression in [Compression::Gzip, Compression::Zlib, Compression::None, Compression::Lz4] {
+            let compressed = compression.compress(&data).unwrap();
+            assert_eq!(compression.decompress(&compressed).unwrap(), data, "{compression:?}");
+        }
+    }
+
+    #[test]
+    fn test_lz4_blocks() {
+        let compressed = Compression::Lz4.compress(&[0; 70_000]).unwrap();
+
+        assert_eq!(&compressed[..8], b"LZ4Block");
+        assert_eq!(compressed[8], 0x26);
+        assert_eq!(&compressed[13..17], &65_536u32.to_le_bytes());
+        // Ends with an empty raw block
+        assert_eq!(&compressed[compressed.len() - 21..], b"LZ4Block\x16\0\0\0\0\0\0\0\0\0\0\0\0");
+
+        let mut corrupted = compressed.clone();
+        let last = corrupted.len() - 22;
+        corrupted[last] ^= 1;
+        assert!(Compression::Lz4.decompress(&corrupted).is_err());
+        assert!(Compression::Lz4.decompress(&compressed[..compressed.len() - 21]).is_err());
+    }
+}
@@ -11,4 +11,22 @@ pub enum AnvilError {
     UnableToReadFile(PathBuf, std::io::Error),
     #[error("Unable to map file {0}: {1}")]
     UnableToMapFile(PathBuf, std::io::Error),
+    #[error("Unable to write file {0}: {1}")]
+    UnableToWriteFile(PathBuf, std::io::Error),
+    #[error("Chunk {0}, {1} is not in the file")]
+    ChunkNotFound(u32, u32),
+    #[error("Invalid chunk location: {0:#010x}")]
+    InvalidLocation(u32),
+    #[error("Invalid length of the chunk at {0:#010x}")]
+    InvalidChunkLength(u32),
+    #[error("Unable to tell the region of {0} from its name")]
+    UnknownRegion(PathBuf),
+    #[error("Chunk {0}, {1} is not in the region of {2}")]
+    ChunkOutsideRegion(i32, i32, PathBuf),
+    #[error("Unknown compression type: {0}")]
+    UnknownCompression(u8),
+    #[error("Unable to compress chunk: {0}")]
+    CompressionFailed(String),
+    #[error("Unable to decompress chunk: {0}")]
+    DecompressionFailed(String),
 }
@@ -1,20 +1,41 @@
-mod errors;
+pub mod compression;
+pub mod errors;
+pub mod writer;
 
-use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use memmap2::Mmap;
-use tracing::error;
-use yazi::Adler32;
+use crate::compression::Compression;
 use crate::errors::AnvilError;
+use crate::writer::{EXTERNAL_FLAG, SECTOR_SIZE};
+
+pub use writer::RegionWriter;
+
+/// The tables at the start of every region file, with a location and a timestamp for each chunk.
+const TABLE_SIZE: usize = 4096;
 
 pub struct LoadedAnvilFile {
     pub table: [u8; 4096],
     data_map: Mmap,
+    path: PathBuf,
 }
 
-pub fn get_chunk(x: u32, z: u32, file_path: PathBuf) -> Option<Vec<u8>> {
-    let loaded_file = load_anvil_file(file_path).ok()?;
-    loaded_file.get_chunk(x, z)
+/// A chunk stored in a region file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionChunk {
+    /// The coordinates of the chunk within its region, 0 to 31.
+    pub x: u32,
+    pub z: u32,
+    /// When the chunk was last saved, in seconds since the epoch.
+    pub timestamp: u32,
+    /// The decompressed chunk data
+    pub data: Vec<u8>,
+}
+
+/// Get a single chunk from a file
+///
+/// This maps the file on every call, use [load_anvil_file] to read more than one chunk.
+pub fn get_chunk(x: u32, z: u32, file_path: PathBuf) -> Result<Vec<u8>, AnvilError> {
+    load_anvil_file(file_path)?.get_chunk(x, z)
 }
 
 /// Memory map the file and return a `LoadedAnvilFile` struct
@@ -44,7 +65,7 @@ pub fn get_chunk(x: u32, z: u32, file_path: PathBuf) -> Option<Vec<u8>> {
 /// let mut fast_file = Region::from_stream(File::open(file_path.clone()).unwrap()).unwrap();
 /// let loaded_file = load_anvil_file(file_path).unwrap();
 ///
-/// let chunk = loaded_file.get_chunk(0, 0);
+/// let chunk = loaded_file.get_chunk(0, 0).ok();
 /// let fast_chunk = fast_file.read_chunk(0, 0).unwrap();
 ///
 /// assert_eq!(chunk, fast_chunk);
@@ -60,7 +81,7 @@ pub fn load_anvil_file(file_path: PathBuf) -> Result<LoadedAnvilFile, AnvilError
     match file_path.metadata() {
         Ok(meta) => {
             // We should have at least 8KB of data; 4KB for locations and 4KB for timestamps
-            if meta.len() <= (4 * 1024) * 2 {
+            if meta.len() < (TABLE_SIZE * 2) as u64 {
                 return Err(AnvilError::InvalidTables(file_path));
             }
         }
@@ -74,18 +95,24 @@ pub fn load_anvil_file(file_path: PathBuf) -> Result<LoadedAnvilFile, AnvilError
     )?;
 
     let res = unsafe { Mmap::map(&file) }.map_err(
-        |e| AnvilError::UnableToMapFile(file_path, e)
+        |e| AnvilError::UnableToMapFile(file_path.clone(), e)
     )?;
 
+    // The file could have been truncated since its length was checked.
+    if res.len() < TABLE_SIZE * 2 {
+        return Err(AnvilError::InvalidTables(file_path));
+    }
+
     let table = {
-        let mut table = [0; 4096];
-        table.copy_from_slice(&res[0..4096]);
+        let mut table = [0; TABLE_SIZE];
+        table.copy_from_slice(&res[0..TABLE_SIZE]);
         table
     };
 
     Ok(LoadedAnvilFile {
         table,
         data_map: res,
+        path: file_path,
     })
 }
 
@@ -98,24 +125,24 @@ impl LoadedAnvilFile {
     /// can be used to get the chunk data with `get_chunk_from_location`. They are probably in order
     /// but not guaranteed to be
     pub fn get_locations(&self) -> Vec<u32> {
-        (0..1024).map(|i| {
-            u32::from(self.table[i * 4]) << 24
-                | u32::from(self.table[i * 4 + 1]) << 16
-                | u32::from(self.table[i * 4 + 2]) << 8
-                | u32::from(self.table[i * 4 + 3])
-        })
+        (0..1024)
+            .map(|index| self.location(index))
             .filter(|&x| x != 0)
             .collect::<Vec<u32>>()
     }
 
+    fn location(&self, index: usize) -> u32 {
+        read_u32(&self.table, index * 4)
+    }
+
+    /// When the chunk was last saved, in seconds since the epoch, 0 if it isn't in the file
+    pub fn get_timestamp(&self, x: u32, z: u32) -> u32 {
+        read_u32(&self.data_map[TABLE_SIZE..TABLE_SIZE * 2], chunk_index(x, z) * 4)
+    }
+
     /// Get the data from the mmaped file, given an offset and size
-    #[allow(unsafe_code)]
-    fn get_data_from_file(&self, offset: u32, size: u32) -> Result<Vec<u8>, AnvilError> {
-        unsafe {
-            let start = self.data_map.as_ptr().add(offset as usize);
-            let slice = std::slice::from_raw_parts(start, size as usize);
-            Ok(slice.to_vec())
-        }
+    fn get_data_from_file(&self, offset: usize, size: usize) -> Option<&[u8]> {
+        self.data_map.get(offset..offset.checked_add(size)?)
     }
 
     /// Get the chunk data from a location
@@ -131,71 +158,18 @@ impl LoadedAnvilFile {
     /// 3: None
     /// 4: LZ4
     ///
-    /// The next 4 bytes are the uncompressed size of the chunk
+    /// If the highest bit of the compression type is set, the compressed chunk data is in a
+    /// `c.X.Z.mcc` file next to the region file instead, as it's too large for the region file
     ///
-    /// The rest of the data is the compressed chunk data
+    /// The compression type comes after 4 bytes with the length of the compression type and the
+    /// compressed chunk data
     ///
     /// This function will return the decompressed chunk data
     ///
-    /// If the compression type is unknown, it will return None
-    ///
-    /// If the decompression fails, it will return None
-    ///
-    /// If the location is invalid, it will return None
-    pub fn get_chunk_from_location(&self, location: u32) -> Option<Vec<u8>> {
-        let offset = (location >> 8) & 0xFFFFFF;
-        if u64::from(offset) * 4096 >= u64::from(u32::MAX) {
-            error!("Invalid offset: {}", offset);
-            return None;
-        }
-        let offset = offset * 4096;
-        let size = (location & 0xFF) * 4096;
-        let chunk_data = self.get_data_from_file(offset, size).ok()?;
-        let chunk_compressed_data = chunk_data[5..].to_vec();
-        let compression_type = chunk_data[4];
-        match compression_type {
-            1 => {
-                let mut decompressed_data = Vec::new();
-                let mut decoder = flate2::read::GzDecoder::new(&chunk_compressed_data[..]);
-                decoder.read_to_end(&mut decompressed_data).unwrap();
-                Some(decompressed_data)
-            }
-            2 => {
-                let out = yazi::decompress(&chunk_compressed_data[..], yazi::Format::Zlib).ok();
-                match out {
-                    Some(data) => {
-                        match data.1 {
-                            Some(checksum) => {
-                                if Adler32::from_buf(&data.0).finish() == checksum {
-                                    Some(data.0)
-                                } else {
-                                    error!("Checksum does not match");
-                                    None
-                                }
-                            }
-                            None => {
-                                error!("Failed to decompress Zlib data (No checksum)");
-                                None
-                            }
-                        }
-                    }
-                    None => {
-                        error!("Failed to decompress Zlib data");
-                        None
-                    }
-                }
-            }
-            3 => Some(chunk_compressed_data),
-            4 => {
-                let mut decompressed_data = vec![];
-                lzzzz::lz4::decompress(&chunk_compressed_data[..], &mut decompressed_data).ok()?;
-                Some(decompressed_data)
-            }
-            _ => {
-                error!("Unknown compression type: {}", compression_type);
-                None
-            }
-        }
+    /// Fails if the location isn't one of the file, the chunk data doesn't fit in the file, the
+    /// compression type is unknown or the decompression fails
+    pub fn get_chunk_from_location(&self, location: u32) -> Result<Vec<u8>, AnvilError> {
+        self.read_chunk(location, None)
     }
 
     /// Get the chunk data from the table
@@ -203,16 +177,93 @@ impl LoadedAnvilFile {
     /// The x and z coordinates are the chunk coordinates
     ///
     /// This function will return the decompressed chunk data
-    pub fn get_chunk(&self, x: u32, z: u32) -> Option<Vec<u8>> {
-        let index = u64::from(4 * ((x % 32) + (z % 32) * 32));
-        let location = u32::from(self.table[index as usize * 4]) << 24
-            | u32::from(self.table[index as usize * 4 + 1]) << 16
-            | u32::from(self.table[index as usize * 4 + 2]) << 8
-            | u32::from(self.table[index as usize * 4 + 3]);
-        self.get_chunk_from_location(location)
+    pub fn get_chunk(&self, x: u32, z: u32) -> Result<Vec<u8>, AnvilError> {
+        let index = chunk_index(x, z);
+        match self.location(index) {
+            0 => Err(AnvilError::ChunkNotFound(x % 32, z % 32)),
+            location => self.read_chunk(location, Some(index)),
+        }
+    }
+
+    /// Every chunk in the file, in the order of the table
+    ///
+    /// The chunks are decompressed as they're iterated over.
+    pub fn chunks(&self) -> impl Iterator<Item = Result<RegionChunk, AnvilError>> + '_ {
+        (0..1024)
+            .filter(|index| self.location(*index) != 0)
+            .map(|index| {
+                let (x, z) = ((index % 32) as u32, (index / 32) as u32);
+                Ok(RegionChunk {
+                    x,
+                    z,
+                    timestamp: self.get_timestamp(x, z),
+                    data: self.read_chunk(self.location(index), Some(index))?,
+                })
+            })
+    }
+
+    /// `index` is where the location is in the table, only needed for chunks in `c.X.Z.mcc` files.
+    fn read_chunk(&self, location: u32, index: Option<usize>) -> Result<Vec<u8>, AnvilError> {
+        let offset = (location >> 8) as usize;
+        let size = (location & 0xFF) as usize;
+        // The chunk can't be in the tables.
+        if offset < 2 || size == 0 {
+            return Err(AnvilError::InvalidLocation(location));
+        }
+        let chunk_data = self
+            .get_data_from_file(offset * SECTOR_SIZE, size * SECTOR_SIZE)
+            .ok_or(AnvilError::InvalidLocation(location))?;
+
+        let length = read_u32(chunk_data, 0) as usize;
+        let compression_type = chunk_data[4];
+        let chunk_compressed_data = 4usize
+            .checked_add(length)
+            .filter(|_| length > 0)
+            .and_then(|end| chunk_data.get(5..end))
+            .ok_or(AnvilError::InvalidChunkLength(location))?;
+
+        let compression = Compression::from_id(compression_type & !EXTERNAL_FLAG)?;
+        if compression_type & EXTERNAL_FLAG == 0 {
+            return compression.decompress(chunk_compressed_data);
+        }
+
+        let index = index
+            .or_else(|| (0..1024).find(|index| self.location(*index) == location))
+            .ok_or(AnvilError::InvalidLocation(location))?;
+        let path = self.external_path((index % 32) as u32, (index / 32) as u32)?;
+        let external_data = std::fs::read(&path).map_err(|e| AnvilError::UnableToReadFile(path, e))?;
+        compression.decompress(&external_data)
+    }
+
+    /// The `c.X.Z.mcc` file of a chunk, which is named after the absolute coordinates of the chunk
+    /// so the region has to be known from the name of the region file.
+    fn external_path(&self, x: u32, z: u32) -> Result<PathBuf, AnvilError> {
+        let (region_x, region_z) = region_coordinates(&self.path)?;
+        let (chunk_x, chunk_z) = (region_x * 32 + x as i32, region_z * 32 + z as i32);
+        Ok(self.path.with_file_name(format!("c.{chunk_x}.{chunk_z}.mcc")))
     }
 }
 
+/// The coordinates of a region, from the `r.X.Z.mca` name of its file.
+pub(crate) fn region_coordinates(path: &Path) -> Result<(i32, i32), AnvilError> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("r."))
+        .and_then(|name| name.strip_suffix(".mca"))
+        .and_then(|name| name.split_once('.'))
+        .and_then(|(region_x, region_z)| Some((region_x.parse::<i32>().ok()?, region_z.parse::<i32>().ok()?)))
+        .ok_or_else(|| AnvilError::UnknownRegion(path.to_path_buf()))
+}
+
+/// The index of a chunk in the tables
+fn chunk_index(x: u32, z: u32) -> usize {
+    ((x % 32) + (z % 32) * 32) as usize
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -220,8 +271,18 @@ mod tests {
     use std::io::Read;
     use fastanvil::Region;
     use super::*;
+    use crate::writer::RegionWriter;
     use ferrumc_utils::root;
     use rayon::prelude::*;
+    use std::path::Path;
+
+    /// A region file with one sector after the tables, where the chunk at 0, 0 is.
+    fn write_region(path: &Path, location: u32, sector: &[u8]) {
+        let mut data = vec![0; TABLE_SIZE * 2 + SECTOR_SIZE];
+        data[0..4].copy_from_slice(&location.to_be_bytes());
+        data[TABLE_SIZE * 2..TABLE_SIZE * 2 + sector.len()].copy_from_slice(sector);
+        std::fs::write(path, data).unwrap();
+    }
 
     #[test]
     fn test_load_anvil_file() {
@@ -247,7 +308,7 @@ mod tests {
         let file_path = PathBuf::from(root!(".etc/codec.nbt"));
         let loaded_file = load_anvil_file(file_path).unwrap();
         let chunk = loaded_file.get_chunk(15, 3);
-        assert!(chunk.is_none());
+        assert!(chunk.is_err());
     }
 
     #[test]
@@ -257,9 +318,9 @@ mod tests {
         let loaded_file = load_anvil_file(file_path).unwrap();
         let chunk = loaded_file.get_chunk(0, 0);
         let fast_chunk = Region::from_stream(File::open(root!(".etc/r.0.0.mca")).unwrap()).unwrap().read_chunk(0, 0).unwrap();
-        assert!(chunk.is_some());
+        assert!(chunk.is_ok());
         assert!(fast_chunk.is_some());
-        assert_eq!(chunk.clone().unwrap(), fast_chunk.unwrap());
+        assert_eq!(chunk.unwrap(), fast_chunk.unwrap());
     }
 
     #[test]
@@ -273,4 +334,97 @@ mod tests {
             });
         });
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_chunks() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.0.0.mca");
+        let mut writer = RegionWriter::open(&path).unwrap();
+        writer.write_chunk_with_timestamp(3, 5, b"three five", 100).unwrap();
+        writer.write_chunk_with_timestamp(31, 2, b"thirty one two", 200).unwrap();
+
+        let loaded_file = load_anvil_file(path).unwrap();
+        let chunks = loaded_file.chunks().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(chunks, [
+            RegionChunk { x: 31, z: 2, timestamp: 200, data: b"thirty one two".to_vec() },
+            RegionChunk { x: 3, z: 5, timestamp: 100, data: b"three five".to_vec() },
+        ]);
+
+        assert_eq!(loaded_file.get_chunk(3, 5).unwrap(), b"three five");
+        assert_eq!(loaded_file.get_timestamp(31, 2), 200);
+        assert!(matches!(loaded_file.get_chunk(1, 1), Err(AnvilError::ChunkNotFound(1, 1))));
+    }
+
+    #[test]
+    fn test_empty_region() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.0.0.mca");
+        RegionWriter::open(&path).unwrap();
+
+        let loaded_file = load_anvil_file(path).unwrap();
+        assert_eq!(loaded_file.chunks().count(), 0);
+        assert!(loaded_file.get_locations().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_chunks_fail() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.0.0.mca");
+
+        // Past the end of the file
+        write_region(&path, 3 << 8 | 1, &[]);
+        let chunk = load_anvil_file(path.clone()).unwrap().get_chunk(0, 0);
+        assert!(matches!(chunk, Err(AnvilError::InvalidLocation(_))));
+
+        // In the tables
+        write_region(&path, 1 << 8 | 1, &[]);
+        let chunk = load_anvil_file(path.clone()).unwrap().get_chunk(0, 0);
+        assert!(matches!(chunk, Err(AnvilError::InvalidLocation(_))));
+
+        // Longer than its sectors
+        write_region(&path, 2 << 8 | 1, &[0, 0, 0x10, 0, 3]);
+        let chunk = load_anvil_file(path.clone()).unwrap().get_chunk(0, 0);
+        assert!(matches!(chunk, Err(AnvilError::InvalidChunkLength(_))));
+
+        write_region(&path, 2 << 8 | 1, &[0, 0, 0, 0, 3]);
+        let chunk = load_anvil_file(path.clone()).unwrap().get_chunk(0, 0);
+        assert!(matches!(chunk, Err(AnvilError::InvalidChunkLength(_))));
+
+        write_region(&path, 2 << 8 | 1, &[0, 0, 0, 2, 9, 0]);
+        let chunk = load_anvil_file(path.clone()).unwrap().get_chunk(0, 0);
+        assert!(matches!(chunk, Err(AnvilError::UnknownCompression(9))));
+
+        // Not gzip
+        write_region(&path, 2 << 8 | 1, &[0, 0, 0, 4, 1, 1, 2, 3]);
+        let loaded_file = load_anvil_file(path.clone()).unwrap();
+        assert!(matches!(loaded_file.get_chunk(0, 0), Err(AnvilError::DecompressionFailed(_))));
+        assert!(matches!(loaded_file.chunks().next(), Some(Err(AnvilError::DecompressionFailed(_)))));
+
+        assert!(matches!(loaded_file.get_chunk_from_location(0), Err(AnvilError::InvalidLocation(0))));
+    }
+
+    #[test]
+    fn test_external_chunks() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.-1.2.mca");
+        let mut writer = RegionWriter::open(&path).unwrap().with_compression(Compression::None);
+
+        // Too large for the region file, even compressed
+        let data = (0..2_000_000u32).map(|i| i.wrapping_mul(2_654_435_761).to_be_bytes()[0]).collect::<Vec<_>>();
+        writer.write_chunk(-3, 70, &data).unwrap();
+        assert!(directory.path().join("c.-3.70.mcc").exists());
+
+        let loaded_file = load_anvil_file(path.clone()).unwrap();
+        assert_eq!(loaded_file.get_chunk(29, 6).unwrap(), data);
+        assert_eq!(loaded_file.get_chunk_from_location(loaded_file.get_locations()[0]).unwrap(), data);
+
+        // The chunk coordinates can't be known without the region coordinates.
+        let renamed = directory.path().join("region.mca");
+        std::fs::copy(&path, &renamed).unwrap();
+        let chunk = load_anvil_file(renamed).unwrap().get_chunk(29, 6);
+        assert!(matches!(chunk, Err(AnvilError::UnknownRegion(_))));
+
+        std::fs::remove_file(directory.path().join("c.-3.70.mcc")).unwrap();
+        assert!(matches!(loaded_file.get_chunk(29, 6), Err(AnvilError::UnableToReadFile(..))));
+    }
+}
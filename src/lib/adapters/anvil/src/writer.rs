@@ -0,0 +1,413 @@
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::compression::Compression;
+use crate::errors::AnvilError;
+use crate::region_coordinates;
+
+/// Region files are made of sectors, the first two hold the location and timestamp tables.
+pub const SECTOR_SIZE: usize = 4096;
+const HEADER_SECTORS: usize = 2;
+/// The most sectors a chunk can take up, bigger chunks are stored in a `c.X.Z.mcc` file next to
+/// the region file.
+const MAX_SECTORS: usize = 255;
+/// Set in the compression type of chunks that are stored in a `c.X.Z.mcc` file.
+pub const EXTERNAL_FLAG: u8 = 0x80;
+
+/// Writes chunks to a region file, in the format vanilla reads.
+///
+/// A chunk is always written to free sectors before the tables point to it, and its old sectors
+/// are only reused after that, so a crash while writing leaves either the old or the new chunk.
+pub struct RegionWriter {
+    path: PathBuf,
+    /// The region the file holds, from its `r.X.Z.mca` name.
+    region: (i32, i32),
+    file: File,
+    locations: [u32; 1024],
+    timestamps: [u32; 1024],
+    /// Which sectors of the file are in use, including the tables.
+    used_sectors: Vec<bool>,
+    compression: Compression,
+}
+
+impl RegionWriter {
+    /// Opens a region file, creating it if it doesn't exist.
+    ///
+    /// The file has to be named `r.X.Z.mca` after its region, like vanilla names them.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, AnvilError> {
+        let path = path.into();
+        let region = region_coordinates(&path)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| AnvilError::UnableToWriteFile(path.clone(), e))?;
+
+        let length = file
+            .metadata()
+            .map_err(|e| AnvilError::UnableToReadFile(path.clone(), e))?
+            .len();
+        let mut header = [0; SECTOR_SIZE * HEADER_SECTORS];
+        if length == 0 {
+            file.write_all(&header)
+                .and_then(|_| file.sync_all())
+                .map_err(|e| AnvilError::UnableToWriteFile(path.clone(), e))?;
+        } else if length < header.len() as u64 {
+            return Err(AnvilError::InvalidTables(path));
+        } else {
+            file.read_exact(&mut header)
+                .map_err(|e| AnvilError::UnableToReadFile(path.clone(), e))?;
+        }
+
+        let read_table = |table: &[u8]| {
+            let mut entries = [0; 1024];
+            for (entry, bytes) in entries.iter_mut().zip(table.chunks_exact(4)) {
+                *entry = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+            entries
+        };
+        let locations = read_table(&header[..SECTOR_SIZE]);
+        let timestamps = read_table(&header[SECTOR_SIZE..]);
+
+        let mut writer = Self {
+            path,
+            region,
+            file,
+            locations,
+            timestamps,
+            used_sectors: vec![true; HEADER_SECTORS],
+            compression: Compression::default(),
+        };
+        for location in locations.into_iter().filter(|location| *location != 0) {
+            writer.mark(location, true);
+        }
+
+        Ok(writer)
+    }
+
+    /// The compression chunks are written with, zlib by default.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes the uncompressed NBT of the chunk at chunk coordinates `x` and `z`, replacing the
+    /// chunk that's there.
+    ///
+    /// The coordinates are absolute and have to be in the region of the file, otherwise
+    /// [AnvilError::ChunkOutsideRegion] is returned. They also name the `c.X.Z.mcc` file of large chunks.
+    pub fn write_chunk(&mut self, x: i32, z: i32, data: &[u8]) -> Result<(), AnvilError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs() as u32);
+        self.write_chunk_with_timestamp(x, z, data, timestamp)
+    }
+
+    /// Like [write_chunk](RegionWriter::write_chunk), with the time it was saved in seconds since
+    /// the epoch.
+    pub fn write_chunk_with_timestamp(&mut self, x: i32, z: i32, data: &[u8], timestamp: u32) -> Result<(), AnvilError> {
+        let index = self.index(x, z)?;
+        let compressed = self.compression.compress(data)?;
+        let external_path = self.external_path(x, z);
+
+        let external = compressed.len() + 5 > MAX_SECTORS * SECTOR_SIZE;
+        let mut sectors = Vec::new();
+        if external {
+            self.write_external(&external_path, &compressed)?;
+            // Only the compression type is left in the region file.
+            sectors.extend_from_slice(&1u32.to_be_bytes());
+            sectors.push(self.compression.id() | EXTERNAL_FLAG);
+        } else {
+            sectors.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+            sectors.push(self.compression.id());
+            sectors.extend_from_slice(&compressed);
+        }
+        sectors.resize(sectors.len().div_ceil(SECTOR_SIZE) * SECTOR_SIZE, 0);
+
+        let count = sectors.len() / SECTOR_SIZE;
+        let offset = self.allocate(count);
+        self.write_at(offset * SECTOR_SIZE, &sectors)?;
+        self.sync()?;
+
+        let old_location = self.locations[index];
+        self.write_header(index, ((offset as u32) << 8) | count as u32, timestamp)?;
+        self.mark(old_location, false);
+
+        if !external {
+            self.remove_external(&external_path)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the chunk at chunk coordinates `x` and `z`, if there is one.
+    pub fn remove_chunk(&mut self, x: i32, z: i32) -> Result<(), AnvilError> {
+        let index = self.index(x, z)?;
+        let old_location = self.locations[index];
+        if old_location == 0 {
+            return Ok(());
+        }
+
+        self.write_header(index, 0, 0)?;
+        self.mark(old_location, false);
+        self.remove_external(&self.external_path(x, z))
+    }
+
+    /// The index of the chunk at chunk coordinates `x` and `z` in the tables, if it's in the region of this file.
+    fn index(&self, x: i32, z: i32) -> Result<usize, AnvilError> {
+        if (x.div_euclid(32), z.div_euclid(32)) != self.region {
+            return Err(AnvilError::ChunkOutsideRegion(x, z, self.path.clone()));
+        }
+        Ok(chunk_index(x, z))
+    }
+
+    /// The first `count` free sectors in a row, at the end of the file if there are none.
+    fn allocate(&mut self, count: usize) -> usize {
+        let mut free = 0;
+        for sector in HEADER_SECTORS..self.used_sectors.len() {
+            if self.used_sectors[sector] {
+                free = 0;
+                continue;
+            }
+            free += 1;
+            if free == count {
+                let start = sector + 1 - count;
+                self.used_sectors[start..=sector].fill(true);
+                return start;
+            }
+        }
+
+        // The free sectors at the end of the file are used too.
+        let start = self.used_sectors.len() - free;
+        self.used_sectors.resize(start + count, true);
+        self.used_sectors[start..].fill(true);
+        start
+    }
+
+    fn mark(&mut self, location: u32, used: bool) {
+        let offset = (location >> 8) as usize;
+        let count = (location & 0xFF) as usize;
+        // The tables are always in use.
+        if offset < HEADER_SECTORS || count == 0 {
+            return;
+        }
+
+        if self.used_sectors.len() < offset + count {
+            self.used_sectors.resize(offset + count, false);
+        }
+        self.used_sectors[offset..offset + count].fill(used);
+    }
+
+    /// Points the tables to the new location of a chunk, the location last so a crash in between
+    /// only leaves a wrong timestamp.
+    fn write_header(&mut self, index: usize, location: u32, timestamp: u32) -> Result<(), AnvilError> {
+        self.write_at(SECTOR_SIZE + index * 4, &timestamp.to_be_bytes())?;
+        self.write_at(index * 4, &location.to_be_bytes())?;
+        self.sync()?;
+
+        self.locations[index] = location;
+        self.timestamps[index] = timestamp;
+        Ok(())
+    }
+
+    fn write_at(&mut self, position: usize, data: &[u8]) -> Result<(), AnvilError> {
+        self.file
+            .seek(SeekFrom::Start(position as u64))
+            .and_then(|_| self.file.write_all(data))
+            .map_err(|e| AnvilError::UnableToWriteFile(self.path.clone(), e))
+    }
+
+    fn sync(&self) -> Result<(), AnvilError> {
+        self.file
+            .sync_data()
+            .map_err(|e| AnvilError::UnableToWriteFile(self.path.clone(), e))
+    }
+
+    fn external_path(&self, x: i32, z: i32) -> PathBuf {
+        self.path.with_file_name(format!("c.{x}.{z}.mcc"))
+    }
+
+    /// Writes the compressed data of a chunk to a temporary file first, which then replaces the old
+    /// one in a single step.
+    fn write_external(&self, path: &Path, data: &[u8]) -> Result<(), AnvilError> {
+        let temporary = path.with_extension("mcc.tmp");
+        File::create(&temporary)
+            .and_then(|mut file| {
+                file.write_all(data)?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&temporary, path))
+            .map_err(|e| AnvilError::UnableToWriteFile(path.to_path_buf(), e))
+    }
+
+    fn remove_external(&self, path: &Path) -> Result<(), AnvilError> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(AnvilError::UnableToWriteFile(path.to_path_buf(), e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The index of a chunk in the tables of its region.
+fn chunk_index(x: i32, z: i32) -> usize {
+    (x.rem_euclid(32) + z.rem_euclid(32) * 32) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use fastanvil::Region;
+    use crate::load_anvil_file;
+    use super::*;
+
+    /// Data that doesn't compress, so it takes up as many sectors as it's long.
+    fn noise(length: usize) -> Vec<u8> {
+        (0..length as u32)
+            .map(|i| i.wrapping_mul(2_654_435_761).to_be_bytes()[0])
+            .collect()
+    }
+
+    fn location(path: &Path, x: i32, z: i32) -> u32 {
+        let loaded_file = load_anvil_file(path.to_path_buf()).unwrap();
+        let index = chunk_index(x, z) * 4;
+        u32::from_be_bytes(loaded_file.table[index..index + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let directory = tempfile::tempdir().unwrap();
+
+        for compression in [Compression::Gzip, Compression::Zlib, Compression::None, Compression::Lz4] {
+            let region_directory = directory.path().join(compression.id().to_string());
+            std::fs::create_dir(&region_directory).unwrap();
+            let path = region_directory.join("r.0.0.mca");
+            let mut writer = RegionWriter::open(&path).unwrap().with_compression(compression);
+            let chunks = [(0, 0), (3, 5), (31, 31)];
+            for (x, z) in chunks {
+                writer.write_chunk(x, z, format!("chunk {x} {z}").repeat(1000).as_bytes()).unwrap();
+            }
+
+            let loaded_file = load_anvil_file(path.clone()).unwrap();
+            for (x, z) in chunks {
+                let data = loaded_file.get_chunk(x as u32, z as u32);
+                assert_eq!(data.unwrap(), format!("chunk {x} {z}").repeat(1000).as_bytes(), "{compression:?}");
+            }
+
+            // fastanvil doesn't know LZ4
+            if compression != Compression::Lz4 {
+                let mut region = Region::from_stream(File::open(&path).unwrap()).unwrap();
+                for (x, z) in chunks {
+                    let data = region.read_chunk(x as usize, z as usize).unwrap().unwrap();
+                    assert_eq!(data, format!("chunk {x} {z}").repeat(1000).as_bytes());
+                }
+            }
+            assert_eq!(std::fs::metadata(&path).unwrap().len() % SECTOR_SIZE as u64, 0);
+        }
+    }
+
+    #[test]
+    fn test_sector_reuse() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.0.0.mca");
+        let mut writer = RegionWriter::open(&path).unwrap().with_compression(Compression::None);
+
+        writer.write_chunk(0, 0, &noise(3 * SECTOR_SIZE - 5)).unwrap();
+        writer.write_chunk(1, 0, &noise(10)).unwrap();
+        assert_eq!(location(&path, 0, 0), 2 << 8 | 3);
+        assert_eq!(location(&path, 1, 0), 5 << 8 | 1);
+
+        // The old sectors are only freed once the new ones are written.
+        writer.write_chunk(0, 0, &noise(10)).unwrap();
+        assert_eq!(location(&path, 0, 0), 6 << 8 | 1);
+
+        writer.write_chunk(2, 0, &noise(2 * SECTOR_SIZE - 5)).unwrap();
+        assert_eq!(location(&path, 2, 0), 2 << 8 | 2);
+
+        // The free sectors are found again after reopening the file.
+        let mut writer = RegionWriter::open(&path).unwrap();
+        writer.write_chunk(3, 0, &[0; 100]).unwrap();
+        assert_eq!(location(&path, 3, 0), 4 << 8 | 1);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 7 * SECTOR_SIZE as u64);
+
+        writer.remove_chunk(2, 0).unwrap();
+        assert_eq!(location(&path, 2, 0), 0);
+    }
+
+    #[test]
+    fn test_chunks_outside_the_region() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.0.0.mca");
+        let mut writer = RegionWriter::open(&path).unwrap();
+        writer.write_chunk(2, 0, b"chunk").unwrap();
+
+        // Another region's chunk would end up in the same slot.
+        let result = writer.write_chunk(-30, 32, b"other");
+        assert!(matches!(result, Err(AnvilError::ChunkOutsideRegion(-30, 32, _))));
+        assert!(matches!(writer.remove_chunk(34, 0), Err(AnvilError::ChunkOutsideRegion(34, 0, _))));
+        assert_eq!(load_anvil_file(path.clone()).unwrap().get_chunk(2, 0).unwrap(), b"chunk");
+
+        let path = directory.path().join("r.-1.2.mca");
+        let mut writer = RegionWriter::open(&path).unwrap();
+        writer.write_chunk(-32, 64, b"corner").unwrap();
+        writer.write_chunk(-1, 95, b"corner").unwrap();
+        assert!(writer.write_chunk(0, 64, b"next").is_err());
+        assert!(writer.write_chunk(-1, 96, b"next").is_err());
+    }
+
+    #[test]
+    fn test_unknown_region() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("region.mca");
+
+        assert!(matches!(RegionWriter::open(&path), Err(AnvilError::UnknownRegion(_))));
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.0.0.mca");
+        let mut writer = RegionWriter::open(&path).unwrap();
+
+        writer.write_chunk_with_timestamp(4, 1, b"chunk", 1_700_000_000).unwrap();
+
+        let header = std::fs::read(&path).unwrap();
+        let index = SECTOR_SIZE + chunk_index(4, 1) * 4;
+        assert_eq!(header[index..index + 4], 1_700_000_000u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_external_chunks() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.-1.0.mca");
+        let external_path = directory.path().join("c.-3.4.mcc");
+        let mut writer = RegionWriter::open(&path).unwrap().with_compression(Compression::None);
+
+        let data = noise(MAX_SECTORS * SECTOR_SIZE);
+        writer.write_chunk(-3, 4, &data).unwrap();
+
+        assert_eq!(std::fs::read(&external_path).unwrap(), data);
+        let offset = (location(&path, -3, 4) >> 8) as usize * SECTOR_SIZE;
+        let region = std::fs::read(&path).unwrap();
+        assert_eq!(region[offset..offset + 5], [0, 0, 0, 1, 0x83]);
+        assert_eq!(region.len(), 3 * SECTOR_SIZE);
+
+        // Small enough again
+        writer.write_chunk(-3, 4, &data[..100]).unwrap();
+        assert!(!external_path.exists());
+    }
+
+    #[test]
+    fn test_invalid_tables() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("r.0.0.mca");
+        std::fs::write(&path, [0; 100]).unwrap();
+
+        assert!(matches!(RegionWriter::open(&path), Err(AnvilError::InvalidTables(_))));
+    }
+}
@@ -17,7 +17,7 @@ fn test() {
     ];
 
     let mut nbt = ferrumc_nbt::de::borrow::NbtTape::new(&some_test_nbt);
-    nbt.parse();
+    nbt.parse().unwrap();
 
     println!("{:?}", nbt.root);
 
@@ -4,6 +4,7 @@ use crate::structs::{BlockState, Chunk, Palette};
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use fastnbt::Value;
 use ferrumc_macros::NBTDeserialize;
+use ferrumc_nbt::NbtTape;
 use nbt as hematite_nbt;
 use std::io::Cursor;
 
@@ -52,6 +53,23 @@ fn bench_ferrumc_nbt(data: &[u8]) {
     assert_eq!(chunk.heightmaps.motion_blocking.len(), 37);
 }
 
+fn bench_ferrumc_nbt_query(data: &[u8]) {
+    let tape = NbtTape::new(data);
+    let x_pos = tape.query_as::<i32>("xPos").unwrap();
+    let z_pos = tape.query_as::<i32>("zPos").unwrap();
+    let motion_blocking = tape
+        .query_as::<Vec<i64>>("Heightmaps.MOTION_BLOCKING")
+        .unwrap();
+    let palette = tape
+        .query_as::<Vec<Palette>>("sections[3].block_states.palette")
+        .unwrap();
+
+    assert_eq!(x_pos, 0);
+    assert_eq!(z_pos, 32);
+    assert_eq!(motion_blocking.len(), 37);
+    assert!(!palette.is_empty());
+}
+
 fn bench_simdnbt(data: &[u8]) {
     let nbt = simdnbt::borrow::read(&mut Cursor::new(data)).unwrap();
 
@@ -149,6 +167,9 @@ fn criterion_benchmark(c: &mut Criterion) {
     group.bench_function("FerrumC NBT", |b| {
         b.iter(|| bench_ferrumc_nbt(black_box(data)))
     });
+    group.bench_function("FerrumC NBT query", |b| {
+        b.iter(|| bench_ferrumc_nbt_query(black_box(data)))
+    });
     group.bench_function("simdnbt borrow", |b| {
         b.iter(|| bench_simdnbt(black_box(data)))
     });
@@ -1,9 +1,16 @@
 use crate::de::converter::FromNbt;
+use ferrumc_net_codec::encode::errors::NetEncodeError;
 use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use std::borrow::Cow;
 use std::io::Write;
-use crate::{NBTSerializable, NBTSerializeOptions};
+use crate::{NBTError, NBTSerializable, NBTSerializeOptions, NbtEncoding, Result};
 use ferrumc_general_purpose::simd::arrays;
 
+/// How deep compounds and lists may be nested, the same limit vanilla uses.
+pub const MAX_DEPTH: usize = 512;
+/// How many bytes a tape reads before giving up, the same budget vanilla gives network NBT.
+pub const MAX_SIZE: usize = 2 * 1024 * 1024;
+
 #[repr(u8)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum NbtTag {
@@ -22,9 +29,11 @@ pub enum NbtTag {
     LongArray = 12,
 }
 
-impl From<u8> for NbtTag {
-    fn from(tag: u8) -> Self {
-        match tag {
+impl TryFrom<u8> for NbtTag {
+    type Error = NBTError;
+
+    fn try_from(tag: u8) -> Result<Self> {
+        Ok(match tag {
             0 => NbtTag::End,
             1 => NbtTag::Byte,
             2 => NbtTag::Short,
@@ -38,7 +47,27 @@ impl From<u8> for NbtTag {
             10 => NbtTag::Compound,
             11 => NbtTag::IntArray,
             12 => NbtTag::LongArray,
-            _ => panic!("Invalid NbtTag: {}", tag),
+            _ => return Err(NBTError::InvalidTagType(tag)),
+        })
+    }
+}
+
+impl NbtTag {
+    pub const fn nbt_type(&self) -> &'static str {
+        match self {
+            NbtTag::End => "End",
+            NbtTag::Byte => "Byte",
+            NbtTag::Short => "Short",
+            NbtTag::Int => "Int",
+            NbtTag::Long => "Long",
+            NbtTag::Float => "Float",
+            NbtTag::Double => "Double",
+            NbtTag::ByteArray => "ByteArray",
+            NbtTag::String => "String",
+            NbtTag::List => "List",
+            NbtTag::Compound => "Compound",
+            NbtTag::IntArray => "IntArray",
+            NbtTag::LongArray => "LongArray",
         }
     }
 }
@@ -53,7 +82,8 @@ pub enum NbtTapeElement<'a> {
     Float(f32),
     Double(f64),
     ByteArray(&'a [i8]),
-    String(&'a str),
+    // Only owned when the string was written with Java's modified UTF-8.
+    String(Cow<'a, str>),
     List {
         el_type: NbtTag,
         size: usize,
@@ -109,6 +139,9 @@ pub struct NbtTape<'a> {
     data: &'a [u8],
     pos: usize,
     depth: usize,
+    max_depth: usize,
+    max_size: usize,
+    encoding: NbtEncoding,
     // => The root tag is always a compound tag.
     pub root: Option<(&'a str, NbtTapeElement<'a>)>,
 }
@@ -139,7 +172,7 @@ impl<'a> NbtTapeElement<'a> {
         tape.unpack_list(self)
     }*/
     pub fn as_list<T: FromNbt<'a>>(&self, tape: &NbtTape<'a>) -> Option<Vec<T>> {
-        tape.unpack_list(self)
+        tape.unpack_list(self).ok()
     }
 }
 
@@ -150,107 +183,211 @@ impl<'a> NbtTape<'a> {
             data,
             pos: 0,
             depth: 0,
+            max_depth: MAX_DEPTH,
+            max_size: MAX_SIZE,
+            encoding: NbtEncoding::BigEndian,
             root: None,
         }
     }
 
-    pub fn parse(&mut self) {
-        self.parse_tag();
+    /// Sets how deep compounds and lists may be nested, [MAX_DEPTH] by default.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets how many bytes may be read while parsing, [MAX_SIZE] by default.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
     }
 
-    fn parse_tag(&mut self) {
-        let tag = NbtTag::from(self.read_byte());
-        if tag != NbtTag::Compound {
-            panic!("Root tag must be a compound tag! Instead got: {:?}", tag);
+    /// Sets how the data is encoded, [NbtEncoding::BigEndian] (Java Edition) by default.
+    pub fn with_encoding(mut self, encoding: NbtEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Parses the root compound. Malformed data, and data that goes over the depth or size
+    /// limits, is an error rather than a panic, so this is safe to call on untrusted input.
+    pub fn parse(&mut self) -> Result<()> {
+        let tag = self.read_byte()?;
+        if tag != NbtTag::Compound as u8 {
+            return Err(NBTError::InvalidRootCompound(tag));
         }
 
-        let name: &str = <&str>::parse_from_nbt(self, NbtDeserializableOptions::None);
+        let name = self.read_key()?;
+        let root = NbtTapeElement::parse_from_nbt(self, NbtDeserializableOptions::TagType(NbtTag::Compound))?;
+        self.root = Some((name, root));
+
+        Ok(())
+    }
+
+    /// Parses network NBT, which packets carry since 1.20.2: the root has no name and can be any
+    /// tag, or `End` when there's no value at all. The root is stored with an empty name.
+    ///
+    /// The data may continue after the NBT, e.g. with the rest of a packet, so this returns how
+    /// many bytes the NBT took up.
+    pub fn parse_network(&mut self) -> Result<usize> {
+        let start = self.pos;
+        let tag = NbtTag::try_from(self.read_byte()?)?;
+        let root = NbtTapeElement::parse_from_nbt(self, NbtDeserializableOptions::TagType(tag))?;
+        self.root = Some(("", root));
 
-        self.root = Some((
-            name,
-            NbtTapeElement::parse_from_nbt(
-                self,
-                NbtDeserializableOptions::TagType(NbtTag::Compound),
-            ),
-        ));
+        Ok(self.pos - start)
+    }
+
+    /// A tape over the same data, positioned at `pos`, with the same limits.
+    fn at(&self, pos: usize) -> NbtTape<'a> {
+        NbtTape {
+            data: self.data,
+            pos,
+            depth: 0,
+            max_depth: self.max_depth,
+            max_size: self.max_size,
+            encoding: self.encoding,
+            root: None,
+        }
     }
 
     #[inline]
-    fn read_byte(&mut self) -> u8 {
-        let byte = self.data[self.pos];
-        self.pos += 1;
-        byte
+    fn read_byte(&mut self) -> Result<u8> {
+        Ok(self.read_n_bytes(1)?[0])
     }
 
     #[inline]
-    fn read_n_bytes(&mut self, n: usize) -> &'a [u8] {
+    fn read_n_bytes(&mut self, n: usize) -> Result<&'a [u8]> {
         let start = self.pos;
-        self.pos += n;
-        &self.data[start..self.pos]
+        let end = start.checked_add(n).ok_or(NBTError::ReachedEOF)?;
+        if end > self.max_size {
+            return Err(NBTError::SizeLimitExceeded(self.max_size));
+        }
+        let bytes = self.data.get(start..end).ok_or(NBTError::ReachedEOF)?;
+        self.pos = end;
+        Ok(bytes)
     }
 
-    pub fn get(&self, key: &str) -> Option<&NbtTapeElement<'a>> {
-        let res = self.root.as_ref().map(|(_, element)| element.get(key));
+    /// Reads an `i32` length prefix, which must not be negative.
+    fn read_length(&mut self) -> Result<usize> {
+        let length = i32::parse_from_nbt(self, NbtDeserializableOptions::None)?;
+        usize::try_from(length).map_err(|_| NBTError::InvalidLength(length))
+    }
 
-        res.flatten()
+    /// Reads an unsigned varint of at most `bits` bits.
+    fn read_varint(&mut self, bits: u32) -> Result<u64> {
+        let mut val = 0u64;
+        for shift in (0..bits).step_by(7) {
+            let byte = self.read_byte()?;
+            val |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                if bits < u64::BITS && val >> bits != 0 {
+                    return Err(NBTError::InvalidVarInt);
+                }
+                return Ok(val);
+            }
+        }
+        Err(NBTError::InvalidVarInt)
     }
 
-    pub fn unpack_list<T: FromNbt<'a>>(&self, element: &NbtTapeElement<'a>) -> Option<Vec<T>> {
-        match element {
-            NbtTapeElement::List {
-                elements_pos,
-                size,
-                el_type,
-            } => {
-                let mut tape = NbtTape {
-                    data: self.data,
-                    pos: *elements_pos,
-                    depth: 0,
-                    root: None,
-                };
-                let mut elements = vec![];
-                for _ in 0..*size {
-                    let nbt_element = NbtTapeElement::parse_from_nbt(
-                        &mut tape,
-                        NbtDeserializableOptions::TagType(el_type.clone()),
-                    );
+    /// Reads a zigzag encoded varint, which is how Bedrock packets write signed numbers.
+    fn read_zigzag(&mut self, bits: u32) -> Result<i64> {
+        let val = self.read_varint(bits)?;
+        Ok((val >> 1) as i64 ^ -((val & 1) as i64))
+    }
 
-                    let element = T::from_nbt(&tape, &nbt_element).unwrap();
+    /// Reads the length prefix of a string or a compound key.
+    fn read_string_length(&mut self) -> Result<usize> {
+        match self.encoding {
+            NbtEncoding::BedrockNetwork => Ok(self.read_varint(32)? as usize),
+            _ => Ok(u16::parse_from_nbt(self, NbtDeserializableOptions::None)? as usize),
+        }
+    }
 
-                    elements.push(element);
-                }
-                Some(elements)
-            }
-            NbtTapeElement::ByteArray(data) => {
-                // I mean you wouldn't want to get the wrong type of data right?
-                let data_vec = (*data).to_vec();
-                let data = unsafe { std::mem::transmute::<Vec<i8>, Vec<T>>(data_vec) };
+    /// Reads a compound key. Keys are borrowed, so unlike string values they must be valid UTF-8.
+    fn read_key(&mut self) -> Result<&'a str> {
+        let len = self.read_string_length()?;
+        <&str>::parse_from_bytes(self.read_n_bytes(len)?)
+    }
 
-                if size_of::<T>() != size_of::<i8>() {
-                    panic!("Invalid type conversion!");
-                }
+    fn read_int_array(&mut self, len: usize) -> Result<Vec<i32>> {
+        Ok(match self.encoding {
+            NbtEncoding::BigEndian => arrays::u8_slice_to_i32_be(self.read_n_bytes(len * size_of::<i32>())?),
+            NbtEncoding::LittleEndian => arrays::u8_slice_to_i32_le(self.read_n_bytes(len * size_of::<i32>())?),
+            NbtEncoding::BedrockNetwork => (0..len)
+                .map(|_| i32::parse_from_nbt(self, NbtDeserializableOptions::None))
+                .collect::<Result<_>>()?,
+        })
+    }
 
-                // safety: there is none :) jk its a byte array so its fine
-                // todo: revisit and see if this is actually safe
-                // let data = unsafe {
-                //     std::mem::forget(data);
-                //     Vec::from_raw_parts(data.as_ptr() as *mut T, data.len(), data.len())
-                // };
+    fn read_long_array(&mut self, len: usize) -> Result<Vec<i64>> {
+        Ok(match self.encoding {
+            NbtEncoding::BigEndian => arrays::u8_slice_to_i64_be(self.read_n_bytes(len * size_of::<i64>())?),
+            NbtEncoding::LittleEndian => arrays::u8_slice_to_i64_le(self.read_n_bytes(len * size_of::<i64>())?),
+            NbtEncoding::BedrockNetwork => (0..len)
+                .map(|_| i64::parse_from_nbt(self, NbtDeserializableOptions::None))
+                .collect::<Result<_>>()?,
+        })
+    }
 
-                Some(data)
-            }
-            NbtTapeElement::IntArray(data) => {
-                let data = data.clone();
-                let data = unsafe { std::mem::transmute::<Vec<i32>, Vec<T>>(data) };
-                Some(data)
-            }
-            NbtTapeElement::LongArray(data) => {
-                let data = data.clone();
-                let data = unsafe { std::mem::transmute::<Vec<i64>, Vec<T>>(data) };
-                Some(data)
-            }
-            _ => None,
+    /// Reads a list header, rejecting non-empty lists of `End` tags since they take no space.
+    fn read_list_header(&mut self) -> Result<(NbtTag, usize)> {
+        let el_type = NbtTag::try_from(self.read_byte()?)?;
+        let size = self.read_length()?;
+        if el_type == NbtTag::End && size > 0 {
+            return Err(NBTError::InvalidTagType(NbtTag::End as u8));
         }
+        Ok((el_type, size))
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return Err(NBTError::DepthLimitExceeded(self.max_depth));
+        }
+        Ok(())
+    }
+
+    fn exit(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub fn get(&self, key: &str) -> Option<&NbtTapeElement<'a>> {
+        let res = self.root.as_ref().map(|(_, element)| element.get(key));
+
+        res.flatten()
+    }
+
+    pub fn unpack_list<T: FromNbt<'a>>(&self, element: &NbtTapeElement<'a>) -> Result<Vec<T>> {
+        match element {
+            NbtTapeElement::List { .. } => self
+                .list_elements(element)?
+                .iter()
+                .map(|element| T::from_nbt(self, element))
+                .collect(),
+            // Arrays go element by element, so asking for the wrong type is an error instead of a bad cast.
+            NbtTapeElement::ByteArray(data) => data.iter().map(|val| T::from_nbt(self, &NbtTapeElement::Byte(*val))).collect(),
+            NbtTapeElement::IntArray(data) => data.iter().map(|val| T::from_nbt(self, &NbtTapeElement::Int(*val))).collect(),
+            NbtTapeElement::LongArray(data) => data.iter().map(|val| T::from_nbt(self, &NbtTapeElement::Long(*val))).collect(),
+            _ => Err(NBTError::TypeMismatch {
+                expected: "List",
+                found: element.nbt_type(),
+            }),
+        }
+    }
+
+    /// The elements of a list.
+    pub(crate) fn list_elements(&self, element: &NbtTapeElement<'a>) -> Result<Vec<NbtTapeElement<'a>>> {
+        let NbtTapeElement::List { el_type, size, elements_pos } = element else {
+            return Err(NBTError::TypeMismatch {
+                expected: "List",
+                found: element.nbt_type(),
+            });
+        };
+
+        let mut tape = self.at(*elements_pos);
+        (0..*size)
+            .map(|_| NbtTapeElement::parse_from_nbt(&mut tape, NbtDeserializableOptions::TagType(el_type.clone())))
+            .collect()
     }
 
     pub fn unpack_list_sliced<T: NbtDeserializable<'a>>(
@@ -294,83 +431,103 @@ impl<'a> NbtTape<'a> {
 }
 impl NbtTape<'_> {
     /// Skips over a single tag based on its type.
-    fn skip_tag(&mut self, tag: u8) -> usize {
+    fn skip_tag(&mut self, tag: NbtTag) -> Result<usize> {
         let start_pos = self.pos;
-        match NbtTag::from(tag) {
+        match tag {
             NbtTag::End => {
                 // End tag has no payload.
             }
             NbtTag::Byte => {
-                self.pos += 1;
+                self.read_n_bytes(1)?;
             }
             NbtTag::Short => {
-                self.pos += 2;
+                self.read_n_bytes(2)?;
+            }
+            NbtTag::Int => {
+                i32::parse_from_nbt(self, NbtDeserializableOptions::None)?;
+            }
+            NbtTag::Long => {
+                i64::parse_from_nbt(self, NbtDeserializableOptions::None)?;
             }
-            NbtTag::Int | NbtTag::Float => {
-                self.pos += 4;
+            NbtTag::Float => {
+                self.read_n_bytes(4)?;
             }
-            NbtTag::Long | NbtTag::Double => {
-                self.pos += 8;
+            NbtTag::Double => {
+                self.read_n_bytes(8)?;
             }
             NbtTag::ByteArray => {
                 // ByteArray: 4-byte length followed by 'length' bytes.
-                let length = i32::parse_from_nbt(self, NbtDeserializableOptions::None) as usize;
-                self.pos += length;
+                let length = self.read_length()?;
+                self.read_n_bytes(length)?;
             }
             NbtTag::String => {
                 // String: 2-byte length followed by 'length' bytes.
-                let length = u16::parse_from_nbt(self, NbtDeserializableOptions::None) as usize;
-                self.pos += length;
+                let length = self.read_string_length()?;
+                self.read_n_bytes(length)?;
             }
             NbtTag::List => {
                 // List: 1-byte element type, 4-byte length, followed by elements.
-                let el_type = self.read_byte();
-                let length = i32::parse_from_nbt(self, NbtDeserializableOptions::None) as usize;
-                self.skip_list(el_type, length);
+                let (el_type, length) = self.read_list_header()?;
+                self.enter()?;
+                self.skip_list(el_type, length)?;
+                self.exit();
             }
             NbtTag::Compound => {
                 // Compound: Contains named tags until an End tag.
-                self.skip_compound();
+                self.enter()?;
+                self.skip_compound()?;
+                self.exit();
             }
             NbtTag::IntArray => {
                 // IntArray: 4-byte length followed by 'length' * 4 bytes.
-                let length = i32::parse_from_nbt(self, NbtDeserializableOptions::None) as usize;
-                self.pos += length * 4;
+                let length = self.read_length()?;
+                self.skip_array::<i32>(length)?;
             }
             NbtTag::LongArray => {
                 // LongArray: 4-byte length followed by 'length' * 8 bytes.
-                let length = i32::parse_from_nbt(self, NbtDeserializableOptions::None) as usize;
-                self.pos += length * 8;
+                let length = self.read_length()?;
+                self.skip_array::<i64>(length)?;
             }
         }
-        self.pos - start_pos
+        Ok(self.pos - start_pos)
+    }
+
+    /// Skips over the elements of an int or long array, which are varints in Bedrock packets.
+    fn skip_array<T>(&mut self, length: usize) -> Result<()> {
+        let width = size_of::<T>();
+        if self.encoding == NbtEncoding::BedrockNetwork {
+            for _ in 0..length {
+                self.read_varint(width as u32 * 8)?;
+            }
+        } else {
+            self.read_n_bytes(length * width)?;
+        }
+        Ok(())
     }
 
     /// Skips over a list's elements based on element type and length.
-    fn skip_list(&mut self, el_type: u8, length: usize) -> usize {
+    fn skip_list(&mut self, el_type: NbtTag, length: usize) -> Result<usize> {
         let start_pos = self.pos;
         for _ in 0..length {
-            self.skip_tag(el_type);
+            self.skip_tag(el_type.clone())?;
         }
-        self.pos - start_pos
+        Ok(self.pos - start_pos)
     }
 
     /// Skips over a compound's elements until an End tag is encountered.
-    fn skip_compound(&mut self) -> usize {
+    fn skip_compound(&mut self) -> Result<usize> {
         let start_pos = self.pos;
         loop {
-            let tag_byte = self.read_byte();
-            let tag = NbtTag::from(tag_byte);
+            let tag = NbtTag::try_from(self.read_byte()?)?;
             if tag == NbtTag::End {
                 break;
             }
             // Skip the name: 2-byte length + name bytes.
-            let name_length = u16::parse_from_nbt(self, NbtDeserializableOptions::None) as usize;
-            self.pos += name_length;
+            self.read_key()?;
             // Skip the tag's payload.
-            self.skip_tag(tag as u8);
+            self.skip_tag(tag)?;
         }
-        self.pos - start_pos
+        Ok(self.pos - start_pos)
     }
 }
 
@@ -379,85 +536,51 @@ pub enum NbtDeserializableOptions {
     TagType(NbtTag),
 }
 pub trait NbtDeserializable<'a>: Sized {
-    fn parse_from_bytes(data: &'a [u8]) -> Self;
-    fn parse_from_nbt(tape: &mut NbtTape<'a>, _opts: NbtDeserializableOptions) -> Self {
+    fn parse_from_bytes(data: &'a [u8]) -> Result<Self>;
+    fn parse_from_nbt(tape: &mut NbtTape<'a>, _opts: NbtDeserializableOptions) -> Result<Self> {
         //! By default, this function directly reads the bytes
         //! from the tape and BE deserializes them.
 
         // Read from current pos ~ pos + size_of::<Self>()
-        Self::parse_from_bytes(tape.read_n_bytes(size_of::<Self>()))
+        Self::parse_from_bytes(tape.read_n_bytes(size_of::<Self>())?)
     }
 }
 
 mod primitives {
-    use super::NbtDeserializable;
-    impl NbtDeserializable<'_> for i8 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            data[0] as i8
-        }
-    }
-    impl NbtDeserializable<'_> for u8 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            u8::from_be_bytes([data[0]])
-        }
-    }
-    impl NbtDeserializable<'_> for i16 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            i16::from_be_bytes([data[0], data[1]])
-        }
-    }
-
-    impl NbtDeserializable<'_> for u16 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            u16::from_be_bytes([data[0], data[1]])
-        }
-    }
-
-    impl NbtDeserializable<'_> for i32 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            i32::from_be_bytes([data[0], data[1], data[2], data[3]])
-        }
-    }
-
-    impl NbtDeserializable<'_> for u32 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            u32::from_be_bytes([data[0], data[1], data[2], data[3]])
-        }
-    }
-
-    impl NbtDeserializable<'_> for i64 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            i64::from_be_bytes([
-                data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
-            ])
-        }
-    }
+    use super::{NbtDeserializable, NbtDeserializableOptions, NbtTape};
+    use crate::{NBTError, NbtEncoding, Result};
+
+    macro_rules! impl_for_primitives {
+        ($($ty:ty $(=> $varint_bits:literal)?),*) => {
+            $(
+            impl<'a> NbtDeserializable<'a> for $ty {
+                fn parse_from_bytes(data: &'a [u8]) -> Result<Self> {
+                    Ok(<$ty>::from_be_bytes(data.try_into()?))
+                }
 
-    impl NbtDeserializable<'_> for u64 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            u64::from_be_bytes([
-                data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
-            ])
-        }
-    }
+                fn parse_from_nbt(tape: &mut NbtTape<'a>, _opts: NbtDeserializableOptions) -> Result<Self> {
+                    $(
+                    if tape.encoding == NbtEncoding::BedrockNetwork {
+                        return Ok(tape.read_zigzag($varint_bits)? as $ty);
+                    }
+                    )?
 
-    impl NbtDeserializable<'_> for f32 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            f32::from_be_bytes([data[0], data[1], data[2], data[3]])
-        }
+                    let data = tape.read_n_bytes(size_of::<Self>())?.try_into()?;
+                    Ok(match tape.encoding {
+                        NbtEncoding::BigEndian => <$ty>::from_be_bytes(data),
+                        _ => <$ty>::from_le_bytes(data),
+                    })
+                }
+            })*
+        };
     }
 
-    impl NbtDeserializable<'_> for f64 {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            f64::from_be_bytes([
-                data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
-            ])
-        }
-    }
+    // Bedrock packets write ints and longs as zigzag varints.
+    impl_for_primitives!(i8, u8, i16, u16, i32 => 32, u32 => 32, i64 => 64, u64 => 64, f32, f64);
 
     impl NbtDeserializable<'_> for bool {
-        fn parse_from_bytes(data: &[u8]) -> Self {
-            data[0] != 0
+        fn parse_from_bytes(data: &[u8]) -> Result<Self> {
+            data.first().map(|byte| *byte != 0).ok_or(NBTError::ReachedEOF)
         }
     }
 }
@@ -466,61 +589,58 @@ mod taped {
     use super::*;
 
     impl<'a> NbtDeserializable<'a> for NbtTapeElement<'a> {
-        fn parse_from_bytes(data: &'a [u8]) -> Self {
+        fn parse_from_bytes(data: &'a [u8]) -> Result<Self> {
             let mut tape = NbtTape::new(data);
             let opts = NbtDeserializableOptions::TagType(NbtTag::Compound);
             Self::parse_from_nbt(&mut tape, opts)
         }
 
-        fn parse_from_nbt(tape: &mut NbtTape<'a>, opts: NbtDeserializableOptions) -> Self {
-            let tag = match opts {
-                NbtDeserializableOptions::None => panic!("NbtTapeElement must have a tag type!"),
-                NbtDeserializableOptions::TagType(tag) => tag,
+        fn parse_from_nbt(tape: &mut NbtTape<'a>, opts: NbtDeserializableOptions) -> Result<Self> {
+            let NbtDeserializableOptions::TagType(tag) = opts else {
+                return Err(NBTError::MissingNbtValue);
             };
-            match tag {
+            Ok(match tag {
                 NbtTag::End => NbtTapeElement::End,
                 NbtTag::Byte => {
-                    NbtTapeElement::Byte(i8::parse_from_nbt(tape, NbtDeserializableOptions::None))
+                    NbtTapeElement::Byte(i8::parse_from_nbt(tape, NbtDeserializableOptions::None)?)
                 }
                 NbtTag::Short => {
-                    NbtTapeElement::Short(i16::parse_from_nbt(tape, NbtDeserializableOptions::None))
+                    NbtTapeElement::Short(i16::parse_from_nbt(tape, NbtDeserializableOptions::None)?)
                 }
                 NbtTag::Int => {
-                    NbtTapeElement::Int(i32::parse_from_nbt(tape, NbtDeserializableOptions::None))
+                    NbtTapeElement::Int(i32::parse_from_nbt(tape, NbtDeserializableOptions::None)?)
                 }
                 NbtTag::Long => {
-                    NbtTapeElement::Long(i64::parse_from_nbt(tape, NbtDeserializableOptions::None))
+                    NbtTapeElement::Long(i64::parse_from_nbt(tape, NbtDeserializableOptions::None)?)
                 }
                 NbtTag::Float => {
-                    NbtTapeElement::Float(f32::parse_from_nbt(tape, NbtDeserializableOptions::None))
+                    NbtTapeElement::Float(f32::parse_from_nbt(tape, NbtDeserializableOptions::None)?)
                 }
                 NbtTag::Double => NbtTapeElement::Double(f64::parse_from_nbt(
                     tape,
                     NbtDeserializableOptions::None,
-                )),
+                )?),
                 NbtTag::ByteArray => {
-                    let len = i32::parse_from_nbt(tape, NbtDeserializableOptions::None) as usize;
-                    let data = tape.read_n_bytes(len);
+                    let len = tape.read_length()?;
+                    let data = tape.read_n_bytes(len)?;
                     let data = arrays::u8_slice_to_i8(data);
                     NbtTapeElement::ByteArray(data)
                 }
                 NbtTag::String => {
-                    let len = u16::parse_from_nbt(tape, NbtDeserializableOptions::None) as usize;
-                    let data = tape.read_n_bytes(len);
-                    // SAFETY: The string is of len and is valid utf8
-                    let data = unsafe { std::str::from_utf8_unchecked(data) };
-                    NbtTapeElement::String(data)
+                    let len = tape.read_string_length()?;
+                    let data = tape.read_n_bytes(len)?;
+                    NbtTapeElement::String(decode_java_string(data)?)
                 }
                 NbtTag::List => {
-                    let el_type = tape.read_byte();
-                    let size = i32::parse_from_nbt(tape, NbtDeserializableOptions::None) as usize;
+                    let (el_type, size) = tape.read_list_header()?;
 
                     let elements_pos = tape.pos;
 
-                    // Skip the list's elements
-                    tape.skip_list(el_type, size);
+                    // Skip the list's elements, checking them on the way
+                    tape.enter()?;
+                    tape.skip_list(el_type.clone(), size)?;
+                    tape.exit();
 
-                    let el_type = NbtTag::from(el_type);
                     NbtTapeElement::List {
                         el_type,
                         size,
@@ -528,38 +648,229 @@ mod taped {
                     }
                 }
                 NbtTag::Compound => {
-                    tape.depth += 1;
+                    tape.enter()?;
                     let mut elements = vec![];
                     loop {
-                        let tag = NbtTag::from(tape.read_byte());
+                        let tag = NbtTag::try_from(tape.read_byte()?)?;
                         if tag == NbtTag::End {
-                            tape.depth -= 1;
+                            tape.exit();
 
-                            return NbtTapeElement::Compound(elements);
+                            return Ok(NbtTapeElement::Compound(elements));
                         }
 
-                        let name = <&str>::parse_from_nbt(tape, NbtDeserializableOptions::None);
+                        let name = tape.read_key()?;
                         let element = NbtTapeElement::parse_from_nbt(
                             tape,
                             NbtDeserializableOptions::TagType(tag),
-                        );
+                        )?;
                         elements.push((name, element));
                     }
                 }
                 NbtTag::IntArray => {
-                    let len = i32::parse_from_nbt(tape, NbtDeserializableOptions::None) as usize;
-                    let data = tape.read_n_bytes(len * size_of::<i32>());
-                    let data = arrays::u8_slice_to_i32_be(data);
-                    NbtTapeElement::IntArray(data)
+                    let len = tape.read_length()?;
+                    NbtTapeElement::IntArray(tape.read_int_array(len)?)
                 }
                 NbtTag::LongArray => {
-                    let len = i32::parse_from_nbt(tape, NbtDeserializableOptions::None) as usize;
-                    let data = tape.read_n_bytes(len * size_of::<i64>());
-                    let data = arrays::u8_slice_to_i64_be(data);
-                    NbtTapeElement::LongArray(data)
+                    let len = tape.read_length()?;
+                    NbtTapeElement::LongArray(tape.read_long_array(len)?)
                 }
+            })
+        }
+    }
+
+    /// Decodes a string, falling back to the "modified UTF-8" Java writes NBT with when it isn't
+    /// valid UTF-8. Java stores characters outside the BMP as two 3-byte surrogates, and NUL as
+    /// `C0 80`, neither of which is valid UTF-8.
+    fn decode_java_string(data: &[u8]) -> Result<Cow<'_, str>> {
+        let error = match std::str::from_utf8(data) {
+            Ok(string) => return Ok(Cow::Borrowed(string)),
+            Err(error) => error,
+        };
+
+        let continuation = |i: usize| match data.get(i) {
+            Some(byte) if byte & 0xC0 == 0x80 => Ok(u16::from(byte & 0x3F)),
+            _ => Err(NBTError::InvalidUTF8(error)),
+        };
+
+        let mut units = Vec::with_capacity(data.len());
+        let mut i = 0;
+        while i < data.len() {
+            let byte = data[i];
+            let (unit, width) = match byte {
+                0x00..=0x7F => (u16::from(byte), 1),
+                0xC0..=0xDF => ((u16::from(byte & 0x1F) << 6) | continuation(i + 1)?, 2),
+                0xE0..=0xEF => (
+                    (u16::from(byte & 0x0F) << 12) | (continuation(i + 1)? << 6) | continuation(i + 2)?,
+                    3,
+                ),
+                _ => return Err(NBTError::InvalidUTF8(error)),
+            };
+            units.push(unit);
+            i += width;
+        }
+
+        String::from_utf16(&units)
+            .map(Cow::Owned)
+            .map_err(|_| NBTError::InvalidUTF8(error))
+    }
+}
+
+mod query {
+    use super::*;
+
+    /// One step of a query path.
+    enum Segment<'p> {
+        Key(&'p str),
+        Index(usize),
+    }
+
+    /// Splits a path like `sections[3].block_states.palette` into its steps. Like
+    /// [get_path](crate::NbtCompound::get_path), `sections.3` indexes the list as well.
+    fn parse_path(path: &str) -> Result<Vec<Segment<'_>>> {
+        let invalid = || NBTError::InvalidPath(path.to_string());
+
+        let mut segments = vec![];
+        if path.is_empty() {
+            return Ok(segments);
+        }
+
+        for part in path.split('.') {
+            let (key, mut indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+            if !key.is_empty() {
+                segments.push(Segment::Key(key));
+            } else if indices.is_empty() {
+                return Err(invalid());
+            }
+
+            while !indices.is_empty() {
+                let end = indices.find(']').ok_or_else(invalid)?;
+                let index = indices[1..end].parse().map_err(|_| invalid())?;
+                segments.push(Segment::Index(index));
+
+                indices = &indices[end + 1..];
+                if !indices.is_empty() && !indices.starts_with('[') {
+                    return Err(invalid());
+                }
+            }
+        }
+
+        Ok(segments)
+    }
+
+    impl<'a> NbtTape<'a> {
+        /// Looks up the value at `path`, e.g. `sections[3].block_states.palette`, straight from
+        /// the data. The tape doesn't need to be [parsed](NbtTape::parse) first.
+        ///
+        /// Only the value itself is decoded. Everything before it is skipped over, and nothing
+        /// after it is read at all, so data that's malformed further on isn't noticed here.
+        /// Keys containing `.` or `[` can't be looked up this way.
+        pub fn query(&self, path: &str) -> Result<Option<NbtTapeElement<'a>>> {
+            let segments = parse_path(path)?;
+            let mut tape = self.at(0);
+
+            let Some(tag) = tape.find(&segments)? else {
+                return Ok(None);
+            };
+            NbtTapeElement::parse_from_nbt(&mut tape, NbtDeserializableOptions::TagType(tag)).map(Some)
+        }
+
+        /// Like [query](NbtTape::query), but converts the value, and a missing value is an error.
+        ///
+        /// The value is dropped once converted, so read int and long arrays as `Vec`s rather than
+        /// borrowed slices, which would point into it.
+        ///
+        /// ```ignore
+        /// let palette: Vec<BlockState> = tape.query_as("sections[3].block_states.palette")?;
+        /// ```
+        pub fn query_as<T: FromNbt<'a>>(&self, path: &str) -> Result<T> {
+            let element = self
+                .query(path)?
+                .ok_or_else(|| NBTError::PathNotFound(path.to_string()))?;
+            T::from_nbt(self, &element)
+        }
+
+        /// The keys of the compound at `path`, in order, without decoding their values. An empty
+        /// path is the root compound.
+        pub fn keys(&self, path: &str) -> Result<Vec<&'a str>> {
+            let segments = parse_path(path)?;
+            let mut tape = self.at(0);
+
+            let tag = tape
+                .find(&segments)?
+                .ok_or_else(|| NBTError::PathNotFound(path.to_string()))?;
+            if tag != NbtTag::Compound {
+                return Err(NBTError::TypeMismatch {
+                    expected: "Compound",
+                    found: tag.nbt_type(),
+                });
+            }
+
+            tape.enter()?;
+            let mut keys = vec![];
+            loop {
+                let tag = NbtTag::try_from(tape.read_byte()?)?;
+                if tag == NbtTag::End {
+                    return Ok(keys);
+                }
+                keys.push(tape.read_key()?);
+                tape.skip_tag(tag)?;
+            }
+        }
+
+        /// Moves to the payload of the value at `segments`, starting from the root compound, and
+        /// returns its tag.
+        fn find(&mut self, segments: &[Segment]) -> Result<Option<NbtTag>> {
+            let tag = self.read_byte()?;
+            if tag != NbtTag::Compound as u8 {
+                return Err(NBTError::InvalidRootCompound(tag));
+            }
+            self.read_key()?;
+
+            let mut tag = NbtTag::Compound;
+            for segment in segments {
+                self.enter()?;
+                let found = match (&tag, segment) {
+                    (NbtTag::Compound, Segment::Key(key)) => self.find_key(key)?,
+                    (NbtTag::List, Segment::Index(index)) => self.find_index(*index)?,
+                    (NbtTag::List, Segment::Key(key)) => match key.parse() {
+                        Ok(index) => self.find_index(index)?,
+                        Err(_) => None,
+                    },
+                    _ => None,
+                };
+
+                match found {
+                    Some(found) => tag = found,
+                    None => return Ok(None),
+                }
+            }
+
+            Ok(Some(tag))
+        }
+
+        /// Skips entries of a compound until the one named `key`.
+        fn find_key(&mut self, key: &str) -> Result<Option<NbtTag>> {
+            loop {
+                let tag = NbtTag::try_from(self.read_byte()?)?;
+                if tag == NbtTag::End {
+                    return Ok(None);
+                }
+                if self.read_key()? == key {
+                    return Ok(Some(tag));
+                }
+                self.skip_tag(tag)?;
             }
         }
+
+        /// Skips elements of a list until the one at `index`.
+        fn find_index(&mut self, index: usize) -> Result<Option<NbtTag>> {
+            let (el_type, size) = self.read_list_header()?;
+            if index >= size {
+                return Ok(None);
+            }
+            self.skip_list(el_type.clone(), index)?;
+            Ok(Some(el_type))
+        }
     }
 }
 
@@ -567,34 +878,26 @@ mod general {
     use super::*;
 
     impl<'a> NbtDeserializable<'a> for String {
-        fn parse_from_bytes(data: &'a [u8]) -> Self {
+        fn parse_from_bytes(data: &'a [u8]) -> Result<Self> {
             //! Mustn't call this function with length prefixed data. Must only be the string itself.
             //! Look at the implementation of `<&str>::parse_from_bytes` for more detail!
-            // SAFETY: The string is valid utf8, unless ofc, you mess up the call.
-            unsafe { std::str::from_utf8_unchecked(data) }.to_string()
+            Ok(<&str>::parse_from_bytes(data)?.to_string())
         }
 
-        fn parse_from_nbt(tape: &mut NbtTape<'a>, _opts: NbtDeserializableOptions) -> Self {
-            <&str>::parse_from_nbt(tape, NbtDeserializableOptions::None).to_string()
+        fn parse_from_nbt(tape: &mut NbtTape<'a>, _opts: NbtDeserializableOptions) -> Result<Self> {
+            Ok(<&str>::parse_from_nbt(tape, NbtDeserializableOptions::None)?.to_string())
         }
     }
 
     impl<'a> NbtDeserializable<'a> for &'a str {
-        fn parse_from_bytes(data: &'a [u8]) -> Self {
+        fn parse_from_bytes(data: &'a [u8]) -> Result<Self> {
             // This function must be called with the length buffer exactly of the string.
             // The data must NOT be length prefixed. Just the plain utf data.
-            // (I don't know why I made it like this lmfao)
-            // SAFETY: The string is valid utf8. Not length prefixed!
-            unsafe { std::str::from_utf8_unchecked(data) }
+            Ok(std::str::from_utf8(data)?)
         }
 
-        fn parse_from_nbt(tape: &mut NbtTape<'a>, _opts: NbtDeserializableOptions) -> Self {
-            let len = u16::parse_from_nbt(tape, NbtDeserializableOptions::None) as usize;
-            if len == 0 {
-                return "";
-            }
-            let data = tape.read_n_bytes(len);
-            Self::parse_from_bytes(data)
+        fn parse_from_nbt(tape: &mut NbtTape<'a>, _opts: NbtDeserializableOptions) -> Result<Self> {
+            tape.read_key()
         }
     }
 }
@@ -673,7 +976,7 @@ impl NbtTapeElement<'_> {
                 Ok(())
             }
             NbtTapeElement::String(data) => {
-                data.serialize(writer, &NBTSerializeOptions::None);
+                data.as_ref().serialize(writer, &NBTSerializeOptions::None);
                 /*let data = data.as_bytes();
                 (data.len() as u16).serialize(writer, &NBTSerializeOptions::None);
                 writer.write_all(data)?;*/
@@ -719,7 +1022,8 @@ impl NbtTapeElement<'_> {
                     let element = NbtTapeElement::parse_from_nbt(
                         tape,
                         NbtDeserializableOptions::TagType(el_type.clone()),
-                    );
+                    )
+                    .map_err(|err| NetEncodeError::ExternalError(Box::new(err)))?;
                     element.serialize_as_network(tape, writer, &NBTSerializeOptions::None)?;
                 }
 
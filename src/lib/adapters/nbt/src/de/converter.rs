@@ -9,6 +9,7 @@ mod primitives {
     use super::*;
     use crate::de::borrow::NbtDeserializable;
     use uuid::Uuid;
+    use std::borrow::Cow;
     use std::str::FromStr;
 
     macro_rules! impl_for_primitives {
@@ -59,7 +60,8 @@ mod primitives {
     impl<'a> FromNbt<'a> for &'a str {
         fn from_nbt(_tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> Result<Self> {
             match element {
-                NbtTapeElement::String(val) => Ok(val),
+                NbtTapeElement::String(Cow::Borrowed(val)) => Ok(val),
+                NbtTapeElement::String(Cow::Owned(_)) => Err(NBTError::UnborrowableString),
                 _ => Err(NBTError::TypeMismatch {
                     expected: "String",
                     found: element.nbt_type(),
@@ -82,13 +84,7 @@ mod primitives {
 
     impl<'a, T: FromNbt<'a>> FromNbt<'a> for Vec<T> {
         fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> Result<Self> {
-            match tapes.unpack_list::<T>(element) {
-                Some(vec) => Ok(vec),
-                None => Err(NBTError::TypeMismatch {
-                    expected: "List",
-                    found: element.nbt_type(),
-                }),
-            }
+            tapes.unpack_list::<T>(element)
         }
     }
 
@@ -108,6 +104,10 @@ mod primitives {
     impl<'a, T: FromNbt<'a>> FromNbt<'a> for Option<T> {
         fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> Result<Self> {
             // handle optionals yourself lol (jk they're handled by the derive macro :p)
+            // An `End` is how network NBT says there's nothing there.
+            if let NbtTapeElement::End = element {
+                return Ok(None);
+            }
             Ok(Some(T::from_nbt(tapes, element)?))
         }
     }
@@ -197,7 +197,7 @@ mod test_map {
         };
 
         let mut tapes = crate::de::borrow::NbtTape::new(&data);
-        tapes.parse();
+        tapes.parse().unwrap();
         let root = tapes.root.as_ref().map(|(_, b)| b).unwrap();
         let hashmap = HashMap::<&str, i32>::from_nbt(&tapes, root).unwrap();
 
@@ -219,7 +219,7 @@ mod test_map {
         };
 
         let mut tapes = crate::de::borrow::NbtTape::new(&data);
-        tapes.parse();
+        tapes.parse().unwrap();
         let root = tapes.root.as_ref().map(|(_, b)| b).unwrap();
         let btreemap = std::collections::BTreeMap::<&str, i32>::from_nbt(&tapes, root).unwrap();
 
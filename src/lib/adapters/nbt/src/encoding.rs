@@ -0,0 +1,128 @@
+//! The byte layouts NBT comes in.
+//!
+//! Java Edition writes everything big-endian. Bedrock writes little-endian, and its packets also
+//! write ints, longs and all lengths as varints.
+//!
+//! ```ignore
+//! let mut tape = NbtTape::new(&data).with_encoding(NbtEncoding::LittleEndian);
+//! tape.parse()?;
+//! let level = NbtCompound::from_tape(&tape)?;
+//!
+//! let bytes = level.to_encoded_bytes("", NbtEncoding::LittleEndian);
+//! ```
+
+use ferrumc_general_purpose::simd::arrays;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NbtEncoding {
+    /// Java Edition, on disk and over the network.
+    #[default]
+    BigEndian,
+    /// Bedrock files, like `level.dat` and the values in its LevelDB.
+    LittleEndian,
+    /// Bedrock packets. Little-endian, except that ints, longs and the lengths of lists and
+    /// arrays are zigzag varints, and string lengths are unsigned varints.
+    BedrockNetwork,
+}
+
+impl NbtEncoding {
+    pub(crate) fn write_i8(self, buf: &mut Vec<u8>, val: i8) {
+        buf.push(val as u8);
+    }
+
+    pub(crate) fn write_i16(self, buf: &mut Vec<u8>, val: i16) {
+        match self {
+            NbtEncoding::BigEndian => buf.extend_from_slice(&val.to_be_bytes()),
+            _ => buf.extend_from_slice(&val.to_le_bytes()),
+        }
+    }
+
+    pub(crate) fn write_i32(self, buf: &mut Vec<u8>, val: i32) {
+        match self {
+            NbtEncoding::BigEndian => buf.extend_from_slice(&val.to_be_bytes()),
+            NbtEncoding::LittleEndian => buf.extend_from_slice(&val.to_le_bytes()),
+            NbtEncoding::BedrockNetwork => write_varint(buf, u64::from(((val << 1) ^ (val >> 31)) as u32)),
+        }
+    }
+
+    pub(crate) fn write_i64(self, buf: &mut Vec<u8>, val: i64) {
+        match self {
+            NbtEncoding::BigEndian => buf.extend_from_slice(&val.to_be_bytes()),
+            NbtEncoding::LittleEndian => buf.extend_from_slice(&val.to_le_bytes()),
+            NbtEncoding::BedrockNetwork => write_varint(buf, ((val << 1) ^ (val >> 63)) as u64),
+        }
+    }
+
+    pub(crate) fn write_f32(self, buf: &mut Vec<u8>, val: f32) {
+        match self {
+            NbtEncoding::BigEndian => buf.extend_from_slice(&val.to_be_bytes()),
+            _ => buf.extend_from_slice(&val.to_le_bytes()),
+        }
+    }
+
+    pub(crate) fn write_f64(self, buf: &mut Vec<u8>, val: f64) {
+        match self {
+            NbtEncoding::BigEndian => buf.extend_from_slice(&val.to_be_bytes()),
+            _ => buf.extend_from_slice(&val.to_le_bytes()),
+        }
+    }
+
+    /// Writes the length of a list or an array.
+    pub(crate) fn write_length(self, buf: &mut Vec<u8>, len: usize) {
+        self.write_i32(buf, len as i32);
+    }
+
+    /// Writes a length prefixed string, which is also how compound keys are written.
+    ///
+    /// The length of a Java string is a `u16`, so longer strings are cut off at the last character
+    /// that fits, rather than writing a length that doesn't match the data. That's a bug in the
+    /// caller, and debug builds panic on it.
+    pub(crate) fn write_string(self, buf: &mut Vec<u8>, val: &str) {
+        let mut bytes = val.as_bytes();
+        if self != NbtEncoding::BedrockNetwork && bytes.len() > u16::MAX as usize {
+            debug_assert!(false, "NBT string of {} bytes is longer than {}", bytes.len(), u16::MAX);
+            let end = (0..=u16::MAX as usize)
+                .rev()
+                .find(|&end| val.is_char_boundary(end))
+                .unwrap_or(0);
+            bytes = &bytes[..end];
+        }
+        match self {
+            NbtEncoding::BigEndian => buf.extend_from_slice(&(bytes.len() as u16).to_be_bytes()),
+            NbtEncoding::LittleEndian => buf.extend_from_slice(&(bytes.len() as u16).to_le_bytes()),
+            NbtEncoding::BedrockNetwork => write_varint(buf, bytes.len() as u64),
+        }
+        buf.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn write_int_array(self, buf: &mut Vec<u8>, data: &[i32]) {
+        self.write_length(buf, data.len());
+
+        // Same size, and every bit pattern is valid for both.
+        let data = unsafe { std::mem::transmute::<&[i32], &[u32]>(data) };
+        match self {
+            NbtEncoding::BigEndian => arrays::extend_u8_from_u32_be(buf, data),
+            NbtEncoding::LittleEndian => arrays::extend_u8_from_u32_le(buf, data),
+            NbtEncoding::BedrockNetwork => data.iter().for_each(|val| self.write_i32(buf, *val as i32)),
+        }
+    }
+
+    pub(crate) fn write_long_array(self, buf: &mut Vec<u8>, data: &[i64]) {
+        self.write_length(buf, data.len());
+
+        let data = unsafe { std::mem::transmute::<&[i64], &[u64]>(data) };
+        match self {
+            NbtEncoding::BigEndian => arrays::extend_u8_from_u64_be(buf, data),
+            NbtEncoding::LittleEndian => arrays::extend_u8_from_u64_le(buf, data),
+            NbtEncoding::BedrockNetwork => data.iter().for_each(|val| self.write_i64(buf, *val as i64)),
+        }
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut val: u64) {
+    while val >= 0x80 {
+        buf.push(val as u8 | 0x80);
+        val >>= 7;
+    }
+    buf.push(val as u8);
+}
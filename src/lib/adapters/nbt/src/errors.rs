@@ -35,4 +35,38 @@ pub enum NBTError {
     NoRootTag,
     #[error("Element `{0}` not found in NBT data")]
     ElementNotFound(&'static str),
+    #[error("Invalid length in NBT data: {0}")]
+    InvalidLength(i32),
+    #[error("Invalid varint in NBT data")]
+    InvalidVarInt,
+    #[error("NBT data is nested deeper than the limit of {0}")]
+    DepthLimitExceeded(usize),
+    #[error("NBT data is larger than the limit of {0} bytes")]
+    SizeLimitExceeded(usize),
+    #[error("The string was written with Java's modified UTF-8 and can't be borrowed")]
+    UnborrowableString,
+    #[error("{0}")]
+    Serde(String),
+    #[error("Invalid NBT path: `{0}`")]
+    InvalidPath(String),
+    #[error("Nothing found at `{0}` in NBT data")]
+    PathNotFound(String),
+    #[error("Invalid SNBT at line {line}, column {column}: {message}")]
+    InvalidSnbt {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl serde::ser::Error for NBTError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        NBTError::Serde(msg.to_string())
+    }
+}
+
+impl serde::de::Error for NBTError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        NBTError::Serde(msg.to_string())
+    }
 }
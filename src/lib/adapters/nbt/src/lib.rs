@@ -3,15 +3,24 @@
 extern crate core;
 
 pub mod de;
+pub mod encoding;
 pub mod errors;
+pub mod network;
 pub mod ser;
+pub mod serde_backend;
+pub mod snbt;
+pub mod value;
 
 pub type Result<T> = std::result::Result<T, NBTError>;
 
 pub use de::borrow::{NbtTape, NbtTapeElement};
 pub use de::converter::FromNbt;
+pub use encoding::NbtEncoding;
 pub use errors::NBTError;
+pub use network::Nbt;
 pub use ser::{NBTSerializable, NBTSerializeOptions};
+pub use serde_backend::{from_slice, from_value, to_bytes, to_value, SerdeOptions};
+pub use value::{NbtCompound, NbtValue};
 
 pub fn decompress_gzip(data: &[u8]) -> Result<Vec<u8>> {
     use libflate::gzip::Decoder;
@@ -0,0 +1,242 @@
+//! Network NBT inside packets.
+//!
+//! Packets don't say how long their NBT is, so [Nbt] finds the end by walking the tags while it
+//! reads, then parses the bytes with [NbtTape::parse_network].
+
+use crate::de::borrow::{NbtTag, MAX_DEPTH, MAX_SIZE};
+use crate::{FromNbt, NBTError, NBTSerializable, NBTSerializeOptions, NbtTape, Result};
+use ferrumc_net_codec::decode::errors::NetDecodeError;
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// A packet field holding network NBT, e.g. a text component or item data.
+///
+/// `T` is written with [NBTSerializeOptions::Network] and read with [FromNbt], so anything with
+/// the NBT derives works, as do [NbtCompound](crate::NbtCompound) and [NbtValue](crate::NbtValue).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Nbt<T>(pub T);
+
+impl<T> Nbt<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Nbt<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> std::ops::Deref for Nbt<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Nbt<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: NBTSerializable> Nbt<T> {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.0.serialize(&mut buf, &NBTSerializeOptions::Network);
+        buf
+    }
+}
+
+impl<T: for<'a> FromNbt<'a>> Nbt<T> {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut tape = NbtTape::new(data);
+        tape.parse_network()?;
+        let (_, root) = tape.root.as_ref().ok_or(NBTError::NoRootTag)?;
+        T::from_nbt(&tape, root).map(Self)
+    }
+}
+
+impl<T: NBTSerializable> NetEncode for Nbt<T> {
+    fn encode<W: Write>(&self, writer: &mut W, _: &NetEncodeOpts) -> NetEncodeResult<()> {
+        writer.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, _: &NetEncodeOpts) -> NetEncodeResult<()> {
+        writer.write_all(&self.to_bytes()).await?;
+        Ok(())
+    }
+}
+
+impl<T: for<'a> FromNbt<'a>> NetDecode for Nbt<T> {
+    fn decode<R: Read>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let mut scanner = NetworkNbtScanner::default();
+        let mut buf = Vec::new();
+        while let Some(n) = scanner.advance(&buf).map_err(external)? {
+            let start = buf.len();
+            buf.resize(start + n, 0);
+            reader.read_exact(&mut buf[start..])?;
+        }
+
+        Self::from_bytes(&buf).map_err(external)
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let mut scanner = NetworkNbtScanner::default();
+        let mut buf = Vec::new();
+        while let Some(n) = scanner.advance(&buf).map_err(external)? {
+            let start = buf.len();
+            buf.resize(start + n, 0);
+            reader.read_exact(&mut buf[start..]).await?;
+        }
+
+        Self::from_bytes(&buf).map_err(external)
+    }
+}
+
+fn external(err: NBTError) -> NetDecodeError {
+    NetDecodeError::ExternalError(Box::new(err))
+}
+
+/// What the bytes asked for last are.
+#[derive(Debug, Default)]
+enum Step {
+    #[default]
+    Start,
+    RootTag,
+    Payload(NbtTag),
+    ArrayLength(usize),
+    StringLength,
+    ListHeader,
+    EntryTag,
+    EntryNameLength(NbtTag),
+    EntryName(NbtTag),
+    Value,
+}
+
+#[derive(Debug)]
+enum Frame {
+    Compound,
+    List { el_type: NbtTag, remaining: usize },
+}
+
+/// Walks network NBT as it's read, without recursion, so the same code serves blocking and async readers.
+///
+/// [advance](Self::advance) is given everything read so far and says how many more bytes to read,
+/// or `None` once the NBT is complete. It enforces the same limits as [NbtTape].
+#[derive(Debug, Default)]
+struct NetworkNbtScanner {
+    step: Step,
+    requested: usize,
+    stack: Vec<Frame>,
+}
+
+impl NetworkNbtScanner {
+    fn advance(&mut self, buf: &[u8]) -> Result<Option<usize>> {
+        // Only the steps right after a request look at what was read.
+        let read = &buf[buf.len() - self.requested..];
+        loop {
+            match std::mem::take(&mut self.step) {
+                Step::Start => return self.request(buf, 1, Step::RootTag),
+                Step::RootTag => {
+                    let tag = NbtTag::try_from(read[0])?;
+                    self.step = if tag == NbtTag::End { Step::Value } else { Step::Payload(tag) };
+                }
+                Step::Payload(tag) => {
+                    self.step = match tag {
+                        NbtTag::End => Step::Value,
+                        NbtTag::Byte => return self.request(buf, 1, Step::Value),
+                        NbtTag::Short => return self.request(buf, 2, Step::Value),
+                        NbtTag::Int | NbtTag::Float => return self.request(buf, 4, Step::Value),
+                        NbtTag::Long | NbtTag::Double => return self.request(buf, 8, Step::Value),
+                        NbtTag::ByteArray => return self.request(buf, 4, Step::ArrayLength(1)),
+                        NbtTag::IntArray => return self.request(buf, 4, Step::ArrayLength(4)),
+                        NbtTag::LongArray => return self.request(buf, 4, Step::ArrayLength(8)),
+                        NbtTag::String => return self.request(buf, 2, Step::StringLength),
+                        NbtTag::List => return self.request(buf, 5, Step::ListHeader),
+                        NbtTag::Compound => {
+                            self.enter(Frame::Compound)?;
+                            return self.request(buf, 1, Step::EntryTag);
+                        }
+                    };
+                }
+                Step::ArrayLength(size) => {
+                    let length = read_length(read)?;
+                    return self.request(buf, length * size, Step::Value);
+                }
+                Step::StringLength => {
+                    let length = u16::from_be_bytes([read[0], read[1]]) as usize;
+                    return self.request(buf, length, Step::Value);
+                }
+                Step::ListHeader => {
+                    let el_type = NbtTag::try_from(read[0])?;
+                    let remaining = read_length(&read[1..])?;
+                    if el_type == NbtTag::End && remaining > 0 {
+                        return Err(NBTError::InvalidTagType(NbtTag::End as u8));
+                    }
+                    self.enter(Frame::List { el_type, remaining })?;
+                    self.step = Step::Value;
+                }
+                Step::EntryTag => {
+                    let tag = NbtTag::try_from(read[0])?;
+                    if tag != NbtTag::End {
+                        return self.request(buf, 2, Step::EntryNameLength(tag));
+                    }
+                    self.stack.pop();
+                    self.step = Step::Value;
+                }
+                Step::EntryNameLength(tag) => {
+                    let length = u16::from_be_bytes([read[0], read[1]]) as usize;
+                    return self.request(buf, length, Step::EntryName(tag));
+                }
+                Step::EntryName(tag) => self.step = Step::Payload(tag),
+                // A value just ended, move on to whatever contains it.
+                Step::Value => match self.stack.last_mut() {
+                    None => return Ok(None),
+                    Some(Frame::Compound) => return self.request(buf, 1, Step::EntryTag),
+                    Some(Frame::List { el_type, remaining }) => {
+                        if *remaining == 0 {
+                            self.stack.pop();
+                            self.step = Step::Value;
+                        } else {
+                            *remaining -= 1;
+                            self.step = Step::Payload(el_type.clone());
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Asks for `n` more bytes, which `step` then handles.
+    fn request(&mut self, buf: &[u8], n: usize, step: Step) -> Result<Option<usize>> {
+        if buf.len().saturating_add(n) > MAX_SIZE {
+            return Err(NBTError::SizeLimitExceeded(MAX_SIZE));
+        }
+        self.step = step;
+        self.requested = n;
+        Ok(Some(n))
+    }
+
+    fn enter(&mut self, frame: Frame) -> Result<()> {
+        if self.stack.len() >= MAX_DEPTH {
+            return Err(NBTError::DepthLimitExceeded(MAX_DEPTH));
+        }
+        self.stack.push(frame);
+        Ok(())
+    }
+}
+
+fn read_length(data: &[u8]) -> Result<usize> {
+    let length = i32::from_be_bytes(data[..4].try_into()?);
+    usize::try_from(length).map_err(|_| NBTError::InvalidLength(length))
+}
@@ -1,22 +1,35 @@
 use std::collections::HashMap;
-use ferrumc_general_purpose::simd::arrays;
 use super::{NBTSerializable, NBTSerializeOptions};
+use crate::NbtEncoding;
 use uuid::Uuid;
 
 macro_rules! impl_ser_primitives {
-    ($($($ty:ty) | * > $id:expr),*) => {
-        $($(
+    ($($ty:ty $(| $alt:ty)? > $id:expr, $write:ident),*) => {
+        $(
             impl NBTSerializable for $ty {
-                fn serialize(&self, buf: &mut Vec<u8>, options: & NBTSerializeOptions<'_> ) {
-                    write_header::<Self>(buf, options);
-                    buf.extend_from_slice(&self.to_be_bytes());
+                fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+                    write_header::<Self>(buf, options, encoding);
+                    encoding.$write(buf, *self);
                 }
 
                 fn id() -> u8 {
                     $id
                 }
             }
-        )?)*
+
+            $(
+                impl NBTSerializable for $alt {
+                    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+                        write_header::<Self>(buf, options, encoding);
+                        encoding.$write(buf, *self as $ty);
+                    }
+
+                    fn id() -> u8 {
+                        $id
+                    }
+                }
+            )?
+        )*
     };
 }
 
@@ -34,20 +47,20 @@ const TAG_INT_ARRAY: u8 = 11;
 const TAG_LONG_ARRAY: u8 = 12;
 
 impl_ser_primitives!(
-    i8 | u8 > TAG_BYTE,
-    i16 | u16 > TAG_SHORT,
-    i32 | u32 > TAG_INT,
-    i64 | u64 > TAG_LONG,
-    f32 > TAG_FLOAT,
-    f64 > TAG_DOUBLE
+    i8 | u8 > TAG_BYTE, write_i8,
+    i16 | u16 > TAG_SHORT, write_i16,
+    i32 | u32 > TAG_INT, write_i32,
+    i64 | u64 > TAG_LONG, write_i64,
+    f32 > TAG_FLOAT, write_f32,
+    f64 > TAG_DOUBLE, write_f64
 );
 
 impl<T> NBTSerializable for Box<T>
 where
     T: NBTSerializable,
 {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-        T::serialize(&*self, buf, options);
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        T::serialize_encoded(&*self, buf, options, encoding);
     }
 
     fn id() -> u8 {
@@ -56,8 +69,8 @@ where
 }
 
 impl NBTSerializable for bool {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-        write_header::<Self>(buf, options);
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        write_header::<Self>(buf, options, encoding);
         buf.push(if *self { 1 } else { 0 });
     }
 
@@ -67,8 +80,8 @@ impl NBTSerializable for bool {
 }
 
 impl NBTSerializable for String {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-        self.as_str().serialize(buf, options);
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        self.as_str().serialize_encoded(buf, options, encoding);
     }
 
     fn id() -> u8 {
@@ -77,8 +90,8 @@ impl NBTSerializable for String {
 }
 
 impl NBTSerializable for Uuid {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-        NBTSerializable::serialize(&self.as_hyphenated().to_string().as_str(), buf, options);
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        NBTSerializable::serialize_encoded(&self.as_hyphenated().to_string().as_str(), buf, options, encoding);
     }
 
     fn id() -> u8 {
@@ -87,11 +100,9 @@ impl NBTSerializable for Uuid {
 }
 
 impl NBTSerializable for &str {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-        write_header::<Self>(buf, options);
-        let bytes = self.as_bytes();
-        (bytes.len() as u16).serialize(buf, &NBTSerializeOptions::None);
-        buf.extend_from_slice(bytes);
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        write_header::<Self>(buf, options, encoding);
+        encoding.write_string(buf, self);
     }
 
     fn id() -> u8 {
@@ -100,8 +111,8 @@ impl NBTSerializable for &str {
 }
 
 impl<T: NBTSerializable + std::fmt::Debug> NBTSerializable for Vec<T> {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-        self.as_slice().serialize(buf, options);
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        self.as_slice().serialize_encoded(buf, options, encoding);
     }
 
     #[inline]
@@ -116,48 +127,35 @@ impl<T: NBTSerializable + std::fmt::Debug> NBTSerializable for Vec<T> {
 }
 
 impl<T: NBTSerializable> NBTSerializable for &'_ [T] {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-        write_header::<Self>(buf, options);
-
-        let is_special = [TAG_BYTE_ARRAY, TAG_INT_ARRAY, TAG_LONG_ARRAY].contains(&Self::id());
-
-        if !is_special {
-            buf.push(T::id());
-        }
-
-        (self.len() as i32).serialize(buf, &NBTSerializeOptions::None);
-
-        if is_special {
-            match Self::id() {
-                TAG_BYTE_ARRAY => {
-                    let bytes = unsafe {
-                        std::slice::from_raw_parts(self.as_ptr() as *const u8, self.len())
-                    };
-                    buf.extend_from_slice(bytes);
-                }
-                TAG_INT_ARRAY => {
-                    let bytes = unsafe {
-                        arrays::u32_slice_to_u8_be(std::slice::from_raw_parts(
-                            self.as_ptr() as *const u32,
-                            self.len(),
-                        ))
-                    };
-                    buf.extend_from_slice(bytes.as_slice());
-                }
-                TAG_LONG_ARRAY => {
-                    let bytes = unsafe {
-                        arrays::u64_slice_to_u8_be(std::slice::from_raw_parts(
-                            self.as_ptr() as *const u64,
-                            self.len(),
-                        ))
-                    };
-                    buf.extend_from_slice(&bytes);
-                }
-                _ => unreachable!(),
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        write_header::<Self>(buf, options, encoding);
+
+        match Self::id() {
+            TAG_BYTE_ARRAY => {
+                encoding.write_length(buf, self.len());
+                let bytes = unsafe {
+                    std::slice::from_raw_parts(self.as_ptr() as *const u8, self.len())
+                };
+                buf.extend_from_slice(bytes);
+            }
+            TAG_INT_ARRAY => {
+                let ints = unsafe {
+                    std::slice::from_raw_parts(self.as_ptr() as *const i32, self.len())
+                };
+                encoding.write_int_array(buf, ints);
+            }
+            TAG_LONG_ARRAY => {
+                let longs = unsafe {
+                    std::slice::from_raw_parts(self.as_ptr() as *const i64, self.len())
+                };
+                encoding.write_long_array(buf, longs);
+            }
+            _ => {
+                buf.push(T::id());
+                encoding.write_length(buf, self.len());
+                self.iter()
+                    .for_each(|item| item.serialize_encoded(buf, &NBTSerializeOptions::None, encoding));
             }
-        } else {
-            self.iter()
-                .for_each(|item| item.serialize(buf, &NBTSerializeOptions::None));
         }
     }
 
@@ -173,9 +171,9 @@ impl<T: NBTSerializable> NBTSerializable for &'_ [T] {
 }
 
 impl<T: NBTSerializable> NBTSerializable for Option<T> {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
         if let Some(value) = self {
-            value.serialize(buf, options);
+            value.serialize_encoded(buf, options, encoding);
         }
     }
 
@@ -186,12 +184,12 @@ impl<T: NBTSerializable> NBTSerializable for Option<T> {
 
 
 /// Serialize multiple values to a buffer.
-/// Order: buf, options, values...
+/// Order: buf, options, encoding, values...
 macro_rules! ser {
 
-    ($buf: expr, $opts: expr, $($value: expr),*) => {
+    ($buf: expr, $opts: expr, $encoding: expr, $($value: expr),*) => {
         $(
-            $value.serialize($buf, &$opts);
+            $value.serialize_encoded($buf, &$opts, $encoding);
         )*
     };
 }
@@ -202,17 +200,17 @@ mod hashmaps {
     use crate::ser::NBTSerializeOptions;
     impl<T: NBTSerializable> NBTSerializable for HashMap<String, T> {
         //! Equivalent to a COMPOUND tag in NBT.
-        fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-            write_header::<Self>(buf, options);
+        fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+            write_header::<Self>(buf, options, encoding);
 
             for (key, value) in self {
                 // tag type ; name length; name
-                ser!(buf, NBTSerializeOptions::None, T::id(), key, value);
+                ser!(buf, NBTSerializeOptions::None, encoding, T::id(), key, value);
             }
 
             if !matches!(options, NBTSerializeOptions::None) {
                 // end tag
-                0u8.serialize(buf, &NBTSerializeOptions::None);
+                0u8.serialize_encoded(buf, &NBTSerializeOptions::None, encoding);
             }
         }
 
@@ -222,18 +220,18 @@ mod hashmaps {
     }
 
     impl<V: NBTSerializable> NBTSerializable for HashMap<&str, V> {
-        fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-            write_header::<Self>(buf, options);
+        fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+            write_header::<Self>(buf, options, encoding);
 
             for (tag_name, value) in self {
                 // tag type ; name length; name
-                ser!(buf, NBTSerializeOptions::None, V::id(), tag_name, value);
+                ser!(buf, NBTSerializeOptions::None, encoding, V::id(), tag_name, value);
             }
 
             // compounds need an ending tag too
             if !matches!(options, NBTSerializeOptions::None) {
                 // end tag
-                0u8.serialize(buf, &NBTSerializeOptions::None);
+                0u8.serialize_encoded(buf, &NBTSerializeOptions::None, encoding);
             }
         }
 
@@ -244,18 +242,18 @@ mod hashmaps {
 
 
     impl<V: NBTSerializable> NBTSerializable for BTreeMap<&str, V> {
-        fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-            write_header::<Self>(buf, options);
+        fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+            write_header::<Self>(buf, options, encoding);
 
             for (tag_name, value) in self {
                 // tag type ; name length; name
-                ser!(buf, NBTSerializeOptions::None, V::id(), tag_name, value);
+                ser!(buf, NBTSerializeOptions::None, encoding, V::id(), tag_name, value);
             }
 
             // compounds need an ending tag too
             if !matches!(options, NBTSerializeOptions::None) {
                 // end tag
-                0u8.serialize(buf, &NBTSerializeOptions::None);
+                0u8.serialize_encoded(buf, &NBTSerializeOptions::None, encoding);
             }
         }
 
@@ -265,18 +263,18 @@ mod hashmaps {
     }
     
     impl<V: NBTSerializable> NBTSerializable for BTreeMap<String, V> {
-        fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
-            write_header::<Self>(buf, options);
+        fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+            write_header::<Self>(buf, options, encoding);
 
             for (tag_name, value) in self {
                 // tag type ; name length; name
-                ser!(buf, NBTSerializeOptions::None, V::id(), tag_name, value);
+                ser!(buf, NBTSerializeOptions::None, encoding, V::id(), tag_name, value);
             }
 
             // compounds need an ending tag too
             if !matches!(options, NBTSerializeOptions::None) {
                 // end tag
-                0u8.serialize(buf, &NBTSerializeOptions::None);
+                0u8.serialize_encoded(buf, &NBTSerializeOptions::None, encoding);
             }
         }
 
@@ -285,16 +283,16 @@ mod hashmaps {
         }
     }
 }
-fn write_header<T: NBTSerializable>(buf: &mut Vec<u8>, opts: &NBTSerializeOptions<'_>) {
+fn write_header<T: NBTSerializable>(buf: &mut Vec<u8>, opts: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
     // tag type ; name length; name
     match opts {
         NBTSerializeOptions::None => {}
         NBTSerializeOptions::WithHeader(tag_name) => {
-            T::id().serialize(buf, &NBTSerializeOptions::None);
-            tag_name.serialize(buf, &NBTSerializeOptions::None);
+            buf.push(T::id());
+            encoding.write_string(buf, tag_name);
         }
         NBTSerializeOptions::Network | NBTSerializeOptions::Flatten => {
-            T::id().serialize(buf, &NBTSerializeOptions::None);
+            buf.push(T::id());
         }
     }
 }
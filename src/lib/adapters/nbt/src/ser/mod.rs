@@ -1,7 +1,14 @@
 mod r#impl;
 
+use crate::NbtEncoding;
+
 pub trait NBTSerializable {
-    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>);
+    fn serialize(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
+        self.serialize_encoded(buf, options, NbtEncoding::BigEndian);
+    }
+
+    /// Like [serialize](NBTSerializable::serialize), but in any encoding.
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding);
     fn id() -> u8;
 }
 
@@ -0,0 +1,282 @@
+use super::SerdeOptions;
+use crate::{NBTError, NbtValue, Result};
+use serde::de::value::SeqDeserializer;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// Deserializes any [Deserialize](serde::Deserialize) type from an [NbtValue].
+///
+/// Lists and all three array types can be read as sequences, and bytes can always be read as
+/// `bool`s. Unsigned integers are read bit for bit from the signed tag of the same width.
+pub struct Deserializer<'a> {
+    value: &'a NbtValue,
+    options: &'a SerdeOptions,
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new(value: &'a NbtValue, options: &'a SerdeOptions) -> Self {
+        Self { value, options }
+    }
+}
+
+macro_rules! deserialize_unsigned {
+    ($($method:ident: $variant:ident => $visit:ident as $ty:ty),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                match self.value {
+                    NbtValue::$variant(val) => visitor.$visit(*val as $ty),
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_> {
+    type Error = NBTError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            NbtValue::Byte(val @ (0 | 1)) if self.options.bytes_as_bools => visitor.visit_bool(*val == 1),
+            NbtValue::Byte(val) => visitor.visit_i8(*val),
+            NbtValue::Short(val) => visitor.visit_i16(*val),
+            NbtValue::Int(val) => visitor.visit_i32(*val),
+            NbtValue::Long(val) => visitor.visit_i64(*val),
+            NbtValue::Float(val) => visitor.visit_f32(*val),
+            NbtValue::Double(val) => visitor.visit_f64(*val),
+            NbtValue::String(val) => visitor.visit_str(val),
+            NbtValue::ByteArray(data) => visitor.visit_seq(SeqDeserializer::new(data.iter().copied())),
+            NbtValue::IntArray(data) => visitor.visit_seq(SeqDeserializer::new(data.iter().copied())),
+            NbtValue::LongArray(data) => visitor.visit_seq(SeqDeserializer::new(data.iter().copied())),
+            NbtValue::List(elements) => visitor.visit_seq(ListAccess {
+                elements: elements.iter(),
+                options: self.options,
+            }),
+            NbtValue::Compound(compound) => visitor.visit_map(CompoundAccess {
+                entries: compound.iter(),
+                value: None,
+                options: self.options,
+            }),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            NbtValue::Byte(val) => visitor.visit_bool(*val != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_unsigned!(
+        deserialize_u8: Byte => visit_u8 as u8,
+        deserialize_u16: Short => visit_u16 as u16,
+        deserialize_u32: Int => visit_u32 as u32,
+        deserialize_u64: Long => visit_u64 as u64
+    );
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            NbtValue::ByteArray(data) => visitor.visit_byte_buf(data.iter().map(|byte| *byte as u8).collect()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // Missing values are `None`, anything that's there is `Some`.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.value {
+            NbtValue::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            NbtValue::Compound(compound) if compound.len() == 1 => {
+                let (variant, value) = compound.iter().next().expect("the compound has one entry");
+                visitor.visit_enum(EnumAccess {
+                    variant,
+                    value,
+                    options: self.options,
+                })
+            }
+            _ => Err(de::Error::invalid_type(unexpected(self.value), &"a string or a compound with one entry")),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+fn unexpected(value: &NbtValue) -> de::Unexpected<'_> {
+    match value {
+        NbtValue::Byte(val) => de::Unexpected::Signed(i64::from(*val)),
+        NbtValue::Short(val) => de::Unexpected::Signed(i64::from(*val)),
+        NbtValue::Int(val) => de::Unexpected::Signed(i64::from(*val)),
+        NbtValue::Long(val) => de::Unexpected::Signed(*val),
+        NbtValue::Float(val) => de::Unexpected::Float(f64::from(*val)),
+        NbtValue::Double(val) => de::Unexpected::Float(*val),
+        NbtValue::String(val) => de::Unexpected::Str(val),
+        NbtValue::ByteArray(_) | NbtValue::IntArray(_) | NbtValue::LongArray(_) | NbtValue::List(_) => de::Unexpected::Seq,
+        NbtValue::Compound(_) => de::Unexpected::Map,
+    }
+}
+
+struct ListAccess<'a> {
+    elements: std::slice::Iter<'a, NbtValue>,
+    options: &'a SerdeOptions,
+}
+
+impl<'de> de::SeqAccess<'de> for ListAccess<'_> {
+    type Error = NBTError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        self.elements
+            .next()
+            .map(|value| seed.deserialize(Deserializer::new(value, self.options)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
+
+struct CompoundAccess<'a, I> {
+    entries: I,
+    value: Option<&'a NbtValue>,
+    options: &'a SerdeOptions,
+}
+
+impl<'de, 'a, I: Iterator<Item = (&'a str, &'a NbtValue)>> de::MapAccess<'de> for CompoundAccess<'a, I> {
+    type Error = NBTError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(KeyDeserializer(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| NBTError::Serde("`next_value_seed` called before `next_key_seed`".to_string()))?;
+        seed.deserialize(Deserializer::new(value, self.options))
+    }
+}
+
+struct EnumAccess<'a> {
+    variant: &'a str,
+    value: &'a NbtValue,
+    options: &'a SerdeOptions,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for EnumAccess<'a> {
+    type Error = NBTError;
+    type Variant = Deserializer<'a>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant)> {
+        let variant = seed.deserialize(KeyDeserializer(self.variant))?;
+        Ok((variant, Deserializer::new(self.value, self.options)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializer<'_> {
+    type Error = NBTError;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+/// Compound keys, which can also be read as the numbers or chars they were written from.
+struct KeyDeserializer<'a>(&'a str);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                match self.0.parse() {
+                    Ok(val) => visitor.$visit(val),
+                    Err(_) => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'_> {
+    type Error = NBTError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_str(self.0)
+    }
+
+    deserialize_parsed_key!(
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char
+    );
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf option unit unit_struct
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
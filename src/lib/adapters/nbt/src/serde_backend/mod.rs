@@ -0,0 +1,106 @@
+//! A serde data format for NBT, for types that already derive `Serialize`/`Deserialize` and
+//! shouldn't need the NBT derives as well.
+//!
+//! ```ignore
+//! let bytes = ferrumc_nbt::to_bytes(&component, &SerdeOptions::default())?;
+//! let component: TextComponent = ferrumc_nbt::from_slice(&bytes, &SerdeOptions::default())?;
+//! ```
+//!
+//! Values go through an [NbtValue] tree, which is then written or was parsed with [NbtTape].
+
+mod de;
+mod ser;
+
+pub use de::Deserializer;
+pub use ser::Serializer;
+
+use crate::{NBTError, NBTSerializable, NBTSerializeOptions, NbtCompound, NbtTape, NbtValue, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// How Rust types map onto NBT tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerdeOptions {
+    /// `None` for network NBT, whose root has no name and can be any tag. `Some(name)` for the
+    /// format files use, where the root is a compound with a name. The name is ignored when reading.
+    pub root_name: Option<String>,
+    /// Write sequences of `i8`/`u8`, `i32`/`u32` and `i64`/`u64` as byte, int and long arrays
+    /// rather than lists. Both are accepted when reading either way.
+    pub typed_arrays: bool,
+    /// Hand bytes that are 0 or 1 to serde as `bool`s when it doesn't say what it wants, which is
+    /// the case behind `#[serde(flatten)]` and untagged enums. Those `i8`s then can't be read there.
+    pub bytes_as_bools: bool,
+}
+
+impl Default for SerdeOptions {
+    fn default() -> Self {
+        Self {
+            root_name: None,
+            typed_arrays: true,
+            bytes_as_bools: false,
+        }
+    }
+}
+
+impl SerdeOptions {
+    /// The format files use, with a root compound named `name`.
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            root_name: Some(name.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Serializes `value` into an [NbtValue], or `None` if it serializes to nothing (`None` or `()`).
+pub fn to_value<T: ?Sized + Serialize>(value: &T, options: &SerdeOptions) -> Result<Option<NbtValue>> {
+    value.serialize(Serializer::new(options))
+}
+
+/// Deserializes a `T` from an [NbtValue].
+pub fn from_value<T: DeserializeOwned>(value: &NbtValue, options: &SerdeOptions) -> Result<T> {
+    T::deserialize(Deserializer::new(value, options))
+}
+
+/// Serializes `value` as NBT. With network NBT, nothing at all (`None` or `()`) is written as an
+/// `End` tag.
+pub fn to_bytes<T: ?Sized + Serialize>(value: &T, options: &SerdeOptions) -> Result<Vec<u8>> {
+    let value = to_value(value, options)?;
+
+    let Some(name) = &options.root_name else {
+        let mut buf = Vec::new();
+        match value {
+            Some(value) => value.serialize(&mut buf, &NBTSerializeOptions::Network),
+            None => buf.push(0),
+        }
+        return Ok(buf);
+    };
+
+    match value {
+        Some(NbtValue::Compound(compound)) => Ok(compound.to_bytes(name)),
+        Some(value) => Err(NBTError::TypeMismatch {
+            expected: "Compound",
+            found: value.nbt_type(),
+        }),
+        None => Ok(NbtCompound::new().to_bytes(name)),
+    }
+}
+
+/// Deserializes a `T` from NBT. A network `End` root deserializes like a missing value, so it
+/// can be read as an `Option`.
+pub fn from_slice<T: DeserializeOwned>(data: &[u8], options: &SerdeOptions) -> Result<T> {
+    let mut tape = NbtTape::new(data);
+    match options.root_name {
+        Some(_) => tape.parse()?,
+        None => {
+            tape.parse_network()?;
+        }
+    }
+
+    let (_, root) = tape.root.as_ref().ok_or(NBTError::NoRootTag)?;
+    if let crate::NbtTapeElement::End = root {
+        return T::deserialize(serde::de::value::UnitDeserializer::new());
+    }
+
+    from_value(&NbtValue::from_tape_element(&tape, root)?, options)
+}
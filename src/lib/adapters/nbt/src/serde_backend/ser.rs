@@ -0,0 +1,460 @@
+use super::SerdeOptions;
+use crate::{NBTError, NbtCompound, NbtValue, Result};
+use serde::ser::{self, Serialize};
+
+/// Serializes any [Serialize] type into an [NbtValue].
+///
+/// `None` and `()` serialize to nothing, so they're left out of compounds. Unsigned integers are
+/// stored in the signed tag of the same width, bit for bit, and read back the same way.
+pub struct Serializer<'a> {
+    options: &'a SerdeOptions,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(options: &'a SerdeOptions) -> Self {
+        Self { options }
+    }
+}
+
+fn custom(message: impl std::fmt::Display) -> NBTError {
+    NBTError::Serde(message.to_string())
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+    type SerializeSeq = SerializeList<'a>;
+    type SerializeTuple = SerializeList<'a>;
+    type SerializeTupleStruct = SerializeList<'a>;
+    type SerializeTupleVariant = SerializeVariant<SerializeList<'a>>;
+    type SerializeMap = SerializeCompound<'a>;
+    type SerializeStruct = SerializeCompound<'a>;
+    type SerializeStructVariant = SerializeVariant<SerializeCompound<'a>>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Byte(i8::from(v))))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Byte(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Short(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Int(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Long(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Byte(v as i8)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Short(v as i16)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Int(v as i32)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Long(v as i64)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Float(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Double(v)))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::String(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::String(v.to_string())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::ByteArray(v.iter().map(|byte| *byte as i8).collect())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Compound(NbtCompound::new())))
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::String(variant.to_string())))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        let mut compound = NbtCompound::new();
+        if let Some(value) = value.serialize(self)? {
+            compound.insert(variant, value);
+        }
+        Ok(Some(NbtValue::Compound(compound)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SerializeList {
+            options: self.options,
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(SerializeCompound {
+            options: self.options,
+            compound: NbtCompound::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+pub struct SerializeList<'a> {
+    options: &'a SerdeOptions,
+    elements: Vec<NbtValue>,
+}
+
+impl SerializeList<'_> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let value = value
+            .serialize(Serializer::new(self.options))?
+            .ok_or_else(|| custom("NBT lists can't hold `None` or `()`"))?;
+        if let Some(first) = self.elements.first() {
+            if first.nbt_id() != value.nbt_id() {
+                return Err(custom(format!(
+                    "NBT lists hold a single type, found a {} in a list of {}",
+                    value.nbt_type(),
+                    first.nbt_type()
+                )));
+            }
+        }
+        self.elements.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> NbtValue {
+        if !self.options.typed_arrays {
+            return NbtValue::List(self.elements);
+        }
+
+        match self.elements.first() {
+            Some(NbtValue::Byte(_)) => NbtValue::ByteArray(self.elements.iter().filter_map(NbtValue::as_byte).collect()),
+            Some(NbtValue::Int(_)) => NbtValue::IntArray(self.elements.iter().filter_map(NbtValue::as_int).collect()),
+            Some(NbtValue::Long(_)) => NbtValue::LongArray(self.elements.iter().filter_map(NbtValue::as_long).collect()),
+            _ => NbtValue::List(self.elements),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeList<'_> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.finish()))
+    }
+}
+
+impl ser::SerializeTuple for SerializeList<'_> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.finish()))
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList<'_> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.finish()))
+    }
+}
+
+pub struct SerializeCompound<'a> {
+    options: &'a SerdeOptions,
+    compound: NbtCompound,
+    key: Option<String>,
+}
+
+impl SerializeCompound<'_> {
+    fn insert<T: ?Sized + Serialize>(&mut self, key: String, value: &T) -> Result<()> {
+        if let Some(value) = value.serialize(Serializer::new(self.options))? {
+            self.compound.insert(key, value);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeCompound<'_> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().ok_or_else(|| custom("`serialize_value` called before `serialize_key`"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Compound(self.compound)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeCompound<'_> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(NbtValue::Compound(self.compound)))
+    }
+}
+
+/// A tuple or struct variant, written as a compound with the variant name as its only key.
+pub struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> SerializeVariant<S> {
+    fn wrap(variant: &'static str, value: NbtValue) -> Option<NbtValue> {
+        Some(NbtValue::Compound([(variant, value)].into_iter().collect()))
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeList<'_>> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Self::wrap(self.variant, self.inner.finish()))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeCompound<'_>> {
+    type Ok = Option<NbtValue>;
+    type Error = NBTError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.inner.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Self::wrap(self.variant, NbtValue::Compound(self.inner.compound)))
+    }
+}
+
+/// Compound keys are strings, but map keys that print as one (numbers, chars, unit variants) work too.
+struct KeySerializer;
+
+macro_rules! key_to_string {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<String> {
+                Ok(v.to_string())
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = NBTError;
+    type SerializeSeq = ser::Impossible<String, NBTError>;
+    type SerializeTuple = ser::Impossible<String, NBTError>;
+    type SerializeTupleStruct = ser::Impossible<String, NBTError>;
+    type SerializeTupleVariant = ser::Impossible<String, NBTError>;
+    type SerializeMap = ser::Impossible<String, NBTError>;
+    type SerializeStruct = ser::Impossible<String, NBTError>;
+    type SerializeStructVariant = ser::Impossible<String, NBTError>;
+
+    key_to_string!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str
+    );
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String> {
+        Err(custom("compound keys must be strings, not bytes"))
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(custom("compound keys can't be `None`"))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(custom("compound keys can't be `()`"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<String> {
+        Err(custom(format!("compound keys can't be the unit struct `{name}`")))
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<String> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _value: &T,
+    ) -> Result<String> {
+        Err(custom(format!("compound keys can't be the enum variant `{variant}`")))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(custom("compound keys can't be sequences"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(custom("compound keys can't be tuples"))
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(custom(format!("compound keys can't be the tuple struct `{name}`")))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(custom(format!("compound keys can't be the enum variant `{variant}`")))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(custom("compound keys can't be maps"))
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(custom(format!("compound keys can't be the struct `{name}`")))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(custom(format!("compound keys can't be the enum variant `{variant}`")))
+    }
+}
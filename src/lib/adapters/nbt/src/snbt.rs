@@ -0,0 +1,489 @@
+//! Stringified NBT, the text format used by commands and item definitions, e.g. `{Count:1b,id:"minecraft:stone"}`.
+//!
+//! Parse it with [str::parse], and print it with [Display](std::fmt::Display):
+//! `{}` prints it compact, `{:#}` prints it indented.
+//! Infinite and NaN floats have no SNBT representation.
+//!
+//! ```ignore
+//! let item: NbtCompound = r#"{Count: 1b, id: "minecraft:stone"}"#.parse()?;
+//! assert_eq!(item.to_string(), r#"{Count:1b,id:"minecraft:stone"}"#);
+//! ```
+
+use crate::de::borrow::MAX_DEPTH;
+use crate::value::{NbtCompound, NbtValue};
+use crate::{NBTError, Result};
+use std::fmt::{self, Display, Formatter, Write};
+use std::str::FromStr;
+
+const INDENT: &str = "    ";
+
+impl FromStr for NbtValue {
+    type Err = NBTError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = SnbtParser::new(s);
+        let value = parser.parse_value()?;
+        parser.expect_end()?;
+        Ok(value)
+    }
+}
+
+impl FromStr for NbtCompound {
+    type Err = NBTError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = SnbtParser::new(s);
+        parser.skip_whitespace();
+        if parser.peek() != Some('{') {
+            return Err(parser.error("Expected a compound"));
+        }
+        let NbtValue::Compound(compound) = parser.parse_value()? else {
+            unreachable!("Values starting with `{{` are compounds");
+        };
+        parser.expect_end()?;
+        Ok(compound)
+    }
+}
+
+/// Characters that don't have to be quoted in keys and strings.
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+struct SnbtParser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// How many compounds and lists the parser is in, at most [MAX_DEPTH].
+    depth: usize,
+}
+
+impl<'a> SnbtParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0, depth: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    /// An error at the current position.
+    fn error(&self, message: impl Into<String>) -> NBTError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> NBTError {
+        let before = &self.input[..pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+
+        NBTError::InvalidSnbt {
+            message: message.into(),
+            line,
+            column,
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.next();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("Expected `{expected}` but found `{c}`"))),
+            None => Err(self.error(format!("Expected `{expected}` but reached the end"))),
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(format!("Unexpected `{c}` after the value"))),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<NbtValue> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c @ ('{' | '[')) => {
+                // Every level is parsed recursively, so without a limit deep nesting overflows the stack.
+                if self.depth >= MAX_DEPTH {
+                    return Err(self.error(format!("Compounds and lists are nested deeper than {MAX_DEPTH} levels")));
+                }
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.parse_compound().map(NbtValue::Compound)
+                } else {
+                    self.parse_list_or_array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"' | '\'') => self.parse_quoted().map(NbtValue::String),
+            Some(_) => self.parse_unquoted_value(),
+            None => Err(self.error("Expected a value but reached the end")),
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<NbtCompound> {
+        self.expect('{')?;
+        let mut compound = NbtCompound::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(compound);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_pos = self.pos;
+            let key = self.parse_key()?;
+            self.expect(':')?;
+            let value = self.parse_value()?;
+
+            if compound.insert(key.as_str(), value).is_some() {
+                return Err(self.error_at(key_pos, format!("Duplicate key `{key}`")));
+            }
+
+            if !self.parse_separator('}')? {
+                return Ok(compound);
+            }
+        }
+    }
+
+    /// Parses a `,` and returns true, or the closing character and returns false.
+    fn parse_separator(&mut self, close: char) -> Result<bool> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(',') => Ok(true),
+            Some(c) if c == close => Ok(false),
+            Some(c) => Err(self.error_at(self.pos - c.len_utf8(), format!("Expected `,` or `{close}` but found `{c}`"))),
+            None => Err(self.error(format!("Expected `,` or `{close}` but reached the end"))),
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        match self.peek() {
+            Some('"' | '\'') => self.parse_quoted(),
+            _ => {
+                let key = self.parse_unquoted();
+                if key.is_empty() {
+                    return Err(self.error("Expected a key"));
+                }
+                Ok(key.to_string())
+            }
+        }
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let start = self.pos;
+        let Some(quote) = self.next() else {
+            return Err(self.error("Expected a string"));
+        };
+
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => match self.next() {
+                    Some(c) if c == '\\' || c == quote => string.push(c),
+                    Some(c) => {
+                        return Err(self.error_at(self.pos - c.len_utf8() - 1, format!("Invalid escape `\\{c}`")));
+                    }
+                    None => return Err(self.error("Unterminated string")),
+                },
+                Some(c) if c == quote => return Ok(string),
+                Some(c) => string.push(c),
+                None => return Err(self.error_at(start, "Unterminated string")),
+            }
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.next();
+        }
+        &self.input[start..self.pos]
+    }
+
+    /// Numbers, booleans and unquoted strings.
+    fn parse_unquoted_value(&mut self) -> Result<NbtValue> {
+        let start = self.pos;
+        let token = self.parse_unquoted();
+        if token.is_empty() {
+            let c = self.peek().unwrap_or_default();
+            return Err(self.error(format!("Unexpected `{c}`")));
+        }
+
+        match parse_number(token) {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(message)) => Err(self.error_at(start, message)),
+            None => Ok(match token {
+                "true" => NbtValue::Byte(1),
+                "false" => NbtValue::Byte(0),
+                _ => NbtValue::String(token.to_string()),
+            }),
+        }
+    }
+
+    fn parse_list_or_array(&mut self) -> Result<NbtValue> {
+        self.expect('[')?;
+
+        // `[B;`, `[I;` and `[L;` start arrays, anything else is a list.
+        let mut rest = self.input[self.pos..].chars();
+        let first = rest.next();
+        let array_type = match (first, rest.as_str().trim_start().chars().next()) {
+            (Some(c @ ('B' | 'I' | 'L')), Some(';')) => Some(c),
+            _ => None,
+        };
+
+        match array_type {
+            Some(array_type) => {
+                self.next();
+                self.expect(';')?;
+                self.parse_array(array_type)
+            }
+            None => self.parse_list(),
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<NbtValue> {
+        let mut elements: Vec<NbtValue> = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(NbtValue::List(elements));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let element_pos = self.pos;
+            let element = self.parse_value()?;
+
+            if let Some(first) = elements.first() {
+                if first.nbt_id() != element.nbt_id() {
+                    return Err(self.error_at(
+                        element_pos,
+                        format!("Can't insert a {} into a list of {}", element.nbt_type(), first.nbt_type()),
+                    ));
+                }
+            }
+            elements.push(element);
+
+            if !self.parse_separator(']')? {
+                return Ok(NbtValue::List(elements));
+            }
+        }
+    }
+
+    fn parse_array(&mut self, array_type: char) -> Result<NbtValue> {
+        let mut array = match array_type {
+            'B' => NbtValue::ByteArray(Vec::new()),
+            'I' => NbtValue::IntArray(Vec::new()),
+            _ => NbtValue::LongArray(Vec::new()),
+        };
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(array);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let element_pos = self.pos;
+            let element = self.parse_value()?;
+
+            match (&mut array, element) {
+                (NbtValue::ByteArray(data), NbtValue::Byte(value)) => data.push(value),
+                (NbtValue::IntArray(data), NbtValue::Int(value)) => data.push(value),
+                (NbtValue::LongArray(data), NbtValue::Long(value)) => data.push(value),
+                (array, element) => {
+                    return Err(self.error_at(
+                        element_pos,
+                        format!("Can't insert a {} into a {}", element.nbt_type(), array.nbt_type()),
+                    ));
+                }
+            }
+
+            if !self.parse_separator(']')? {
+                return Ok(array);
+            }
+        }
+    }
+}
+
+/// Parses a number with an optional type suffix.
+///
+/// Returns `None` if the token isn't a number, so it's an unquoted string instead.
+fn parse_number(token: &str) -> Option<std::result::Result<NbtValue, String>> {
+    let is_integer = |digits: &str| {
+        let digits = digits.strip_prefix(['-', '+']).unwrap_or(digits);
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    };
+    let is_decimal = |digits: &str| {
+        let digits = digits.strip_prefix(['-', '+']).unwrap_or(digits);
+        let mantissa = match digits.find(['e', 'E']) {
+            Some(e) => {
+                if !is_integer(&digits[e + 1..]) {
+                    return false;
+                }
+                &digits[..e]
+            }
+            None => digits,
+        };
+        mantissa.chars().any(|c| c.is_ascii_digit())
+            && mantissa.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && mantissa.matches('.').count() <= 1
+    };
+
+    fn out_of_range(token: &str, nbt_type: &str) -> String {
+        format!("`{token}` doesn't fit into a {nbt_type}")
+    }
+
+    let (digits, suffix) = match token.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&token[..i], Some(c.to_ascii_lowercase())),
+        _ => (token, None),
+    };
+
+    let value = match suffix {
+        Some('b') if is_integer(digits) => digits.parse().map(NbtValue::Byte).map_err(|_| out_of_range(token, "Byte")),
+        Some('s') if is_integer(digits) => digits.parse().map(NbtValue::Short).map_err(|_| out_of_range(token, "Short")),
+        Some('l') if is_integer(digits) => digits.parse().map(NbtValue::Long).map_err(|_| out_of_range(token, "Long")),
+        Some('f') if is_decimal(digits) => digits.parse().map(NbtValue::Float).map_err(|_| out_of_range(token, "Float")),
+        Some('d') if is_decimal(digits) => digits.parse().map(NbtValue::Double).map_err(|_| out_of_range(token, "Double")),
+        None if is_integer(digits) => digits.parse().map(NbtValue::Int).map_err(|_| out_of_range(token, "Int")),
+        None if digits.contains('.') && is_decimal(digits) => digits.parse().map(NbtValue::Double).map_err(|_| out_of_range(token, "Double")),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+impl Display for NbtValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_value(self, f, f.alternate(), 0)
+    }
+}
+
+impl Display for NbtCompound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_compound(self, f, f.alternate(), 0)
+    }
+}
+
+fn write_indent(f: &mut Formatter<'_>, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str(INDENT)?;
+    }
+    Ok(())
+}
+
+fn write_string(string: &str, f: &mut Formatter<'_>) -> fmt::Result {
+    f.write_char('"')?;
+    for c in string.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+fn write_key(key: &str, f: &mut Formatter<'_>) -> fmt::Result {
+    if !key.is_empty() && key.chars().all(is_unquoted_char) {
+        f.write_str(key)
+    } else {
+        write_string(key, f)
+    }
+}
+
+/// Writes the elements of a list, array or compound, one per line if `pretty`.
+fn write_sequence<T>(
+    f: &mut Formatter<'_>,
+    (open, close): (&str, &str),
+    elements: &[T],
+    pretty: bool,
+    depth: usize,
+    mut write_element: impl FnMut(&T, &mut Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    f.write_str(open)?;
+    if elements.is_empty() {
+        return f.write_str(close);
+    }
+
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            f.write_char(',')?;
+        }
+        if pretty {
+            f.write_char('\n')?;
+            write_indent(f, depth + 1)?;
+        }
+        write_element(element, f)?;
+    }
+
+    if pretty {
+        f.write_char('\n')?;
+        write_indent(f, depth)?;
+    }
+    f.write_str(close)
+}
+
+/// Writes the elements of an array on a single line.
+fn write_array<T: Display>(f: &mut Formatter<'_>, prefix: &str, suffix: &str, elements: &[T], pretty: bool) -> fmt::Result {
+    write!(f, "[{prefix};")?;
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            f.write_char(',')?;
+        }
+        if pretty {
+            f.write_char(' ')?;
+        }
+        write!(f, "{element}{suffix}")?;
+    }
+    f.write_char(']')
+}
+
+fn write_compound(compound: &NbtCompound, f: &mut Formatter<'_>, pretty: bool, depth: usize) -> fmt::Result {
+    let entries = compound.iter().collect::<Vec<_>>();
+    write_sequence(f, ("{", "}"), &entries, pretty, depth, |(key, value), f| {
+        write_key(key, f)?;
+        f.write_str(if pretty { ": " } else { ":" })?;
+        write_value(value, f, pretty, depth + 1)
+    })
+}
+
+fn write_value(value: &NbtValue, f: &mut Formatter<'_>, pretty: bool, depth: usize) -> fmt::Result {
+    match value {
+        NbtValue::Byte(val) => write!(f, "{val}b"),
+        NbtValue::Short(val) => write!(f, "{val}s"),
+        NbtValue::Int(val) => write!(f, "{val}"),
+        NbtValue::Long(val) => write!(f, "{val}L"),
+        NbtValue::Float(val) => write!(f, "{val}f"),
+        NbtValue::Double(val) => write!(f, "{val}d"),
+        NbtValue::ByteArray(data) => write_array(f, "B", "b", data, pretty),
+        NbtValue::String(val) => write_string(val, f),
+        NbtValue::List(elements) => write_sequence(f, ("[", "]"), elements, pretty, depth, |element, f| {
+            write_value(element, f, pretty, depth + 1)
+        }),
+        NbtValue::Compound(compound) => write_compound(compound, f, pretty, depth),
+        NbtValue::IntArray(data) => write_array(f, "I", "", data, pretty),
+        NbtValue::LongArray(data) => write_array(f, "L", "L", data, pretty),
+    }
+}
@@ -0,0 +1,502 @@
+//! An owned NBT tree that can be edited, unlike the read-only [NbtTape].
+//!
+//! ```ignore
+//! let mut tape = NbtTape::new(&data);
+//! tape.parse()?;
+//! let mut level = NbtCompound::from_tape(&tape)?;
+//!
+//! level.insert("DataVersion", 3955);
+//! if let Some(player) = level.get_path_mut("Data.Player").and_then(NbtValue::as_compound_mut) {
+//!     player.remove("Inventory");
+//! }
+//!
+//! let bytes = level.to_gzip("")?;
+//! ```
+
+use crate::de::borrow::{NbtTape, NbtTapeElement, NbtTag};
+use crate::{FromNbt, NBTError, NBTSerializable, NBTSerializeOptions, NbtEncoding, Result};
+use std::io::Write;
+
+/// A single NBT tag and its payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtValue {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// All elements have to be of the same type.
+    List(Vec<NbtValue>),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+/// Named NBT values, in the order they were inserted.
+///
+/// Like [NbtTapeElement::Compound] this is a vec instead of a map, compounds are small enough
+/// that the lookups are fast anyway.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NbtCompound {
+    entries: Vec<(String, NbtValue)>,
+}
+
+impl NbtValue {
+    pub const fn nbt_type(&self) -> &'static str {
+        match self {
+            NbtValue::Byte(_) => "Byte",
+            NbtValue::Short(_) => "Short",
+            NbtValue::Int(_) => "Int",
+            NbtValue::Long(_) => "Long",
+            NbtValue::Float(_) => "Float",
+            NbtValue::Double(_) => "Double",
+            NbtValue::ByteArray(_) => "ByteArray",
+            NbtValue::String(_) => "String",
+            NbtValue::List(_) => "List",
+            NbtValue::Compound(_) => "Compound",
+            NbtValue::IntArray(_) => "IntArray",
+            NbtValue::LongArray(_) => "LongArray",
+        }
+    }
+
+    pub const fn nbt_id(&self) -> u8 {
+        match self {
+            NbtValue::Byte(_) => NbtTag::Byte as u8,
+            NbtValue::Short(_) => NbtTag::Short as u8,
+            NbtValue::Int(_) => NbtTag::Int as u8,
+            NbtValue::Long(_) => NbtTag::Long as u8,
+            NbtValue::Float(_) => NbtTag::Float as u8,
+            NbtValue::Double(_) => NbtTag::Double as u8,
+            NbtValue::ByteArray(_) => NbtTag::ByteArray as u8,
+            NbtValue::String(_) => NbtTag::String as u8,
+            NbtValue::List(_) => NbtTag::List as u8,
+            NbtValue::Compound(_) => NbtTag::Compound as u8,
+            NbtValue::IntArray(_) => NbtTag::IntArray as u8,
+            NbtValue::LongArray(_) => NbtTag::LongArray as u8,
+        }
+    }
+
+    /// Copies an element of a parsed tape, including everything nested in it.
+    pub fn from_tape_element<'a>(tape: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> Result<Self> {
+        Ok(match element {
+            NbtTapeElement::End => return Err(NBTError::InvalidTagType(NbtTag::End as u8)),
+            NbtTapeElement::Byte(val) => NbtValue::Byte(*val),
+            NbtTapeElement::Short(val) => NbtValue::Short(*val),
+            NbtTapeElement::Int(val) => NbtValue::Int(*val),
+            NbtTapeElement::Long(val) => NbtValue::Long(*val),
+            NbtTapeElement::Float(val) => NbtValue::Float(*val),
+            NbtTapeElement::Double(val) => NbtValue::Double(*val),
+            NbtTapeElement::ByteArray(data) => NbtValue::ByteArray(data.to_vec()),
+            NbtTapeElement::String(val) => NbtValue::String(val.to_string()),
+            NbtTapeElement::List { .. } => {
+                let elements = tape.list_elements(element)?;
+                NbtValue::List(
+                    elements
+                        .iter()
+                        .map(|element| NbtValue::from_tape_element(tape, element))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            NbtTapeElement::Compound(_) => NbtValue::Compound(NbtCompound::from_tape_element(tape, element)?),
+            NbtTapeElement::IntArray(data) => NbtValue::IntArray(data.clone()),
+            NbtTapeElement::LongArray(data) => NbtValue::LongArray(data.clone()),
+        })
+    }
+
+    pub fn as_byte(&self) -> Option<i8> {
+        match self {
+            NbtValue::Byte(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_short(&self) -> Option<i16> {
+        match self {
+            NbtValue::Short(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            NbtValue::Int(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_long(&self) -> Option<i64> {
+        match self {
+            NbtValue::Long(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            NbtValue::Float(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_double(&self) -> Option<f64> {
+        match self {
+            NbtValue::Double(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NbtValue::String(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&Vec<NbtValue>> {
+        match self {
+            NbtValue::List(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<NbtValue>> {
+        match self {
+            NbtValue::List(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
+        match self {
+            NbtValue::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut NbtCompound> {
+        match self {
+            NbtValue::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    /// The element at `index` of a list.
+    fn index(&self, index: &str) -> Option<&NbtValue> {
+        self.as_list()?.get(index.parse::<usize>().ok()?)
+    }
+
+    fn index_mut(&mut self, index: &str) -> Option<&mut NbtValue> {
+        self.as_list_mut()?.get_mut(index.parse::<usize>().ok()?)
+    }
+
+    /// Writes the payload of the tag, without its id and name.
+    pub(crate) fn write_payload(&self, buf: &mut Vec<u8>, encoding: NbtEncoding) {
+        match self {
+            NbtValue::Byte(val) => encoding.write_i8(buf, *val),
+            NbtValue::Short(val) => encoding.write_i16(buf, *val),
+            NbtValue::Int(val) => encoding.write_i32(buf, *val),
+            NbtValue::Long(val) => encoding.write_i64(buf, *val),
+            NbtValue::Float(val) => encoding.write_f32(buf, *val),
+            NbtValue::Double(val) => encoding.write_f64(buf, *val),
+            NbtValue::ByteArray(data) => {
+                encoding.write_length(buf, data.len());
+                buf.extend(data.iter().map(|byte| *byte as u8));
+            }
+            NbtValue::String(val) => encoding.write_string(buf, val),
+            NbtValue::List(elements) => {
+                let el_type = elements.first().map_or(NbtTag::End as u8, NbtValue::nbt_id);
+                debug_assert!(
+                    elements.iter().all(|element| element.nbt_id() == el_type),
+                    "NBT lists can't contain different types"
+                );
+
+                buf.push(el_type);
+                encoding.write_length(buf, elements.len());
+                for element in elements {
+                    element.write_payload(buf, encoding);
+                }
+            }
+            NbtValue::Compound(compound) => compound.write_payload(buf, encoding),
+            NbtValue::IntArray(data) => encoding.write_int_array(buf, data),
+            NbtValue::LongArray(data) => encoding.write_long_array(buf, data),
+        }
+    }
+
+    fn write(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        if let NbtValue::Compound(compound) = self {
+            return compound.write(buf, options, encoding);
+        }
+
+        write_header(self.nbt_id(), buf, options, encoding);
+        self.write_payload(buf, encoding);
+    }
+}
+
+impl NbtCompound {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copies the root compound of a parsed tape.
+    pub fn from_tape(tape: &NbtTape<'_>) -> Result<Self> {
+        let (_, root) = tape.root.as_ref().ok_or(NBTError::NoRootTag)?;
+        Self::from_tape_element(tape, root)
+    }
+
+    /// Parses NBT data with a named root compound, gzip compressed or not.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let data = crate::decompress_gzip(data)?;
+        let mut tape = NbtTape::new(&data);
+        tape.parse()?;
+        Self::from_tape(&tape)
+    }
+
+    pub fn from_tape_element<'a>(tape: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> Result<Self> {
+        let elements = element.as_compound().ok_or(NBTError::TypeMismatch {
+            expected: "Compound",
+            found: element.nbt_type(),
+        })?;
+
+        let entries = elements
+            .iter()
+            .map(|(name, element)| Ok((name.to_string(), NbtValue::from_tape_element(tape, element)?)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&NbtValue> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut NbtValue> {
+        self.entries
+            .iter_mut()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value, replacing and returning the previous value with the same key.
+    ///
+    /// A replaced value keeps its position, new values are appended.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<NbtValue>) -> Option<NbtValue> {
+        let key = key.into();
+        let value = value.into();
+
+        match self.get_mut(&key) {
+            Some(existing) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<NbtValue> {
+        let index = self.entries.iter().position(|(name, _)| name == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NbtValue)> {
+        self.entries.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Looks up a nested value by a path of keys separated by dots, e.g. `Level.Sections.0.Y`.
+    ///
+    /// Numbers index into lists.
+    pub fn get_path(&self, path: &str) -> Option<&NbtValue> {
+        let mut keys = path.split('.');
+        let mut value = self.get(keys.next()?)?;
+
+        for key in keys {
+            value = match value {
+                NbtValue::Compound(compound) => compound.get(key)?,
+                _ => value.index(key)?,
+            };
+        }
+
+        Some(value)
+    }
+
+    /// Like [get_path](NbtCompound::get_path), but mutable.
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut NbtValue> {
+        let mut keys = path.split('.');
+        let mut value = self.get_mut(keys.next()?)?;
+
+        for key in keys {
+            value = match value {
+                NbtValue::Compound(compound) => compound.get_mut(key)?,
+                _ => value.index_mut(key)?,
+            };
+        }
+
+        Some(value)
+    }
+
+    /// Serializes the compound as the root tag with the given name, like it's stored on disk.
+    pub fn to_bytes(&self, name: &str) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.serialize(&mut buf, &NBTSerializeOptions::WithHeader(name));
+        buf
+    }
+
+    /// Serializes the compound as a nameless root tag, like it's sent over the network since 1.20.2.
+    pub fn to_network_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.serialize(&mut buf, &NBTSerializeOptions::Network);
+        buf
+    }
+
+    /// Like [to_bytes](NbtCompound::to_bytes), but in any encoding.
+    pub fn to_encoded_bytes(&self, name: &str, encoding: NbtEncoding) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write(&mut buf, &NBTSerializeOptions::WithHeader(name), encoding);
+        buf
+    }
+
+    /// Like [from_bytes](NbtCompound::from_bytes), but in any encoding. Bedrock doesn't gzip its
+    /// NBT, so this doesn't decompress.
+    pub fn from_encoded_bytes(data: &[u8], encoding: NbtEncoding) -> Result<Self> {
+        let mut tape = NbtTape::new(data).with_encoding(encoding);
+        tape.parse()?;
+        Self::from_tape(&tape)
+    }
+
+    /// Like [to_bytes](NbtCompound::to_bytes), but gzip compressed.
+    pub fn to_gzip(&self, name: &str) -> Result<Vec<u8>> {
+        let mut encoder = libflate::gzip::Encoder::new(Vec::new())?;
+        encoder.write_all(&self.to_bytes(name))?;
+        Ok(encoder.finish().into_result()?)
+    }
+
+    pub(crate) fn write_payload(&self, buf: &mut Vec<u8>, encoding: NbtEncoding) {
+        self.write_entries(buf, encoding);
+        buf.push(NbtTag::End as u8);
+    }
+
+    pub(crate) fn write_entries(&self, buf: &mut Vec<u8>, encoding: NbtEncoding) {
+        for (name, value) in &self.entries {
+            buf.push(value.nbt_id());
+            encoding.write_string(buf, name);
+            value.write_payload(buf, encoding);
+        }
+    }
+
+    fn write(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        // The entries become part of the surrounding compound, which writes the end tag.
+        if options == &NBTSerializeOptions::Flatten {
+            self.write_entries(buf, encoding);
+            return;
+        }
+
+        write_header(NbtTag::Compound as u8, buf, options, encoding);
+        self.write_payload(buf, encoding);
+    }
+}
+
+impl<K: Into<String>, V: Into<NbtValue>> FromIterator<(K, V)> for NbtCompound {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut compound = NbtCompound::new();
+        for (key, value) in iter {
+            compound.insert(key, value);
+        }
+        compound
+    }
+}
+
+impl NBTSerializable for NbtCompound {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        self.write(buf, options, encoding);
+    }
+
+    fn id() -> u8 {
+        NbtTag::Compound as u8
+    }
+}
+
+impl NBTSerializable for NbtValue {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        self.write(buf, options, encoding);
+    }
+
+    /// The type of a value is only known at runtime, so this is [NbtTag::End].
+    /// Use [NbtCompound] or a `Vec<NbtValue>` field instead of maps of values.
+    fn id() -> u8 {
+        NbtTag::End as u8
+    }
+}
+
+impl<'a> FromNbt<'a> for NbtValue {
+    fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> Result<Self> {
+        NbtValue::from_tape_element(tapes, element)
+    }
+}
+
+impl<'a> FromNbt<'a> for NbtCompound {
+    fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> Result<Self> {
+        NbtCompound::from_tape_element(tapes, element)
+    }
+}
+
+fn write_header(id: u8, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+    match options {
+        NBTSerializeOptions::None => {}
+        NBTSerializeOptions::WithHeader(name) => {
+            buf.push(id);
+            encoding.write_string(buf, name);
+        }
+        NBTSerializeOptions::Network | NBTSerializeOptions::Flatten => buf.push(id),
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl From<$ty> for NbtValue {
+                fn from(value: $ty) -> Self {
+                    NbtValue::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    Vec<i8> => ByteArray,
+    String => String,
+    &str => String,
+    Vec<NbtValue> => List,
+    NbtCompound => Compound,
+    Vec<i32> => IntArray,
+    Vec<i64> => LongArray
+);
+
+impl From<bool> for NbtValue {
+    fn from(value: bool) -> Self {
+        NbtValue::Byte(i8::from(value))
+    }
+}
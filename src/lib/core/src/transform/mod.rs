@@ -1 +1,2 @@
-pub mod position;
\ No newline at end of file
+pub mod position;
+pub mod movement;
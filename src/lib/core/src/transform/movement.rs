@@ -0,0 +1,309 @@
+use std::time::{Duration, Instant};
+use crate::transform::position::Position;
+
+/// The duration of a single server tick.
+pub const TICK_DURATION: Duration = Duration::from_millis(50);
+
+/// The vanilla server refuses any y coordinate with an absolute value bigger than this.
+pub const MAX_ABSOLUTE_Y: f64 = 20_000_000.0;
+
+/// Thresholds used when validating player movement.
+///
+/// All speeds are in blocks per tick.
+#[derive(Debug, Clone)]
+pub struct MovementLimits {
+    pub max_horizontal_speed: f64,
+    pub max_vertical_speed: f64,
+    pub max_fall_speed: f64,
+    pub flying_multiplier: f64,
+    pub elytra_multiplier: f64,
+    /// How many ticks of idle time a single movement may make up for.
+    pub max_buffered_ticks: u32,
+    /// How many ticks after a teleport movement gets `teleport_grace_margin` extra blocks.
+    pub teleport_grace_ticks: u32,
+    /// Extra blocks allowed in each direction while in the teleport grace period.
+    pub teleport_grace_margin: f64,
+    pub world_border: f64,
+}
+
+/// What the player is currently allowed to do which affects how fast they may move.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MovementAbilities {
+    /// If the player is currently flying.
+    pub flying: bool,
+    /// If the player is gliding with an elytra.
+    pub gliding: bool,
+    /// If the player is allowed to fly, clients claiming to fly without this are ignored.
+    pub may_fly: bool,
+}
+
+/// The reason a movement was rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MovementViolation {
+    /// One of the coordinates is NaN or infinite.
+    InvalidCoordinates,
+    /// The position is outside the world border.
+    OutsideWorldBorder,
+    /// The player moved further horizontally than allowed.
+    TooFastHorizontal { distance: f64, allowed: f64 },
+    /// The player moved further vertically than allowed.
+    TooFastVertical { distance: f64, allowed: f64 },
+    /// The server teleported the player and the client hasn't confirmed it yet.
+    AwaitingTeleport,
+}
+
+/// Tracks the last accepted position of a player so new positions can be compared against it.
+///
+/// This is stored as a component on the player entity.
+#[derive(Debug)]
+pub struct MovementState {
+    /// The last position that passed validation (or that the server teleported the player to).
+    pub last_valid: Position,
+    /// When `last_valid` was accepted.
+    pub last_update: Instant,
+    /// The id of a teleport the client still has to confirm.
+    pub pending_teleport: Option<i32>,
+    /// When the server last teleported the player.
+    pub teleported_at: Option<Instant>,
+    next_teleport_id: i32,
+}
+
+impl MovementState {
+    pub fn new(position: Position) -> Self {
+        Self {
+            last_valid: position,
+            last_update: Instant::now(),
+            pending_teleport: None,
+            teleported_at: None,
+            next_teleport_id: 0,
+        }
+    }
+
+    /// Records a server side teleport to `position` and returns the teleport id
+    /// that has to be sent to the client.
+    pub fn teleport(&mut self, position: Position) -> i32 {
+        let id = self.next_teleport_id;
+        self.next_teleport_id = self.next_teleport_id.wrapping_add(1);
+
+        let now = Instant::now();
+        self.last_valid = position;
+        self.last_update = now;
+        self.pending_teleport = Some(id);
+        self.teleported_at = Some(now);
+
+        id
+    }
+
+    /// Marks the teleport with `id` as confirmed by the client.
+    ///
+    /// Returns `false` if that teleport wasn't pending.
+    pub fn confirm_teleport(&mut self, id: i32) -> bool {
+        if self.pending_teleport == Some(id) {
+            self.pending_teleport = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Validates a movement to `to` and, if it's valid, accepts it as the new last valid position.
+    pub fn validate(
+        &mut self,
+        to: &Position,
+        abilities: &MovementAbilities,
+        limits: &MovementLimits,
+    ) -> Result<(), MovementViolation> {
+        self.validate_at(to, abilities, limits, Instant::now())
+    }
+
+    fn validate_at(
+        &mut self,
+        to: &Position,
+        abilities: &MovementAbilities,
+        limits: &MovementLimits,
+        now: Instant,
+    ) -> Result<(), MovementViolation> {
+        if self.pending_teleport.is_some() {
+            return Err(MovementViolation::AwaitingTeleport);
+        }
+
+        if !(to.x.is_finite() && to.y.is_finite() && to.z.is_finite()) {
+            return Err(MovementViolation::InvalidCoordinates);
+        }
+
+        if to.x.abs() > limits.world_border
+            || to.z.abs() > limits.world_border
+            || to.y.abs() > MAX_ABSOLUTE_Y
+        {
+            return Err(MovementViolation::OutsideWorldBorder);
+        }
+
+        let elapsed = now.saturating_duration_since(self.last_update);
+        // A client sends at most one movement per tick, so always allow at least one tick worth of movement.
+        // Idle time only counts for a few ticks so quiet clients can't save up for one huge movement.
+        let ticks = (elapsed.as_secs_f64() / TICK_DURATION.as_secs_f64())
+            .clamp(1.0, limits.max_buffered_ticks.max(1) as f64);
+
+        // Right after a teleport the client may still be settling on the new position,
+        // so allow a small margin around it, but keep checking the speed.
+        let in_grace = self.teleported_at.is_some_and(|at| {
+            now.saturating_duration_since(at) < TICK_DURATION * limits.teleport_grace_ticks
+        });
+        let margin = if in_grace { limits.teleport_grace_margin } else { 0.0 };
+
+        let multiplier = if abilities.gliding {
+            limits.elytra_multiplier
+        } else if abilities.flying {
+            limits.flying_multiplier
+        } else {
+            1.0
+        };
+
+        let dx = to.x - self.last_valid.x;
+        let dz = to.z - self.last_valid.z;
+        let horizontal = (dx * dx + dz * dz).sqrt();
+        let allowed = limits.max_horizontal_speed * multiplier * ticks + margin;
+        if horizontal > allowed {
+            return Err(MovementViolation::TooFastHorizontal {
+                distance: horizontal,
+                allowed,
+            });
+        }
+
+        let dy = to.y - self.last_valid.y;
+        let allowed = if dy >= 0.0 {
+            limits.max_vertical_speed * multiplier * ticks
+        } else {
+            limits.max_fall_speed * multiplier * ticks
+        } + margin;
+        if dy.abs() > allowed {
+            return Err(MovementViolation::TooFastVertical {
+                distance: dy.abs(),
+                allowed,
+            });
+        }
+
+        self.accept_at(to, now);
+
+        Ok(())
+    }
+
+    /// Accepts `to` as the new last valid position without validating it.
+    pub fn accept(&mut self, to: &Position) {
+        self.accept_at(to, Instant::now());
+    }
+
+    fn accept_at(&mut self, to: &Position, now: Instant) {
+        self.last_valid = *to;
+        self.last_update = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> MovementLimits {
+        MovementLimits {
+            max_horizontal_speed: 1.0,
+            max_vertical_speed: 1.0,
+            max_fall_speed: 4.0,
+            flying_multiplier: 3.0,
+            elytra_multiplier: 8.0,
+            max_buffered_ticks: 5,
+            teleport_grace_ticks: 20,
+            teleport_grace_margin: 1.0,
+            world_border: 29_999_984.0,
+        }
+    }
+
+    #[test]
+    fn accepts_walking_and_rejects_teleporting() {
+        let mut state = MovementState::new(Position::new(0.0, 64.0, 0.0));
+        let start = state.last_update;
+        let abilities = MovementAbilities::default();
+
+        let walk = Position::new(0.5, 64.0, 0.2);
+        assert!(state.validate_at(&walk, &abilities, &limits(), start + TICK_DURATION).is_ok());
+        assert_eq!(state.last_valid, walk);
+
+        let far = Position::new(500.0, 64.0, 0.0);
+        let res = state.validate_at(&far, &abilities, &limits(), start + TICK_DURATION * 2);
+        assert!(matches!(res, Err(MovementViolation::TooFastHorizontal { .. })));
+        assert_eq!(state.last_valid, walk);
+    }
+
+    #[test]
+    fn rejects_invalid_coordinates() {
+        let mut state = MovementState::new(Position::default());
+        let abilities = MovementAbilities::default();
+
+        let nan = Position::new(f64::NAN, 0.0, 0.0);
+        assert_eq!(state.validate(&nan, &abilities, &limits()), Err(MovementViolation::InvalidCoordinates));
+
+        let inf = Position::new(0.0, f64::INFINITY, 0.0);
+        assert_eq!(state.validate(&inf, &abilities, &limits()), Err(MovementViolation::InvalidCoordinates));
+
+        let border = Position::new(0.0, 0.0, 30_000_000.0);
+        assert_eq!(state.validate(&border, &abilities, &limits()), Err(MovementViolation::OutsideWorldBorder));
+    }
+
+    #[test]
+    fn flying_and_falling_limits() {
+        let mut state = MovementState::new(Position::new(0.0, 100.0, 0.0));
+        let start = state.last_update;
+
+        let fall = Position::new(0.0, 97.0, 0.0);
+        assert!(state.validate_at(&fall, &MovementAbilities::default(), &limits(), start + TICK_DURATION).is_ok());
+
+        let fly = Position::new(2.5, 97.0, 0.0);
+        let flying = MovementAbilities { flying: true, gliding: false, may_fly: true };
+        assert!(state.validate_at(&fly, &MovementAbilities::default(), &limits(), start + TICK_DURATION * 2).is_err());
+        assert!(state.validate_at(&fly, &flying, &limits(), start + TICK_DURATION * 2).is_ok());
+    }
+
+    #[test]
+    fn idle_time_is_capped() {
+        let mut state = MovementState::new(Position::new(0.0, 64.0, 0.0));
+        let start = state.last_update;
+        let abilities = MovementAbilities::default();
+
+        // a minute of idle time only makes up for a few ticks
+        let later = start + Duration::from_secs(60);
+        let far = Position::new(50.0, 64.0, 0.0);
+        let res = state.validate_at(&far, &abilities, &limits(), later);
+        assert!(matches!(res, Err(MovementViolation::TooFastHorizontal { allowed, .. }) if allowed == 5.0));
+
+        let near = Position::new(4.5, 64.0, 0.0);
+        assert!(state.validate_at(&near, &abilities, &limits(), later).is_ok());
+    }
+
+    #[test]
+    fn teleports_must_be_confirmed_and_grant_grace() {
+        let mut state = MovementState::new(Position::default());
+        let abilities = MovementAbilities::default();
+
+        let id = state.teleport(Position::new(1000.0, 64.0, 1000.0));
+        let moved = Position::new(1001.5, 64.0, 1000.0);
+        assert_eq!(state.validate(&moved, &abilities, &limits()), Err(MovementViolation::AwaitingTeleport));
+
+        assert!(!state.confirm_teleport(id + 1));
+        assert!(state.confirm_teleport(id));
+        // within the grace period the margin is added on top of the normal speed
+        assert!(state.validate(&moved, &abilities, &limits()).is_ok());
+    }
+
+    #[test]
+    fn grace_period_still_checks_speed() {
+        let mut state = MovementState::new(Position::default());
+        let abilities = MovementAbilities::default();
+
+        let id = state.teleport(Position::new(0.0, 64.0, 0.0));
+        assert!(state.confirm_teleport(id));
+
+        let far = Position::new(500.0, 64.0, 0.0);
+        let res = state.validate(&far, &abilities, &limits());
+        assert!(matches!(res, Err(MovementViolation::TooFastHorizontal { .. })));
+        assert_eq!(state.last_valid, Position::new(0.0, 64.0, 0.0));
+    }
+}
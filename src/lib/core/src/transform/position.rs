@@ -1,5 +1,5 @@
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -11,6 +11,11 @@ impl Position {
     pub fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
     }
+
+    /// The x and z of the chunk this position is in.
+    pub fn chunk(&self) -> (i32, i32) {
+        ((self.x.floor() as i32).div_euclid(16), (self.z.floor() as i32).div_euclid(16))
+    }
 }
 
 // Implementations:
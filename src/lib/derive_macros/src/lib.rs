@@ -55,8 +55,4 @@ pub fn packet(args: TokenStream, input: TokenStream) -> TokenStream {
     net::packets::attribute(args, input)
 }
 
-#[proc_macro]
-pub fn bake_packet_registry(input: TokenStream) -> TokenStream {
-    net::packets::bake_registry(input)
-}
 // #=================== PACKETS ===================#
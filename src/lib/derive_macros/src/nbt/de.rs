@@ -120,7 +120,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
         impl #impl_generics #struct_name #ty_generics #where_clause {
             pub fn from_bytes(bytes: &#lifetime_without_ident [u8]) -> ::ferrumc_nbt::Result<Self> {
                 let mut tape = ::ferrumc_nbt::NbtTape::new(bytes);
-                tape.parse();
+                tape.parse()?;
                 let root = tape.root.as_ref()
                     .map(|(_, b)| b)
                     .ok_or(::ferrumc_nbt::NBTError::NoRootTag)?;
@@ -80,19 +80,19 @@ pub fn derive(input: TokenStream) -> TokenStream {
 
                 if flatten {
                     return quote! {
-                        <#ty as ::ferrumc_nbt::NBTSerializable>::serialize(&self.#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::Flatten);
+                        <#ty as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&self.#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::Flatten, encoding);
                     };
                 }
 
                 if let Some(condition) = skip_if {
                     quote! {
                         if !#condition (&self.#ident) {
-                            <#ty as ::ferrumc_nbt::NBTSerializable>::serialize(&self.#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#serialize_name));
+                            <#ty as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&self.#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#serialize_name), encoding);
                         }
                     }
                 } else {
                     quote! {
-                        <#ty as ::ferrumc_nbt::NBTSerializable>::serialize(&self.#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#serialize_name));
+                        <#ty as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&self.#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#serialize_name), encoding);
                     }
                 }
             });
@@ -171,7 +171,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
                             }
 
                             quote! {
-                                <#ty as ::ferrumc_nbt::NBTSerializable>::serialize(#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#serialize_name));
+                                <#ty as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#serialize_name), encoding);
                             }
                         });
 
@@ -182,11 +182,11 @@ pub fn derive(input: TokenStream) -> TokenStream {
                             if untagged { fields }
                             else {
                                 quote! {
-                                    <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#tag));
-                                    <u8 as ::ferrumc_nbt::NBTSerializable>::serialize(&10, writer, &::ferrumc_nbt::NBTSerializeOptions::None);
-                                    <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize(&#variant_content, writer, &::ferrumc_nbt::NBTSerializeOptions::None);
+                                    <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#tag), encoding);
+                                    <u8 as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&10, writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
+                                    <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&#variant_content, writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
                                     #fields
-                                    <u8 as ::ferrumc_nbt::NBTSerializable>::serialize(&0u8, writer, &::ferrumc_nbt::NBTSerializeOptions::None);
+                                    <u8 as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&0u8, writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
                                 }
                             }
                         } else { fields };
@@ -205,14 +205,14 @@ pub fn derive(input: TokenStream) -> TokenStream {
                             if !untagged && tagged.is_some() {
                                 if fields_unnamed.unnamed.len() == 1 {
                                     quote! {
-                                        <#ty as ::ferrumc_nbt::NBTSerializable>::serialize(#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#variant_content));
+                                        <#ty as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#variant_content), encoding);
                                     }
                                 } else {
                                     quote! { unimplemented!(); }
                                 }
                             } else {
                                 quote! {
-                                    <#ty as ::ferrumc_nbt::NBTSerializable>::serialize(#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::None);
+                                    <#ty as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(#ident, writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
                                 }
                             }
                         });
@@ -224,7 +224,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
                             if untagged { fields }
                             else {
                                 quote! {
-                                    <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#tag));
+                                    <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#tag), encoding);
                                     #fields
                                 }
                             }
@@ -241,13 +241,13 @@ pub fn derive(input: TokenStream) -> TokenStream {
                             if untagged {
                                 quote! {
                                     => {
-                                        <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::None);
+                                        <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
                                     }
                                 }
                             } else {
                                 quote! {
                                     => {
-                                        <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#tag));
+                                        <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&#tag_name, writer, &::ferrumc_nbt::NBTSerializeOptions::WithHeader(#tag), encoding);
                                     }
                                 }
                             }
@@ -274,14 +274,14 @@ pub fn derive(input: TokenStream) -> TokenStream {
 
     let expanded = quote! {
         impl #impl_generics ::ferrumc_nbt::NBTSerializable for #name #ty_generics #where_clause {
-            fn serialize(&self, writer: &mut Vec<u8>, options: &::ferrumc_nbt::NBTSerializeOptions) {
+            fn serialize_encoded(&self, writer: &mut Vec<u8>, options: &::ferrumc_nbt::NBTSerializeOptions, encoding: ::ferrumc_nbt::NbtEncoding) {
                 match options {
                     ::ferrumc_nbt::NBTSerializeOptions::WithHeader(name) => {
-                        <u8 as ::ferrumc_nbt::NBTSerializable>::serialize(&Self::id(), writer, &::ferrumc_nbt::NBTSerializeOptions::None);
-                        <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize(name, writer, &::ferrumc_nbt::NBTSerializeOptions::None);
+                        <u8 as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&Self::id(), writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
+                        <&'_ str as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(name, writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
                     }
                     ::ferrumc_nbt::NBTSerializeOptions::Network => {
-                        <u8 as ::ferrumc_nbt::NBTSerializable>::serialize(&Self::id(), writer, &::ferrumc_nbt::NBTSerializeOptions::None);
+                        <u8 as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&Self::id(), writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
                     }
                     ::ferrumc_nbt::NBTSerializeOptions::None => {}
                     ::ferrumc_nbt::NBTSerializeOptions::Flatten => {}
@@ -290,7 +290,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
                 #serialize_impl
 
                 if options != &::ferrumc_nbt::NBTSerializeOptions::Flatten && Self::id() == 10 {
-                    <u8 as ::ferrumc_nbt::NBTSerializable>::serialize(&0u8, writer, &::ferrumc_nbt::NBTSerializeOptions::None);
+                    <u8 as ::ferrumc_nbt::NBTSerializable>::serialize_encoded(&0u8, writer, &::ferrumc_nbt::NBTSerializeOptions::None, encoding);
                 }
             }
 
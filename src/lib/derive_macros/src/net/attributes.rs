@@ -0,0 +1,281 @@
+//! The `#[net(...)]` attributes shared by the `NetEncode` and `NetDecode` derives.
+//!
+//! Field attributes:
+//! - `#[net(varint)]`: encodes an integer as a VarInt, or a VarLong for `i64`/`u64`.
+//! - `#[net(length_prefixed)]`: prefixes a `Vec`, slice or array with its length as a VarInt.
+//! - `#[net(optional_if = "expr")]`: an `Option` that's only present if `expr` is true.
+//!   `expr` can use every field declared before, e.g. `optional_if = "has_signature"`.
+//! - `#[net(rest_of_packet)]`: a `Vec<u8>` that takes up the rest of the packet.
+//!
+//! Enum attributes:
+//! - `#[net(varint)]`: each variant is prefixed with its discriminant as a VarInt, followed by its fields.
+
+use quote::quote;
+use syn::{Expr, Field, LitStr, Type};
+
+#[derive(Default, Clone)]
+pub(crate) struct FieldAttributes {
+    pub varint: bool,
+    pub length_prefixed: bool,
+    pub optional_if: Option<Expr>,
+    pub rest_of_packet: bool,
+}
+
+pub(crate) fn parse_field_attributes(field: &Field) -> FieldAttributes {
+    let mut attributes = FieldAttributes::default();
+
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("net")) {
+        attr.parse_nested_meta(|meta| {
+            let Some(ident) = meta.path.get_ident() else {
+                return Err(meta.error("expected an identifier"));
+            };
+
+            match ident.to_string().as_str() {
+                "varint" => attributes.varint = true,
+                "length_prefixed" => attributes.length_prefixed = true,
+                "rest_of_packet" => attributes.rest_of_packet = true,
+                "optional_if" => {
+                    let condition = meta.value()?.parse::<LitStr>()?;
+                    attributes.optional_if = Some(condition.parse::<Expr>()?);
+                }
+                other => return Err(meta.error(format!("unknown net attribute `{other}`"))),
+            }
+
+            Ok(())
+        }).expect("Failed to parse net attribute");
+    }
+
+    let exclusive = [attributes.varint, attributes.length_prefixed, attributes.rest_of_packet];
+    if exclusive.iter().filter(|set| **set).count() > 1 {
+        panic!("`varint`, `length_prefixed` and `rest_of_packet` can't be combined on the same field");
+    }
+
+    attributes
+}
+
+/// Whether an enum has the `#[net(varint)]` attribute.
+pub(crate) fn is_varint_tagged(input: &syn::DeriveInput) -> bool {
+    let mut varint = false;
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("net")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("varint") {
+                varint = true;
+            } else if meta.input.peek(syn::Token![=]) {
+                // Other enum attributes like `type_cast` are handled by the derives themselves.
+                meta.value()?.parse::<syn::Lit>()?;
+            }
+            Ok(())
+        }).expect("Failed to parse net attribute");
+    }
+
+    varint
+}
+
+/// The tag of every variant of a `#[net(varint)]` enum, following the same rules as Rust discriminants.
+pub(crate) fn variant_tags(data: &syn::DataEnum) -> Vec<i32> {
+    let mut next = 0;
+
+    data.variants.iter().map(|variant| {
+        let tag = match &variant.discriminant {
+            Some((_, expr)) => parse_discriminant(expr),
+            None => next,
+        };
+        next = tag + 1;
+        tag
+    }).collect()
+}
+
+fn parse_discriminant(expr: &Expr) -> i32 {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(int), .. }) => {
+            int.base10_parse().expect("Discriminant doesn't fit into an i32")
+        }
+        Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), expr, .. }) => -parse_discriminant(expr),
+        _ => panic!("Discriminants of #[net(varint)] enums have to be integer literals"),
+    }
+}
+
+/// Returns `T` if `ty` is `wrapper<T>`, e.g. `Vec<T>` or `Option<T>`.
+fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    arguments.args.iter().find_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+fn is_64_bit(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.path.is_ident("i64") || type_path.path.is_ident("u64"))
+}
+
+fn var_type(ty: &Type) -> proc_macro2::TokenStream {
+    if is_64_bit(ty) {
+        quote! { ferrumc_net_codec::net_types::var_long::VarLong }
+    } else {
+        quote! { ferrumc_net_codec::net_types::var_int::VarInt }
+    }
+}
+
+fn var_cast(ty: &Type) -> proc_macro2::TokenStream {
+    if is_64_bit(ty) {
+        quote! { i64 }
+    } else {
+        quote! { i32 }
+    }
+}
+
+fn option_type(ty: &Type) -> &Type {
+    inner_type(ty, "Option").expect("`optional_if` can only be used on `Option` fields")
+}
+
+/// Encodes a field, `value` has to be a reference to it.
+pub(crate) fn encode_field(
+    ty: &Type,
+    attributes: &FieldAttributes,
+    value: proc_macro2::TokenStream,
+    asyncness: bool,
+) -> proc_macro2::TokenStream {
+    let (encode, dot_await) = if asyncness {
+        (quote! { encode_async }, quote! { .await })
+    } else {
+        (quote! { encode }, quote! {})
+    };
+    let none = quote! { &ferrumc_net_codec::encode::NetEncodeOpts::None };
+
+    if attributes.optional_if.is_some() {
+        let inner = option_type(ty);
+        let attributes = FieldAttributes { optional_if: None, ..attributes.clone() };
+        let encode_inner = encode_field(inner, &attributes, quote! { value }, asyncness);
+        return quote! {
+            if let Some(value) = #value {
+                #encode_inner
+            }
+        };
+    }
+
+    if attributes.varint {
+        let var_type = var_type(ty);
+        let cast = var_cast(ty);
+        return quote! {
+            <#var_type as ferrumc_net_codec::encode::NetEncode>::#encode(&#var_type::new(*#value as #cast), writer, #none)#dot_await?;
+        };
+    }
+
+    if attributes.length_prefixed {
+        return quote! {
+            <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::encode::NetEncode>::#encode(&(#value).len().into(), writer, #none)#dot_await?;
+            for item in (#value).iter() {
+                ferrumc_net_codec::encode::NetEncode::#encode(item, writer, #none)#dot_await?;
+            }
+        };
+    }
+
+    if attributes.rest_of_packet {
+        return if asyncness {
+            quote! { tokio::io::AsyncWriteExt::write_all(writer, #value).await?; }
+        } else {
+            quote! { std::io::Write::write_all(writer, #value)?; }
+        };
+    }
+
+    quote! {
+        <#ty as ferrumc_net_codec::encode::NetEncode>::#encode(#value, writer, #none)#dot_await?;
+    }
+}
+
+/// The encoded length of a field, `value` has to be a reference to it.
+pub(crate) fn field_len(ty: &Type, attributes: &FieldAttributes, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if attributes.optional_if.is_some() {
+        let inner = option_type(ty);
+        let attributes = FieldAttributes { optional_if: None, ..attributes.clone() };
+        let inner_len = field_len(inner, &attributes, quote! { value });
+        return quote! {
+            (#value).as_ref().map_or(0, |value| #inner_len)
+        };
+    }
+
+    if attributes.varint {
+        let var_type = var_type(ty);
+        let cast = var_cast(ty);
+        return quote! { #var_type::calculate_len(*#value as #cast) };
+    }
+
+    if attributes.length_prefixed {
+        return quote! {
+            (ferrumc_net_codec::net_types::var_int::VarInt::calculate_len((#value).len() as i32)
+                + (#value).iter().map(|item| ferrumc_net_codec::encode::NetEncode::encoded_len(item)).sum::<usize>())
+        };
+    }
+
+    if attributes.rest_of_packet {
+        return quote! { (#value).len() };
+    }
+
+    quote! { <#ty as ferrumc_net_codec::encode::NetEncode>::encoded_len(#value) }
+}
+
+/// An expression that decodes a field from `reader`.
+pub(crate) fn decode_field(ty: &Type, attributes: &FieldAttributes, asyncness: bool) -> proc_macro2::TokenStream {
+    let (decode, dot_await) = if asyncness {
+        (quote! { decode_async }, quote! { .await })
+    } else {
+        (quote! { decode }, quote! {})
+    };
+
+    if let Some(condition) = &attributes.optional_if {
+        let inner = option_type(ty);
+        let attributes = FieldAttributes { optional_if: None, ..attributes.clone() };
+        let decode_inner = decode_field(inner, &attributes, asyncness);
+        return quote! {
+            if #condition {
+                Some(#decode_inner)
+            } else {
+                None
+            }
+        };
+    }
+
+    if attributes.varint {
+        let var_type = var_type(ty);
+        return quote! {
+            <#var_type as ferrumc_net_codec::decode::NetDecode>::#decode(reader, opts)#dot_await?.val as #ty
+        };
+    }
+
+    if attributes.length_prefixed {
+        let element = inner_type(ty, "Vec").expect("Only `Vec` fields can be decoded with `length_prefixed`");
+        return quote! {
+            <ferrumc_net_codec::net_types::length_prefixed_vec::LengthPrefixedVec<#element> as ferrumc_net_codec::decode::NetDecode>::#decode(reader, opts)#dot_await?.data
+        };
+    }
+
+    if attributes.rest_of_packet {
+        let read_to_end = if asyncness {
+            quote! { tokio::io::AsyncReadExt::read_to_end(reader, &mut buf).await?; }
+        } else {
+            quote! { std::io::Read::read_to_end(reader, &mut buf)?; }
+        };
+        return quote! {
+            {
+                let mut buf = Vec::new();
+                #read_to_end
+                buf
+            }
+        };
+    }
+
+    quote! {
+        <#ty as ferrumc_net_codec::decode::NetDecode>::#decode(reader, opts)#dot_await?
+    }
+}
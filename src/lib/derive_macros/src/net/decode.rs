@@ -1,4 +1,5 @@
 use crate::helpers::{get_derive_attributes, StructInfo};
+use crate::net::attributes::{decode_field, is_varint_tagged, parse_field_attributes, variant_tags};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput, LitStr};
@@ -124,6 +125,68 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
                         _ => Err(ferrumc_net_codec::decode::errors::NetDecodeError::InvalidEnumVariant),
                     }
                 }
+
+                async fn decode_async<R: tokio::io::AsyncRead + std::marker::Unpin>(reader: &mut R, opts: &ferrumc_net_codec::decode::NetDecodeOpts) -> ferrumc_net_codec::decode::NetDecodeResult<Self> {
+                    let value = <#type_cast as ferrumc_net_codec::decode::NetDecode>::decode_async(reader, opts).await?;
+                    let value = #type_cast_handler;
+                    let value = value as #repr_attr;
+                    match (value as i32) {
+                        #(#enum_arms)*
+                        _ => Err(ferrumc_net_codec::decode::errors::NetDecodeError::InvalidEnumVariant),
+                    }
+                }
+            }
+        };
+
+        return TokenStream::from(expanded);
+    }
+
+    if let syn::Data::Enum(data) = &input.data {
+        if !is_varint_tagged(&input) {
+            panic!("NetDecode can only be derived for enums with type_cast or varint enabled.");
+        }
+
+        let StructInfo {
+            struct_name: name,
+            impl_generics,
+            ty_generics,
+            where_clause,
+            lifetime: _lifetime,
+            ..
+        } = crate::helpers::extract_struct_info(&input, None);
+
+        let generate_arms = |asyncness: bool| {
+            data.variants.iter().zip(variant_tags(data)).map(|(variant, tag)| {
+                let variant_name = &variant.ident;
+                let (bindings, construct) = decode_fields(&variant.fields, asyncness);
+                quote! {
+                    #tag => {
+                        #(#bindings)*
+                        Ok(#name::#variant_name #construct)
+                    }
+                }
+            }).collect::<Vec<_>>()
+        };
+        let arms = generate_arms(false);
+        let arms_async = generate_arms(true);
+
+        let expanded = quote! {
+            impl #impl_generics ferrumc_net_codec::decode::NetDecode for #name #ty_generics #where_clause {
+                fn decode<R: std::io::Read>(reader: &mut R, opts: &ferrumc_net_codec::decode::NetDecodeOpts) -> ferrumc_net_codec::decode::NetDecodeResult<Self> {
+                    let tag = <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::decode::NetDecode>::decode(reader, opts)?;
+                    match tag.val {
+                        #(#arms)*
+                        _ => Err(ferrumc_net_codec::decode::errors::NetDecodeError::InvalidEnumVariant),
+                    }
+                }
+
+                async fn decode_async<R: tokio::io::AsyncRead + std::marker::Unpin>(reader: &mut R, opts: &ferrumc_net_codec::decode::NetDecodeOpts) -> ferrumc_net_codec::decode::NetDecodeResult<Self> {
+                    let tag = <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::decode::NetDecode>::decode_async(reader, opts).await?;
+                    match tag.val {
+                        #(#arms_async)*
+                        _ => Err(ferrumc_net_codec::decode::errors::NetDecodeError::InvalidEnumVariant),
+                    }
+                }
             }
         };
 
@@ -133,16 +196,12 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
     let fields = if let syn::Data::Struct(data) = &input.data {
         &data.fields
     } else {
-        panic!("NetDecode can only be derived for structs or enums with u8_cast enabled.");
+        panic!("NetDecode can only be derived for structs or enums with type_cast or varint enabled.");
     };
 
-    let decode_fields = fields.iter().map(|field| {
-        let field_name = field.ident.as_ref().unwrap();
-        let field_ty = &field.ty;
-        quote! {
-            #field_name: <#field_ty as ferrumc_net_codec::decode::NetDecode>::decode(reader, opts)?,
-        }
-    });
+    // Every field is decoded into a local first, so `optional_if` conditions can refer to earlier fields.
+    let (field_decoders, construct) = decode_fields(fields, false);
+    let (async_field_decoders, _) = decode_fields(fields, true);
 
     let StructInfo {
         struct_name,
@@ -157,12 +216,39 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
         // impl ferrumc_net_codec::decode::NetDecode for #name {
         impl #impl_generics ferrumc_net_codec::decode::NetDecode for #struct_name #ty_generics #where_clause {
             fn decode<R: std::io::Read>(reader: &mut R, opts: &ferrumc_net_codec::decode::NetDecodeOpts) -> ferrumc_net_codec::decode::NetDecodeResult<Self> {
-                Ok(Self {
-                    #(#decode_fields)*
-                })
+                #(#field_decoders)*
+                Ok(Self #construct)
+            }
+
+            async fn decode_async<R: tokio::io::AsyncRead + std::marker::Unpin>(reader: &mut R, opts: &ferrumc_net_codec::decode::NetDecodeOpts) -> ferrumc_net_codec::decode::NetDecodeResult<Self> {
+                #(#async_field_decoders)*
+                Ok(Self #construct)
             }
         }
     };
 
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Generates a `let` binding per field, and the `{ .. }` or `( .. )` that builds the struct or variant from them.
+fn decode_fields(fields: &syn::Fields, asyncness: bool) -> (Vec<proc_macro2::TokenStream>, proc_macro2::TokenStream) {
+    let names = fields.iter().enumerate().map(|(i, field)| {
+        field.ident.clone().unwrap_or_else(|| quote::format_ident!("field{}", i))
+    }).collect::<Vec<_>>();
+
+    let bindings = fields.iter().zip(&names).map(|(field, name)| {
+        let field_ty = &field.ty;
+        let decode = decode_field(field_ty, &parse_field_attributes(field), asyncness);
+        quote! {
+            let #name: #field_ty = #decode;
+        }
+    }).collect();
+
+    let construct = match fields {
+        syn::Fields::Named(_) => quote! { { #(#names),* } },
+        syn::Fields::Unnamed(_) => quote! { ( #(#names),* ) },
+        syn::Fields::Unit => quote! {},
+    };
+
+    (bindings, construct)
+}
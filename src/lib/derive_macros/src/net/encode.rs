@@ -1,4 +1,5 @@
 use crate::helpers::{get_derive_attributes, StructInfo};
+use crate::net::attributes::{self, encode_field, field_len, is_varint_tagged, parse_field_attributes, FieldAttributes};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Attribute, DeriveInput, LitInt, Fields};
@@ -16,6 +17,9 @@ fn extract_packet_id(packet_attr: Vec<Attribute>) -> Option<u8> {
                 let value = meta.value().expect("value failed");
                 let value = value.parse::<LitInt>().expect("parse failed");
                 packet_id = Some(value.base10_parse::<u8>().expect("base10_parse failed"));
+            } else {
+                // Skip the value of other keys like `state`, which only matter for incoming packets.
+                meta.value()?.parse::<syn::Lit>()?;
             }
             Ok(())
         }).unwrap();
@@ -44,118 +48,137 @@ fn generate_packet_id_snippets(packet_id: Option<u8>) -> (proc_macro2::TokenStre
     (sync_snippet, async_snippet)
 }
 
-// Generate field encoding expressions for structs
-fn generate_field_encoders(fields: &syn::Fields) -> proc_macro2::TokenStream {
-    let encode_fields = fields.iter().map(|field| {
+// Generate the length of the packet ID, if any
+fn generate_packet_id_len(packet_id: Option<u8>) -> proc_macro2::TokenStream {
+    match packet_id {
+        Some(id) => quote! {
+            ferrumc_net_codec::net_types::var_int::VarInt::calculate_len(#id as i32)
+        },
+        None => quote! { 0 },
+    }
+}
+
+// Generate the sum of the encoded lengths of all fields of a struct
+fn generate_field_lens(fields: &syn::Fields) -> proc_macro2::TokenStream {
+    let field_lens = fields.iter().map(|field| {
         let field_name = field.ident.as_ref().unwrap();
-        let field_ty = &field.ty;
+        let len = field_len(&field.ty, &parse_field_attributes(field), quote! { &self.#field_name });
+        quote! { + #len }
+    });
+    quote! { #(#field_lens)* }
+}
+
+// The names variant fields are bound to when matching on an enum
+fn variant_field_names(fields: &syn::Fields) -> Vec<syn::Ident> {
+    fields.iter().enumerate().map(|(i, field)| {
+        field.ident.clone().unwrap_or_else(|| syn::Ident::new(&format!("field{}", i), proc_macro2::Span::call_site()))
+    }).collect()
+}
+
+// Generate the pattern that binds all fields of a variant
+fn variant_pattern(variant: &syn::Variant, field_names: &[syn::Ident]) -> proc_macro2::TokenStream {
+    let variant_ident = &variant.ident;
+    match &variant.fields {
+        Fields::Named(_) => quote! { Self::#variant_ident { #(#field_names),* } },
+        Fields::Unnamed(_) => quote! { Self::#variant_ident(#(#field_names),*) },
+        Fields::Unit => quote! { Self::#variant_ident },
+    }
+}
+
+// The VarInt tag of every variant, if the enum is `#[net(varint)]`
+fn variant_tags(input: &DeriveInput, data: &syn::DataEnum) -> Vec<Option<i32>> {
+    if is_varint_tagged(input) {
+        attributes::variant_tags(data).into_iter().map(Some).collect()
+    } else {
+        vec![None; data.variants.len()]
+    }
+}
+
+// Generate the encoded length of whichever variant an enum is
+fn generate_enum_lens(data: &syn::DataEnum, tags: &[Option<i32>]) -> proc_macro2::TokenStream {
+    let variants = data.variants.iter().zip(tags).map(|(variant, tag)| {
+        let field_names = variant_field_names(&variant.fields);
+        let pattern = variant_pattern(variant, &field_names);
+        let tag_len = match tag {
+            Some(tag) => quote! { ferrumc_net_codec::net_types::var_int::VarInt::calculate_len(#tag) },
+            None => quote! { 0 },
+        };
+        let field_lens = variant.fields.iter().zip(&field_names).map(|(field, name)| {
+            field_len(&field.ty, &parse_field_attributes(field), quote! { #name })
+        });
+
         quote! {
-            <#field_ty as ferrumc_net_codec::encode::NetEncode>::encode(&self.#field_name, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None)?;
+            #pattern => {
+                #tag_len #(+ #field_lens)*
+            }
         }
     });
-    quote! { #(#encode_fields)* }
+
+    quote! {
+        + match self {
+            #(#variants),*
+        }
+    }
 }
 
-fn generate_async_field_encoders(fields: &syn::Fields) -> proc_macro2::TokenStream {
+// Generate field encoding expressions for structs
+fn generate_field_encoders(fields: &syn::Fields, asyncness: bool) -> proc_macro2::TokenStream {
     let encode_fields = fields.iter().map(|field| {
         let field_name = field.ident.as_ref().unwrap();
-        let field_ty = &field.ty;
-        quote! {
-            <#field_ty as ferrumc_net_codec::encode::NetEncode>::encode_async(&self.#field_name, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None).await?;
-        }
+        encode_field(&field.ty, &parse_field_attributes(field), quote! { &self.#field_name }, asyncness)
     });
     quote! { #(#encode_fields)* }
 }
 
 // Generate enum variant encoding using static dispatch
-fn generate_enum_encoders(data: &syn::DataEnum) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
-    let variants = data.variants.iter().map(|variant| {
-        let variant_ident = &variant.ident;
-
-        match &variant.fields {
-            Fields::Named(fields) => {
-                let field_idents: Vec<_> = fields.named.iter()
-                    .map(|f| f.ident.as_ref().unwrap())
-                    .collect();
-                let field_tys: Vec<_> = fields.named.iter()
-                    .map(|f| &f.ty)
-                    .collect();
-
-                (quote! {
-                    Self::#variant_ident { #(#field_idents),* } => {
-                        #(
-                            <#field_tys as ferrumc_net_codec::encode::NetEncode>::encode(#field_idents, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None)?;
-                        )*
-                    }
-                },
-                 quote! {
-                    Self::#variant_ident { #(#field_idents),* } => {
-                        #(
-                            <#field_tys as ferrumc_net_codec::encode::NetEncode>::encode_async(#field_idents, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None).await?;
-                        )*
-                    }
-                })
-            },
-            Fields::Unnamed(fields) => {
-                let field_names: Vec<_> = (0..fields.unnamed.len())
-                    .map(|i| syn::Ident::new(&format!("field{}", i), proc_macro2::Span::call_site()))
-                    .collect();
-                let field_tys: Vec<_> = fields.unnamed.iter()
-                    .map(|f| &f.ty)
-                    .collect();
-
-                (quote! {
-                    Self::#variant_ident(#(#field_names),*) => {
-                        #(
-                            <#field_tys as ferrumc_net_codec::encode::NetEncode>::encode(#field_names, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None)?;
-                        )*
-                    }
-                },
-                 quote! {
-                    Self::#variant_ident(#(#field_names),*) => {
-                        #(
-                            <#field_tys as ferrumc_net_codec::encode::NetEncode>::encode_async(#field_names, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None).await?;
-                        )*
-                    }
-                })
-            },
-            Fields::Unit => (
-                quote! {
-                    Self::#variant_ident => {}
-                },
-                quote! {
-                    Self::#variant_ident => {}
+fn generate_enum_encoders(data: &syn::DataEnum, tags: &[Option<i32>]) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let generate = |asyncness: bool| {
+        let variants = data.variants.iter().zip(tags).map(|(variant, tag)| {
+            let field_names = variant_field_names(&variant.fields);
+            let pattern = variant_pattern(variant, &field_names);
+            let tag_encoder = match tag {
+                Some(tag) => encode_field(
+                    &syn::parse_quote!(i32),
+                    &FieldAttributes { varint: true, ..Default::default() },
+                    quote! { &#tag },
+                    asyncness,
+                ),
+                None => quote! {},
+            };
+            let field_encoders = variant.fields.iter().zip(&field_names).map(|(field, name)| {
+                encode_field(&field.ty, &parse_field_attributes(field), quote! { #name }, asyncness)
+            });
+
+            quote! {
+                #pattern => {
+                    #tag_encoder
+                    #(#field_encoders)*
                 }
-            ),
-        }
-    }).unzip::<_, _, Vec<_>, Vec<_>>();
-
-    let (sync_variants, async_variants) = variants;
-
-    (
-        quote! {
-            match self {
-                #(#sync_variants)*
             }
-        },
+        });
+
         quote! {
             match self {
-                #(#async_variants)*
+                #(#variants)*
             }
         }
-    )
+    };
+
+    (generate(false), generate(true))
 }
 
 pub(crate) fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let packet_attr = get_derive_attributes(&input, "packet");
-    let (packet_id_snippet, async_packet_id_snippet) = generate_packet_id_snippets(extract_packet_id(packet_attr));
+    let packet_id = extract_packet_id(packet_attr);
+    let (packet_id_snippet, async_packet_id_snippet) = generate_packet_id_snippets(packet_id);
+    let packet_id_len = generate_packet_id_len(packet_id);
 
     let (sync_impl, async_impl) = match &input.data {
         syn::Data::Struct(data) => {
-            let field_encoders = generate_field_encoders(&data.fields);
-            let async_field_encoders = generate_async_field_encoders(&data.fields);
+            let field_encoders = generate_field_encoders(&data.fields, false);
+            let async_field_encoders = generate_field_encoders(&data.fields, true);
 
             (quote! {
                 fn encode<W: std::io::Write>(&self, writer: &mut W, opts: &ferrumc_net_codec::encode::NetEncodeOpts) -> ferrumc_net_codec::encode::NetEncodeResult<()> {
@@ -165,20 +188,15 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
                             #field_encoders
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::WithLength => {
-                            let actual_writer = writer;
-                            let mut writer = Vec::new();
-                            let mut writer = &mut writer;
+                            let len: ferrumc_net_codec::net_types::var_int::VarInt = <Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self).into();
+                            <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::encode::NetEncode>::encode(&len, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None)?;
 
                             #packet_id_snippet
                             #field_encoders
-
-                            let len: ferrumc_net_codec::net_types::var_int::VarInt = writer.len().into();
-                            <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::encode::NetEncode>::encode(&len, actual_writer, &ferrumc_net_codec::encode::NetEncodeOpts::None)?;
-                            actual_writer.write_all(writer)?;
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::Compressed => {
                             let actual_writer = writer;
-                            let mut writer = Vec::new();
+                            let mut writer = Vec::with_capacity(<Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self));
                             let mut writer = &mut writer;
 
                             let compression_threshold = ferrumc_config::statics::get_global_config().network_compression_threshold;
@@ -223,20 +241,16 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
                             #async_field_encoders
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::WithLength => {
-                            let actual_writer = writer;
-                            let mut writer = Vec::new();
-                            let mut writer = &mut writer;
+                            // Encode into a buffer first, so the whole packet is sent with a single write
+                            let len = <Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self);
+                            let mut buffer = Vec::with_capacity(ferrumc_net_codec::net_types::var_int::VarInt::calculate_len(len as i32) + len);
+                            <Self as ferrumc_net_codec::encode::NetEncode>::encode(self, &mut buffer, opts)?;
 
-                            #async_packet_id_snippet
-                            #field_encoders
-
-                            let len: ferrumc_net_codec::net_types::var_int::VarInt = writer.len().into();
-                            <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::encode::NetEncode>::encode_async(&len, actual_writer, &ferrumc_net_codec::encode::NetEncodeOpts::None).await?;
-                            <W as tokio::io::AsyncWriteExt>::write_all(actual_writer, writer).await?;
+                            <W as tokio::io::AsyncWriteExt>::write_all(writer, &buffer).await?;
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::Compressed => {
                             let actual_writer = writer;
-                            let mut writer = Vec::new();
+                            let mut writer = Vec::with_capacity(<Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self));
                             let mut writer = &mut writer;
 
                             let compression_threshold = ferrumc_config::statics::get_global_config().network_compression_threshold;
@@ -277,7 +291,7 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
             })
         },
         syn::Data::Enum(data) => {
-            let (sync_enum_encoder, async_enum_encoder) = generate_enum_encoders(data);
+            let (sync_enum_encoder, async_enum_encoder) = generate_enum_encoders(data, &variant_tags(&input, data));
 
             (quote! {
                 fn encode<W: std::io::Write>(&self, writer: &mut W, opts: &ferrumc_net_codec::encode::NetEncodeOpts) -> ferrumc_net_codec::encode::NetEncodeResult<()> {
@@ -287,20 +301,15 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
                             #sync_enum_encoder
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::WithLength => {
-                            let actual_writer = writer;
-                            let mut writer = Vec::new();
-                            let mut writer = &mut writer;
+                            let len: ferrumc_net_codec::net_types::var_int::VarInt = <Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self).into();
+                            <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::encode::NetEncode>::encode(&len, writer, &ferrumc_net_codec::encode::NetEncodeOpts::None)?;
 
                             #packet_id_snippet
                             #sync_enum_encoder
-
-                            let len: ferrumc_net_codec::net_types::var_int::VarInt = writer.len().into();
-                            <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::encode::NetEncode>::encode(&len, actual_writer, &ferrumc_net_codec::encode::NetEncodeOpts::None)?;
-                            actual_writer.write_all(writer)?;
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::Compressed => {
                             let actual_writer = writer;
-                            let mut writer = Vec::new();
+                            let mut writer = Vec::with_capacity(<Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self));
                             let mut writer = &mut writer;
 
                             let compression_threshold = ferrumc_config::statics::get_global_config().network_compression_threshold;
@@ -345,20 +354,16 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
                             #async_enum_encoder
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::WithLength => {
-                            let actual_writer = writer;
-                            let mut writer = Vec::new();
-                            let mut writer = &mut writer;
+                            // Encode into a buffer first, so the whole packet is sent with a single write
+                            let len = <Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self);
+                            let mut buffer = Vec::with_capacity(ferrumc_net_codec::net_types::var_int::VarInt::calculate_len(len as i32) + len);
+                            <Self as ferrumc_net_codec::encode::NetEncode>::encode(self, &mut buffer, opts)?;
 
-                            #async_packet_id_snippet
-                            #sync_enum_encoder
-
-                            let len: ferrumc_net_codec::net_types::var_int::VarInt = writer.len().into();
-                            <ferrumc_net_codec::net_types::var_int::VarInt as ferrumc_net_codec::encode::NetEncode>::encode_async(&len, actual_writer, &ferrumc_net_codec::encode::NetEncodeOpts::None).await?;
-                            <W as tokio::io::AsyncWriteExt>::write_all(actual_writer, writer).await?;
+                            <W as tokio::io::AsyncWriteExt>::write_all(writer, &buffer).await?;
                         }
                         ferrumc_net_codec::encode::NetEncodeOpts::Compressed => {
                             let actual_writer = writer;
-                            let mut writer = Vec::new();
+                            let mut writer = Vec::with_capacity(<Self as ferrumc_net_codec::encode::NetEncode>::encoded_len(self));
                             let mut writer = &mut writer;
 
                             let compression_threshold = ferrumc_config::statics::get_global_config().network_compression_threshold;
@@ -399,6 +404,12 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
         _ => unimplemented!("NetEncode can only be derived for structs and enums"),
     };
 
+    let data_len = match &input.data {
+        syn::Data::Struct(data) => generate_field_lens(&data.fields),
+        syn::Data::Enum(data) => generate_enum_lens(data, &variant_tags(&input, data)),
+        _ => unreachable!(),
+    };
+
     let StructInfo {
         struct_name,
         impl_generics,
@@ -412,6 +423,10 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
         impl #impl_generics ferrumc_net_codec::encode::NetEncode for #struct_name #ty_generics #where_clause {
             #sync_impl
             #async_impl
+
+            fn encoded_len(&self) -> usize {
+                #packet_id_len #data_len
+            }
         }
     })
 }
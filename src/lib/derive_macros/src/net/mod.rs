@@ -1,3 +1,4 @@
+pub(super) mod attributes;
 pub(super) mod encode;
 pub(super) mod decode; 
-pub(super) mod packets;
\ No newline at end of file
+pub(super) mod packets; 
\ No newline at end of file
@@ -0,0 +1,177 @@
+//! Compares broadcasting packets through `StreamWriter` by encoding them for every player with
+//! `send_raw`, with encoding them once into a pooled buffer that every player shares with `send_bytes`.
+//! Both go through the real send path to local sockets and wait until everything is written.
+//!
+//! Allocations per tick are counted with a wrapping global allocator and printed before the timings.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ferrumc_net::connection::StreamWriter;
+use ferrumc_net::packets::outgoing::synchronize_player_position::SynchronizePlayerPositionPacket;
+use ferrumc_net::packets::outgoing::update_time::UpdateTimePacket;
+use ferrumc_net_codec::encode::pool::BufferPool;
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// How many players every packet is sent to.
+const PLAYERS: usize = 100;
+
+/// The packets that are broadcast every tick.
+struct Tick {
+    time: UpdateTimePacket,
+    positions: Vec<SynchronizePlayerPositionPacket>,
+}
+
+impl Tick {
+    fn new() -> Self {
+        Self {
+            time: UpdateTimePacket::new(12345, 12345),
+            // A movement update of every player
+            positions: (0..PLAYERS)
+                .map(|i| SynchronizePlayerPositionPacket::new(i as f64, 64.0, -(i as f64), 0.0, 0.0, 0, VarInt::new(0)))
+                .collect(),
+        }
+    }
+}
+
+/// What `NetEncodeOpts::WithLength` used to do: encode into a temporary `Vec`, then copy it after the length.
+fn encode_into_vec<T: NetEncode>(packet: &T) -> Vec<u8> {
+    let mut data = Vec::new();
+    packet.encode(&mut data, &NetEncodeOpts::None).unwrap();
+
+    let mut buffer = Vec::new();
+    VarInt::from(data.len()).write(&mut buffer).unwrap();
+    buffer.write_all(&data).unwrap();
+    buffer
+}
+
+/// Connects a writer for every player, whose client reads and drops everything it gets.
+async fn connect_players() -> Vec<StreamWriter> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut writers = Vec::with_capacity(PLAYERS);
+    for _ in 0..PLAYERS {
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (mut client, _) = listener.accept().await.unwrap();
+        tokio::spawn(async move {
+            let mut buf = vec![0; 64 * 1024];
+            while client.read(&mut buf).await.is_ok_and(|read| read > 0) {}
+        });
+
+        let (_, write_half) = stream.into_split();
+        writers.push(StreamWriter::new(write_half));
+    }
+    writers
+}
+
+/// Waits until every writer wrote everything that was queued.
+async fn flush(writers: &[StreamWriter]) {
+    while writers.iter().any(|writer| writer.queued_bytes() > 0) {
+        tokio::task::yield_now().await;
+    }
+}
+
+async fn broadcast_per_player<T: NetEncode>(packet: &T, writers: &mut [StreamWriter]) {
+    for writer in writers.iter_mut() {
+        writer.send_raw(packet, &NetEncodeOpts::WithLength).await.unwrap();
+    }
+}
+
+async fn tick_per_player(tick: &Tick, writers: &mut [StreamWriter]) {
+    broadcast_per_player(&tick.time, writers).await;
+    for position in &tick.positions {
+        broadcast_per_player(position, writers).await;
+    }
+    flush(writers).await;
+}
+
+async fn broadcast_shared<T: NetEncode>(packet: &T, writers: &mut [StreamWriter]) {
+    let packet = BufferPool::global().encode(packet, &NetEncodeOpts::WithLength).unwrap();
+    for writer in writers.iter_mut() {
+        writer.send_bytes(packet.clone()).await.unwrap();
+    }
+    BufferPool::global().recycle(packet);
+}
+
+async fn tick_shared(tick: &Tick, writers: &mut [StreamWriter]) {
+    broadcast_shared(&tick.time, writers).await;
+    for position in &tick.positions {
+        broadcast_shared(position, writers).await;
+    }
+    flush(writers).await;
+}
+
+fn allocations_per_tick(mut tick: impl FnMut()) -> f64 {
+    const TICKS: usize = 100;
+
+    // Warm up, so the pool is filled
+    tick();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..TICKS {
+        tick();
+    }
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / TICKS as f64
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let tick = Tick::new();
+    let pool = BufferPool::new();
+
+    let runtime = Runtime::new().unwrap();
+    let mut writers = runtime.block_on(connect_players());
+
+    println!(
+        "Allocations per tick ({} packets broadcast to {} players): per player {:.1}, shared {:.1}",
+        tick.positions.len() + 1,
+        PLAYERS,
+        allocations_per_tick(|| runtime.block_on(tick_per_player(&tick, &mut writers))),
+        allocations_per_tick(|| runtime.block_on(tick_shared(&tick, &mut writers))),
+    );
+
+    let mut group = c.benchmark_group("Broadcast tick");
+    group.bench_function("send_raw per player", |b| {
+        b.iter(|| runtime.block_on(tick_per_player(&tick, &mut writers)))
+    });
+    group.bench_function("send_bytes shared", |b| {
+        b.iter(|| runtime.block_on(tick_shared(&tick, &mut writers)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("Encode UpdateTime");
+    group.bench_function("Vec", |b| b.iter(|| black_box(encode_into_vec(&tick.time))));
+    group.bench_function("BufferPool", |b| {
+        b.iter(|| pool.recycle(black_box(pool.encode(&tick.time, &NetEncodeOpts::WithLength).unwrap())))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
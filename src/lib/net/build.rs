@@ -0,0 +1,455 @@
+//! Generates packets from the protocol spec in `protocol/`.
+//!
+//! Every packet of the supported version is listed in the spec with its id, state and direction.
+//! Packets with `fields` get a generated struct in `packets::generated`, packets without are
+//! hand-written in `src/packets/incoming` or `src/packets/outgoing`. A hand-written packet with
+//! the same name as a generated one replaces it, so custom behaviour can be added when needed.
+//!
+//! The build fails if a hand-written packet is missing from the spec or disagrees with it,
+//! which keeps all packet ids in one place when updating to a new Minecraft version.
+//!
+//! Besides the packet structs, the incoming packet registry (`handle_packet` and `debug_packet`)
+//! is generated for all serverbound packets, and an `OutgoingPacket` implementation and an entry
+//! in `CLIENTBOUND_PACKETS` for all clientbound packets, hand-written or not.
+
+use quote::ToTokens;
+use syn::visit_mut::VisitMut;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const SPEC: &str = "protocol/1.21.1.json";
+const INCOMING_DIR: &str = "src/packets/incoming";
+const OUTGOING_DIR: &str = "src/packets/outgoing";
+const STATES: [&str; 5] = ["handshake", "status", "login", "configuration", "play"];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Spec {
+    minecraft_version: String,
+    protocol_version: i32,
+    packets: Vec<PacketSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PacketSpec {
+    name: String,
+    direction: Direction,
+    state: String,
+    /// In hexadecimal, e.g. `"0x2B"`.
+    id: String,
+    doc: Option<String>,
+    /// `None` if the packet is hand-written.
+    fields: Option<Vec<FieldSpec>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldSpec {
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    doc: Option<String>,
+    #[serde(default)]
+    varint: bool,
+    #[serde(default)]
+    length_prefixed: bool,
+    optional_if: Option<String>,
+    #[serde(default)]
+    rest_of_packet: bool,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Serverbound,
+    Clientbound,
+}
+
+/// A packet struct with a `#[packet]` attribute in one of the packet directories.
+struct HandWritten {
+    name: String,
+    module: String,
+    id: u8,
+    state: Option<String>,
+    /// The generics of the struct, as needed for an `impl` block.
+    impl_generics: String,
+    ty_generics: String,
+    where_clause: String,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={SPEC}");
+    println!("cargo:rerun-if-changed={INCOMING_DIR}");
+    println!("cargo:rerun-if-changed={OUTGOING_DIR}");
+
+    let spec = std::fs::read_to_string(SPEC).unwrap_or_else(|e| panic!("Failed to read {SPEC}: {e}"));
+    let spec: Spec = serde_json::from_str(&spec).unwrap_or_else(|e| panic!("Failed to parse {SPEC}: {e}"));
+
+    let ids = validate_spec(&spec);
+
+    let incoming = find_hand_written(Path::new(INCOMING_DIR));
+    let outgoing = find_hand_written(Path::new(OUTGOING_DIR));
+    check_hand_written(&spec, &ids, Direction::Serverbound, &incoming);
+    check_hand_written(&spec, &ids, Direction::Clientbound, &outgoing);
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR not set"));
+    let generated = out_dir.join("packets");
+    std::fs::create_dir_all(&generated).expect("Failed to create the generated packets directory");
+
+    write(&generated.join("spec.rs"), &generate_spec_info(&spec, &ids));
+    write(&generated.join("incoming.rs"), &generate_packets(&spec, Direction::Serverbound, &incoming));
+    write(&generated.join("outgoing.rs"), &generate_packets(&spec, Direction::Clientbound, &outgoing));
+    let mut registry = generate_registry(&spec, &ids, &incoming);
+    registry.push_str(&generate_outgoing_impls(&spec, &outgoing));
+    write(&out_dir.join("registry.rs"), &registry);
+}
+
+fn write(path: &Path, content: &str) {
+    std::fs::write(path, content).unwrap_or_else(|e| panic!("Failed to write {}: {e}", path.display()));
+}
+
+/// Checks that states, ids and names are valid and unique, and returns the parsed ids.
+fn validate_spec(spec: &Spec) -> Vec<u8> {
+    let mut names = HashSet::new();
+    let mut keys = HashSet::new();
+
+    spec.packets.iter().map(|packet| {
+        let id = packet.id.strip_prefix("0x")
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .unwrap_or_else(|| panic!("{}: invalid packet id `{}`, expected a hex byte like `0x2B`", packet.name, packet.id));
+
+        if !STATES.contains(&packet.state.as_str()) {
+            panic!("{}: unknown state `{}`, expected one of {STATES:?}", packet.name, packet.state);
+        }
+        if !names.insert((packet.direction, packet.name.as_str())) {
+            panic!("{}: there's more than one {:?} packet with this name", packet.name, packet.direction);
+        }
+        if !keys.insert((packet.direction, packet.state.as_str(), id)) {
+            panic!("{}: there's more than one {:?} packet with id 0x{id:02X} in the {} state", packet.name, packet.direction, packet.state);
+        }
+
+        id
+    }).collect()
+}
+
+fn find_hand_written(dir: &Path) -> Vec<HandWritten> {
+    let mut entries = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {e}", dir.display()))
+        .map(|entry| entry.expect("Failed to read directory entry").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .collect::<Vec<_>>();
+    // Keeps the generated registry the same between builds.
+    entries.sort();
+
+    let mut packets = Vec::new();
+    for path in entries {
+        let module = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let content = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+        let file = syn::parse_file(&content).unwrap_or_else(|e| panic!("Failed to parse {}: {e}", path.display()));
+
+        let imports = find_imports(&file);
+
+        for item in file.items {
+            let syn::Item::Struct(mut item_struct) = item else {
+                continue;
+            };
+            // The generics are used outside of the module, so the names imported there have to be resolved.
+            ResolveImports(&imports).visit_generics_mut(&mut item_struct.generics);
+
+            for attr in item_struct.attrs.iter().filter(|attr| attr.path().is_ident("packet")) {
+                let mut id = None;
+                let mut state = None;
+
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("packet_id") {
+                        id = Some(meta.value()?.parse::<syn::LitInt>()?.base10_parse::<u8>()?);
+                    } else if meta.path.is_ident("state") {
+                        state = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    }
+                    Ok(())
+                }).unwrap_or_else(|e| panic!("{}: invalid packet attribute: {e}", path.display()));
+
+                let (impl_generics, ty_generics, where_clause) = item_struct.generics.split_for_impl();
+                packets.push(HandWritten {
+                    name: item_struct.ident.to_string(),
+                    module: module.clone(),
+                    id: id.unwrap_or_else(|| panic!("{}: packet_id not found on {}", path.display(), item_struct.ident)),
+                    state,
+                    impl_generics: impl_generics.to_token_stream().to_string(),
+                    ty_generics: ty_generics.to_token_stream().to_string(),
+                    where_clause: where_clause.to_token_stream().to_string(),
+                });
+            }
+        }
+    }
+
+    packets
+}
+
+/// Maps the names imported by `use` items in a file to their full paths.
+fn find_imports(file: &syn::File) -> HashMap<String, syn::Path> {
+    fn walk(tree: &syn::UseTree, prefix: &mut Vec<syn::Ident>, imports: &mut HashMap<String, syn::Path>) {
+        let path = |prefix: &[syn::Ident], ident: &syn::Ident| {
+            let segments = prefix.iter().chain(std::iter::once(ident)).map(|ident| ident.to_string()).collect::<Vec<_>>();
+            syn::parse_str::<syn::Path>(&segments.join("::")).expect("Failed to parse import path")
+        };
+
+        match tree {
+            syn::UseTree::Path(use_path) => {
+                prefix.push(use_path.ident.clone());
+                walk(&use_path.tree, prefix, imports);
+                prefix.pop();
+            }
+            syn::UseTree::Name(name) if name.ident != "self" => {
+                imports.insert(name.ident.to_string(), path(prefix, &name.ident));
+            }
+            syn::UseTree::Rename(rename) => {
+                imports.insert(rename.rename.to_string(), path(prefix, &rename.ident));
+            }
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    walk(tree, prefix, imports);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut imports = HashMap::new();
+    for item in &file.items {
+        if let syn::Item::Use(item_use) = item {
+            walk(&item_use.tree, &mut Vec::new(), &mut imports);
+        }
+    }
+    imports
+}
+
+/// Replaces the first segment of every path with its import, if it's imported.
+struct ResolveImports<'a>(&'a HashMap<String, syn::Path>);
+
+impl VisitMut for ResolveImports<'_> {
+    fn visit_path_mut(&mut self, path: &mut syn::Path) {
+        if path.leading_colon.is_none() {
+            if let Some(import) = path.segments.first().and_then(|segment| self.0.get(&segment.ident.to_string())) {
+                let mut resolved = import.clone();
+                resolved.segments.last_mut().unwrap().arguments = path.segments[0].arguments.clone();
+                resolved.segments.extend(path.segments.iter().skip(1).cloned());
+                // `crate::` and dependency paths work from anywhere, imports with `super::` or `self::` don't.
+                *path = resolved;
+            }
+        }
+        syn::visit_mut::visit_path_mut(self, path);
+    }
+}
+
+/// Makes sure every hand-written packet matches its spec entry, and every packet without fields is hand-written.
+fn check_hand_written(spec: &Spec, ids: &[u8], direction: Direction, hand_written: &[HandWritten]) {
+    let by_name = hand_written.iter().map(|packet| (packet.name.as_str(), packet)).collect::<HashMap<_, _>>();
+
+    for packet in hand_written {
+        let Some((index, entry)) = spec.packets.iter().enumerate()
+            .find(|(_, entry)| entry.direction == direction && entry.name == packet.name) else {
+            panic!("{} is not in {SPEC}, add it as a {direction:?} packet", packet.name);
+        };
+
+        if packet.id != ids[index] {
+            panic!("{} has packet id 0x{:02X}, but {SPEC} says {}", packet.name, packet.id, entry.id);
+        }
+        if packet.state.as_deref() != Some(entry.state.as_str()) {
+            panic!("{} has state {:?}, but {SPEC} says \"{}\"", packet.name, packet.state, entry.state);
+        }
+    }
+
+    for entry in spec.packets.iter().filter(|entry| entry.direction == direction) {
+        if entry.fields.is_none() && !by_name.contains_key(entry.name.as_str()) {
+            panic!("{} has no fields in {SPEC}, so it needs to be hand-written", entry.name);
+        }
+    }
+}
+
+fn is_generated(entry: &PacketSpec, hand_written: &[HandWritten]) -> bool {
+    entry.fields.is_some() && !hand_written.iter().any(|packet| packet.name == entry.name)
+}
+
+/// The `ConnectionState` variant of a state in the spec.
+fn state_variant(state: &str) -> &'static str {
+    match state {
+        "handshake" => "Handshaking",
+        "status" => "Status",
+        "login" => "Login",
+        "configuration" => "Configuration",
+        "play" => "Play",
+        _ => unreachable!("states are validated when loading the spec"),
+    }
+}
+
+fn generate_spec_info(spec: &Spec, ids: &[u8]) -> String {
+    let mut out = String::new();
+    out.push_str("// @generated by build.rs from the protocol spec, do not edit.\n\n");
+    writeln!(out, "/// The Minecraft version the packets are generated for.").unwrap();
+    writeln!(out, "pub const MINECRAFT_VERSION: &str = {:?};", spec.minecraft_version).unwrap();
+    writeln!(out, "/// The protocol version of [`MINECRAFT_VERSION`].").unwrap();
+    writeln!(out, "pub const PROTOCOL_VERSION: i32 = {};", spec.protocol_version).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "/// Every clientbound packet in the protocol spec, hand-written or generated.").unwrap();
+    writeln!(out, "pub const CLIENTBOUND_PACKETS: &[crate::packets::PacketInfo] = &[").unwrap();
+    for (entry, id) in spec.packets.iter().zip(ids) {
+        if entry.direction != Direction::Clientbound {
+            continue;
+        }
+        writeln!(
+            out,
+            "    crate::packets::PacketInfo {{ name: {:?}, id: {id:#04X}, state: crate::connection::ConnectionState::{} }},",
+            entry.name,
+            state_variant(&entry.state)
+        ).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    out
+}
+
+fn generate_packets(spec: &Spec, direction: Direction, hand_written: &[HandWritten]) -> String {
+    let mut out = String::new();
+    out.push_str("// @generated by build.rs from the protocol spec, do not edit.\n\n");
+    out.push_str("#[allow(unused_imports)]\n");
+    out.push_str("use ferrumc_net_codec::net_types::{\n");
+    out.push_str("    angle::Angle, bit_set::{BitSet, FixedBitSet}, identifier::Identifier, length_prefixed_vec::LengthPrefixedVec,\n");
+    out.push_str("    network_position::NetworkPosition, prefixed_optional::PrefixedOptional, uuid::Uuid, var_int::VarInt, var_long::VarLong,\n");
+    out.push_str("};\n");
+    out.push_str("use ferrumc_macros::{packet, NetDecode, NetEncode};\n");
+    out.push_str("use std::io::Write;\n");
+
+    for entry in spec.packets.iter().filter(|entry| entry.direction == direction) {
+        if !is_generated(entry, hand_written) {
+            continue;
+        }
+        let fields = entry.fields.as_ref().unwrap();
+
+        writeln!(out).unwrap();
+        match &entry.doc {
+            Some(doc) => writeln!(out, "/// {doc}").unwrap(),
+            None => writeln!(out, "/// Packet {} of the `{}` state.", entry.id, entry.state).unwrap(),
+        }
+        writeln!(out, "#[derive(NetEncode, NetDecode, Debug, Clone, PartialEq)]").unwrap();
+        writeln!(out, "#[packet(packet_id = {}, state = {:?})]", entry.id, entry.state).unwrap();
+        writeln!(out, "pub struct {} {{", entry.name).unwrap();
+        for field in fields {
+            if let Some(doc) = &field.doc {
+                writeln!(out, "    /// {doc}").unwrap();
+            }
+            let mut attributes = Vec::new();
+            if field.varint {
+                attributes.push("varint".to_string());
+            }
+            if field.length_prefixed {
+                attributes.push("length_prefixed".to_string());
+            }
+            if let Some(condition) = &field.optional_if {
+                attributes.push(format!("optional_if = {condition:?}"));
+            }
+            if field.rest_of_packet {
+                attributes.push("rest_of_packet".to_string());
+            }
+            if !attributes.is_empty() {
+                writeln!(out, "    #[net({})]", attributes.join(", ")).unwrap();
+            }
+            writeln!(out, "    pub {}: {},", field.name, field.ty).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+
+        if direction == Direction::Serverbound {
+            writeln!(out).unwrap();
+            writeln!(out, "impl crate::packets::IncomingPacket for {} {{", entry.name).unwrap();
+            writeln!(out, "    async fn handle(self, conn_id: usize, _: std::sync::Arc<crate::ServerState>) -> crate::NetResult<()> {{").unwrap();
+            writeln!(out, "        tracing::trace!(\"Unhandled packet from {{}}: {{:?}}\", conn_id, self);").unwrap();
+            writeln!(out, "        Ok(())").unwrap();
+            writeln!(out, "    }}").unwrap();
+            writeln!(out, "}}").unwrap();
+        }
+    }
+
+    out
+}
+
+/// The incoming packet registry, the same for hand-written and generated packets.
+fn generate_registry(spec: &Spec, ids: &[u8], hand_written: &[HandWritten]) -> String {
+    let mut handle_arms = String::new();
+    let mut debug_arms = String::new();
+
+    for (entry, id) in spec.packets.iter().zip(ids) {
+        if entry.direction != Direction::Serverbound {
+            continue;
+        }
+
+        let path = match hand_written.iter().find(|packet| packet.name == entry.name) {
+            Some(packet) => format!("crate::packets::incoming::{}::{}", packet.module, packet.name),
+            None => format!("crate::packets::generated::incoming::{}", entry.name),
+        };
+        let decode = format!("<{path} as ferrumc_net_codec::decode::NetDecode>::decode(cursor, &ferrumc_net_codec::decode::NetDecodeOpts::None)?");
+
+        writeln!(handle_arms, "        ({id:#04X}, {:?}) => {{", entry.state).unwrap();
+        writeln!(handle_arms, "            let packet = {decode};").unwrap();
+        writeln!(handle_arms, "            <{path} as crate::packets::IncomingPacket>::handle(packet, conn_id, state).await?;").unwrap();
+        writeln!(handle_arms, "        }}").unwrap();
+
+        writeln!(debug_arms, "        ({id:#04X}, {:?}) => {{", entry.state).unwrap();
+        writeln!(debug_arms, "            let packet = {decode};").unwrap();
+        writeln!(debug_arms, "            Ok(Some(format!(\"{{:#?}}\", packet)))").unwrap();
+        writeln!(debug_arms, "        }}").unwrap();
+    }
+
+    format!(
+        "// @generated by build.rs from the protocol spec, do not edit.\n\
+         \n\
+         /// Decodes an incoming packet and handles it.\n\
+         pub async fn handle_packet<R: std::io::Read>(packet_id: u8, conn_id: usize, conn_state: &crate::connection::ConnectionState, cursor: &mut R, state: std::sync::Arc<crate::ServerState>) -> crate::NetResult<()> {{\n\
+         \x20   match (packet_id, conn_state.as_str()) {{\n\
+         {handle_arms}\
+         \x20       _ => tracing::debug!(\"No packet found for ID: 0x{{:02X}} in state: {{}}\", packet_id, conn_state.as_str()),\n\
+         \x20   }}\n\
+         \n\
+         \x20   Ok(())\n\
+         }}\n\
+         \n\
+         /// Decodes a packet without handling it and formats it with its `Debug` implementation.\n\
+         ///\n\
+         /// Returns `None` if no packet is registered for the id in that state.\n\
+         #[allow(clippy::result_large_err)]\n\
+         pub fn debug_packet<R: std::io::Read>(packet_id: u8, conn_state: &crate::connection::ConnectionState, cursor: &mut R) -> crate::NetResult<Option<String>> {{\n\
+         \x20   match (packet_id, conn_state.as_str()) {{\n\
+         {debug_arms}\
+         \x20       _ => Ok(None),\n\
+         \x20   }}\n\
+         }}\n"
+    )
+}
+
+/// Implements `OutgoingPacket` for every clientbound packet.
+fn generate_outgoing_impls(spec: &Spec, hand_written: &[HandWritten]) -> String {
+    let mut out = String::new();
+
+    for entry in spec.packets.iter().filter(|entry| entry.direction == Direction::Clientbound) {
+        let (path, impl_generics, ty_generics, where_clause) = match hand_written.iter().find(|packet| packet.name == entry.name) {
+            Some(packet) => (
+                format!("crate::packets::outgoing::{}::{}", packet.module, packet.name),
+                packet.impl_generics.as_str(),
+                packet.ty_generics.as_str(),
+                packet.where_clause.as_str(),
+            ),
+            None => (format!("crate::packets::generated::outgoing::{}", entry.name), "", "", ""),
+        };
+
+        writeln!(out).unwrap();
+        writeln!(out, "impl {impl_generics} crate::packets::OutgoingPacket for {path} {ty_generics} {where_clause} {{").unwrap();
+        writeln!(out, "    const NAME: &'static str = {:?};", entry.name).unwrap();
+        writeln!(out, "    const ID: u8 = {};", entry.id).unwrap();
+        writeln!(out, "    const STATE: crate::connection::ConnectionState = crate::connection::ConnectionState::{};", state_variant(&entry.state)).unwrap();
+        writeln!(out, "}}").unwrap();
+    }
+
+    out
+}
@@ -0,0 +1,421 @@
+//! Load tests a server with simulated players.
+//!
+//! ```text
+//! loadtest [address] [--clients <n>] [--join-rate <per second>] [--duration <seconds>]
+//!          [--session <seconds>] [--chat-interval <seconds>]
+//! ```
+//!
+//! Every client logs in, walks a random path and chats every `--chat-interval` seconds. After
+//! `--session` seconds it disconnects and joins again, a session of `0` never reconnects.
+//! Clients that get kicked or lose their connection reconnect as well.
+//!
+//! Every few seconds and at the end the following is reported:
+//! - login latency percentiles, from connecting until the client is in the play state
+//! - tick time percentiles, the server has to enable `debug.tick_times` for these
+//! - bytes per second sent and received by all clients
+//! - why clients were disconnected
+
+use anyhow::{anyhow, bail, Context};
+use ferrumc_client::{Client, ClientResult, ReceivedPacket};
+use ferrumc_core::transform::position::Position;
+use ferrumc_net::packets::incoming::chat_message::ChatMessagePacket;
+use ferrumc_net::packets::incoming::set_player_position::SetPlayerPositionPacket;
+use ferrumc_net::packets::outgoing::synchronize_player_position::SynchronizePlayerPositionPacket;
+use ferrumc_net::packets::outgoing::tick_times::{TickTimes, TICK_TIMES_CHANNEL};
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::net_types::bit_set::FixedBitSet;
+use ferrumc_net_codec::net_types::prefixed_optional::PrefixedOptional;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{Instant, MissedTickBehavior};
+
+const USAGE: &str = "Usage:
+    loadtest [address] [--clients <n>] [--join-rate <per second>] [--duration <seconds>]
+             [--session <seconds>] [--chat-interval <seconds>]";
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:25565";
+
+const PLAY_PLUGIN_MESSAGE_ID: u8 = 0x19;
+const SYNCHRONIZE_PLAYER_POSITION_ID: u8 = 0x40;
+
+/// Blocks per tick, a bit below the vanilla walking speed.
+const WALK_SPEED: f64 = 0.2;
+
+const TICK: Duration = Duration::from_millis(50);
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+struct Options {
+    address: String,
+    clients: usize,
+    join_rate: f64,
+    duration: Duration,
+    session: Duration,
+    chat_interval: Duration,
+}
+
+impl Options {
+    fn parse() -> anyhow::Result<Self> {
+        let mut options = Self {
+            address: DEFAULT_ADDRESS.to_string(),
+            clients: 10,
+            join_rate: 5.0,
+            duration: Duration::from_secs(60),
+            session: Duration::from_secs(30),
+            chat_interval: Duration::from_secs(10),
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next().ok_or_else(|| anyhow!("{name} needs a value\n{USAGE}"))
+            };
+
+            match arg.as_str() {
+                "--clients" => options.clients = value("--clients")?.parse().context("invalid --clients")?,
+                "--join-rate" => options.join_rate = value("--join-rate")?.parse().context("invalid --join-rate")?,
+                "--duration" => options.duration = seconds(&value("--duration")?).context("invalid --duration")?,
+                "--session" => options.session = seconds(&value("--session")?).context("invalid --session")?,
+                "--chat-interval" => {
+                    options.chat_interval = seconds(&value("--chat-interval")?).context("invalid --chat-interval")?
+                }
+                "--help" | "-h" => bail!(USAGE),
+                _ if arg.starts_with("--") => bail!("unknown option {arg}\n{USAGE}"),
+                _ => options.address = arg,
+            }
+        }
+
+        if !options.join_rate.is_finite() || options.join_rate <= 0.0 {
+            bail!("--join-rate has to be a positive number");
+        }
+
+        Ok(options)
+    }
+}
+
+fn seconds(value: &str) -> anyhow::Result<Duration> {
+    Ok(Duration::try_from_secs_f64(value.parse()?)?)
+}
+
+#[derive(Default)]
+struct Stats {
+    online: AtomicUsize,
+    logins: AtomicUsize,
+    login_times: Mutex<Vec<Duration>>,
+    /// In microseconds
+    tick_times: Mutex<Vec<u64>>,
+    disconnects: Mutex<HashMap<String, usize>>,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl Stats {
+    fn disconnected(&self, reason: String) {
+        *self.disconnects.lock().unwrap().entry(reason).or_default() += 1;
+    }
+}
+
+/// Adds the traffic of a client to the [Stats] as it happens, so the interval reports are accurate.
+#[derive(Default)]
+struct TrafficCounter {
+    sent: u64,
+    received: u64,
+}
+
+impl TrafficCounter {
+    fn update(&mut self, client: &Client, stats: &Stats) {
+        let sent = client.traffic().sent.load(Ordering::Relaxed);
+        let received = client.traffic().received.load(Ordering::Relaxed);
+
+        stats.bytes_sent.fetch_add(sent - self.sent, Ordering::Relaxed);
+        stats.bytes_received.fetch_add(received - self.received, Ordering::Relaxed);
+        self.sent = sent;
+        self.received = received;
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let options = Arc::new(Options::parse()?);
+    let stats = Arc::new(Stats::default());
+
+    println!(
+        "Starting {} clients against {} at {} joins per second for {:?}",
+        options.clients, options.address, options.join_rate, options.duration
+    );
+
+    let start = Instant::now();
+    let end = start + options.duration;
+
+    let bots = (0..options.clients)
+        .map(|id| {
+            let options = Arc::clone(&options);
+            let stats = Arc::clone(&stats);
+            let join_at = start + Duration::from_secs_f64(id as f64 / options.join_rate);
+
+            tokio::spawn(async move {
+                tokio::time::sleep_until(join_at).await;
+                run_bot(id, &options, &stats, end).await;
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let reporter = tokio::spawn({
+        let stats = Arc::clone(&stats);
+        async move {
+            let mut last = Report::take(&stats, start);
+            loop {
+                tokio::time::sleep(REPORT_INTERVAL).await;
+                let report = Report::take(&stats, start);
+                report.print_interval(&last);
+                last = report;
+            }
+        }
+    });
+
+    for bot in bots {
+        bot.await?;
+    }
+    reporter.abort();
+
+    print_summary(&stats, start.elapsed());
+
+    Ok(())
+}
+
+/// Keeps a client connected until `end`, reconnecting whenever a session ends.
+async fn run_bot(id: usize, options: &Options, stats: &Stats, end: Instant) {
+    let username = format!("bot_{id}");
+
+    while Instant::now() < end {
+        let started = Instant::now();
+        let result = async {
+            let mut client = Client::connect(&options.address).await?;
+            // One client is enough to sample the tick times.
+            if id == 0 {
+                client.register_channel(TICK_TIMES_CHANNEL);
+            }
+            client.login(&username).await?;
+            ClientResult::Ok(client)
+        }.await;
+
+        let mut client = match result {
+            Ok(client) => client,
+            Err(e) => {
+                stats.disconnected(format!("login failed: {e}"));
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        stats.login_times.lock().unwrap().push(started.elapsed());
+        stats.logins.fetch_add(1, Ordering::Relaxed);
+        stats.online.fetch_add(1, Ordering::Relaxed);
+
+        let session_end = if options.session.is_zero() {
+            end
+        } else {
+            end.min(Instant::now() + options.session)
+        };
+
+        let mut traffic = TrafficCounter::default();
+        let result = play(&mut client, &username, options, stats, &mut traffic, session_end).await;
+        stats.online.fetch_sub(1, Ordering::Relaxed);
+        traffic.update(&client, stats);
+
+        match result {
+            Ok(()) => {
+                let _ = client.disconnect().await;
+            }
+            Err(e) => {
+                stats.disconnected(e.to_string());
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Walks around and chats until `end`.
+async fn play(
+    client: &mut Client,
+    username: &str,
+    options: &Options,
+    stats: &Stats,
+    traffic: &mut TrafficCounter,
+    end: Instant,
+) -> ClientResult<()> {
+    let mut position = client.spawn().unwrap_or_default();
+    let mut heading = rand::random::<f64>() * std::f64::consts::TAU;
+    let mut next_chat = Instant::now() + options.chat_interval.mul_f64(rand::random::<f64>());
+
+    let mut ticker = tokio::time::interval(TICK);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        if Instant::now() >= end {
+            return Ok(());
+        }
+
+        while let Some(packet) = client.try_recv()? {
+            handle_packet(packet, &mut position, stats)?;
+        }
+
+        heading += (rand::random::<f64>() - 0.5) * 0.5;
+        position.x += heading.cos() * WALK_SPEED;
+        position.z += heading.sin() * WALK_SPEED;
+        client.send(&SetPlayerPositionPacket {
+            x: position.x,
+            feet_y: position.y,
+            z: position.z,
+            on_ground: true,
+        }).await?;
+
+        if !options.chat_interval.is_zero() && Instant::now() >= next_chat {
+            next_chat += options.chat_interval;
+            client.send(&chat_message(format!("hello from {username}"))).await?;
+        }
+
+        traffic.update(client, stats);
+    }
+}
+
+fn handle_packet(packet: ReceivedPacket, position: &mut Position, stats: &Stats) -> ClientResult<()> {
+    match packet.id {
+        // The server moved us, most likely back after a failed movement check
+        SYNCHRONIZE_PLAYER_POSITION_ID => {
+            let teleport = packet.decode::<SynchronizePlayerPositionPacket>()?;
+            *position = Position::new(teleport.x, teleport.y, teleport.z);
+        }
+        PLAY_PLUGIN_MESSAGE_ID => {
+            let mut cursor = Cursor::new(&packet.data);
+            let channel = String::decode(&mut cursor, &NetDecodeOpts::None)?;
+            if channel == TICK_TIMES_CHANNEL {
+                let tick_times = TickTimes::decode(&mut cursor, &NetDecodeOpts::None)?;
+                stats.tick_times.lock().unwrap().extend(tick_times.samples.data);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn chat_message(message: String) -> ChatMessagePacket {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+
+    ChatMessagePacket {
+        message,
+        timestamp,
+        salt: rand::random(),
+        signature: PrefixedOptional::none(),
+        message_count: VarInt::new(0),
+        acknowledged: FixedBitSet::new(),
+    }
+}
+
+/// A snapshot of the counters, used to compute rates between reports.
+struct Report {
+    at: Duration,
+    online: usize,
+    logins: usize,
+    tick_samples: usize,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl Report {
+    fn take(stats: &Stats, start: Instant) -> Self {
+        Self {
+            at: start.elapsed(),
+            online: stats.online.load(Ordering::Relaxed),
+            logins: stats.logins.load(Ordering::Relaxed),
+            tick_samples: stats.tick_times.lock().unwrap().len(),
+            bytes_sent: stats.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: stats.bytes_received.load(Ordering::Relaxed),
+        }
+    }
+
+    fn print_interval(&self, last: &Report) {
+        let seconds = (self.at - last.at).as_secs_f64();
+        println!(
+            "[{:>6.1}s] online: {:>5} | logins: {:>5} | out: {:>10}/s | in: {:>10}/s | ticks: {} samples",
+            self.at.as_secs_f64(),
+            self.online,
+            self.logins - last.logins,
+            format_bytes((self.bytes_sent - last.bytes_sent) as f64 / seconds),
+            format_bytes((self.bytes_received - last.bytes_received) as f64 / seconds),
+            self.tick_samples - last.tick_samples,
+        );
+    }
+}
+
+fn print_summary(stats: &Stats, elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+
+    println!("\nFinished after {:.1}s", seconds);
+
+    let mut login_times = stats.login_times.lock().unwrap().clone();
+    login_times.sort();
+    println!("\nLogins: {}", login_times.len());
+    print_percentiles(&login_times);
+
+    let mut tick_times = stats
+        .tick_times
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|micros| Duration::from_micros(*micros))
+        .collect::<Vec<_>>();
+    tick_times.sort();
+    println!("\nTick times: {} samples", tick_times.len());
+    if tick_times.is_empty() {
+        println!("    none received, is debug.tick_times enabled on the server?");
+    } else {
+        print_percentiles(&tick_times);
+    }
+
+    println!("\nTraffic:");
+    println!("    sent:     {}/s", format_bytes(stats.bytes_sent.load(Ordering::Relaxed) as f64 / seconds));
+    println!("    received: {}/s", format_bytes(stats.bytes_received.load(Ordering::Relaxed) as f64 / seconds));
+
+    let mut disconnects = stats.disconnects.lock().unwrap().clone().into_iter().collect::<Vec<_>>();
+    disconnects.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    println!("\nDisconnects: {}", disconnects.iter().map(|(_, count)| count).sum::<usize>());
+    for (reason, count) in disconnects {
+        println!("    {count:>6}  {reason}");
+    }
+}
+
+/// Prints the percentiles of sorted durations.
+fn print_percentiles(sorted: &[Duration]) {
+    let Some(max) = sorted.last() else {
+        return;
+    };
+
+    for percentile in [50.0, 90.0, 99.0] {
+        let index = ((percentile / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+        println!("    p{percentile:<4} {:?}", sorted[index]);
+    }
+    println!("    max   {:?}", max);
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{value:.1} {}", UNITS[unit])
+}
@@ -0,0 +1,430 @@
+use crate::errors::ClientError;
+use crate::ClientResult;
+use ferrumc_net::connection::{ConnectionState, GameProfile};
+use ferrumc_net::packets::incoming::ack_finish_configuration::AckFinishConfigurationPacket;
+use ferrumc_net::packets::incoming::acknowledge_configuration::AcknowledgeConfigurationPacket;
+use ferrumc_net::packets::incoming::confirm_teleportation::ConfirmTeleportationPacket;
+use ferrumc_net::packets::incoming::handshake::Handshake;
+use ferrumc_net::packets::incoming::login_acknowledged::LoginAcknowledgedPacket;
+use ferrumc_net::packets::incoming::login_start::LoginStartPacket;
+use ferrumc_net::packets::incoming::packet_skeleton::PacketSkeleton;
+use ferrumc_net::packets::incoming::server_bound_keep_alive::ServerBoundKeepAlive;
+use ferrumc_net::packets::incoming::server_bound_known_packs::{PackOwned, ServerBoundKnownPacks};
+use ferrumc_net::packets::incoming::server_bound_plugin_message::ServerBoundPluginMessage;
+use ferrumc_net::packets::outgoing::keep_alive::KeepAlivePacket;
+use ferrumc_net::packets::outgoing::login_success::LoginSuccessPacket;
+use ferrumc_net::packets::outgoing::synchronize_player_position::SynchronizePlayerPositionPacket;
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::net_types::length_prefixed_vec::LengthPrefixedVec;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use ferrumc_core::transform::position::Position;
+use std::io::Cursor;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, trace};
+use uuid::Uuid;
+
+/// The protocol version the server implements.
+pub use ferrumc_net::packets::generated::PROTOCOL_VERSION;
+
+/// How long [Client::recv] and [Client::await_packet] wait by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Ids of the clientbound packets the client has to react to.
+const LOGIN_DISCONNECT_ID: u8 = 0x00;
+const LOGIN_SUCCESS_ID: u8 = 0x02;
+const CONFIGURATION_DISCONNECT_ID: u8 = 0x02;
+const FINISH_CONFIGURATION_ID: u8 = 0x03;
+const CLIENT_BOUND_KNOWN_PACKS_ID: u8 = 0x0E;
+const PLAY_DISCONNECT_ID: u8 = 0x1D;
+const KEEP_ALIVE_ID: u8 = 0x26;
+const SYNCHRONIZE_PLAYER_POSITION_ID: u8 = 0x40;
+const START_CONFIGURATION_ID: u8 = 0x69;
+
+/// A packet received from the server.
+///
+/// Fields:
+/// `state`: The state of the client when the packet was received.
+/// `id`: The id of the packet.
+/// `data`: The packet data, without the length and id.
+#[derive(Debug, Clone)]
+pub struct ReceivedPacket {
+    pub state: ConnectionState,
+    pub id: u8,
+    pub data: Vec<u8>,
+}
+
+impl ReceivedPacket {
+    /// Decodes the packet data as `T`.
+    pub fn decode<T: NetDecode>(&self) -> ClientResult<T> {
+        Ok(T::decode(&mut Cursor::new(&self.data), &NetDecodeOpts::None)?)
+    }
+
+    /// If this packet disconnects the client in the state it was received in.
+    pub fn is_disconnect(&self) -> bool {
+        match self.state {
+            ConnectionState::Login => self.id == LOGIN_DISCONNECT_ID,
+            ConnectionState::Configuration => self.id == CONFIGURATION_DISCONNECT_ID,
+            ConnectionState::Play => self.id == PLAY_DISCONNECT_ID,
+            _ => false,
+        }
+    }
+
+    fn into_kicked(self) -> ClientError {
+        // The login disconnect reason is a JSON string, the others are NBT.
+        let reason = match self.state {
+            ConnectionState::Login => self.decode::<String>().ok(),
+            _ => None,
+        };
+
+        ClientError::Kicked {
+            state: self.state,
+            reason,
+        }
+    }
+}
+
+/// The bytes a client sent and received, length prefixes included.
+#[derive(Debug, Default)]
+pub struct Traffic {
+    pub sent: AtomicU64,
+    pub received: AtomicU64,
+}
+
+/// A connection to a server.
+///
+/// Packets are read in the background, so keep alives are answered and teleports are confirmed
+/// even while nobody is waiting for a packet. Every received packet, keep alives included, is
+/// queued until it's taken with [recv](Client::recv) or skipped by [await_packet](Client::await_packet).
+/// Disconnect packets are turned into [ClientError::Kicked].
+///
+/// Compression and encryption aren't supported.
+pub struct Client {
+    address: SocketAddr,
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    state: Arc<AtomicU8>,
+    traffic: Arc<Traffic>,
+    packets: mpsc::UnboundedReceiver<ReceivedPacket>,
+    reader: JoinHandle<()>,
+    timeout: Duration,
+    channels: Vec<String>,
+    profile: Option<GameProfile>,
+    spawn: Option<Position>,
+}
+
+impl Client {
+    /// Connects to a server, the client starts in the handshaking state.
+    pub async fn connect(address: impl ToSocketAddrs) -> ClientResult<Self> {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        let address = stream.peer_addr()?;
+        let (reader, writer) = stream.into_split();
+
+        let writer = Arc::new(Mutex::new(writer));
+        let state = Arc::new(AtomicU8::new(ConnectionState::Handshaking as u8));
+        let traffic = Arc::new(Traffic::default());
+        let (sender, packets) = mpsc::unbounded_channel();
+
+        let reader = tokio::spawn(read_packets(
+            reader,
+            Arc::clone(&writer),
+            Arc::clone(&state),
+            Arc::clone(&traffic),
+            sender,
+        ));
+
+        Ok(Self {
+            address,
+            writer,
+            state,
+            traffic,
+            packets,
+            reader,
+            timeout: DEFAULT_TIMEOUT,
+            channels: Vec::new(),
+            profile: None,
+            spawn: None,
+        })
+    }
+
+    /// Connects to a server and logs in as `username`.
+    pub async fn join(address: impl ToSocketAddrs, username: &str) -> ClientResult<Self> {
+        let mut client = Self::connect(address).await?;
+        client.login(username).await?;
+
+        Ok(client)
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        load_state(&self.state)
+    }
+
+    fn set_state(&self, state: ConnectionState) {
+        self.state.store(state as u8, Ordering::Relaxed);
+    }
+
+    /// The profile the server sent on login.
+    pub fn profile(&self) -> Option<&GameProfile> {
+        self.profile.as_ref()
+    }
+
+    /// Where the server spawned the client on login.
+    pub fn spawn(&self) -> Option<Position> {
+        self.spawn
+    }
+
+    pub fn traffic(&self) -> &Traffic {
+        &self.traffic
+    }
+
+    /// Registers a plugin channel on the next [login](Client::login).
+    pub fn register_channel(&mut self, channel: impl Into<String>) {
+        self.channels.push(channel.into());
+    }
+
+    /// Sets how long [recv](Client::recv) and [await_packet](Client::await_packet) wait for a packet.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sends a packet, it has to include its packet id.
+    pub async fn send(&self, packet: &impl NetEncode) -> ClientResult<()> {
+        send_packet(&self.writer, &self.traffic, packet).await
+    }
+
+    /// Waits for the next packet from the server.
+    pub async fn recv(&mut self) -> ClientResult<ReceivedPacket> {
+        match tokio::time::timeout(self.timeout, self.packets.recv()).await {
+            Ok(Some(packet)) if packet.is_disconnect() => Err(packet.into_kicked()),
+            Ok(Some(packet)) => Ok(packet),
+            Ok(None) => Err(ClientError::Disconnected),
+            Err(_) => Err(ClientError::Timeout(self.timeout)),
+        }
+    }
+
+    /// Takes the next packet if one was already received.
+    pub fn try_recv(&mut self) -> ClientResult<Option<ReceivedPacket>> {
+        match self.packets.try_recv() {
+            Ok(packet) if packet.is_disconnect() => Err(packet.into_kicked()),
+            Ok(packet) => Ok(Some(packet)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(ClientError::Disconnected),
+        }
+    }
+
+    /// Waits for a packet with `id`, skipping every other packet.
+    ///
+    /// Fails with [ClientError::Kicked] if the server disconnects the client in the meantime.
+    pub async fn await_packet(&mut self, id: u8) -> ClientResult<ReceivedPacket> {
+        let timeout = self.timeout;
+        let wait = async {
+            loop {
+                let Some(packet) = self.packets.recv().await else {
+                    return Err(ClientError::Disconnected);
+                };
+
+                if packet.id == id {
+                    return Ok(packet);
+                }
+                if packet.is_disconnect() {
+                    return Err(packet.into_kicked());
+                }
+
+                trace!("Skipping packet 0x{:02X} while waiting for 0x{:02X}", packet.id, id);
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| ClientError::Timeout(timeout))?
+    }
+
+    /// Logs in as `username` and goes through the configuration until the client is in the play state.
+    ///
+    /// The uuid is derived from the username, so it's the same every time.
+    pub async fn login(&mut self, username: &str) -> ClientResult<GameProfile> {
+        let uuid = Uuid::new_v3(&Uuid::NAMESPACE_OID, format!("OfflinePlayer:{username}").as_bytes());
+
+        self.send(&Handshake {
+            protocol_version: VarInt::new(PROTOCOL_VERSION),
+            server_address: self.address.ip().to_string(),
+            server_port: self.address.port(),
+            next_state: VarInt::new(2),
+        }).await?;
+        self.set_state(ConnectionState::Login);
+
+        self.send(&LoginStartPacket {
+            username: username.to_string(),
+            uuid: uuid.as_u128(),
+        }).await?;
+
+        let login_success = self
+            .await_packet(LOGIN_SUCCESS_ID)
+            .await?
+            .decode::<LoginSuccessPacket>()?;
+
+        // Switch before sending, the server answers right away.
+        self.set_state(ConnectionState::Configuration);
+        self.send(&LoginAcknowledgedPacket {}).await?;
+
+        if !self.channels.is_empty() {
+            self.send(&ServerBoundPluginMessage {
+                channel: "minecraft:register".to_string(),
+                data: self.channels.join("\0").into_bytes(),
+            }).await?;
+        }
+
+        self.configure().await?;
+
+        debug!("Logged in as {}", login_success.profile.username);
+        self.profile = Some(login_success.profile.clone());
+
+        Ok(login_success.profile)
+    }
+
+    /// Waits until the server sends the client back to the configuration state,
+    /// and goes through the configuration until the client is in the play state again.
+    ///
+    /// The configuration is acknowledged in the background as soon as the server starts it.
+    pub async fn await_reconfiguration(&mut self) -> ClientResult<()> {
+        self.await_packet(START_CONFIGURATION_ID).await?;
+        self.configure().await
+    }
+
+    /// Answers the configuration packets until the server finishes the configuration,
+    /// then waits until the client is spawned.
+    async fn configure(&mut self) -> ClientResult<()> {
+        self.answer_configuration().await?;
+
+        self.set_state(ConnectionState::Play);
+        self.send(&AckFinishConfigurationPacket {}).await?;
+
+        // The teleport is confirmed in the background
+        let spawn = self
+            .await_packet(SYNCHRONIZE_PLAYER_POSITION_ID)
+            .await?
+            .decode::<SynchronizePlayerPositionPacket>()?;
+        self.spawn = Some(Position::new(spawn.x, spawn.y, spawn.z));
+
+        Ok(())
+    }
+
+    /// Answers the configuration packets until the server finishes the configuration.
+    async fn answer_configuration(&mut self) -> ClientResult<()> {
+        loop {
+            let packet = self.recv().await?;
+            match packet.id {
+                CLIENT_BOUND_KNOWN_PACKS_ID => {
+                    self.send(&ServerBoundKnownPacks {
+                        packs: LengthPrefixedVec::new(vec![PackOwned {
+                            namespace: "minecraft".to_string(),
+                            id: "core".to_string(),
+                            version: "1.21".to_string(),
+                        }]),
+                    }).await?;
+                }
+                FINISH_CONFIGURATION_ID => return Ok(()),
+                // Brand, registries and so on.
+                id => trace!("Ignoring configuration packet 0x{:02X}", id),
+            }
+        }
+    }
+
+    /// Closes the connection.
+    pub async fn disconnect(self) -> ClientResult<()> {
+        self.writer.lock().await.shutdown().await?;
+        Ok(())
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+fn load_state(state: &AtomicU8) -> ConnectionState {
+    // Only ever set from a valid ConnectionState
+    ConnectionState::try_from(state.load(Ordering::Relaxed)).unwrap_or(ConnectionState::Handshaking)
+}
+
+async fn send_packet(writer: &Mutex<OwnedWriteHalf>, traffic: &Traffic, packet: &impl NetEncode) -> ClientResult<()> {
+    // Encode first so the packet is written in a single call.
+    let mut buf = Vec::new();
+    packet.encode(&mut buf, &NetEncodeOpts::WithLength)?;
+    writer.lock().await.write_all(&buf).await?;
+    traffic.sent.fetch_add(buf.len() as u64, Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// Answers the packets a client has to answer right away.
+async fn respond(packet: &ReceivedPacket, writer: &Mutex<OwnedWriteHalf>, traffic: &Traffic, state: &AtomicU8) -> ClientResult<()> {
+    if packet.state != ConnectionState::Play {
+        return Ok(());
+    }
+
+    match packet.id {
+        KEEP_ALIVE_ID => {
+            let keep_alive = packet.decode::<KeepAlivePacket>()?;
+            send_packet(writer, traffic, &ServerBoundKeepAlive { id: keep_alive.id.id }).await
+        }
+        SYNCHRONIZE_PLAYER_POSITION_ID => {
+            let teleport = packet.decode::<SynchronizePlayerPositionPacket>()?;
+            send_packet(writer, traffic, &ConfirmTeleportationPacket {
+                teleport_id: teleport.teleport_id,
+            }).await
+        }
+        START_CONFIGURATION_ID => {
+            // Switch before the next packet is read, everything after this belongs to the configuration.
+            state.store(ConnectionState::Configuration as u8, Ordering::Relaxed);
+            send_packet(writer, traffic, &AcknowledgeConfigurationPacket {}).await
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn read_packets(
+    mut reader: OwnedReadHalf,
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    state: Arc<AtomicU8>,
+    traffic: Arc<Traffic>,
+    packets: mpsc::UnboundedSender<ReceivedPacket>,
+) {
+    while let Ok(skeleton) = PacketSkeleton::new(&mut reader, false).await {
+        let length = skeleton.length + VarInt::from(skeleton.length).len;
+        traffic.received.fetch_add(length as u64, Ordering::Relaxed);
+
+        let position = skeleton.data.position() as usize;
+        let packet = ReceivedPacket {
+            state: load_state(&state),
+            id: skeleton.id,
+            data: skeleton.data.into_inner().split_off(position),
+        };
+
+        if let Err(e) = respond(&packet, &writer, &traffic, &state).await {
+            debug!("Failed to answer packet 0x{:02X}: {}", packet.id, e);
+            break;
+        }
+
+        // Nobody is listening anymore
+        if packets.send(packet).is_err() {
+            break;
+        }
+    }
+
+    trace!("Stopped reading packets");
+}
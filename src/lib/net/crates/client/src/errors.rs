@@ -0,0 +1,30 @@
+use ferrumc_net::connection::ConnectionState;
+use ferrumc_net_codec::decode::errors::NetDecodeError;
+use ferrumc_net_codec::encode::errors::NetEncodeError;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+
+    #[error("Decoder Error: {0}")]
+    DecoderError(#[from] NetDecodeError),
+
+    #[error("Encoder Error: {0}")]
+    EncoderError(#[from] NetEncodeError),
+
+    #[error("Timed out after {0:?} waiting for a packet")]
+    Timeout(Duration),
+
+    #[error("The server closed the connection")]
+    Disconnected,
+
+    #[error("Kicked by the server during {}: {}", .state.as_str(), .reason.as_deref().unwrap_or("no reason"))]
+    Kicked {
+        state: ConnectionState,
+        /// Only known when kicked while logging in, other disconnect reasons are NBT.
+        reason: Option<String>,
+    },
+}
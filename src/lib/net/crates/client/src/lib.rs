@@ -0,0 +1,18 @@
+//! A headless client that speaks just enough of the protocol to log into a server.
+//!
+//! It's meant for integration tests and benchmarks, so it reuses the packet structs of `ferrumc-net`.
+//!
+//! ```ignore
+//! let mut client = Client::connect("127.0.0.1:25565").await?;
+//! let profile = client.login("Steve").await?;
+//!
+//! client.send(&SetPlayerPositionPacket { x: 0.0, feet_y: 64.0, z: 0.0, on_ground: true }).await?;
+//! let sync = client.await_packet(0x40).await?.decode::<SynchronizePlayerPositionPacket>()?;
+//! ```
+
+pub mod client;
+pub mod errors;
+
+pub use client::{Client, ReceivedPacket, Traffic};
+
+pub type ClientResult<T> = Result<T, errors::ClientError>;
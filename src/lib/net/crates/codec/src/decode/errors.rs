@@ -16,4 +16,13 @@ pub enum NetDecodeError {
 
     #[error("Invalid Enum Variant")]
     InvalidEnumVariant,
+
+    #[error("Invalid length: {0}")]
+    InvalidLength(i32),
+
+    #[error("Packet too large: {0} bytes, the maximum is {1} bytes")]
+    PacketTooLarge(usize, usize),
+
+    #[error("Decompressed packet is {actual} bytes, but 'data_length' = {expected}")]
+    DecompressedSizeMismatch { expected: usize, actual: usize },
 }
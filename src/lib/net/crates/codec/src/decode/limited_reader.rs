@@ -0,0 +1,109 @@
+use std::io::Read;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+
+/// Wraps a reader so at most `limit` bytes can be read from it, after that it behaves as if the input ended.
+///
+/// Implements both [`Read`] and [`AsyncRead`], so a packet can be decoded straight from a socket
+/// without reading into the next packet, or a large payload can be size checked while it's streamed.
+///
+/// ```ignore
+/// let length = VarInt::read_async(&mut socket).await?.val as usize;
+/// let mut packet = LimitedReader::new(&mut socket, length);
+/// let message = ServerBoundPluginMessage::decode_async(&mut packet, &NetDecodeOpts::None).await?;
+/// // Keep the socket in sync, even if the packet wasn't read completely.
+/// packet.skip_remaining_async().await?;
+/// ```
+#[derive(Debug)]
+pub struct LimitedReader<R> {
+    inner: R,
+    remaining: usize,
+}
+
+impl<R> LimitedReader<R> {
+    pub fn new(inner: R, limit: usize) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+        }
+    }
+
+    /// How many more bytes can be read.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Whether all bytes up to the limit have been read.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> LimitedReader<R> {
+    /// Reads and discards everything up to the limit.
+    pub fn skip_remaining(&mut self) -> std::io::Result<()> {
+        let expected = self.remaining as u64;
+        let skipped = std::io::copy(self, &mut std::io::sink())?;
+        if skipped != expected {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+}
+
+impl<R: AsyncRead + Unpin> LimitedReader<R> {
+    /// Async version of [`LimitedReader::skip_remaining`].
+    pub async fn skip_remaining_async(&mut self) -> std::io::Result<()> {
+        let mut buf = [0; 1024];
+        while self.remaining > 0 {
+            if self.read(&mut buf).await? == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let max = buf.len().min(self.remaining);
+        if max == 0 {
+            return Ok(0);
+        }
+
+        let read = self.inner.read(&mut buf[..max])?;
+        self.remaining -= read;
+        Ok(read)
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for LimitedReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let max = buf.remaining().min(self.remaining);
+        if max == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut limited = buf.take(max);
+        match Pin::new(&mut self.inner).poll_read(cx, &mut limited) {
+            Poll::Ready(Ok(())) => {
+                let read = limited.filled().len();
+                // SAFETY: `limited` wrote into the unfilled part of `buf`, so those bytes are initialized.
+                unsafe { buf.assume_init(read) };
+                buf.advance(read);
+                self.remaining -= read;
+                Poll::Ready(Ok(()))
+            }
+            other => other,
+        }
+    }
+}
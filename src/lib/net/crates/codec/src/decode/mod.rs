@@ -1,6 +1,8 @@
 use std::io::Read;
+use tokio::io::AsyncRead;
 
 pub mod errors;
+pub mod limited_reader;
 mod primitives;
 
 pub type NetDecodeResult<T> = Result<T, errors::NetDecodeError>;
@@ -12,6 +14,30 @@ pub enum NetDecodeOpts {
     None,
     IsSizePrefixed,
 }
+/// The maximum number of elements allocated up front when decoding a length prefixed collection.
+///
+/// Lengths are sent by the client, so they can't be trusted to allocate with.
+/// Collections longer than this still decode fine, they just grow while reading.
+pub(crate) const MAX_PREALLOCATED_ELEMENTS: usize = 1024;
+
+/// Reads a VarInt length prefix, rejecting negative lengths.
+pub(crate) fn read_length<R: Read>(reader: &mut R) -> NetDecodeResult<usize> {
+    let length = <crate::net_types::var_int::VarInt as NetDecode>::decode(reader, &NetDecodeOpts::None)?.val;
+    usize::try_from(length).map_err(|_| errors::NetDecodeError::InvalidLength(length))
+}
+
+/// Async version of [`read_length`].
+pub(crate) async fn read_length_async<R: AsyncRead + Unpin>(reader: &mut R) -> NetDecodeResult<usize> {
+    let length = <crate::net_types::var_int::VarInt as NetDecode>::decode_async(reader, &NetDecodeOpts::None).await?.val;
+    usize::try_from(length).map_err(|_| errors::NetDecodeError::InvalidLength(length))
+}
+
+#[allow(async_fn_in_trait)]
 pub trait NetDecode: Sized {
     fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self>;
+    /// Decodes straight from an async reader like a socket, without buffering the whole packet first.
+    ///
+    /// Types that read until the end of the input (e.g. `Vec<T>` without a size prefix) read until
+    /// the reader is exhausted, so wrap the reader in a [`limited_reader::LimitedReader`] to stop at the end of the packet.
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self>;
 }
@@ -1,12 +1,12 @@
 use std::collections::HashMap;
 use std::hash::Hash;
-use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
-use crate::net_types::var_int::VarInt;
+use crate::decode::{read_length, read_length_async, NetDecode, NetDecodeOpts, NetDecodeResult, MAX_PREALLOCATED_ELEMENTS};
 use std::io::Read;
 use std::mem::MaybeUninit;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 macro_rules! impl_for_primitives {
-    ($($primitive_type:ty | $alt:ty),*) => {
+    ($($primitive_type:ty $(| $alt:ty)?),*) => {
         $(
             impl NetDecode for $primitive_type {
                 fn decode<R: Read>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
@@ -14,16 +14,29 @@ macro_rules! impl_for_primitives {
                     reader.read_exact(&mut buf)?;
                     Ok(Self::from_be_bytes(buf))
                 }
-            }
 
-            impl NetDecode for $alt {
-                fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
-                    // Basically use the decode method of the primitive type,
-                    // and then convert it to the alternative type.
-                    <$primitive_type as NetDecode>::decode(reader, opts)
-                    .map(|x| x as Self)
+                async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+                    let mut buf = [0; std::mem::size_of::<Self>()];
+                    reader.read_exact(&mut buf).await?;
+                    Ok(Self::from_be_bytes(buf))
                 }
             }
+
+            $(
+                impl NetDecode for $alt {
+                    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+                        // Basically use the decode method of the primitive type,
+                        // and then convert it to the alternative type.
+                        <$primitive_type as NetDecode>::decode(reader, opts)
+                        .map(|x| x as Self)
+                    }
+
+                    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+                        <$primitive_type as NetDecode>::decode_async(reader, opts).await
+                        .map(|x| x as Self)
+                    }
+                }
+            )?
         )*
     };
 }
@@ -34,26 +47,49 @@ impl_for_primitives!(
     u32 | i32,
     u64 | i64,
     u128 | i128,
-    f32 | f64
+    f32,
+    f64
 );
 
 impl NetDecode for () {
     fn decode<R: Read>(_reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
         Ok(())
     }
+
+    async fn decode_async<R: AsyncRead + Unpin>(_reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(())
+    }
 }
 
 impl NetDecode for bool {
     fn decode<R: Read>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
         Ok(<u8 as NetDecode>::decode(reader, &NetDecodeOpts::None)? != 0)
     }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(<u8 as NetDecode>::decode_async(reader, &NetDecodeOpts::None).await? != 0)
+    }
 }
 
 impl NetDecode for String {
     fn decode<R: Read>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
-        let len = <VarInt as NetDecode>::decode(reader, &NetDecodeOpts::None)?.val as usize;
-        let mut buf = vec![0; len];
-        reader.read_exact(&mut buf)?;
+        let len = read_length(reader)?;
+        // Don't allocate `len` bytes up front, only as much as is actually there.
+        let mut buf = Vec::new();
+        reader.take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(String::from_utf8(buf)?)
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let len = read_length_async(reader).await?;
+        let mut buf = Vec::new();
+        reader.take(len as u64).read_to_end(&mut buf).await?;
+        if buf.len() != len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
         Ok(String::from_utf8(buf)?)
     }
 }
@@ -65,8 +101,8 @@ where
     fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
         if matches!(opts, NetDecodeOpts::IsSizePrefixed)
         {
-            let len = <VarInt as NetDecode>::decode(reader, opts)?.val as usize;
-            let mut vec = Vec::with_capacity(len);
+            let len = read_length(reader)?;
+            let mut vec = Vec::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
             for _ in 0..len {
                 vec.push(T::decode(reader, opts)?);
             }
@@ -86,6 +122,31 @@ where
 
         Ok(vec)
     }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        if matches!(opts, NetDecodeOpts::IsSizePrefixed)
+        {
+            let len = read_length_async(reader).await?;
+            let mut vec = Vec::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
+            for _ in 0..len {
+                vec.push(T::decode_async(reader, opts).await?);
+            }
+            return Ok(vec);
+        }
+
+        // read to end, the elements themselves can be decoded from memory
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+
+        let mut cursor = std::io::Cursor::new(data);
+
+        let mut vec = Vec::new();
+        while cursor.position() < cursor.get_ref().len() as u64 {
+            vec.push(T::decode(&mut cursor, opts)?);
+        }
+
+        Ok(vec)
+    }
 }
 
 impl<T, const N: usize> NetDecode for [T; N]
@@ -101,6 +162,16 @@ where
 
         Ok(unsafe { MaybeUninit::array_assume_init(arr) })
     }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let mut arr: [MaybeUninit<T>; N] = [const { MaybeUninit::uninit() }; N];
+
+        for elem in &mut arr[..] {
+            elem.write(T::decode_async(reader, opts).await?);
+        }
+
+        Ok(unsafe { MaybeUninit::array_assume_init(arr) })
+    }
 }
 
 /// This isn't actually a type in the Minecraft Protocol. This is just for saving data/ or for general use.
@@ -111,8 +182,8 @@ where
     V: NetDecode,
 {
     fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
-        let len = <VarInt as NetDecode>::decode(reader, opts)?.val as usize;
-        let mut map = HashMap::with_capacity(len);
+        let len = read_length(reader)?;
+        let mut map = HashMap::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
         for _ in 0..len {
             let key = K::decode(reader, opts)?;
             let value = V::decode(reader, opts)?;
@@ -120,4 +191,15 @@ where
         }
         Ok(map)
     }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let len = read_length_async(reader).await?;
+        let mut map = HashMap::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
+        for _ in 0..len {
+            let key = K::decode_async(reader, opts).await?;
+            let value = V::decode_async(reader, opts).await?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
 }
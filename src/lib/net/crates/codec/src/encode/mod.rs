@@ -1,6 +1,7 @@
 use std::io::Write;
 
 pub mod errors;
+pub mod pool;
 mod primitives;
 
 pub type NetEncodeResult<T> = Result<T, errors::NetEncodeError>;
@@ -23,4 +24,30 @@ pub trait NetEncode: Sized {
         writer: &mut W,
         opts: &NetEncodeOpts,
     ) -> NetEncodeResult<()>;
+
+    /// The number of bytes [`encode`](NetEncode::encode) writes with [`NetEncodeOpts::None`].
+    ///
+    /// Used to size buffers up front and to write length prefixes without encoding twice.
+    /// The default implementation encodes into a counter, so implement it for anything encoded often.
+    fn encoded_len(&self) -> usize {
+        let mut counter = ByteCounter::default();
+        // Errors can only come from the value itself, in which case encoding it fails anyway.
+        let _ = self.encode(&mut counter, &NetEncodeOpts::None);
+        counter.0
+    }
+}
+
+/// A writer that only counts what's written to it.
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
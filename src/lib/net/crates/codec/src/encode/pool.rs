@@ -0,0 +1,124 @@
+use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use crate::net_types::var_int::VarInt;
+use bytes::{BufMut, Bytes, BytesMut};
+use std::sync::Mutex;
+
+/// Buffers that grew larger than this are dropped instead of pooled,
+/// so a single huge packet doesn't keep its memory around forever.
+pub const MAX_POOLED_CAPACITY: usize = 64 * 1024;
+
+/// The maximum number of idle buffers a pool keeps.
+pub const MAX_POOLED_BUFFERS: usize = 256;
+
+static GLOBAL_POOL: BufferPool = BufferPool::new();
+
+/// A pool of reusable buffers to encode packets into.
+///
+/// Encoding a packet with [`BufferPool::encode`] sizes the buffer with [`NetEncode::encoded_len`],
+/// so the packet is written exactly once without the buffer having to grow.
+/// The result is a [`Bytes`], which can be cloned for free to send the same packet to many players.
+/// Once everyone got it, [`BufferPool::recycle`] puts the buffer back.
+///
+/// ```ignore
+/// let packet = BufferPool::global().encode(&UpdateTimePacket::new(0, 0), &NetEncodeOpts::WithLength)?;
+/// for writer in writers {
+///     writer.send_bytes(packet.clone()).await?;
+/// }
+/// BufferPool::global().recycle(packet);
+/// ```
+#[derive(Debug, Default)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<BytesMut>>,
+}
+
+impl BufferPool {
+    pub const fn new() -> Self {
+        Self {
+            buffers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The pool shared by the whole server.
+    pub fn global() -> &'static BufferPool {
+        &GLOBAL_POOL
+    }
+
+    /// Takes an empty buffer that can hold at least `capacity` bytes.
+    pub fn take(&self, capacity: usize) -> BytesMut {
+        let buffer = self.buffers.lock().ok().and_then(|mut buffers| buffers.pop());
+
+        match buffer {
+            Some(mut buffer) => {
+                buffer.clear();
+                buffer.reserve(capacity);
+                buffer
+            }
+            None => BytesMut::with_capacity(capacity),
+        }
+    }
+
+    /// Puts a buffer back into the pool.
+    ///
+    /// Nothing happens if the buffer is still shared, e.g. because a clone is still being sent somewhere.
+    pub fn recycle(&self, buffer: impl Into<PooledBuffer>) {
+        let buffer = match buffer.into() {
+            PooledBuffer::Mut(buffer) => buffer,
+            PooledBuffer::Frozen(bytes) => match bytes.try_into_mut() {
+                Ok(buffer) => buffer,
+                Err(_) => return,
+            },
+        };
+
+        if buffer.capacity() > MAX_POOLED_CAPACITY {
+            return;
+        }
+
+        if let Ok(mut buffers) = self.buffers.lock() {
+            if buffers.len() < MAX_POOLED_BUFFERS {
+                buffers.push(buffer);
+            }
+        }
+    }
+
+    /// Encodes a value into a buffer from this pool.
+    pub fn encode<T: NetEncode>(&self, value: &T, opts: &NetEncodeOpts) -> NetEncodeResult<Bytes> {
+        let len = value.encoded_len();
+        let capacity = match opts {
+            NetEncodeOpts::WithLength => VarInt::calculate_len(len as i32) + len,
+            // Compressed packets are usually smaller, so this is an upper bound minus a few header bytes
+            _ => len + 10,
+        };
+
+        let mut writer = self.take(capacity).writer();
+        value.encode(&mut writer, opts)?;
+
+        Ok(writer.into_inner().freeze())
+    }
+
+    /// The number of idle buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.lock().map_or(0, |buffers| buffers.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Either kind of buffer that can be given back to a [`BufferPool`].
+pub enum PooledBuffer {
+    Mut(BytesMut),
+    Frozen(Bytes),
+}
+
+impl From<BytesMut> for PooledBuffer {
+    fn from(value: BytesMut) -> Self {
+        Self::Mut(value)
+    }
+}
+
+impl From<Bytes> for PooledBuffer {
+    fn from(value: Bytes) -> Self {
+        Self::Frozen(value)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
 use crate::net_types::var_int::VarInt;
+use bytes::Bytes;
 use std::collections::HashMap;
 use std::io::Write;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
@@ -17,6 +18,10 @@ macro_rules! impl_for_primitives {
                     writer.write_all(&self.to_be_bytes()).await?;
                     Ok(())
                 }
+
+                fn encoded_len(&self) -> usize {
+                    std::mem::size_of::<Self>()
+                }
             }
 
             $(
@@ -28,6 +33,10 @@ macro_rules! impl_for_primitives {
                     async fn encode_async<W: tokio::io::AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
                         (*self as $primitive_type).encode_async(writer, opts).await
                     }
+
+                    fn encoded_len(&self) -> usize {
+                        std::mem::size_of::<$primitive_type>()
+                    }
                 }
             )?
         )*
@@ -52,6 +61,10 @@ impl NetEncode for () {
     async fn encode_async<W: AsyncWrite + Unpin>(&self, _writer: &mut W, _: &NetEncodeOpts) -> NetEncodeResult<()> {
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        0
+    }
 }
 
 impl NetEncode for bool {
@@ -62,6 +75,10 @@ impl NetEncode for bool {
     async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
         (*self as u8).encode_async(writer, opts).await
     }
+
+    fn encoded_len(&self) -> usize {
+        1
+    }
 }
 
 impl NetEncode for String {
@@ -72,6 +89,10 @@ impl NetEncode for String {
     async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, _: &NetEncodeOpts) -> NetEncodeResult<()> {
         self.as_str().encode_async(writer, &NetEncodeOpts::None).await
     }
+
+    fn encoded_len(&self) -> usize {
+        self.as_str().encoded_len()
+    }
 }
 
 impl<'a> NetEncode for &'a str {
@@ -88,6 +109,10 @@ impl<'a> NetEncode for &'a str {
         writer.write_all(self.as_bytes()).await?;
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        VarInt::calculate_len(self.len() as i32) + self.len()
+    }
 }
 
 impl<T> NetEncode for Vec<T>
@@ -119,6 +144,10 @@ where
         }
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        self.iter().map(NetEncode::encoded_len).sum()
+    }
 }
 
 impl NetEncode for &[u8] {
@@ -143,6 +172,25 @@ impl NetEncode for &[u8] {
         writer.write_all(self).await?;
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Already encoded data, e.g. from a [`BufferPool`](crate::encode::pool::BufferPool), written as is.
+impl NetEncode for Bytes {
+    fn encode<W: Write>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.as_ref().encode(writer, opts)
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.as_ref().encode_async(writer, opts).await
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.len()
+    }
 }
 
 impl<T, const N: usize> NetEncode for [T; N]
@@ -174,6 +222,10 @@ where
         }
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        self.iter().map(NetEncode::encoded_len).sum()
+    }
 }
 
 impl NetEncode for &[&str] {
@@ -202,6 +254,10 @@ impl NetEncode for &[&str] {
         }
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        self.iter().map(NetEncode::encoded_len).sum()
+    }
 }
 
 impl<T: NetEncode> NetEncode for Option<T> {
@@ -218,6 +274,10 @@ impl<T: NetEncode> NetEncode for Option<T> {
             None => Ok(()),
         }
     }
+
+    fn encoded_len(&self) -> usize {
+        self.as_ref().map_or(0, NetEncode::encoded_len)
+    }
 }
 
 impl<K, V> NetEncode for HashMap<K, V>
@@ -246,4 +306,9 @@ where
         }
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        VarInt::calculate_len(self.len() as i32)
+            + self.iter().map(|(key, value)| key.encoded_len() + value.encoded_len()).sum::<usize>()
+    }
 }
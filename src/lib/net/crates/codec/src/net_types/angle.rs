@@ -0,0 +1,47 @@
+use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// A rotation angle in steps of 1/256 of a full turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Angle(pub u8);
+
+impl Angle {
+    pub fn new(steps: u8) -> Self {
+        Self(steps)
+    }
+
+    /// Converts degrees to the nearest angle step, wrapping around at 360 degrees.
+    pub fn from_degrees(degrees: f32) -> Self {
+        Self((degrees.rem_euclid(360.0) / 360.0 * 256.0).round() as u32 as u8)
+    }
+
+    pub fn to_degrees(self) -> f32 {
+        self.0 as f32 * 360.0 / 256.0
+    }
+}
+
+impl NetEncode for Angle {
+    fn encode<W: Write>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.0.encode(writer, opts)
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.0.encode_async(writer, opts).await
+    }
+
+    fn encoded_len(&self) -> usize {
+        1
+    }
+}
+
+impl NetDecode for Angle {
+    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(Self(u8::decode(reader, opts)?))
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(Self(u8::decode_async(reader, opts).await?))
+    }
+}
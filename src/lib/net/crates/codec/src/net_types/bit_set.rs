@@ -0,0 +1,184 @@
+use crate::decode::{read_length, read_length_async, NetDecode, NetDecodeOpts, NetDecodeResult, MAX_PREALLOCATED_ELEMENTS};
+use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use crate::net_types::var_int::VarInt;
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+
+/// A growable set of bits, sent as a VarInt prefixed array of longs.
+///
+/// Bit `i` is stored in long `i / 64` at position `i % 64`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet {
+    pub data: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a bit set that can hold `bits` bits without growing.
+    pub fn with_capacity(bits: usize) -> Self {
+        Self {
+            data: vec![0; bits.div_ceil(64)],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.data
+            .get(index / 64)
+            .is_some_and(|long| long & (1 << (index % 64)) != 0)
+    }
+
+    /// Sets a bit, growing the set if necessary.
+    pub fn set(&mut self, index: usize, value: bool) {
+        let long = index / 64;
+        if long >= self.data.len() {
+            if !value {
+                return;
+            }
+            self.data.resize(long + 1, 0);
+        }
+
+        if value {
+            self.data[long] |= 1 << (index % 64);
+        } else {
+            self.data[long] &= !(1 << (index % 64));
+        }
+    }
+
+    /// The number of bits that are set.
+    pub fn count_ones(&self) -> usize {
+        self.data.iter().map(|long| long.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.iter().all(|long| *long == 0)
+    }
+}
+
+impl NetEncode for BitSet {
+    fn encode<W: Write>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        VarInt::from(self.data.len()).encode(writer, opts)?;
+        for long in &self.data {
+            long.encode(writer, opts)?;
+        }
+        Ok(())
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        VarInt::from(self.data.len()).encode_async(writer, opts).await?;
+        for long in &self.data {
+            long.encode_async(writer, opts).await?;
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        VarInt::calculate_len(self.data.len() as i32) + self.data.len() * 8
+    }
+}
+
+impl NetDecode for BitSet {
+    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let length = read_length(reader)?;
+
+        let mut data = Vec::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS));
+        for _ in 0..length {
+            data.push(u64::decode(reader, opts)?);
+        }
+
+        Ok(Self { data })
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let length = read_length_async(reader).await?;
+
+        let mut data = Vec::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS));
+        for _ in 0..length {
+            data.push(u64::decode_async(reader, opts).await?);
+        }
+
+        Ok(Self { data })
+    }
+}
+
+/// A set of exactly `N` bits, sent as `ceil(N / 8)` bytes without a length prefix.
+///
+/// Bit `i` is stored in byte `i / 8` at position `i % 8`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedBitSet<const N: usize> {
+    data: Box<[u8]>,
+}
+
+impl<const N: usize> FixedBitSet<N> {
+    /// The number of bytes this bit set takes up on the wire.
+    pub const BYTES: usize = N.div_ceil(8);
+
+    pub fn new() -> Self {
+        Self {
+            data: vec![0; Self::BYTES].into_boxed_slice(),
+        }
+    }
+
+    /// # Panics
+    /// If `index` is not smaller than `N`.
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < N, "bit {index} is out of bounds for a FixedBitSet<{N}>");
+        self.data[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    /// # Panics
+    /// If `index` is not smaller than `N`.
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < N, "bit {index} is out of bounds for a FixedBitSet<{N}>");
+        if value {
+            self.data[index / 8] |= 1 << (index % 8);
+        } else {
+            self.data[index / 8] &= !(1 << (index % 8));
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl<const N: usize> Default for FixedBitSet<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> NetEncode for FixedBitSet<N> {
+    fn encode<W: Write>(&self, writer: &mut W, _: &NetEncodeOpts) -> NetEncodeResult<()> {
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, _: &NetEncodeOpts) -> NetEncodeResult<()> {
+        use tokio::io::AsyncWriteExt;
+        writer.write_all(&self.data).await?;
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        Self::BYTES
+    }
+}
+
+impl<const N: usize> NetDecode for FixedBitSet<N> {
+    fn decode<R: Read>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let mut data = vec![0; Self::BYTES].into_boxed_slice();
+        reader.read_exact(&mut data)?;
+
+        Ok(Self { data })
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, _: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let mut data = vec![0; Self::BYTES].into_boxed_slice();
+        reader.read_exact(&mut data).await?;
+
+        Ok(Self { data })
+    }
+}
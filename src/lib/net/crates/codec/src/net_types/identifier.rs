@@ -0,0 +1,113 @@
+use crate::decode::errors::NetDecodeError;
+use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use crate::net_types::var_int::VarInt;
+use crate::net_types::NetTypesError;
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::str::FromStr;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// The namespace used when an identifier doesn't specify one.
+pub const DEFAULT_NAMESPACE: &str = "minecraft";
+
+/// The maximum length of an identifier in the protocol, namespace and separator included.
+pub const MAX_IDENTIFIER_LENGTH: usize = 32767;
+
+/// A namespaced key like `minecraft:stone`.
+///
+/// The namespace may only contain `a-z`, `0-9`, `.`, `-` and `_`, the path may additionally contain `/`.
+/// Parsing a key without a namespace puts it in the `minecraft` namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Identifier {
+    namespace: String,
+    path: String,
+}
+
+impl Identifier {
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Result<Self, NetTypesError> {
+        let namespace = namespace.into();
+        let path = path.into();
+
+        if namespace.is_empty()
+            || !namespace.chars().all(is_valid_namespace_char)
+            || !path.chars().all(|c| is_valid_namespace_char(c) || c == '/')
+            || namespace.len() + 1 + path.len() > MAX_IDENTIFIER_LENGTH
+        {
+            return Err(NetTypesError::InvalidIdentifier(format!("{namespace}:{path}")));
+        }
+
+        Ok(Self { namespace, path })
+    }
+
+    /// Creates an identifier in the `minecraft` namespace.
+    pub fn minecraft(path: impl Into<String>) -> Result<Self, NetTypesError> {
+        Self::new(DEFAULT_NAMESPACE, path)
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+fn is_valid_namespace_char(c: char) -> bool {
+    matches!(c, 'a'..='z' | '0'..='9' | '.' | '-' | '_')
+}
+
+impl FromStr for Identifier {
+    type Err = NetTypesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((namespace, path)) => Self::new(namespace, path),
+            None => Self::minecraft(s),
+        }
+    }
+}
+
+impl TryFrom<&str> for Identifier {
+    type Error = NetTypesError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+impl NetEncode for Identifier {
+    fn encode<W: Write>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.to_string().encode(writer, opts)
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.to_string().encode_async(writer, opts).await
+    }
+
+    fn encoded_len(&self) -> usize {
+        let len = self.namespace.len() + 1 + self.path.len();
+        VarInt::calculate_len(len as i32) + len
+    }
+}
+
+impl NetDecode for Identifier {
+    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        String::decode(reader, opts)?
+            .parse()
+            .map_err(|e: NetTypesError| NetDecodeError::ExternalError(e.into()))
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        String::decode_async(reader, opts).await?
+            .parse()
+            .map_err(|e: NetTypesError| NetDecodeError::ExternalError(e.into()))
+    }
+}
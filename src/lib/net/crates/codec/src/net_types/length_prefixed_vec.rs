@@ -1,6 +1,6 @@
 use std::io::{Read, Write};
-use tokio::io::AsyncWrite;
-use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use tokio::io::{AsyncRead, AsyncWrite};
+use crate::decode::{read_length, read_length_async, NetDecode, NetDecodeOpts, NetDecodeResult, MAX_PREALLOCATED_ELEMENTS};
 use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
 use crate::net_types::var_int::VarInt;
 
@@ -29,6 +29,12 @@ impl<T> LengthPrefixedVec<T>
     }
 }
 
+impl<T> Default for LengthPrefixedVec<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
 impl<T> NetEncode for LengthPrefixedVec<T>
 where
     T: NetEncode,
@@ -52,16 +58,21 @@ where
 
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        VarInt::calculate_len(self.data.len() as i32)
+            + self.data.iter().map(NetEncode::encoded_len).sum::<usize>()
+    }
 }
 impl<T> NetDecode for LengthPrefixedVec<T>
 where
     T: NetDecode,
 {
     fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
-        let length = VarInt::decode(reader, opts)?;
+        let length = read_length(reader)?;
 
-        let mut data = Vec::new();
-        for _ in 0..length.val {
+        let mut data = Vec::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS));
+        for _ in 0..length {
             data.push(T::decode(reader, opts)?);
         }
 
@@ -70,4 +81,15 @@ where
             data,
         })
     }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        let length = read_length_async(reader).await?;
+
+        let mut data = Vec::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS));
+        for _ in 0..length {
+            data.push(T::decode_async(reader, opts).await?);
+        }
+
+        Ok(Self { data })
+    }
 }
@@ -1,6 +1,12 @@
 pub mod var_int;
 pub mod length_prefixed_vec;
 pub mod network_position;
+pub mod var_long;
+pub mod angle;
+pub mod identifier;
+pub mod bit_set;
+pub mod uuid;
+pub mod prefixed_optional;
 
 #[derive(Debug, thiserror::Error)]
 pub enum NetTypesError {
@@ -10,4 +16,8 @@ pub enum NetTypesError {
     InvalidVarInt,
     #[error("I couldn't convert the value into a valid i32")]
     InvalidInputI32,
-}
\ No newline at end of file
+    #[error("Invalid VarLong")]
+    InvalidVarLong,
+    #[error("Invalid identifier: {0}")]
+    InvalidIdentifier(String),
+}
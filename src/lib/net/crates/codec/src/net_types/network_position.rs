@@ -1,12 +1,13 @@
 // I have no clue why it is saving i32 and i16. There is no precision. The actual player position is saved in f32.
 
+use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
 use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
 use std::fmt::Display;
-use std::io::Write;
-use tokio::io::AsyncWrite;
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncWrite};
 
 /// The definition of a "Position" in the Minecraft protocol.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct NetworkPosition {
     // Encoded as a 26 bit int
     pub x: i32,
@@ -39,11 +40,35 @@ impl NetEncode for NetworkPosition {
         writer.write_all(self.as_u64().to_be_bytes().as_ref()).await?;
         Ok(())
     }
+
+    fn encoded_len(&self) -> usize {
+        8
+    }
 }
+impl NetDecode for NetworkPosition {
+    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(Self::from_u64(u64::decode(reader, opts)?))
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(Self::from_u64(u64::decode_async(reader, opts).await?))
+    }
+}
+
 impl NetworkPosition {
     pub fn as_u64(&self) -> u64 {
         ((self.x as u64 & 0x3FFFFFF) << 38)
             | ((self.z as u64 & 0x3FFFFFF) << 12)
             | (self.y as u64 & 0xFFF)
     }
+
+    pub fn from_u64(val: u64) -> Self {
+        // Shifting the signed value back down sign extends every component.
+        let val = val as i64;
+        Self {
+            x: (val >> 38) as i32,
+            z: (val << 26 >> 38) as i32,
+            y: (val << 52 >> 52) as i16,
+        }
+    }
 }
\ No newline at end of file
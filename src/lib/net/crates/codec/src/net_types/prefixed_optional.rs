@@ -0,0 +1,75 @@
+use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// An optional value preceded by a boolean that says whether it's there.
+///
+/// A plain `Option<T>` is only written if it's `Some`, and can't be decoded at all,
+/// since nothing on the wire says whether the value is present.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefixedOptional<T>(pub Option<T>);
+
+impl<T> PrefixedOptional<T> {
+    pub fn new(value: Option<T>) -> Self {
+        Self(value)
+    }
+
+    pub fn some(value: T) -> Self {
+        Self(Some(value))
+    }
+
+    pub fn none() -> Self {
+        Self(None)
+    }
+
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> From<Option<T>> for PrefixedOptional<T> {
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: NetEncode> NetEncode for PrefixedOptional<T> {
+    fn encode<W: Write>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.0.is_some().encode(writer, opts)?;
+        if let Some(value) = &self.0 {
+            value.encode(writer, opts)?;
+        }
+        Ok(())
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.0.is_some().encode_async(writer, opts).await?;
+        if let Some(value) = &self.0 {
+            value.encode_async(writer, opts).await?;
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        1 + self.0.as_ref().map_or(0, NetEncode::encoded_len)
+    }
+}
+
+impl<T: NetDecode> NetDecode for PrefixedOptional<T> {
+    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        if bool::decode(reader, opts)? {
+            Ok(Self(Some(T::decode(reader, opts)?)))
+        } else {
+            Ok(Self(None))
+        }
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        if bool::decode_async(reader, opts).await? {
+            Ok(Self(Some(T::decode_async(reader, opts).await?)))
+        } else {
+            Ok(Self(None))
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! UUIDs are sent as a single big endian 128 bit integer.
+
+use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+pub use ::uuid::Uuid;
+
+impl NetEncode for Uuid {
+    fn encode<W: Write>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.as_u128().encode(writer, opts)
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.as_u128().encode_async(writer, opts).await
+    }
+
+    fn encoded_len(&self) -> usize {
+        16
+    }
+}
+
+impl NetDecode for Uuid {
+    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(Uuid::from_u128(u128::decode(reader, opts)?))
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        Ok(Uuid::from_u128(u128::decode_async(reader, opts).await?))
+    }
+}
@@ -74,17 +74,9 @@ impl VarInt {
     }
 
     pub fn calculate_len(value: i32) -> usize {
-        if (-128..128).contains(&value) {
-            1
-        } else if (-16384..16384).contains(&value) {
-            2
-        } else if (-2097152..2097152).contains(&value) {
-            3
-        } else if (-268435456..268435456).contains(&value) {
-            4
-        } else {
-            5
-        }
+        // Negative values always use all 32 bits.
+        let bits = 32 - (value as u32).leading_zeros() as usize;
+        bits.div_ceil(7).max(1)
     }
 
     pub fn read<R: Read>(cursor: &mut R) -> Result<Self, NetTypesError> {
@@ -155,6 +147,11 @@ impl NetDecode for VarInt {
         VarInt::read(reader)
             .map_err(|e| NetDecodeError::ExternalError(e.into()))
     }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, _opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        VarInt::read_async(reader).await
+            .map_err(|e| NetDecodeError::ExternalError(e.into()))
+    }
 }
 
 impl NetEncode for VarInt {
@@ -167,4 +164,8 @@ impl NetEncode for VarInt {
         self.write_async(writer).await
             .map_err(|e| NetEncodeError::ExternalError(e.into()))
     }
+
+    fn encoded_len(&self) -> usize {
+        Self::calculate_len(self.val)
+    }
 }
@@ -0,0 +1,159 @@
+use crate::decode::errors::NetDecodeError;
+use crate::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use crate::encode::errors::NetEncodeError;
+use crate::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use crate::net_types::NetTypesError;
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// The 64 bit version of [`VarInt`](crate::net_types::var_int::VarInt), takes up to 10 bytes.
+#[derive(Debug, Clone, Eq)]
+pub struct VarLong {
+    /// The value of the VarLong.
+    pub val: i64,
+    /// The length of the VarLong in bytes.
+    pub len: usize,
+}
+
+mod adapters {
+    use crate::net_types::var_long::VarLong;
+
+    impl From<i64> for VarLong {
+        fn from(value: i64) -> Self {
+            Self::new(value)
+        }
+    }
+
+    impl From<i32> for VarLong {
+        fn from(value: i32) -> Self {
+            Self::new(i64::from(value))
+        }
+    }
+
+    impl From<VarLong> for i64 {
+        fn from(value: VarLong) -> Self {
+            value.val
+        }
+    }
+
+    impl Default for VarLong {
+        fn default() -> Self {
+            Self::new(0)
+        }
+    }
+}
+
+impl PartialEq for VarLong {
+    fn eq(&self, other: &Self) -> bool {
+        self.val == other.val
+    }
+}
+
+const SEGMENT_BITS: i64 = 0x7F;
+const CONTINUE_BIT: i64 = 0x80;
+const MAX_LEN: usize = 10;
+
+impl VarLong {
+    pub fn new(value: i64) -> Self {
+        Self {
+            val: value,
+            len: Self::calculate_len(value),
+        }
+    }
+
+    pub fn calculate_len(value: i64) -> usize {
+        // Negative values always use all 64 bits.
+        let bits = 64 - (value as u64).leading_zeros() as usize;
+        bits.div_ceil(7).max(1)
+    }
+
+    pub fn read<R: Read>(cursor: &mut R) -> Result<Self, NetTypesError> {
+        let mut val = 0;
+        for i in 0..MAX_LEN {
+            let byte = {
+                let mut buf = [0u8; 1];
+                cursor.read_exact(&mut buf)?;
+                buf[0]
+            } as i64;
+
+            val |= (byte & SEGMENT_BITS) << (7 * i);
+            if byte & CONTINUE_BIT == 0 {
+                return Ok(Self { val, len: i + 1 });
+            }
+        }
+
+        Err(NetTypesError::InvalidVarLong)
+    }
+
+    pub async fn read_async<R: AsyncRead + Unpin>(cursor: &mut R) -> Result<Self, NetTypesError> {
+        let mut val = 0;
+        for i in 0..MAX_LEN {
+            let byte = {
+                let mut buf = [0];
+                cursor.read_exact(&mut buf).await?;
+                buf[0]
+            } as i64;
+
+            val |= (byte & SEGMENT_BITS) << (7 * i);
+            if byte & CONTINUE_BIT == 0 {
+                return Ok(Self { val, len: i + 1 });
+            }
+        }
+
+        Err(NetTypesError::InvalidVarLong)
+    }
+
+    pub fn write<W: Write>(&self, cursor: &mut W) -> Result<(), NetTypesError> {
+        let mut val = self.val;
+        loop {
+            if (val & !SEGMENT_BITS) == 0 {
+                cursor.write_all(&[val as u8])?;
+                return Ok(());
+            }
+
+            cursor.write_all(&[((val & SEGMENT_BITS) | CONTINUE_BIT) as u8])?;
+            val = ((val as u64) >> 7) as i64; // Rust equivalent of Java's >>> operator
+        }
+    }
+
+    pub async fn write_async<W: AsyncWrite + Unpin>(&self, cursor: &mut W) -> Result<(), NetTypesError> {
+        let mut val = self.val;
+        loop {
+            if (val & !SEGMENT_BITS) == 0 {
+                cursor.write_all(&[val as u8]).await?;
+                return Ok(());
+            }
+
+            cursor.write_all(&[((val & SEGMENT_BITS) | CONTINUE_BIT) as u8]).await?;
+            val = ((val as u64) >> 7) as i64; // Rust equivalent of Java's >>> operator
+        }
+    }
+}
+
+impl NetDecode for VarLong {
+    fn decode<R: Read>(reader: &mut R, _opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        VarLong::read(reader)
+            .map_err(|e| NetDecodeError::ExternalError(e.into()))
+    }
+
+    async fn decode_async<R: AsyncRead + Unpin>(reader: &mut R, _opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
+        VarLong::read_async(reader).await
+            .map_err(|e| NetDecodeError::ExternalError(e.into()))
+    }
+}
+
+impl NetEncode for VarLong {
+    fn encode<W: Write>(&self, writer: &mut W, _opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.write(writer)
+            .map_err(|e| NetEncodeError::ExternalError(e.into()))
+    }
+
+    async fn encode_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, _opts: &NetEncodeOpts) -> NetEncodeResult<()> {
+        self.write_async(writer).await
+            .map_err(|e| NetEncodeError::ExternalError(e.into()))
+    }
+
+    fn encoded_len(&self) -> usize {
+        Self::calculate_len(self.val)
+    }
+}
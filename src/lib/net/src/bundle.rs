@@ -0,0 +1,54 @@
+use crate::connection::{check_packet_state, ConnectionState};
+use crate::errors::NetError;
+use crate::packets::OutgoingPacket;
+use crate::NetResult;
+use ferrumc_net_codec::encode::NetEncodeOpts;
+
+/// The most packets the client accepts between two bundle delimiters.
+pub const MAX_BUNDLED_PACKETS: usize = 4096;
+
+/// Packets that the client applies in the same tick, see [send_bundle](crate::connection::StreamWriter::send_bundle).
+///
+/// Packets are encoded right away and only sent once the bundle is complete,
+/// so nothing else sent to the connection can end up in the middle of it.
+pub struct PacketBundle {
+    buffer: Vec<u8>,
+    count: usize,
+    state: ConnectionState,
+}
+
+impl PacketBundle {
+    pub(crate) fn new(state: ConnectionState) -> Self {
+        Self {
+            buffer: Vec::new(),
+            count: 0,
+            state,
+        }
+    }
+
+    /// Adds a packet to the bundle, fails once the bundle holds [MAX_BUNDLED_PACKETS] packets.
+    pub fn add<P: OutgoingPacket>(&mut self, packet: &P, net_encode_opts: &NetEncodeOpts) -> NetResult<()> {
+        check_packet_state::<P>(&self.state)?;
+
+        if self.count == MAX_BUNDLED_PACKETS {
+            return Err(NetError::BundleTooLarge(MAX_BUNDLED_PACKETS));
+        }
+
+        packet.encode(&mut self.buffer, net_encode_opts)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// The number of packets in the bundle.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}
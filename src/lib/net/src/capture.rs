@@ -0,0 +1,494 @@
+//! Packet captures.
+//!
+//! A capture records every packet sent and received on a connection into a binary file,
+//! so it can be inspected or replayed against a server later on.
+//!
+//! The file starts with a header:
+//! - `magic`: `FCAP`
+//! - `version`: u8, see [CAPTURE_VERSION]
+//! - `started_at`: u64, unix timestamp in milliseconds of when the capture was started
+//!
+//! Followed by any number of [CapturedPacket]s until the end of the file.
+//!
+//! Every connection is captured if it's enabled in the config, otherwise captures can be started
+//! at runtime for chosen connections with [start_capturing], e.g. with the `capture` console command.
+
+use crate::connection::{ConnectionState, StreamWriter};
+use crate::errors::CaptureError;
+use crate::ServerState;
+use ferrumc_config::statics::get_global_config;
+use ferrumc_general_purpose::paths::get_root_path;
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use parking_lot::RwLock;
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Cursor, Read, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::JoinHandle;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+pub type CaptureResult<T> = Result<T, CaptureError>;
+
+pub const CAPTURE_MAGIC: [u8; 4] = *b"FCAP";
+pub const CAPTURE_VERSION: u8 = 1;
+pub const CAPTURE_EXTENSION: &str = "fcap";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PacketDirection {
+    /// Sent by the client to the server.
+    Inbound,
+    /// Sent by the server to the client.
+    Outbound,
+}
+
+impl TryFrom<u8> for PacketDirection {
+    type Error = CaptureError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PacketDirection::Inbound),
+            1 => Ok(PacketDirection::Outbound),
+            _ => Err(CaptureError::InvalidDirection(value)),
+        }
+    }
+}
+
+/// A single packet in a capture file.
+///
+/// Fields:
+/// `timestamp`: Microseconds since the capture was started.
+/// `direction`: If the packet was received or sent by the server.
+/// `state`: The state of the connection when the packet was received/sent.
+/// `packet_id`: The id of the packet.
+/// `payload`: The uncompressed packet data, without the length and id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedPacket {
+    pub timestamp: u64,
+    pub direction: PacketDirection,
+    pub state: ConnectionState,
+    pub packet_id: u8,
+    pub payload: Vec<u8>,
+}
+
+impl CapturedPacket {
+    /// Encodes the packet the same way it would be sent over an uncompressed connection.
+    pub fn to_frame(&self) -> CaptureResult<Vec<u8>> {
+        let id = VarInt::new(i32::from(self.packet_id));
+        let mut id_buf = Vec::with_capacity(5);
+        id.write(&mut id_buf)?;
+
+        let mut frame = Vec::with_capacity(id_buf.len() + self.payload.len() + 5);
+        VarInt::from(id_buf.len() + self.payload.len()).write(&mut frame)?;
+        frame.extend_from_slice(&id_buf);
+        frame.extend_from_slice(&self.payload);
+
+        Ok(frame)
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> CaptureResult<()> {
+        let opts = &NetEncodeOpts::None;
+        self.timestamp.encode(writer, opts)?;
+        (self.direction as u8).encode(writer, opts)?;
+        (self.state.clone() as u8).encode(writer, opts)?;
+        VarInt::from(self.packet_id).encode(writer, opts)?;
+        VarInt::from(self.payload.len()).encode(writer, opts)?;
+        writer.write_all(&self.payload)?;
+
+        Ok(())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> CaptureResult<Self> {
+        let opts = &NetDecodeOpts::None;
+        let timestamp = u64::decode(reader, opts)?;
+        let direction = PacketDirection::try_from(u8::decode(reader, opts)?)?;
+        let state = u8::decode(reader, opts)?;
+        let state = ConnectionState::try_from(state).map_err(|_| CaptureError::InvalidState(state))?;
+        let packet_id = VarInt::decode(reader, opts)?.val as u8;
+        let length = VarInt::decode(reader, opts)?.val as usize;
+
+        let mut payload = vec![0; length];
+        reader.read_exact(&mut payload)?;
+
+        Ok(Self {
+            timestamp,
+            direction,
+            state,
+            packet_id,
+            payload,
+        })
+    }
+}
+
+/// Writes the packets of a single connection into a capture file.
+///
+/// Inbound packets are recorded by the connection loop, outbound packets by the
+/// [StreamWriter] the capture is attached to. The file is written on a background thread, which
+/// finishes writing what was recorded after the capture is dropped, see [finish](PacketCapture::finish)
+/// to wait for it.
+pub struct PacketCapture {
+    path: PathBuf,
+    started: Instant,
+    state: AtomicU8,
+    /// Only `None` once the capture is finished or dropped.
+    sender: Option<Sender<CapturedPacket>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl PacketCapture {
+    /// Creates a new capture file at `path`, overwriting any existing file.
+    pub fn create(path: impl Into<PathBuf>) -> CaptureResult<Self> {
+        let path = path.into();
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        writer.write_all(&CAPTURE_MAGIC)?;
+        CAPTURE_VERSION.encode(&mut writer, &NetEncodeOpts::None)?;
+        started_at.encode(&mut writer, &NetEncodeOpts::None)?;
+        writer.flush()?;
+
+        let (sender, receiver) = mpsc::channel();
+        let thread_path = path.clone();
+        let writer = std::thread::Builder::new()
+            .name("packet-capture".to_string())
+            .spawn(move || {
+                if let Err(e) = write_packets(writer, receiver) {
+                    warn!("Failed to write packet capture {}: {}", thread_path.display(), e);
+                }
+            })?;
+
+        Ok(Self {
+            path,
+            started: Instant::now(),
+            state: AtomicU8::new(ConnectionState::Handshaking as u8),
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// Creates a new timestamped capture file for `conn_id` in the configured capture directory.
+    ///
+    /// Relative directories are resolved from the directory of the server executable.
+    pub fn for_connection(conn_id: usize) -> CaptureResult<Self> {
+        let directory = &get_global_config().packet_capture.directory;
+        let directory = get_root_path()
+            .map(|root| root.join(directory))
+            .unwrap_or_else(|_| PathBuf::from(directory));
+        std::fs::create_dir_all(&directory)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        Self::create(directory.join(format!("{timestamp}-{conn_id}.{CAPTURE_EXTENSION}")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn state(&self) -> ConnectionState {
+        let state = self.state.load(Ordering::Relaxed);
+        // Only ever set from a valid ConnectionState
+        ConnectionState::try_from(state).unwrap_or(ConnectionState::Handshaking)
+    }
+
+    /// Sets the connection state recorded with the following packets.
+    pub fn set_state(&self, state: &ConnectionState) {
+        self.state.store(state.clone() as u8, Ordering::Relaxed);
+    }
+
+    /// Records a packet received from the client.
+    pub fn record_inbound(&self, packet_id: u8, payload: &[u8]) -> CaptureResult<()> {
+        self.record(PacketDirection::Inbound, packet_id, payload)
+    }
+
+    /// Records everything that was written to the client.
+    ///
+    /// `bytes` may contain multiple length prefixed packets, each one is recorded separately.
+    pub fn record_outbound(&self, bytes: &[u8]) -> CaptureResult<()> {
+        let mut cursor = Cursor::new(bytes);
+
+        while (cursor.position() as usize) < bytes.len() {
+            let length = VarInt::read(&mut cursor)?.val as usize;
+            let start = cursor.position() as usize;
+            let end = start
+                .checked_add(length)
+                .filter(|end| *end <= bytes.len())
+                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+
+            let mut frame = Cursor::new(&bytes[start..end]);
+            let packet_id = VarInt::read(&mut frame)?.val as u8;
+            let payload = &bytes[start + frame.position() as usize..end];
+            self.record(PacketDirection::Outbound, packet_id, payload)?;
+
+            cursor.set_position(end as u64);
+        }
+
+        Ok(())
+    }
+
+    fn record(&self, direction: PacketDirection, packet_id: u8, payload: &[u8]) -> CaptureResult<()> {
+        let packet = CapturedPacket {
+            timestamp: self.started.elapsed().as_micros() as u64,
+            direction,
+            state: self.state(),
+            packet_id,
+            payload: payload.to_vec(),
+        };
+
+        self.sender
+            .as_ref()
+            .and_then(|sender| sender.send(packet).ok())
+            .ok_or(CaptureError::WriterStopped)
+    }
+}
+
+impl PacketCapture {
+    /// Stops recording and blocks until every recorded packet is written to the file.
+    ///
+    /// Dropping the capture doesn't wait, so it never blocks the thread it's dropped on.
+    pub fn finish(mut self) {
+        drop(self.sender.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+impl Drop for PacketCapture {
+    fn drop(&mut self) {
+        // Closing the channel stops the writer once it has written everything queued.
+        drop(self.sender.take());
+    }
+}
+
+/// Writes the packets received from `packets` until every sender is gone.
+fn write_packets(mut writer: BufWriter<File>, packets: Receiver<CapturedPacket>) -> CaptureResult<()> {
+    let Ok(mut packet) = packets.recv() else {
+        return Ok(());
+    };
+
+    loop {
+        packet.write(&mut writer)?;
+
+        packet = match packets.try_recv() {
+            Ok(packet) => packet,
+            Err(TryRecvError::Empty) => {
+                // Flush whenever the queue is empty so the capture is still usable if the server crashes.
+                writer.flush()?;
+                match packets.recv() {
+                    Ok(packet) => packet,
+                    Err(_) => return Ok(()),
+                }
+            }
+            Err(TryRecvError::Disconnected) => break,
+        };
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Which connections to capture, see [start_capturing].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureFilter {
+    /// Every connection.
+    All,
+    /// A single connection, by its entity.
+    Entity(usize),
+    /// Every connection from this address.
+    Address(IpAddr),
+}
+
+impl CaptureFilter {
+    pub fn matches(&self, conn_id: usize, address: Option<SocketAddr>) -> bool {
+        match self {
+            CaptureFilter::All => true,
+            CaptureFilter::Entity(entity) => *entity == conn_id,
+            CaptureFilter::Address(ip) => address.is_some_and(|address| address.ip() == *ip),
+        }
+    }
+}
+
+/// The filters started at runtime that new connections are captured for, besides the config.
+static CAPTURE_FILTERS: RwLock<Vec<CaptureFilter>> = RwLock::new(Vec::new());
+
+/// If a new connection has to be captured from the start.
+pub fn should_capture(conn_id: usize, address: Option<SocketAddr>) -> bool {
+    get_global_config().packet_capture.enabled
+        || CAPTURE_FILTERS.read().iter().any(|filter| filter.matches(conn_id, address))
+}
+
+/// Starts capturing the connections matching `filter` that aren't captured yet.
+///
+/// [All](CaptureFilter::All) and [Address](CaptureFilter::Address) filters also apply to
+/// connections made later on, until they're passed to [stop_capturing].
+///
+/// Returns the paths of the new capture files.
+pub fn start_capturing(state: &ServerState, filter: CaptureFilter) -> Vec<PathBuf> {
+    if !matches!(filter, CaptureFilter::Entity(_)) {
+        let mut filters = CAPTURE_FILTERS.write();
+        if !filters.contains(&filter) {
+            filters.push(filter);
+        }
+    }
+
+    let mut paths = Vec::new();
+    for conn_id in state.universe.query::<&mut StreamWriter>().into_entities() {
+        let Ok(mut writer) = state.universe.get_mut::<StreamWriter>(conn_id) else {
+            continue;
+        };
+        if writer.capture.is_some() || !filter.matches(conn_id, writer.address()) {
+            continue;
+        }
+
+        match writer.start_capture(conn_id) {
+            Ok(path) => paths.push(path),
+            Err(e) => warn!("Failed to start capturing packets of entity {}: {}", conn_id, e),
+        }
+    }
+
+    paths
+}
+
+/// Stops capturing the connections matching `filter`, and removes it from the filters for new connections.
+///
+/// Returns the paths of the finished capture files.
+pub fn stop_capturing(state: &ServerState, filter: CaptureFilter) -> Vec<PathBuf> {
+    CAPTURE_FILTERS.write().retain(|started| *started != filter);
+
+    let mut paths = Vec::new();
+    for conn_id in state.universe.query::<&mut StreamWriter>().into_entities() {
+        let Ok(mut writer) = state.universe.get_mut::<StreamWriter>(conn_id) else {
+            continue;
+        };
+        if !filter.matches(conn_id, writer.address()) {
+            continue;
+        }
+
+        if let Some(path) = writer.stop_capture() {
+            debug!("Stopped capturing packets of entity {}", conn_id);
+            paths.push(path);
+        }
+    }
+
+    paths
+}
+
+/// Reads the packets of a capture file.
+///
+/// ```ignore
+/// let reader = CaptureReader::new(BufReader::new(File::open("capture.fcap")?))?;
+/// for packet in reader {
+///     let packet = packet?;
+///     println!("{:?} 0x{:02X}", packet.direction, packet.packet_id);
+/// }
+/// ```
+pub struct CaptureReader<R: BufRead> {
+    reader: R,
+    started_at: u64,
+}
+
+impl<R: BufRead> CaptureReader<R> {
+    /// Reads and validates the capture header.
+    pub fn new(mut reader: R) -> CaptureResult<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != CAPTURE_MAGIC {
+            return Err(CaptureError::InvalidMagic);
+        }
+
+        let version = u8::decode(&mut reader, &NetDecodeOpts::None)?;
+        if version != CAPTURE_VERSION {
+            return Err(CaptureError::UnsupportedVersion(version));
+        }
+
+        let started_at = u64::decode(&mut reader, &NetDecodeOpts::None)?;
+
+        Ok(Self { reader, started_at })
+    }
+
+    /// Unix timestamp in milliseconds of when the capture was started.
+    pub fn started_at(&self) -> u64 {
+        self.started_at
+    }
+}
+
+impl<R: BufRead> Iterator for CaptureReader<R> {
+    type Item = CaptureResult<CapturedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.fill_buf() {
+            Ok([]) => None,
+            Ok(_) => Some(CapturedPacket::read(&mut self.reader)),
+            Err(e) => Some(Err(CaptureError::IOError(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn capture_round_trip() {
+        let path = std::env::temp_dir().join(format!("ferrumc-capture-{}.fcap", std::process::id()));
+
+        {
+            let capture = PacketCapture::create(&path).unwrap();
+            capture.record_inbound(0x00, &[1, 2, 3]).unwrap();
+            capture.set_state(&ConnectionState::Play);
+
+            // Two length prefixed packets written at once
+            capture.record_outbound(&[2, 0x26, 7, 1, 0x40]).unwrap();
+            capture.finish();
+        }
+
+        let reader = CaptureReader::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let packets = reader.collect::<CaptureResult<Vec<_>>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].direction, PacketDirection::Inbound);
+        assert_eq!(packets[0].state, ConnectionState::Handshaking);
+        assert_eq!(packets[0].payload, vec![1, 2, 3]);
+        assert_eq!(packets[0].to_frame().unwrap(), vec![4, 0x00, 1, 2, 3]);
+
+        assert_eq!(packets[1].direction, PacketDirection::Outbound);
+        assert_eq!(packets[1].state, ConnectionState::Play);
+        assert_eq!(packets[1].packet_id, 0x26);
+        assert_eq!(packets[1].payload, vec![7]);
+        assert_eq!(packets[2].packet_id, 0x40);
+        assert!(packets[2].payload.is_empty());
+    }
+
+    #[test]
+    fn capture_filters() {
+        let address = Some(SocketAddr::from(([127, 0, 0, 1], 50_000)));
+
+        assert!(CaptureFilter::All.matches(1, None));
+        assert!(CaptureFilter::Entity(1).matches(1, address));
+        assert!(!CaptureFilter::Entity(2).matches(1, address));
+        assert!(CaptureFilter::Address(IpAddr::from([127, 0, 0, 1])).matches(1, address));
+        assert!(!CaptureFilter::Address(IpAddr::from([10, 0, 0, 1])).matches(1, address));
+        assert!(!CaptureFilter::Address(IpAddr::from([127, 0, 0, 1])).matches(1, None));
+    }
+
+    #[test]
+    fn rejects_invalid_header() {
+        let res = CaptureReader::new(Cursor::new(b"NOPE\x01".to_vec()));
+        assert!(matches!(res, Err(CaptureError::InvalidMagic)));
+    }
+}
@@ -3,22 +3,33 @@ use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tracing::{debug, debug_span, trace, warn, error, Instrument};
 use ferrumc_net_codec::{
-    encode::{NetEncode, NetEncodeOpts},
-    decode::{NetDecode, NetDecodeOpts, NetDecodeResult}, 
+    encode::{pool::BufferPool, NetEncode, NetEncodeOpts},
     net_types::length_prefixed_vec::LengthPrefixedVec
 };
+use bytes::Bytes;
 use crate::{handle_packet, NetResult, ServerState};
 use crate::packets::incoming::packet_skeleton::PacketSkeleton;
+use crate::bundle::PacketBundle;
+use crate::packets::generated::outgoing::{BundleDelimiterPacket, StartConfigurationPacket};
+use crate::packets::OutgoingPacket;
 use ferrumc_macros::{Event, NetEncode, NetDecode};
 use ferrumc_events::infrastructure::Event;
 use ferrumc_ecs::entities::Entity;
 use std::io::Write;
-use std::io::Read;
-use crate::errors::NetError;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::LazyLock;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use crate::capture::{self, CaptureResult, PacketCapture};
+use crate::errors::{NetError, PacketError};
 use ferrumc_text::*;
 use crate::packets::outgoing::disconnect::*;
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 #[repr(u8)]
 pub enum ConnectionState {
     Handshaking,
@@ -40,6 +51,21 @@ impl ConnectionState {
     }
 }
 
+impl TryFrom<u8> for ConnectionState {
+    type Error = PacketError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ConnectionState::Handshaking),
+            1 => Ok(ConnectionState::Status),
+            2 => Ok(ConnectionState::Login),
+            3 => Ok(ConnectionState::Play),
+            4 => Ok(ConnectionState::Configuration),
+            _ => Err(PacketError::InvalidState(value)),
+        }
+    }
+}
+
 /// The GameProfile holds information about a player.
 ///
 /// Fields:
@@ -78,7 +104,7 @@ pub struct GameProfile {
 /// `is_signed`: If the Property is signed.
 /// `signature`: The signature of the Property
 ///
-#[derive(Debug, Clone, NetEncode, Eq, PartialEq)]
+#[derive(Debug, Clone, NetEncode, NetDecode, Eq, PartialEq)]
 pub struct ProfileProperty {
     /// The name of this Property.
     pub name: String,
@@ -87,26 +113,10 @@ pub struct ProfileProperty {
     /// If this Property is signed.
     pub is_signed: bool, // note: perhaps this field can be removed with custom NetEncode implementation.
     /// The signature of this Property.
+    #[net(optional_if = "is_signed")]
     pub signature: Option<String>,
 }
 
-impl NetDecode for ProfileProperty {
-    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
-        let name = String::decode(reader, opts)?;
-        let value = String::decode(reader, opts)?;
-        let is_signed = bool::decode(reader, opts)?;
-        let signature = if is_signed {
-            Some(String::decode(reader, opts)?)
-        } else {
-            None
-        };
-
-        Ok(ProfileProperty {
-            name, value, is_signed, signature
-        })
-    }
-}
-
 impl GameProfile {
     /// Create a new GameProfile from uuid and username.
     pub fn new(uuid: u128, username: String) -> Self {
@@ -135,41 +145,267 @@ impl StreamReader {
     }
 }
 
+/// How many bytes may wait to be written to a client before it's disconnected for not keeping up.
+pub const MAX_QUEUED_BYTES: usize = 32 * 1024 * 1024;
+
+/// Sends packets to a connection.
+///
+/// Packets are queued to a task that writes them to the socket, so holding this component never
+/// waits on a slow client. A client that lets more than [MAX_QUEUED_BYTES] pile up is disconnected.
+///
+/// Written buffers go back to the global [BufferPool] once no other connection still shares them.
 pub struct StreamWriter {
-    pub writer: OwnedWriteHalf,
+    sender: UnboundedSender<Bytes>,
+    /// Bytes that were queued but aren't written yet.
+    queued: Arc<AtomicUsize>,
+    writer_task: JoinHandle<()>,
+    /// Notified when the connection is closed because the client isn't keeping up.
+    closed: Arc<Notify>,
+    address: Option<SocketAddr>,
+    /// The capture outgoing packets are recorded to, see [start_capture](StreamWriter::start_capture).
+    pub capture: Option<Arc<PacketCapture>>,
+    /// The state of the connection, which decides which packets can be sent and received.
+    state: ConnectionState,
+    /// If the client was told to start a configuration and hasn't acknowledged it yet,
+    /// see [start_reconfiguration](StreamWriter::start_reconfiguration).
+    reconfiguring: bool,
 }
 
 impl StreamWriter {
-    pub fn new(writer: OwnedWriteHalf) -> Self {
-        Self { writer }
+    /// Has to be called inside a tokio runtime, which the writing task is spawned on.
+    pub fn new(mut writer: OwnedWriteHalf) -> Self {
+        let address = writer.peer_addr().ok();
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Bytes>();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let writer_task = tokio::spawn({
+            let queued = Arc::clone(&queued);
+            async move {
+                while let Some(buf) = receiver.recv().await {
+                    if let Err(e) = writer.write_all(&buf).await {
+                        trace!("Failed to write to connection: {}", e);
+                        break;
+                    }
+                    queued.fetch_sub(buf.len(), Ordering::Relaxed);
+                    BufferPool::global().recycle(buf);
+                }
+            }
+        });
+
+        Self {
+            sender,
+            queued,
+            writer_task,
+            closed: Arc::new(Notify::new()),
+            address,
+            capture: None,
+            state: ConnectionState::Handshaking,
+            reconfiguring: false,
+        }
     }
 
-    pub async fn send_packet(
+    /// How many bytes are waiting to be written to the client.
+    pub fn queued_bytes(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Notified once if the connection is closed for not keeping up, see [MAX_QUEUED_BYTES].
+    pub fn closed(&self) -> Arc<Notify> {
+        Arc::clone(&self.closed)
+    }
+
+    /// The address of the client, if it was still connected when the writer was created.
+    pub fn address(&self) -> Option<SocketAddr> {
+        self.address
+    }
+
+    /// The state of the connection, which is only kept here.
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
+    /// Switches the connection to `state`, which is also recorded with the following packets of a capture.
+    pub fn set_state(&mut self, state: ConnectionState) {
+        if let Some(capture) = &self.capture {
+            capture.set_state(&state);
+        }
+        self.state = state;
+        self.reconfiguring = false;
+    }
+
+    /// If play packets can be sent, which stops once a re-configuration is started.
+    ///
+    /// Anything sent to every player, like chunks or the time, should only be sent to connections where this is true.
+    pub fn is_playing(&self) -> bool {
+        self.state == ConnectionState::Play && !self.reconfiguring
+    }
+
+    /// Tells a playing client to go back to the configuration state, e.g. to send it new registries.
+    ///
+    /// The connection stays in the play state until the client acknowledges it, because the client may still send
+    /// play packets until then. No more play packets are sent to it in between, [send_packet](StreamWriter::send_packet)
+    /// refuses them and [is_playing](StreamWriter::is_playing) is false.
+    pub async fn start_reconfiguration(&mut self) -> NetResult<()> {
+        if !self.is_playing() {
+            return Err(NetError::InvalidState(self.state.clone() as u8));
+        }
+
+        self.send_packet(&StartConfigurationPacket {}, &NetEncodeOpts::WithLength).await?;
+        self.reconfiguring = true;
+        Ok(())
+    }
+
+    /// Sends a packet, in debug builds it's refused if it doesn't belong to the current state of the connection.
+    ///
+    /// Play packets are always refused between [start_reconfiguration](StreamWriter::start_reconfiguration)
+    /// and the client acknowledging it.
+    pub async fn send_packet<P: OutgoingPacket>(
+        &mut self,
+        packet: &P,
+        net_encode_opts: &NetEncodeOpts,
+    ) -> NetResult<()> {
+        self.check_outgoing::<P>()?;
+        self.send_raw(packet, net_encode_opts).await
+    }
+
+    /// The state the client expects packets from the server in, which already is the configuration state
+    /// once a re-configuration was started.
+    fn outgoing_state(&self) -> ConnectionState {
+        if self.reconfiguring {
+            ConnectionState::Configuration
+        } else {
+            self.state.clone()
+        }
+    }
+
+    fn check_outgoing<P: OutgoingPacket>(&self) -> NetResult<()> {
+        if self.reconfiguring && P::STATE == ConnectionState::Play {
+            return Err(NetError::InvalidState(ConnectionState::Play as u8));
+        }
+        check_packet_state::<P>(&self.outgoing_state())
+    }
+
+    /// Sends the packets added to the bundle in `f` between two bundle delimiters,
+    /// so the client applies all of them in the same tick.
+    ///
+    /// ```ignore
+    /// writer.send_bundle(|bundle| {
+    ///     bundle.add(&spawn_entity, &NetEncodeOpts::WithLength)?;
+    ///     bundle.add(&entity_metadata, &NetEncodeOpts::WithLength)
+    /// }).await?;
+    /// ```
+    pub async fn send_bundle<F>(&mut self, f: F) -> NetResult<()>
+    where
+        F: FnOnce(&mut PacketBundle) -> NetResult<()>,
+    {
+        self.check_outgoing::<BundleDelimiterPacket>()?;
+
+        let mut bundle = PacketBundle::new(self.state.clone());
+        f(&mut bundle)?;
+        if bundle.is_empty() {
+            return Ok(());
+        }
+
+        let mut buf = Vec::new();
+        BundleDelimiterPacket {}.encode(&mut buf, &NetEncodeOpts::WithLength)?;
+        buf.extend_from_slice(&bundle.into_bytes());
+        BundleDelimiterPacket {}.encode(&mut buf, &NetEncodeOpts::WithLength)?;
+
+        self.send_bytes(Bytes::from(buf)).await
+    }
+
+    /// Sends anything encodable without checking the state of the connection.
+    ///
+    /// It's encoded into a buffer from the global [BufferPool] sized with [NetEncode::encoded_len].
+    /// For packets that are already encoded use [send_bytes](StreamWriter::send_bytes), which doesn't copy them.
+    pub async fn send_raw(
         &mut self,
         packet: &impl NetEncode,
         net_encode_opts: &NetEncodeOpts,
     ) -> NetResult<()> {
-        packet
-            .encode_async(&mut self.writer, net_encode_opts)
-            .await?;
+        let buf = BufferPool::global().encode(packet, net_encode_opts)?;
+        self.send_bytes(buf).await
+    }
+
+    /// Queues already encoded bytes as they are, without checking the state of the connection.
+    ///
+    /// Cloning [Bytes] doesn't copy them, so a packet broadcast to many players is encoded once
+    /// and every connection shares the same buffer.
+    pub async fn send_bytes(&mut self, buf: Bytes) -> NetResult<()> {
+        if let Some(capture) = &self.capture {
+            if let Err(e) = capture.record_outbound(&buf) {
+                warn!("Failed to record outgoing packet to {}: {}", capture.path().display(), e);
+            }
+        }
+
+        let queued = self.queued.fetch_add(buf.len(), Ordering::Relaxed) + buf.len();
+        if queued > MAX_QUEUED_BYTES {
+            // Only once, nothing is written after this so it never goes back under.
+            if queued - buf.len() <= MAX_QUEUED_BYTES {
+                warn!("Disconnecting {:?}, {} bytes are waiting to be sent to it", self.address, queued);
+                // Dropping the socket with the task closes it, without writing what's left.
+                self.writer_task.abort();
+                self.closed.notify_one();
+            }
+            return Err(NetError::SendQueueFull(queued));
+        }
+
+        self.sender.send(buf).map_err(|_| NetError::ConnectionClosed)?;
         Ok(())
     }
 
-    pub async fn kick<S: Into<TextComponent>>(&mut self, conn_state: &ConnectionState, reason: S) -> NetResult<()> {
-        let packet = match conn_state {
+    /// Starts capturing every packet of this connection into a new file in the configured capture directory.
+    /// To choose connections by their entity or address, see [start_capturing](crate::capture::start_capturing).
+    ///
+    /// Returns the path of the capture file.
+    pub fn start_capture(&mut self, conn_id: usize) -> CaptureResult<PathBuf> {
+        let capture = PacketCapture::for_connection(conn_id)?;
+        capture.set_state(&self.state);
+        let path = capture.path().to_path_buf();
+        debug!("Capturing packets of entity {} to {}", conn_id, path.display());
+
+        self.capture = Some(Arc::new(capture));
+        Ok(path)
+    }
+
+    /// Stops the current capture, if any, and returns the path of its file.
+    pub fn stop_capture(&mut self) -> Option<PathBuf> {
+        self.capture
+            .take()
+            .map(|capture| capture.path().to_path_buf())
+    }
+
+    /// Tells the client why it's disconnected.
+    ///
+    /// Fails with [NetError::InvalidState] while handshaking or in the status state, which have no disconnect packet.
+    pub async fn kick<S: Into<TextComponent>>(&mut self, reason: S) -> NetResult<()> {
+        match self.outgoing_state() {
             ConnectionState::Login => {
-                DisconnectPacket::Login(LoginDisconnect::new(reason.into()))
+                self.send_packet(&LoginDisconnect::new(reason.into()), &NetEncodeOpts::WithLength).await
+            }
+            ConnectionState::Configuration => {
+                self.send_packet(&ConfigurationDisconnect::new(reason), &NetEncodeOpts::WithLength).await
             }
             ConnectionState::Play => {
-                DisconnectPacket::Play(PlayDisconnect::new(reason))
+                self.send_packet(&PlayDisconnect::new(reason), &NetEncodeOpts::WithLength).await
             }
-            _ => {
-                return Err(NetError::InvalidState(conn_state.clone() as u8));
+            state => {
+                Err(NetError::InvalidState(state as u8))
             }
-        };
+        }
+    }
+}
 
-        self.send_packet(&packet, &NetEncodeOpts::WithLength).await
+/// In debug builds, refuses packets that don't belong to the current state of the connection.
+pub(crate) fn check_packet_state<P: OutgoingPacket>(state: &ConnectionState) -> NetResult<()> {
+    if cfg!(debug_assertions) && P::STATE != *state {
+        return Err(NetError::WrongPacketState {
+            packet: P::NAME,
+            expected: P::STATE.as_str(),
+            actual: state.as_str(),
+        });
     }
+
+    Ok(())
 }
 
 /// This component was created before [PlayerIdentity](ferrumc_core::identity::player_identity::PlayerIdentity) was added this contains more info like properties/textures.
@@ -222,6 +458,9 @@ impl PlayerDisconnectEvent {
     }
 }
 
+/// If every received packet should be logged, set with the `FERRUMC_LOG_PACKETS` environment variable.
+static LOG_PACKETS: LazyLock<bool> = LazyLock::new(|| std::env::var_os("FERRUMC_LOG_PACKETS").is_some());
+
 pub async fn handle_connection(state: Arc<ServerState>, tcp_stream: TcpStream) -> NetResult<()> {
     let (mut reader, writer) = tcp_stream.into_split();
 
@@ -230,24 +469,51 @@ pub async fn handle_connection(state: Arc<ServerState>, tcp_stream: TcpStream) -
         .builder()
         //.with(StreamReader::new(reader))?
         .with(StreamWriter::new(writer))?
-        .with(ConnectionState::Handshaking)?
         .with(CompressionStatus::new())?
         .with(Profile::new())? // initialize with empty profile
         .build();
 
+    {
+        let mut writer = state.universe.get_mut::<StreamWriter>(entity)?;
+        if capture::should_capture(entity, writer.address()) {
+            if let Err(e) = writer.start_capture(entity) {
+                warn!("Failed to start capturing packets of entity {}: {}", entity, e);
+            }
+        }
+    }
+
+    let closed = state.universe.get::<StreamWriter>(entity)?.closed();
+
     'recv: loop {
         let compressed = state.universe.get::<CompressionStatus>(entity)?.enabled;
-        let Ok(mut packet_skele) = PacketSkeleton::new(&mut reader, compressed).await else {
+        let packet = tokio::select! {
+            packet = PacketSkeleton::new(&mut reader, compressed) => packet,
+            _ = closed.notified() => {
+                trace!("Connection closed by the server.");
+                break 'recv;
+            }
+        };
+        let Ok(mut packet_skele) = packet else {
             trace!("Failed to read packet. Possibly connection closed.");
             break 'recv;
         };
 
-        // Log the packet if the environment variable is set (this env variable is set at compile time not runtime!)
-        if option_env!("FERRUMC_LOG_PACKETS").is_some() {
+        // Log the packet if the environment variable is set
+        if *LOG_PACKETS {
             trace!("Received packet: {:?}", packet_skele);
         }
 
-        let conn_state = state.universe.get::<ConnectionState>(entity)?.clone();
+        let (conn_state, capture) = {
+            let writer = state.universe.get::<StreamWriter>(entity)?;
+            (writer.state().clone(), writer.capture.clone())
+        };
+        if let Some(capture) = &capture {
+            let payload = &packet_skele.data.get_ref()[packet_skele.data.position() as usize..];
+            if let Err(e) = capture.record_inbound(packet_skele.id, payload) {
+                warn!("Failed to record incoming packet to {}: {}", capture.path().display(), e);
+            }
+        }
+
         if let Err(e) = handle_packet(
             packet_skele.id,
             entity,
@@ -263,13 +529,13 @@ pub async fn handle_connection(state: Arc<ServerState>, tcp_stream: TcpStream) -
                 NetError::Kick(msg) => {
                     warn!("Failed to handle packet: {}. packet_id: {:02X}; conn_state: {}", msg, packet_skele.id, conn_state.as_str());
                     let _ = state.universe.get_mut::<StreamWriter>(entity)?
-                        .kick(&conn_state, msg)
+                        .kick(*msg)
                         .await;
                 },
                 _ => {
                     warn!("Failed to handle packet: {:?}. packet_id: {:02X}; conn_state: {}", e, packet_skele.id, conn_state.as_str());
                     let _ = state.universe.get_mut::<StreamWriter>(entity)?
-                        .kick(&conn_state, TextComponent::from("§cDisconnected".to_string()))
+                        .kick(TextComponent::from("§cDisconnected".to_string()))
                         .await;
                 }
             }
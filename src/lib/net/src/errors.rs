@@ -39,11 +39,31 @@ pub enum NetError {
     #[error("Invalid State: {0}")]
     InvalidState(u8),
 
+    #[error("{packet} can only be sent in the {expected} state, but the connection is in the {actual} state")]
+    WrongPacketState {
+        packet: &'static str,
+        expected: &'static str,
+        actual: &'static str,
+    },
+
+    #[error("A bundle can't hold more than {0} packets")]
+    BundleTooLarge(usize),
+
+    #[error("The connection is closed")]
+    ConnectionClosed,
+
+    #[error("{0} bytes are waiting to be sent, so the client was disconnected")]
+    SendQueueFull(usize),
+
     #[error("{0}")]
     Packet(#[from] PacketError),
 
+    #[error("Capture Error: {0}")]
+    CaptureError(#[from] CaptureError),
+
+    /// Boxed to keep [NetResult](crate::NetResult) small, use [NetError::kick] to create it.
     #[error("{0}")]
-    Kick(ferrumc_text::TextComponent),
+    Kick(Box<ferrumc_text::TextComponent>),
 }
 
 #[derive(Debug, Error)]
@@ -52,8 +72,30 @@ pub enum PacketError {
     InvalidState(u8),
 }
 
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("Decoder Error: {0}")]
+    DecoderError(#[from] NetDecodeError),
+    #[error("Encoder Error: {0}")]
+    EncoderError(#[from] NetEncodeError),
+    #[error("VarInt Error: {0}")]
+    TypesError(#[from] ferrumc_net_codec::net_types::NetTypesError),
+    #[error("Not a packet capture file")]
+    InvalidMagic,
+    #[error("Unsupported capture version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Invalid packet direction: {0}")]
+    InvalidDirection(u8),
+    #[error("Invalid connection state: {0}")]
+    InvalidState(u8),
+    #[error("The capture file isn't written anymore")]
+    WriterStopped,
+}
+
 impl NetError {
     pub fn kick<T: Into<ferrumc_text::TextComponent> + Send + Sync>(component: T) -> Self {
-        Self::Kick(component.into())
+        Self::Kick(Box::new(component.into()))
     }
 }
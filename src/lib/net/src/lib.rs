@@ -1,8 +1,9 @@
 use tokio::net::TcpListener;
 use ferrumc_ecs::Universe;
-use ferrumc_macros::bake_packet_registry;
 use std::sync::{Arc};
 
+pub mod bundle;
+pub mod capture;
 pub mod connection;
 pub mod errors;
 pub mod packets;
@@ -18,4 +19,5 @@ pub struct ServerState {
 pub type GlobalState = Arc<ServerState>;
 
 
-bake_packet_registry!("\\src\\packets\\incoming");
+// The incoming packet registry, `handle_packet` and `debug_packet`, generated by `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/registry.rs"));
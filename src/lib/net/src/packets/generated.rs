@@ -0,0 +1,14 @@
+//! Packets generated from the protocol spec in `protocol/` by `build.rs`.
+//!
+//! A hand-written packet with the same name in `packets::incoming` or `packets::outgoing`
+//! replaces the generated one.
+
+include!(concat!(env!("OUT_DIR"), "/packets/spec.rs"));
+
+pub mod incoming {
+    include!(concat!(env!("OUT_DIR"), "/packets/incoming.rs"));
+}
+
+pub mod outgoing {
+    include!(concat!(env!("OUT_DIR"), "/packets/outgoing.rs"));
+}
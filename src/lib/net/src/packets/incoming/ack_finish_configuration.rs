@@ -1,10 +1,11 @@
 use crate::packets::IncomingPacket;
 use crate::{NetResult, ServerState};
-use ferrumc_macros::{packet, Event, NetDecode};
+use ferrumc_macros::{packet, Event, NetDecode, NetEncode};
+use std::io::Write;
 use std::sync::Arc;
 use ferrumc_events::infrastructure::Event;
 
-#[derive(NetDecode)]
+#[derive(NetDecode, NetEncode, Debug)]
 #[packet(packet_id = 0x03, state = "configuration")]
 pub struct AckFinishConfigurationPacket {}
 
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+use ferrumc_macros::{Event, NetDecode, NetEncode, packet};
+use std::io::Write;
+use ferrumc_events::infrastructure::Event;
+use crate::packets::IncomingPacket;
+use crate::{NetResult, ServerState};
+
+/// The answer to a [StartConfigurationPacket](crate::packets::generated::outgoing::StartConfigurationPacket),
+/// the client is in the configuration state from now on.
+#[derive(Debug, NetDecode, NetEncode)]
+#[packet(packet_id = 0x0C, state = "play")]
+pub struct AcknowledgeConfigurationPacket {
+}
+
+#[derive(Event)]
+pub struct AcknowledgeConfigurationEvent {
+    pub acknowledge_configuration_packet: AcknowledgeConfigurationPacket,
+    pub conn_id: usize,
+}
+
+impl IncomingPacket for AcknowledgeConfigurationPacket {
+    async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
+        AcknowledgeConfigurationEvent::trigger(AcknowledgeConfigurationEvent::new(self, conn_id), state).await?;
+        Ok(())
+    }
+}
+
+impl AcknowledgeConfigurationEvent {
+    pub fn new(acknowledge_configuration_packet: AcknowledgeConfigurationPacket, conn_id: usize) -> Self {
+        Self {
+            acknowledge_configuration_packet,
+            conn_id,
+        }
+    }
+}
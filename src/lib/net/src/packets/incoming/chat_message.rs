@@ -1,11 +1,13 @@
 use crate::packets::IncomingPacket;
 use crate::{NetResult, ServerState};
 use std::sync::Arc;
-use ferrumc_macros::{packet, NetDecode, Event};
-use ferrumc_net_codec::{
-    decode::{NetDecode, NetDecodeResult, NetDecodeOpts},
-    net_types::var_int::VarInt
+use ferrumc_macros::{packet, NetDecode, NetEncode, Event};
+use ferrumc_net_codec::net_types::{
+    bit_set::FixedBitSet,
+    prefixed_optional::PrefixedOptional,
+    var_int::VarInt
 };
+use std::io::Write;
 use ferrumc_events::infrastructure::Event;
 
 #[derive(Event)]
@@ -14,30 +16,16 @@ pub struct PlayerAsyncChatEvent {
     pub message: ChatMessagePacket,
 }
 
-// Custom type because not all options have a boolean value before it to know if it exists or not
-#[derive(Debug)]
-pub struct Signature(pub Option<[u8; 256]>);
-
-impl NetDecode for Signature {
-    fn decode<R: std::io::Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
-        if bool::decode(reader, opts)? {
-            Ok(Signature(Some(<[u8; 256]>::decode(reader, opts)?)))
-        } else {
-            Ok(Signature(None))
-        }
-    }
-}
-
-#[derive(NetDecode, Debug)]
+#[derive(NetDecode, NetEncode, Debug)]
 #[packet(packet_id = 0x06, state = "play")]
 pub struct ChatMessagePacket {
     pub message: String,
     // don't know what to do with most of this
     pub timestamp: i64,
     pub salt: i64,
-    pub signature: Signature,
+    pub signature: PrefixedOptional<[u8; 256]>,
     pub message_count: VarInt,
-    pub acknowledged: [u8; 3], // ceil(20 / 8)
+    pub acknowledged: FixedBitSet<20>,
 }
 
 impl IncomingPacket for ChatMessagePacket {
@@ -0,0 +1,23 @@
+use crate::packets::IncomingPacket;
+use crate::utils::chunk_sender::ChunkSender;
+use crate::{NetResult, ServerState};
+use ferrumc_macros::{packet, NetDecode, NetEncode};
+use std::io::Write;
+use std::sync::Arc;
+
+/// How many chunks per tick the client wants to receive.
+#[derive(NetDecode, NetEncode, Debug)]
+#[packet(packet_id = 0x08, state = "play")]
+pub struct ChunkBatchReceivedPacket {
+    pub chunks_per_tick: f32,
+}
+
+impl IncomingPacket for ChunkBatchReceivedPacket {
+    async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
+        if let Ok(mut chunk_sender) = state.universe.get_mut::<ChunkSender>(conn_id) {
+            chunk_sender.batch_received(self.chunks_per_tick);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use ferrumc_events::infrastructure::Event;
+use ferrumc_macros::{packet, Event, NetDecode, NetEncode};
+use std::io::Write;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use crate::packets::IncomingPacket;
+use crate::{NetResult, ServerState};
+
+#[derive(NetDecode, NetEncode, Debug)]
+#[packet(packet_id = 0x00, state = "play")]
+pub struct ConfirmTeleportationPacket {
+    pub teleport_id: VarInt,
+}
+
+impl IncomingPacket for ConfirmTeleportationPacket {
+    async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
+        let event = ConfirmTeleportationEvent::new(self, conn_id);
+        tokio::spawn(ConfirmTeleportationEvent::trigger(event, state));
+
+        Ok(())
+    }
+}
+
+#[derive(Event)]
+pub struct ConfirmTeleportationEvent {
+    pub data: ConfirmTeleportationPacket,
+    pub conn_id: usize,
+}
+
+impl ConfirmTeleportationEvent {
+    pub fn new(data: ConfirmTeleportationPacket, conn_id: usize) -> Self {
+        Self {
+            data,
+            conn_id
+        }
+    }
+}
@@ -1,12 +1,13 @@
 use crate::packets::IncomingPacket;
 use crate::{NetResult, ServerState};
 use ferrumc_events::infrastructure::Event;
-use ferrumc_macros::{packet, Event, NetDecode};
+use ferrumc_macros::{packet, Event, NetDecode, NetEncode};
+use std::io::Write;
 use ferrumc_net_codec::net_types::var_int::VarInt;
 use std::sync::Arc;
 use tracing::trace;
 
-#[derive(NetDecode, Debug)]
+#[derive(NetDecode, NetEncode, Debug)]
 #[packet(packet_id = 0x00, state = "handshake")]
 pub struct Handshake {
     pub protocol_version: VarInt,
@@ -1,10 +1,11 @@
 use std::sync::Arc;
-use ferrumc_macros::{Event, NetDecode, packet};
+use ferrumc_macros::{Event, NetDecode, NetEncode, packet};
+use std::io::Write;
 use ferrumc_events::infrastructure::Event;
 use crate::packets::IncomingPacket;
 use crate::{NetResult, ServerState};
 
-#[derive(Debug, NetDecode)]
+#[derive(Debug, NetDecode, NetEncode)]
 #[packet(packet_id = 0x03, state = "login")]
 pub struct LoginAcknowledgedPacket {
 }
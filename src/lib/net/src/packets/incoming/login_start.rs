@@ -1,10 +1,11 @@
 use std::sync::Arc;
 use ferrumc_events::infrastructure::Event;
-use ferrumc_macros::{packet, Event, NetDecode};
+use ferrumc_macros::{packet, Event, NetDecode, NetEncode};
+use std::io::Write;
 use crate::packets::IncomingPacket;
 use crate::{NetResult, ServerState};
 
-#[derive(Debug, NetDecode)]
+#[derive(Debug, NetDecode, NetEncode)]
 #[packet(packet_id = 0x00, state = "login")]
 pub struct LoginStartPacket {
     pub username: String,
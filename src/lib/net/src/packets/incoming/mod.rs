@@ -7,7 +7,14 @@ pub mod ping;
 pub mod server_bound_known_packs;
 pub mod server_bound_plugin_message;
 pub mod set_player_position;
+pub mod set_player_position_and_rotation;
 pub mod status_request;
 pub mod chat_message;
+pub mod confirm_teleportation;
+pub mod player_abilities;
+pub mod player_command;
+pub mod server_bound_keep_alive;
+pub mod acknowledge_configuration;
+pub mod chunk_batch_received;
 
 pub mod packet_skeleton;
@@ -5,6 +5,12 @@ use std::io::Cursor;
 use std::{fmt::Debug, io::Read};
 use tokio::io::{AsyncRead, AsyncReadExt};
 
+/// The maximum length of a packet, vanilla doesn't allow packets longer than a 3 byte VarInt.
+pub const MAX_PACKET_LENGTH: usize = 2_097_151;
+
+/// The maximum length of a decompressed packet, the same limit vanilla uses.
+pub const MAX_DECOMPRESSED_LENGTH: usize = 8_388_608;
+
 pub struct PacketSkeleton {
     pub length: usize,
     pub id: u8,
@@ -28,15 +34,30 @@ impl PacketSkeleton {
         }
     }
 
+    /// Reads a length prefix and makes sure it's a valid packet length.
+    async fn read_length<R: AsyncRead + Unpin>(reader: &mut R, max: usize) -> NetResult<usize> {
+        let length = VarInt::read_async(reader).await?.val;
+        let Ok(length) = usize::try_from(length) else {
+            return Err(NetDecodeError::InvalidLength(length).into());
+        };
+
+        if length > max {
+            return Err(NetDecodeError::PacketTooLarge(length, max).into());
+        }
+
+        Ok(length)
+    }
+
+    async fn read_exact<R: AsyncRead + Unpin>(reader: &mut R, length: usize) -> NetResult<Vec<u8>> {
+        let mut buf = vec![0; length];
+        reader.read_exact(&mut buf).await?;
+        Ok(buf)
+    }
+
     #[inline(always)]
     async fn read_uncompressed<R: AsyncRead + Unpin>(reader: &mut R) -> NetResult<Self> {
-        let length = VarInt::read_async(reader).await?.val as usize;
-        let mut buf = {
-            let mut buf = vec![0; length];
-            reader.read_exact(&mut buf).await?;
-
-            Cursor::new(buf)
-        };
+        let length = Self::read_length(reader, MAX_PACKET_LENGTH).await?;
+        let mut buf = Cursor::new(Self::read_exact(reader, length).await?);
 
         let id = VarInt::read(&mut buf)?;
 
@@ -49,17 +70,20 @@ impl PacketSkeleton {
 
     #[inline(always)]
     async fn read_compressed<R: AsyncRead + Unpin>(reader: &mut R) -> NetResult<Self> {
-        let packet_length = VarInt::read_async(reader).await?.val as usize;
-        let data_length = VarInt::read_async(reader).await?.val as usize;
+        let packet_length = Self::read_length(reader, MAX_PACKET_LENGTH).await?;
+        let data_length = VarInt::read_async(reader).await?;
+
+        // The packet length includes the data length VarInt
+        let Some(remaining) = packet_length.checked_sub(data_length.len) else {
+            return Err(NetDecodeError::InvalidLength(packet_length as i32).into());
+        };
+        let Ok(data_length) = usize::try_from(data_length.val) else {
+            return Err(NetDecodeError::InvalidLength(data_length.val).into());
+        };
 
         // Uncompressed packet when data length is 0
         if data_length == 0 {
-            let mut buf = {
-                let mut buf = vec![0; packet_length];
-                reader.read_exact(&mut buf).await?;
-
-                Cursor::new(buf)
-            };
+            let mut buf = Cursor::new(Self::read_exact(reader, remaining).await?);
 
             let id = VarInt::read(&mut buf)?;
 
@@ -83,20 +107,28 @@ impl PacketSkeleton {
             ));
         }
 
-        // Here, guaranteed that data_length >= compression_threshold
-        let mut buf = {
-            let mut buf = vec![];
-            reader.read_to_end(&mut buf).await?;
+        if data_length > MAX_DECOMPRESSED_LENGTH {
+            return Err(NetDecodeError::PacketTooLarge(data_length, MAX_DECOMPRESSED_LENGTH).into());
+        }
 
-            Cursor::new(buf)
-        };
+        // Here, guaranteed that data_length >= compression_threshold
+        let mut buf = Cursor::new(Self::read_exact(reader, remaining).await?);
 
-        // Decompress data
-        let mut decompressed = Vec::new();
+        // Decompress data, reading at most one byte more than expected so zip bombs are caught
+        let mut decompressed = Vec::with_capacity(data_length);
         {
             // Scope for decoder
-            let mut decoder = flate2::read::ZlibDecoder::new(&mut buf);
-            decoder.read_to_end(&mut decompressed)?;
+            let decoder = flate2::read::ZlibDecoder::new(&mut buf);
+            decoder
+                .take(data_length as u64 + 1)
+                .read_to_end(&mut decompressed)?;
+        }
+
+        if decompressed.len() != data_length {
+            return Err(NetDecodeError::DecompressedSizeMismatch {
+                expected: data_length,
+                actual: decompressed.len(),
+            }.into());
         }
 
         let mut buf = Cursor::new(decompressed);
@@ -110,3 +142,77 @@ impl PacketSkeleton {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[tokio::test]
+    async fn rejects_invalid_lengths() {
+        // i32::MAX
+        let mut data: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0x07];
+        assert!(matches!(
+            PacketSkeleton::new(&mut data, false).await,
+            Err(NetError::DecoderError(NetDecodeError::PacketTooLarge(..)))
+        ));
+
+        // -1
+        let mut data: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F];
+        assert!(matches!(
+            PacketSkeleton::new(&mut data, false).await,
+            Err(NetError::DecoderError(NetDecodeError::InvalidLength(-1)))
+        ));
+
+        // Packet length 0 doesn't leave room for the data length
+        let mut data: &[u8] = &[0x00, 0x00];
+        assert!(PacketSkeleton::new(&mut data, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn compressed_packets_stop_at_packet_length() {
+        let payload = vec![0x01; 1024];
+
+        let mut compressed = Vec::new();
+        {
+            let mut encoder = flate2::write::ZlibEncoder::new(&mut compressed, flate2::Compression::default());
+            encoder.write_all(&[0x42]).unwrap();
+            encoder.write_all(&payload).unwrap();
+        }
+
+        let mut frame = Vec::new();
+        let data_length = VarInt::new(1 + payload.len() as i32);
+        VarInt::new((data_length.len + compressed.len()) as i32).write(&mut frame).unwrap();
+        data_length.write(&mut frame).unwrap();
+        frame.extend_from_slice(&compressed);
+        // The next packet must not be consumed
+        frame.extend_from_slice(&[0x01, 0x00]);
+
+        let mut reader = frame.as_slice();
+        let packet = PacketSkeleton::new(&mut reader, true).await.unwrap();
+        assert_eq!(packet.id, 0x42);
+        assert_eq!(&packet.data.get_ref()[packet.data.position() as usize..], payload.as_slice());
+        assert_eq!(reader, &[0x01, 0x00]);
+    }
+
+    #[tokio::test]
+    async fn rejects_decompression_bombs() {
+        let mut compressed = Vec::new();
+        {
+            let mut encoder = flate2::write::ZlibEncoder::new(&mut compressed, flate2::Compression::best());
+            encoder.write_all(&vec![0; 1 << 20]).unwrap();
+        }
+
+        let mut frame = Vec::new();
+        // Claim the packet is a lot smaller than it actually is
+        let data_length = VarInt::new(1024);
+        VarInt::new((data_length.len + compressed.len()) as i32).write(&mut frame).unwrap();
+        data_length.write(&mut frame).unwrap();
+        frame.extend_from_slice(&compressed);
+
+        assert!(matches!(
+            PacketSkeleton::new(&mut frame.as_slice(), true).await,
+            Err(NetError::DecoderError(NetDecodeError::DecompressedSizeMismatch { expected: 1024, .. }))
+        ));
+    }
+}
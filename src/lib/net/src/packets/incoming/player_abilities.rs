@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use ferrumc_core::transform::movement::MovementAbilities;
+use ferrumc_macros::{packet, NetDecode};
+use crate::packets::IncomingPacket;
+use crate::{NetResult, ServerState};
+
+const FLYING_FLAG: u8 = 0x02;
+
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x23, state = "play")]
+pub struct PlayerAbilitiesPacket {
+    pub flags: u8,
+}
+
+impl IncomingPacket for PlayerAbilitiesPacket {
+    async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
+        if let Ok(mut abilities) = state.universe.get_mut::<MovementAbilities>(conn_id) {
+            // Clients can claim to fly whenever they want, only trust them if they're allowed to.
+            abilities.flying = abilities.may_fly && self.flags & FLYING_FLAG != 0;
+        }
+
+        Ok(())
+    }
+}
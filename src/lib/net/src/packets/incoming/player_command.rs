@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use ferrumc_core::transform::movement::MovementAbilities;
+use ferrumc_macros::{packet, NetDecode};
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use crate::packets::IncomingPacket;
+use crate::{NetResult, ServerState};
+
+const START_FLYING_WITH_ELYTRA: i32 = 8;
+
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x25, state = "play")]
+pub struct PlayerCommandPacket {
+    pub entity_id: VarInt,
+    pub action_id: VarInt,
+    pub jump_boost: VarInt,
+}
+
+impl IncomingPacket for PlayerCommandPacket {
+    async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
+        if self.action_id.val == START_FLYING_WITH_ELYTRA {
+            if let Ok(mut abilities) = state.universe.get_mut::<MovementAbilities>(conn_id) {
+                abilities.gliding = true;
+            }
+        }
+
+        Ok(())
+    }
+}
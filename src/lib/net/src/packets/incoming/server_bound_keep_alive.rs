@@ -0,0 +1,26 @@
+use std::sync::Arc;
+use ferrumc_macros::{packet, NetDecode, NetEncode};
+use std::io::Write;
+use tracing::debug;
+use crate::packets::IncomingPacket;
+use crate::packets::outgoing::keep_alive::KeepAlive;
+use crate::{NetResult, ServerState};
+
+#[derive(NetDecode, NetEncode, Debug)]
+#[packet(packet_id = 0x18, state = "play")]
+pub struct ServerBoundKeepAlive {
+    pub id: i64,
+}
+
+impl IncomingPacket for ServerBoundKeepAlive {
+    async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
+        // The last keep alive sent to the player, it's only added after the packet is sent.
+        if let Ok(expected) = state.universe.get::<KeepAlive>(conn_id) {
+            if expected.id != self.id {
+                debug!("Player {} answered keep alive {} but {} was expected", conn_id, self.id, expected.id);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,24 +1,24 @@
 use std::sync::Arc;
 use tracing::debug;
 use ferrumc_events::infrastructure::Event;
-use ferrumc_macros::{packet, Event, NetDecode};
+use ferrumc_macros::{packet, Event, NetDecode, NetEncode};
 use ferrumc_net_codec::net_types::length_prefixed_vec::LengthPrefixedVec;
 use crate::packets::IncomingPacket;
 use crate::{NetResult, ServerState};
+use std::io::Write;
 
-#[derive(Debug, NetDecode)]
+#[derive(Debug, NetDecode, NetEncode)]
 #[packet(packet_id = 0x07, state = "configuration")]
 pub struct ServerBoundKnownPacks {
     #[allow(dead_code)]
     pub packs: LengthPrefixedVec<PackOwned>
 }
 
-#[derive(Debug, NetDecode)]
-#[allow(dead_code)]
+#[derive(Debug, NetDecode, NetEncode)]
 pub struct PackOwned {
-    namespace: String,
-    id: String,
-    version: String
+    pub namespace: String,
+    pub id: String,
+    pub version: String
 }
 
 impl IncomingPacket for ServerBoundKnownPacks {
@@ -1,10 +1,11 @@
-use std::io::Read;
+use std::io::Write;
 use std::sync::Arc;
-use tracing::trace;
-use ferrumc_macros::{packet, Event};
-use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts, NetDecodeResult};
+use tracing::{debug, trace};
+use ferrumc_config::statics::get_global_config;
+use ferrumc_macros::{packet, Event, NetDecode, NetEncode};
 use ferrumc_net_codec::net_types::var_int::VarInt;
 use crate::packets::IncomingPacket;
+use crate::packets::outgoing::tick_times::{TickTimesSubscriber, TICK_TIMES_CHANNEL};
 use crate::{NetResult, ServerState};
 use std::fmt::Debug;
 use ferrumc_events::infrastructure::Event;
@@ -19,36 +20,39 @@ pub struct LoginPluginResponseEvent {
     pub packet: LoginPluginResponse,
 }
 
-#[derive(Debug)]
+#[derive(Debug, NetEncode, NetDecode)]
 #[packet(packet_id = 0x02, state = "configuration")]
 pub struct ServerBoundPluginMessage {
     pub channel: String,
+    #[net(rest_of_packet)]
     pub data: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, NetDecode)]
 #[packet(packet_id = 0x02, state = "login")]
 pub struct LoginPluginResponse {
     pub message_id: VarInt,
     pub success: bool,
+    /// Empty if the client didn't understand the request.
+    #[net(rest_of_packet)]
     pub data: Vec<u8>,
 }
 
-pub struct ClientMinecraftBrand {
-    pub brand: String
+impl ServerBoundPluginMessage {
+    /// The channel names of a `minecraft:register` message, which are separated by null bytes.
+    ///
+    /// Names that aren't valid UTF-8 are skipped, a client registering a channel the server
+    /// doesn't know about isn't an error.
+    pub fn registered_channels(&self) -> impl Iterator<Item = &str> {
+        self.data
+            .split(|&byte| byte == 0)
+            .filter_map(|channel| std::str::from_utf8(channel).ok())
+            .filter(|channel| !channel.is_empty())
+    }
 }
 
-impl NetDecode for ServerBoundPluginMessage {
-    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
-        let channel = <String>::decode(reader, opts)?;
-        let mut buf = Vec::<u8>::new();
-        reader.read_to_end(&mut buf)?;
-
-        Ok(Self {
-            channel,
-            data: buf
-        })
-    }
+pub struct ClientMinecraftBrand {
+    pub brand: String
 }
 
 impl IncomingPacket for ServerBoundPluginMessage {
@@ -60,30 +64,20 @@ impl IncomingPacket for ServerBoundPluginMessage {
             trace!("Client brand: {}", brand);
             
             state.universe.add_component(conn_id, ClientMinecraftBrand { brand })?;
+        } else if self.channel == "minecraft:register"
+            && self.registered_channels().any(|channel| channel == TICK_TIMES_CHANNEL)
+        {
+            if get_global_config().debug.tick_times {
+                state.universe.add_component(conn_id, TickTimesSubscriber)?;
+            } else {
+                debug!("Entity {} registered {} but tick times are disabled", conn_id, TICK_TIMES_CHANNEL);
+            }
         }
 
         Ok(())
     }
 }
 
-impl NetDecode for LoginPluginResponse {
-    fn decode<R: Read>(reader: &mut R, opts: &NetDecodeOpts) -> NetDecodeResult<Self> {
-        let message_id = <VarInt>::decode(reader, opts)?; 
-        let success = <bool>::decode(reader, opts)?;
-
-        let mut buf = Vec::<u8>::new();
-        if success {
-            reader.read_to_end(&mut buf)?;
-        }
-
-        Ok(Self {
-            message_id,
-            success,
-            data: buf
-        })
-    }
-}
-
 impl IncomingPacket for LoginPluginResponse {
     async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
         //trace!("Received login plugin message: {:?}", self);
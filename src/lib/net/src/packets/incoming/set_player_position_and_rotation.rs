@@ -0,0 +1,34 @@
+use std::sync::Arc;
+use ferrumc_events::infrastructure::Event;
+use ferrumc_macros::{packet, NetDecode, NetEncode};
+use std::io::Write;
+use crate::packets::incoming::set_player_position::{SetPlayerPositionEvent, SetPlayerPositionPacket};
+use crate::packets::IncomingPacket;
+use crate::{NetResult, ServerState};
+
+#[derive(NetDecode, NetEncode, Debug)]
+#[packet(packet_id = 0x1B, state = "play")]
+pub struct SetPlayerPositionAndRotationPacket {
+    pub x: f64,
+    pub feet_y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool
+}
+
+impl IncomingPacket for SetPlayerPositionAndRotationPacket {
+    /// The position is validated and applied the same way as a [SetPlayerPositionPacket].
+    async fn handle(self, conn_id: usize, state: Arc<ServerState>) -> NetResult<()> {
+        let position = SetPlayerPositionPacket {
+            x: self.x,
+            feet_y: self.feet_y,
+            z: self.z,
+            on_ground: self.on_ground,
+        };
+        let event = SetPlayerPositionEvent::new(position, conn_id);
+        tokio::spawn(SetPlayerPositionEvent::trigger(event, state));
+
+        Ok(())
+    }
+}
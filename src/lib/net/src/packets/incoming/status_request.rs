@@ -1,4 +1,5 @@
 use crate::connection::StreamWriter;
+use crate::packets::generated::{MINECRAFT_VERSION, PROTOCOL_VERSION};
 use crate::packets::outgoing::status_response::StatusResponse;
 use crate::packets::IncomingPacket;
 use crate::{NetResult, ServerState};
@@ -68,8 +69,8 @@ fn get_server_status() -> String {
     let config = get_global_config();
 
     let version = structs::Version {
-        name: "1.21.1",
-        protocol: 767,
+        name: MINECRAFT_VERSION,
+        protocol: PROTOCOL_VERSION as u16,
     };
 
 
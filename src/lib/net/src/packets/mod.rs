@@ -1,9 +1,28 @@
+use crate::connection::ConnectionState;
 use crate::NetResult;
+use ferrumc_net_codec::encode::NetEncode;
 
+pub mod generated;
 pub mod incoming;
 pub mod outgoing;
 
 #[allow(async_fn_in_trait)]
 pub trait IncomingPacket {
     async fn handle(self, conn_id: usize, state: std::sync::Arc<crate::ServerState>) -> NetResult<()>;
-}
\ No newline at end of file
+}
+
+/// A clientbound packet, implemented by `build.rs` for every clientbound packet in the protocol spec.
+pub trait OutgoingPacket: NetEncode {
+    const NAME: &'static str;
+    const ID: u8;
+    /// The only state this packet can be sent in.
+    const STATE: ConnectionState;
+}
+
+/// A packet in the protocol spec, see [generated::CLIENTBOUND_PACKETS].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketInfo {
+    pub name: &'static str,
+    pub id: u8,
+    pub state: ConnectionState,
+}
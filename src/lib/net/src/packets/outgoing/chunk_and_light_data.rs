@@ -0,0 +1,218 @@
+use ferrumc_macros::{packet, NetEncode};
+use ferrumc_nbt::Nbt;
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts, NetEncodeResult};
+use ferrumc_net_codec::net_types::bit_set::BitSet;
+use ferrumc_net_codec::net_types::length_prefixed_vec::LengthPrefixedVec;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use ferrumc_general_purpose::simd::packed;
+use ferrumc_world::global_palette::DIRECT_BLOCK_BITS;
+use ferrumc_world::{BlockState, Chunk, GlobalPalette, Heightmaps, LightArray, Palette, PaletteKind, PalettedContainer};
+use std::io::Write;
+
+/// Every player is in the overworld, which has 24 sections from y -64 to 320.
+pub const SECTION_COUNT: i32 = 24;
+
+/// The most bits per entry a block state palette is sent with before it's sent as direct.
+const MAX_INDIRECT_BLOCK_BITS: u8 = 8;
+/// The most bits per entry a biome palette is sent with before it's sent as direct.
+const MAX_INDIRECT_BIOME_BITS: u8 = 3;
+
+#[derive(NetEncode)]
+#[packet(packet_id = 0x27, state = "play")]
+pub struct ChunkDataAndUpdateLightPacket {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub heightmaps: Nbt<Heightmaps>,
+    /// The sections, bottom up.
+    #[net(length_prefixed)]
+    pub data: Vec<u8>,
+    /// The number of block entities, which is always 0: [Chunk] doesn't hold block entities, so
+    /// chests, signs and the like are sent without their contents.
+    pub block_entity_count: VarInt,
+    pub light: LightData,
+}
+
+/// The light of a chunk.
+///
+/// Bit `i` of a mask is for section `i - 1` of the world, so that the sections just below and
+/// above the world have light too. Sections in neither mask have no light data.
+#[derive(NetEncode, Default)]
+pub struct LightData {
+    pub sky_light_mask: BitSet,
+    pub block_light_mask: BitSet,
+    /// Sections whose sky light is 0 everywhere, which don't need an array.
+    pub empty_sky_light_mask: BitSet,
+    pub empty_block_light_mask: BitSet,
+    /// An array for each bit in the sky light mask, in order.
+    pub sky_light_arrays: LengthPrefixedVec<LightSection>,
+    pub block_light_arrays: LengthPrefixedVec<LightSection>,
+}
+
+#[derive(NetEncode)]
+pub struct LightSection {
+    #[net(length_prefixed)]
+    pub data: Vec<u8>,
+}
+
+impl ChunkDataAndUpdateLightPacket {
+    pub fn new(chunk: &Chunk, palette: &GlobalPalette) -> Self {
+        Self {
+            chunk_x: chunk.x,
+            chunk_z: chunk.z,
+            // The client only uses these two.
+            heightmaps: Nbt::new(Heightmaps {
+                motion_blocking: chunk.heightmaps.motion_blocking.clone(),
+                world_surface: chunk.heightmaps.world_surface.clone(),
+                ..Heightmaps::default()
+            }),
+            data: encode_sections(chunk, palette),
+            block_entity_count: VarInt::new(0),
+            light: LightData::new(chunk),
+        }
+    }
+}
+
+impl LightData {
+    pub fn new(chunk: &Chunk) -> Self {
+        let mut light = Self::default();
+
+        for index in 0..SECTION_COUNT + 2 {
+            let y = chunk.min_section - 1 + index;
+            let Some(section) = chunk.sections.iter().find(|section| i32::from(section.y) == y) else {
+                continue;
+            };
+
+            let index = index as usize;
+            add_section(
+                &section.sky_light,
+                index,
+                &mut light.sky_light_mask,
+                &mut light.empty_sky_light_mask,
+                &mut light.sky_light_arrays,
+            );
+            add_section(
+                &section.block_light,
+                index,
+                &mut light.block_light_mask,
+                &mut light.empty_block_light_mask,
+                &mut light.block_light_arrays,
+            );
+        }
+
+        light
+    }
+}
+
+fn add_section(
+    array: &Option<LightArray>,
+    index: usize,
+    mask: &mut BitSet,
+    empty_mask: &mut BitSet,
+    arrays: &mut LengthPrefixedVec<LightSection>,
+) {
+    match array {
+        Some(array) if array.is_empty() => empty_mask.set(index, true),
+        Some(array) => {
+            mask.set(index, true);
+            arrays.push(LightSection {
+                data: array.as_bytes().to_vec(),
+            });
+        }
+        None => {}
+    }
+}
+
+/// Every section of the world, with missing ones sent as air.
+fn encode_sections(chunk: &Chunk, palette: &GlobalPalette) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_sections(&mut buf, chunk, palette).expect("Writing to a Vec can't fail");
+    buf
+}
+
+fn write_sections(buf: &mut Vec<u8>, chunk: &Chunk, palette: &GlobalPalette) -> NetEncodeResult<()> {
+    let air = PalettedContainer::new(BlockState::air());
+
+    for y in chunk.min_section..chunk.min_section + SECTION_COUNT {
+        let section = chunk.sections.iter().find(|section| i32::from(section.y) == y);
+        let block_states = section.and_then(|section| section.block_states.as_ref()).unwrap_or(&air);
+
+        (block_count(block_states) as i16).encode(buf, &NetEncodeOpts::None)?;
+        write_container(buf, block_states, MAX_INDIRECT_BLOCK_BITS, DIRECT_BLOCK_BITS, |block| {
+            palette.block_id(block)
+        })?;
+
+        match section.and_then(|section| section.biomes.as_ref()) {
+            Some(biomes) => write_container(
+                buf,
+                biomes,
+                MAX_INDIRECT_BIOME_BITS,
+                palette.direct_biome_bits(),
+                |biome| palette.biome_id(biome),
+            )?,
+            None => write_single(buf, 0)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// The blocks in a section that aren't air, which the client uses to skip empty sections.
+fn block_count(block_states: &PalettedContainer<BlockState>) -> usize {
+    match block_states.palette() {
+        Palette::Single(block) if block.is_air() => 0,
+        Palette::Single(_) => BlockState::ENTRIES,
+        Palette::Indirect(palette) => block_states
+            .ids()
+            .into_iter()
+            .filter(|id| !palette.get(*id as usize).is_some_and(BlockState::is_air))
+            .count(),
+        // Only air is known to be 0.
+        Palette::Direct => block_states.ids().into_iter().filter(|id| *id != 0).count(),
+    }
+}
+
+/// Writes a container with global ids, as indirect if it fits in `max_indirect_bits` and as
+/// direct with `direct_bits` otherwise.
+fn write_container<T: PaletteKind>(
+    buf: &mut Vec<u8>,
+    container: &PalettedContainer<T>,
+    max_indirect_bits: u8,
+    direct_bits: u8,
+    global_id: impl Fn(&T) -> u32,
+) -> NetEncodeResult<()> {
+    let opts = &NetEncodeOpts::None;
+
+    match container.palette() {
+        Palette::Single(value) => write_single(buf, global_id(value)),
+        // Anvil and the network pack indices the same way, so the data can be sent as is.
+        Palette::Indirect(palette) if container.bits_per_entry() <= max_indirect_bits => {
+            container.bits_per_entry().encode(buf, opts)?;
+            let ids = palette.iter().map(|value| VarInt::from(global_id(value) as i32)).collect();
+            LengthPrefixedVec::new(ids).encode(buf, opts)?;
+            LengthPrefixedVec::new(container.data().to_vec()).encode(buf, opts)
+        }
+        Palette::Indirect(palette) => {
+            let ids = container
+                .ids()
+                .into_iter()
+                .map(|id| palette.get(id as usize).map(&global_id).unwrap_or(0))
+                .collect::<Vec<_>>();
+            write_direct(buf, &ids, direct_bits)
+        }
+        Palette::Direct => write_direct(buf, &container.ids(), direct_bits),
+    }
+}
+
+fn write_single(buf: &mut Vec<u8>, id: u32) -> NetEncodeResult<()> {
+    let opts = &NetEncodeOpts::None;
+    0u8.encode(buf, opts)?;
+    VarInt::from(id as i32).encode(buf, opts)?;
+    // No data
+    VarInt::from(0).encode(buf, opts)
+}
+
+fn write_direct(buf: &mut Vec<u8>, ids: &[u32], bits: u8) -> NetEncodeResult<()> {
+    let opts = &NetEncodeOpts::None;
+    bits.encode(buf, opts)?;
+    LengthPrefixedVec::new(packed::pack_longs(ids, bits)).encode(buf, opts)
+}
@@ -6,7 +6,7 @@ use ferrumc_net_codec::{
 use std::io::Write;
 
 #[derive(NetEncode)]
-#[packet(packet_id = 0x01)]
+#[packet(packet_id = 0x01, state = "configuration")]
 pub struct ConfigurationPluginMessagePacket<T>
 where
     T: NetEncode {
@@ -15,7 +15,7 @@ where
 }
 
 #[derive(NetEncode)]
-#[packet(packet_id = 0x19)]
+#[packet(packet_id = 0x19, state = "play")]
 pub struct PlayPluginMessagePacket<T>
 where
     T: NetEncode {
@@ -24,7 +24,7 @@ where
 }
 
 #[derive(NetEncode, Clone)]
-#[packet(packet_id = 0x04)]
+#[packet(packet_id = 0x04, state = "login")]
 pub struct LoginPluginMessagePacket<T>
 where
     T: NetEncode,
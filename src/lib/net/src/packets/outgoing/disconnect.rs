@@ -5,17 +5,24 @@ use ferrumc_text::*;
 #[derive(NetEncode)]
 pub enum DisconnectPacket {
     Login(LoginDisconnect),
+    Configuration(ConfigurationDisconnect),
     Play(PlayDisconnect),
 }
 
 #[derive(NetEncode)]
-#[packet(packet_id = 0x00)]
+#[packet(packet_id = 0x00, state = "login")]
 pub struct LoginDisconnect {
     pub reason: JsonTextComponent,
 }
 
 #[derive(NetEncode)]
-#[packet(packet_id = 0x1D)]
+#[packet(packet_id = 0x02, state = "configuration")]
+pub struct ConfigurationDisconnect {
+    pub reason: TextComponent,
+}
+
+#[derive(NetEncode)]
+#[packet(packet_id = 0x1D, state = "play")]
 pub struct PlayDisconnect {
     pub reason: TextComponent,
 }
@@ -28,6 +35,14 @@ impl LoginDisconnect {
     }
 }
 
+impl ConfigurationDisconnect {
+    pub fn new<C: Into<TextComponent>>(reason: C) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+}
+
 impl PlayDisconnect {
     pub fn new<C: Into<TextComponent>>(reason: C) -> Self {
         Self {
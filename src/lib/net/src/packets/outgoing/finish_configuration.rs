@@ -1,8 +1,8 @@
-use ferrumc_macros::{packet, NetEncode};
+use ferrumc_macros::{packet, NetDecode, NetEncode};
 use std::io::Write;
 
-#[derive(NetEncode)]
-#[packet(packet_id = 0x03)]
+#[derive(NetEncode, NetDecode)]
+#[packet(packet_id = 0x03, state = "configuration")]
 pub struct FinishConfigurationPacket;
 
 impl Default for FinishConfigurationPacket {
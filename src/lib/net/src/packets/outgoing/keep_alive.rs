@@ -1,7 +1,7 @@
-use ferrumc_macros::{packet, NetEncode};
+use ferrumc_macros::{packet, NetDecode, NetEncode};
 use std::io::Write;
 
-#[derive(Debug, NetEncode)]
+#[derive(Debug, NetEncode, NetDecode)]
 pub struct KeepAlive {
     pub id: i64,
 }
@@ -14,8 +14,8 @@ mod adapters {
     }
 }
 
-#[derive(NetEncode)]
-#[packet(packet_id = 0x26)]
+#[derive(NetEncode, NetDecode)]
+#[packet(packet_id = 0x26, state = "play")]
 pub struct KeepAlivePacket {
     pub id: KeepAlive,
 }
@@ -1,13 +1,24 @@
 use ferrumc_macros::{packet, NetEncode};
+use ferrumc_net_codec::net_types::identifier::Identifier;
+use ferrumc_net_codec::net_types::network_position::NetworkPosition;
+use ferrumc_net_codec::net_types::prefixed_optional::PrefixedOptional;
 use ferrumc_net_codec::net_types::var_int::VarInt;
+use crate::utils::chunk_tracker::view_distance;
 use std::io::Write;
 
+/// Where the player last died, used by recovery compasses.
 #[derive(NetEncode)]
-#[packet(packet_id = 0x2B)]
+pub struct DeathLocation {
+    pub dimension_name: Identifier,
+    pub location: NetworkPosition,
+}
+
+#[derive(NetEncode)]
+#[packet(packet_id = 0x2B, state = "play")]
 pub struct LoginPlayPacket<'a> {
     pub entity_id: i32,
     pub is_hardcore: bool,
-    pub dimension_length: VarInt,
+    #[net(length_prefixed)]
     pub dimension_names: &'a [&'a str],
     pub max_players: VarInt,
     pub view_distance: VarInt,
@@ -22,9 +33,7 @@ pub struct LoginPlayPacket<'a> {
     pub previous_gamemode: i8,
     pub is_debug: bool,
     pub is_flat: bool,
-    pub has_death_location: bool,
-    pub death_dimension_name: Option<&'a str>,
-    pub death_location: Option<u8>, // change this to actual Position. this won't work!!
+    pub death_location: PrefixedOptional<DeathLocation>,
     pub portal_cooldown: VarInt,
     pub enforces_secure_chat: bool,
 }
@@ -34,10 +43,9 @@ impl LoginPlayPacket<'_> {
         Self {
             entity_id: conn_id as i32,
             is_hardcore: false,
-            dimension_length: VarInt::from(1),
             dimension_names: &["minecraft:overworld"],
             max_players: VarInt::from(20),
-            view_distance: VarInt::from(10),
+            view_distance: VarInt::from(view_distance(None)),
             simulation_distance: VarInt::from(10),
             reduced_debug_info: false,
             enable_respawn_screen: true,
@@ -49,9 +57,7 @@ impl LoginPlayPacket<'_> {
             previous_gamemode: -1,
             is_debug: false,
             is_flat: false,
-            has_death_location: false,
-            death_dimension_name: None,
-            death_location: None,
+            death_location: PrefixedOptional::none(),
             portal_cooldown: VarInt::from(0),
             enforces_secure_chat: false,
         }
@@ -1,9 +1,9 @@
-use ferrumc_macros::{packet, NetEncode};
+use ferrumc_macros::{packet, NetDecode, NetEncode};
 use std::io::Write;
 use crate::connection::GameProfile;
 
-#[derive(NetEncode)]
-#[packet(packet_id = 0x02)]
+#[derive(NetEncode, NetDecode)]
+#[packet(packet_id = 0x02, state = "login")]
 pub struct LoginSuccessPacket {
     pub profile: GameProfile,
     pub strict_error_handling: bool,
@@ -13,3 +13,5 @@ pub mod update_time;
 pub mod client_bound_plugin_message;
 pub mod player_info_update;
 pub mod disconnect;
+pub mod tick_times;
+pub mod chunk_and_light_data;
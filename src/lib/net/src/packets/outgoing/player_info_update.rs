@@ -21,7 +21,7 @@ pub enum PlayerActions {
 }
 
 #[derive(NetEncode, Debug)]
-#[packet(packet_id = 0x3E)]
+#[packet(packet_id = 0x3E, state = "play")]
 pub struct PlayerInfoUpdatePacket {
     player_actions: PlayerActions,
     player_infos: LengthPrefixedVec<PlayerInfo>,
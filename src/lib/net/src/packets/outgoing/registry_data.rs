@@ -4,7 +4,7 @@ use ferrumc_net_codec::net_types::length_prefixed_vec::LengthPrefixedVec;
 use std::io::Write;
 
 #[derive(NetEncode)]
-#[packet(packet_id = 0x07)]
+#[packet(packet_id = 0x07, state = "configuration")]
 pub struct RegistryDataPacket<'a> {
     pub registry_id: &'a str,
     pub entries: LengthPrefixedVec<RegistryEntry<'a>>,
@@ -32,9 +32,9 @@ impl RegistryDataPacket<'_> {
         let registry_nbt_buf = include_bytes!("../../../../../../.etc/registry.nbt");
 
         let mut tape = NbtTape::new(registry_nbt_buf);
-        tape.parse();
+        tape.parse().unwrap();
         let mut serializer_machine = NbtTape::new(registry_nbt_buf);
-        serializer_machine.parse();
+        serializer_machine.parse().unwrap();
 
         let root = tape.root.as_ref().map(|(_, b)| b).unwrap();
         let root = root.as_compound().unwrap();
@@ -3,7 +3,7 @@ use ferrumc_net_codec::net_types::network_position::NetworkPosition;
 use std::io::Write;
 
 #[derive(NetEncode)]
-#[packet(packet_id = 0x56)]
+#[packet(packet_id = 0x56, state = "play")]
 pub struct SetDefaultSpawnPositionPacket {
     pub spawn_position: NetworkPosition,
     pub angle: f32,
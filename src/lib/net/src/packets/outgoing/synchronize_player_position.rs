@@ -1,10 +1,10 @@
 use crate::packets::outgoing::set_default_spawn_position::DEFAULT_SPAWN_POSITION;
-use ferrumc_macros::{packet, NetEncode};
+use ferrumc_macros::{packet, NetDecode, NetEncode};
 use ferrumc_net_codec::net_types::var_int::VarInt;
 use std::io::Write;
 
-#[derive(NetEncode)]
-#[packet(packet_id = 0x40)]
+#[derive(NetEncode, NetDecode)]
+#[packet(packet_id = 0x40, state = "play")]
 pub struct SynchronizePlayerPositionPacket {
     pub x: f64,
     pub y: f64,
@@ -0,0 +1,31 @@
+use crate::packets::outgoing::client_bound_plugin_message::PlayPluginMessagePacket;
+use ferrumc_macros::{NetDecode, NetEncode};
+use ferrumc_net_codec::net_types::length_prefixed_vec::LengthPrefixedVec;
+use std::io::Write;
+
+/// The plugin channel tick durations are sent on, players have to register it to receive them.
+pub const TICK_TIMES_CHANNEL: &str = "ferrumc:tick_times";
+
+/// How many ticks are sent in a single message.
+pub const TICK_TIMES_BATCH: usize = 20;
+
+/// The durations of the ticks since the last message, in microseconds.
+#[derive(Debug, NetEncode, NetDecode)]
+pub struct TickTimes {
+    pub samples: LengthPrefixedVec<u64>,
+}
+
+impl TickTimes {
+    pub fn new(samples: Vec<u64>) -> Self {
+        Self {
+            samples: LengthPrefixedVec::new(samples),
+        }
+    }
+
+    pub fn into_packet(self) -> PlayPluginMessagePacket<TickTimes> {
+        PlayPluginMessagePacket::new(TICK_TIMES_CHANNEL.to_string(), self)
+    }
+}
+
+/// Added to players that registered the [TICK_TIMES_CHANNEL].
+pub struct TickTimesSubscriber;
@@ -3,7 +3,7 @@ use ferrumc_macros::{packet, NetEncode};
 use std::io::Write;
 
 #[derive(NetEncode)]
-#[packet(packet_id = 0x64)]
+#[packet(packet_id = 0x64, state = "play")]
 pub struct UpdateTimePacket {
     pub world_age: i64,
     pub time_of_day: i64,
@@ -0,0 +1,114 @@
+//! Encoded chunk packets, shared by every player they're sent to.
+//!
+//! A chunk is generated and encoded once on the blocking thread pool, so neither happens on the
+//! tick, and every player that's sent the chunk gets the same buffer. Chunks aren't loaded from
+//! disk yet, they all come from the [FlatGenerator].
+
+use crate::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLightPacket;
+use crate::NetResult;
+use bytes::Bytes;
+use ferrumc_net_codec::encode::pool::BufferPool;
+use ferrumc_net_codec::encode::NetEncodeOpts;
+use ferrumc_world::{FlatGenerator, GlobalPalette};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::LazyLock;
+
+/// How many encoded chunks are kept, a flat chunk with its light takes up about 54 KiB.
+pub const MAX_CACHED_CHUNKS: usize = 1024;
+
+/// Encoded chunk packets by the coordinates of their chunk.
+pub type EncodedChunks = HashMap<(i32, i32), Bytes>;
+
+static GLOBAL_CACHE: LazyLock<ChunkCache> = LazyLock::new(ChunkCache::default);
+
+/// The encoded Chunk Data and Update Light packets of the most recently requested chunks.
+#[derive(Debug, Default)]
+pub struct ChunkCache {
+    chunks: Mutex<CachedChunks>,
+}
+
+#[derive(Debug, Default)]
+struct CachedChunks {
+    packets: HashMap<(i32, i32), Bytes>,
+    /// The cached chunks, oldest first.
+    order: VecDeque<(i32, i32)>,
+}
+
+impl ChunkCache {
+    /// The cache shared by the whole server.
+    pub fn global() -> &'static ChunkCache {
+        &GLOBAL_CACHE
+    }
+
+    /// The encoded packet of a chunk, if it's cached.
+    pub fn get(&self, chunk: (i32, i32)) -> Option<Bytes> {
+        self.chunks.lock().packets.get(&chunk).cloned()
+    }
+
+    /// The number of cached chunks.
+    pub fn len(&self) -> usize {
+        self.chunks.lock().packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The encoded packets of `chunks`, ready to be sent with
+    /// [send_bytes](crate::connection::StreamWriter::send_bytes).
+    ///
+    /// Chunks that aren't cached are generated and encoded together on the blocking thread pool.
+    pub async fn get_or_generate(
+        &self,
+        chunks: impl IntoIterator<Item = (i32, i32)>,
+    ) -> NetResult<EncodedChunks> {
+        let mut packets = HashMap::new();
+        let mut missing = HashSet::new();
+        {
+            let cached = self.chunks.lock();
+            for chunk in chunks {
+                match cached.packets.get(&chunk) {
+                    Some(packet) => {
+                        packets.insert(chunk, packet.clone());
+                    }
+                    None => {
+                        missing.insert(chunk);
+                    }
+                }
+            }
+        }
+        if missing.is_empty() {
+            return Ok(packets);
+        }
+
+        let generated = tokio::task::spawn_blocking(move || {
+            let generator = FlatGenerator::default();
+            let palette = GlobalPalette::get();
+            missing
+                .into_iter()
+                .map(|(x, z)| {
+                    let packet = ChunkDataAndUpdateLightPacket::new(&generator.generate(x, z), palette);
+                    let packet = BufferPool::global().encode(&packet, &NetEncodeOpts::WithLength)?;
+                    Ok(((x, z), packet))
+                })
+                .collect::<NetResult<Vec<_>>>()
+        })
+        .await??;
+
+        let mut cached = self.chunks.lock();
+        for (chunk, packet) in generated {
+            if cached.packets.insert(chunk, packet.clone()).is_none() {
+                cached.order.push_back(chunk);
+            }
+            packets.insert(chunk, packet);
+        }
+        while cached.order.len() > MAX_CACHED_CHUNKS {
+            if let Some(oldest) = cached.order.pop_front() {
+                cached.packets.remove(&oldest);
+            }
+        }
+
+        Ok(packets)
+    }
+}
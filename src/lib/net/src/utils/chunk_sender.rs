@@ -0,0 +1,130 @@
+//! Paces sending chunks to a player, like vanilla does.
+//!
+//! Chunks are sent in batches, each between a Chunk Batch Start and a Chunk Batch Finished. When
+//! the client has processed a batch it answers with how many chunks per tick it can take, and the
+//! batches that follow are sized to match.
+
+use std::collections::VecDeque;
+
+/// How many chunks per tick to send before the client has said how many it can take.
+const START_CHUNKS_PER_TICK: f32 = 9.0;
+const MIN_CHUNKS_PER_TICK: f32 = 0.01;
+const MAX_CHUNKS_PER_TICK: f32 = 64.0;
+/// How many batches may be unacknowledged once the client has acknowledged the first.
+const MAX_UNACKNOWLEDGED_BATCHES: u32 = 10;
+
+/// The chunks waiting to be sent to a player, as a component.
+#[derive(Debug, Clone)]
+pub struct ChunkSender {
+    pending: VecDeque<(i32, i32)>,
+    /// The chunks of each batch the client hasn't acknowledged yet, oldest first.
+    in_flight: VecDeque<Vec<(i32, i32)>>,
+    chunks_per_tick: f32,
+    /// Chunks that may be sent in the next batch, which builds up over the ticks when the client
+    /// takes less than a chunk per tick.
+    batch_quota: f32,
+    unacknowledged_batches: u32,
+    max_unacknowledged_batches: u32,
+}
+
+impl Default for ChunkSender {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            in_flight: VecDeque::new(),
+            chunks_per_tick: START_CHUNKS_PER_TICK,
+            batch_quota: 0.0,
+            unacknowledged_batches: 0,
+            // Until the client has told us how fast it is, only one batch at a time.
+            max_unacknowledged_batches: 1,
+        }
+    }
+}
+
+impl ChunkSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues chunks to be sent after the ones already queued.
+    pub fn queue(&mut self, chunks: impl IntoIterator<Item = (i32, i32)>) {
+        self.pending.extend(chunks);
+    }
+
+    /// Drops the queued chunks `f` returns false for, e.g. ones that went out of view.
+    pub fn retain(&mut self, f: impl FnMut(&(i32, i32)) -> bool) {
+        self.pending.retain(f);
+    }
+
+    /// Sends the queued chunks nearest to `center` first.
+    pub fn sort_nearest_first(&mut self, center: (i32, i32)) {
+        let (center_x, center_z) = center;
+        self.pending
+            .make_contiguous()
+            .sort_by_key(|(x, z)| (i64::from(*x) - i64::from(center_x)).pow(2) + (i64::from(*z) - i64::from(center_z)).pow(2));
+    }
+
+    /// The chunks that haven't been sent yet, in the order they'll be sent.
+    pub fn pending(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.pending.iter()
+    }
+
+    /// The chunks that were sent in batches the client hasn't acknowledged yet.
+    pub fn in_flight(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.in_flight.iter().flatten()
+    }
+
+    /// The chunks to send this tick, if any. Call once per tick, a non-empty batch has to be
+    /// acknowledged by the client before too many more are sent.
+    pub fn next_batch(&mut self) -> Vec<(i32, i32)> {
+        if self.unacknowledged_batches >= self.max_unacknowledged_batches {
+            return Vec::new();
+        }
+
+        self.batch_quota = (self.batch_quota + self.chunks_per_tick).min(self.chunks_per_tick.max(1.0));
+        if self.batch_quota < 1.0 || self.pending.is_empty() {
+            return Vec::new();
+        }
+
+        let count = (self.batch_quota as usize).min(self.pending.len());
+        self.batch_quota -= count as f32;
+        self.unacknowledged_batches += 1;
+        let batch = self.pending.drain(..count).collect::<Vec<_>>();
+        self.in_flight.push_back(batch.clone());
+        batch
+    }
+
+    /// The client has processed a batch and can take `chunks_per_tick` chunks per tick.
+    pub fn batch_received(&mut self, chunks_per_tick: f32) {
+        self.unacknowledged_batches = self.unacknowledged_batches.saturating_sub(1);
+        self.in_flight.pop_front();
+        self.chunks_per_tick = if chunks_per_tick.is_nan() {
+            MIN_CHUNKS_PER_TICK
+        } else {
+            chunks_per_tick.clamp(MIN_CHUNKS_PER_TICK, MAX_CHUNKS_PER_TICK)
+        };
+        if self.unacknowledged_batches == 0 {
+            self.batch_quota = 1.0;
+        }
+        self.max_unacknowledged_batches = MAX_UNACKNOWLEDGED_BATCHES;
+    }
+
+    pub fn chunks_per_tick(&self) -> f32 {
+        self.chunks_per_tick
+    }
+}
+
+/// The chunks within `radius` chunks of `center`, nearest first.
+pub fn chunks_around(center: (i32, i32), radius: i32) -> Vec<(i32, i32)> {
+    let (center_x, center_z) = center;
+    let mut chunks = (-radius..=radius)
+        .flat_map(|dx| (-radius..=radius).map(move |dz| (dx, dz)))
+        .filter(|(dx, dz)| dx * dx + dz * dz <= radius * radius)
+        .collect::<Vec<_>>();
+    chunks.sort_by_key(|(dx, dz)| dx * dx + dz * dz);
+
+    chunks
+        .into_iter()
+        .map(|(dx, dz)| (center_x + dx, center_z + dz))
+        .collect()
+}
@@ -0,0 +1,92 @@
+//! Keeps track of the chunks a player can see, which follow them as they move.
+
+use crate::utils::chunk_sender::chunks_around;
+use ferrumc_config::statics::get_global_config;
+use std::collections::HashSet;
+
+/// The smallest view distance the client supports.
+const MIN_VIEW_DISTANCE: u8 = 2;
+/// The largest view distance the client supports.
+const MAX_VIEW_DISTANCE: u8 = 32;
+
+/// The view distance of a player, the smaller of theirs and the server's.
+///
+/// `client_view_distance` is from their client information, if they sent it.
+pub fn view_distance(client_view_distance: Option<u8>) -> i32 {
+    let server_view_distance = get_global_config().view_distance;
+    let view_distance = client_view_distance.map_or(server_view_distance, |client| client.min(server_view_distance));
+    i32::from(view_distance.clamp(MIN_VIEW_DISTANCE, MAX_VIEW_DISTANCE))
+}
+
+/// The chunks a player has been sent or has queued, as a component.
+#[derive(Debug, Clone)]
+pub struct ChunkTracker {
+    center: (i32, i32),
+    view_distance: i32,
+    chunks: HashSet<(i32, i32)>,
+}
+
+/// The chunks that came into and went out of view when a player moved.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChunkChanges {
+    /// Nearest first
+    pub load: Vec<(i32, i32)>,
+    pub unload: Vec<(i32, i32)>,
+}
+
+impl ChunkTracker {
+    /// Tracks every chunk within `view_distance` of `center`.
+    pub fn new(center: (i32, i32), view_distance: i32) -> Self {
+        Self {
+            center,
+            view_distance,
+            chunks: chunks_around(center, view_distance).into_iter().collect(),
+        }
+    }
+
+    pub fn center(&self) -> (i32, i32) {
+        self.center
+    }
+
+    pub fn view_distance(&self) -> i32 {
+        self.view_distance
+    }
+
+    pub fn contains(&self, chunk: &(i32, i32)) -> bool {
+        self.chunks.contains(chunk)
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.chunks.iter()
+    }
+
+    /// Moves the view to `center` with `view_distance`.
+    ///
+    /// Returns [None] if neither changed, the player is still in the same chunk.
+    pub fn update(&mut self, center: (i32, i32), view_distance: i32) -> Option<ChunkChanges> {
+        if center == self.center && view_distance == self.view_distance {
+            return None;
+        }
+
+        let visible = chunks_around(center, view_distance);
+        let load = visible
+            .iter()
+            .filter(|chunk| !self.chunks.contains(chunk))
+            .copied()
+            .collect();
+
+        let visible = visible.into_iter().collect::<HashSet<_>>();
+        let unload = self
+            .chunks
+            .iter()
+            .filter(|chunk| !visible.contains(chunk))
+            .copied()
+            .collect();
+
+        self.center = center;
+        self.view_distance = view_distance;
+        self.chunks = visible;
+
+        Some(ChunkChanges { load, unload })
+    }
+}
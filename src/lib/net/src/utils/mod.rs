@@ -1 +1,4 @@
-pub mod ecs_helpers;
\ No newline at end of file
+pub mod chunk_cache;
+pub mod chunk_sender;
+pub mod chunk_tracker;
+pub mod ecs_helpers;
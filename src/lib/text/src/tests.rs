@@ -163,3 +163,24 @@ async fn test_serialize_to_nbt() {
     println!("id: {}, length: {}, left: {}", id.val, length.val, length.val as u64 - cursor.position());
     println!("{}", bytes_to_readable_string(&cursor.get_ref()[cursor.position() as usize..]));
 }
+
+#[test]
+fn test_serde_nbt() {
+    let component = ComponentBuilder::text("Press ")
+        .extra(ComponentBuilder::keybind("key.jump").underlined())
+        .extra(ComponentBuilder::translate("chat.type.text", vec![
+            ComponentBuilder::text("ferrumc").bold().build(),
+            ComponentBuilder::text("Hello").color(NamedColor::Red).italic().build(),
+        ]))
+        .build();
+
+    // `bold` and friends are behind `#[serde(flatten)]`, where serde can't ask for a bool
+    let options = ferrumc_nbt::SerdeOptions {
+        bytes_as_bools: true,
+        ..Default::default()
+    };
+
+    let bytes = ferrumc_nbt::to_bytes(&component, &options).unwrap();
+    assert_eq!(bytes, component.serialize_nbt());
+    assert_eq!(ferrumc_nbt::from_slice::<TextComponent>(&bytes, &options).unwrap(), component);
+}
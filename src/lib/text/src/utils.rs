@@ -61,7 +61,7 @@ impl From<NamedColor> for Color {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default, NBTSerialize)]
-#[serde(rename_all(serialize = "snake_case"))]
+#[serde(rename_all = "snake_case")]
 #[nbt(tag_type = 8, tag = "untagged", rename_all = "snake_case")]
 pub enum NamedColor {
     Black,
@@ -19,4 +19,5 @@ pub mod favicon;
 pub use server_config::DatabaseCompression;
 pub use server_config::DatabaseConfig;
 pub use server_config::VelocityConfig;
+pub use server_config::{DebugConfig, MovementConfig, PacketCaptureConfig};
 pub use server_config::ServerConfig;
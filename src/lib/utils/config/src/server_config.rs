@@ -17,7 +17,11 @@ use serde_derive::{Deserialize, Serialize};
 /// - `database` - [DatabaseConfig]: The configuration for the database.
 /// - `world`: The name of the world that the server will load.
 /// - `network_compression_threshold`: The threshold at which the server will compress network packets.
+/// - `view_distance`: How many chunks around them players can see at most, clients may ask for fewer. (2-32)
 /// - `lan`: Open to LAN settings.
+/// - `movement` - [MovementConfig]: Server-side movement validation thresholds.
+/// - `packet_capture` - [PacketCaptureConfig]: Where and when packet captures are written.
+/// - `debug` - [DebugConfig]: Debugging aids, like the tick times plugin channel.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ServerConfig {
     pub host: String,
@@ -28,10 +32,22 @@ pub struct ServerConfig {
     pub database: DatabaseConfig,
     pub world: String,
     pub network_compression_threshold: i32, // Can be negative
+    #[serde(default = "default_view_distance")]
+    pub view_distance: u8,
     #[serde(default)]
     pub velocity: VelocityConfig,
     #[serde(default)]
     pub lan: LanConfig,
+    #[serde(default)]
+    pub movement: MovementConfig,
+    #[serde(default)]
+    pub packet_capture: PacketCaptureConfig,
+    #[serde(default)]
+    pub debug: DebugConfig,
+}
+
+fn default_view_distance() -> u8 {
+    10
 }
 
 /// The velocity configuration struct.
@@ -66,6 +82,88 @@ impl Default for LanConfig {
     }
 }
 
+/// The movement validation configuration struct.
+///
+/// All speeds are in blocks per tick.
+///
+/// Fields:
+/// - `enabled`: If movement sent by clients should be validated.
+/// - `max_horizontal_speed`: The maximum horizontal distance a player may move per tick while walking.
+/// - `max_vertical_speed`: The maximum upwards distance a player may move per tick.
+/// - `max_fall_speed`: The maximum downwards distance a player may move per tick.
+/// - `flying_multiplier`: The speed multiplier applied while the player is flying.
+/// - `elytra_multiplier`: The speed multiplier applied while the player is gliding with an elytra.
+/// - `max_buffered_ticks`: How many ticks of idle time a single movement may make up for.
+/// - `teleport_grace_ticks`: How many ticks after a server teleport movement gets extra leeway.
+/// - `teleport_grace_margin`: The extra blocks allowed in each direction during the teleport grace period.
+/// - `world_border`: The maximum absolute x/z coordinate a player may move to.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MovementConfig {
+    pub enabled: bool,
+    pub max_horizontal_speed: f64,
+    pub max_vertical_speed: f64,
+    pub max_fall_speed: f64,
+    pub flying_multiplier: f64,
+    pub elytra_multiplier: f64,
+    pub max_buffered_ticks: u32,
+    pub teleport_grace_ticks: u32,
+    pub teleport_grace_margin: f64,
+    pub world_border: f64,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_horizontal_speed: 1.0,
+            max_vertical_speed: 1.0,
+            max_fall_speed: 4.0,
+            flying_multiplier: 3.0,
+            elytra_multiplier: 8.0,
+            max_buffered_ticks: 5,
+            teleport_grace_ticks: 20,
+            teleport_grace_margin: 1.0,
+            world_border: 29_999_984.0,
+        }
+    }
+}
+
+/// The packet capture configuration struct.
+///
+/// Captures can also be started and stopped at runtime for single connections with the `capture` console command,
+/// `enabled` only decides if every new connection is captured from the start.
+///
+/// Fields:
+/// - `enabled`: If every connection should be captured.
+/// - `directory`: The directory capture files are written to.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PacketCaptureConfig {
+    pub enabled: bool,
+    pub directory: String,
+}
+
+impl Default for PacketCaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: String::from("captures"),
+        }
+    }
+}
+
+/// The debug configuration struct.
+///
+/// Fields:
+/// - `tick_times`: If players may register the `ferrumc:tick_times` plugin channel to receive
+///   the duration of every tick, used by the load test.
+#[derive(Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct DebugConfig {
+    pub tick_times: bool,
+}
+
 /// The database configuration section from [ServerConfig].
 ///
 /// Fields:
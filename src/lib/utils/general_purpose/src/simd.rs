@@ -1,2 +1,2 @@
-
-pub mod arrays;
\ No newline at end of file
+pub mod arrays;
+pub mod packed;
@@ -3,11 +3,51 @@ use std::slice;
 #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
 use std::arch::x86_64::*;
 
-#[cfg(target_arch = "x86_64")]
+#[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
 #[inline(always)]
 fn has_avx2() -> bool {
-    // is_x86_feature_detected!("avx2")
-    false
+    is_x86_feature_detected!("avx2")
+}
+
+/// Reverses the bytes of each `u32` in a 256-bit lane.
+#[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+#[target_feature(enable = "avx2")]
+unsafe fn u32_shuffle_mask() -> __m256i {
+    _mm256_setr_epi8(
+        3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12, 19, 18, 17, 16, 23, 22, 21, 20, 27,
+        26, 25, 24, 31, 30, 29, 28,
+    )
+}
+
+/// Reverses the bytes of each `u64` in a 256-bit lane.
+#[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+#[target_feature(enable = "avx2")]
+unsafe fn u64_shuffle_mask() -> __m256i {
+    _mm256_setr_epi8(
+        7, 6, 5, 4, 3, 2, 1, 0, // Reverse first u64
+        15, 14, 13, 12, 11, 10, 9, 8, // Reverse second u64
+        23, 22, 21, 20, 19, 18, 17, 16, // Reverse third u64
+        31, 30, 29, 28, 27, 26, 25, 24, // Reverse fourth u64
+    )
+}
+
+/// Copies `len` bytes from `input` to `output` 32 at a time, shuffling each 32 with `mask` if
+/// there is one, and returns how many were copied. x86_64 is little-endian, so big-endian data
+/// needs the bytes of each value reversed and little-endian data can be copied as it is.
+#[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+#[target_feature(enable = "avx2")]
+unsafe fn copy_simd(input: *const u8, output: *mut u8, len: usize, mask: Option<__m256i>) -> usize {
+    let mut i = 0;
+    while i + 32 <= len {
+        let data = _mm256_loadu_si256(input.add(i) as *const __m256i);
+        let data = match mask {
+            Some(mask) => _mm256_shuffle_epi8(data, mask),
+            None => data,
+        };
+        _mm256_storeu_si256(output.add(i) as *mut __m256i, data);
+        i += 32;
+    }
+    i
 }
 
 /// Converts a slice of `u8` to a slice of `i8` without copying.
@@ -17,6 +57,15 @@ pub fn u8_slice_to_i8(input: &[u8]) -> &[i8] {
 
 /// Converts a slice of `u8` to a `Vec<u32>` in big-endian order.
 pub fn u8_slice_to_u32_be(input: &[u8]) -> Vec<u32> {
+    u8_slice_to_u32::<true>(input)
+}
+
+/// Converts a slice of `u8` to a `Vec<u32>` in little-endian order.
+pub fn u8_slice_to_u32_le(input: &[u8]) -> Vec<u32> {
+    u8_slice_to_u32::<false>(input)
+}
+
+fn u8_slice_to_u32<const BIG_ENDIAN: bool>(input: &[u8]) -> Vec<u32> {
     assert_eq!(
         input.len() % 4,
         0,
@@ -26,27 +75,29 @@ pub fn u8_slice_to_u32_be(input: &[u8]) -> Vec<u32> {
     #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
     {
         if has_avx2() {
-            return unsafe { u8_slice_to_u32_be_simd(input) };
+            return unsafe { u8_slice_to_u32_simd::<BIG_ENDIAN>(input) };
         }
     }
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "avx2", not(target_os = "macos"))))]
-    {
-        u8_slice_to_u32_be_normal(input)
-    }
+    u8_slice_to_u32_normal::<BIG_ENDIAN>(input)
 }
 
-fn u8_slice_to_u32_be_normal(input: &[u8]) -> Vec<u32> {
+fn u8_slice_to_u32_normal<const BIG_ENDIAN: bool>(input: &[u8]) -> Vec<u32> {
     input
         .chunks_exact(4)
-        .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()))
+        .map(|chunk| {
+            let bytes = chunk.try_into().unwrap();
+            if BIG_ENDIAN {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            }
+        })
         .collect()
 }
 
 #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
 #[target_feature(enable = "avx2")]
-unsafe fn u8_slice_to_u32_be_simd(input: &[u8]) -> Vec<u32> {
-    use std::mem;
-
+unsafe fn u8_slice_to_u32_simd<const BIG_ENDIAN: bool>(input: &[u8]) -> Vec<u32> {
     debug_assert_eq!(
         input.len() % 4,
         0,
@@ -54,39 +105,12 @@ unsafe fn u8_slice_to_u32_be_simd(input: &[u8]) -> Vec<u32> {
     );
 
     let num_elements = input.len() / 4;
-    let mut output = Vec::with_capacity(num_elements);
-    let mut i = 0;
-
-    let shuffle_mask = _mm256_setr_epi8(
-        3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12, 19, 18, 17, 16, 23, 22, 21, 20, 27,
-        26, 25, 24, 31, 30, 29, 28,
-    );
-
-    while i + 32 <= input.len() {
-        let data = _mm256_loadu_si256(input.as_ptr().add(i) as *const __m256i);
-        let shuffled = _mm256_shuffle_epi8(data, shuffle_mask);
-        let mut temp = mem::MaybeUninit::<[u8; 32]>::uninit();
-        _mm256_storeu_si256(temp.as_mut_ptr() as *mut __m256i, shuffled);
-        let temp = temp.assume_init();
-
-        for j in 0..8 {
-            let bytes: [u8; 4] = temp[j * 4..(j + 1) * 4]
-                .try_into()
-                .expect("SIMD conversion failed");
-            let val = u32::from_le_bytes(bytes);
-            output.push(val);
-        }
-
-        i += 32;
-    }
-
-    while i + 4 <= input.len() {
-        let bytes: [u8; 4] = input[i..i + 4].try_into().expect("SIMD conversion failed");
-        let val = u32::from_be_bytes(bytes);
-        output.push(val);
-        i += 4;
-    }
+    let mut output = Vec::<u32>::with_capacity(num_elements);
+    let mask = if BIG_ENDIAN { Some(u32_shuffle_mask()) } else { None };
+    let copied = copy_simd(input.as_ptr(), output.as_mut_ptr() as *mut u8, input.len(), mask);
+    output.set_len(copied / 4);
 
+    output.extend(u8_slice_to_u32_normal::<BIG_ENDIAN>(&input[copied..]));
     output
 }
 
@@ -95,7 +119,22 @@ pub fn u8_slice_to_i32_be(input: &[u8]) -> Vec<i32> {
     unsafe { std::mem::transmute::<Vec<u32>, Vec<i32>>(u32s) }
 }
 
+pub fn u8_slice_to_i32_le(input: &[u8]) -> Vec<i32> {
+    let u32s = u8_slice_to_u32_le(input);
+    unsafe { std::mem::transmute::<Vec<u32>, Vec<i32>>(u32s) }
+}
+
+/// Converts a slice of `u8` to a `Vec<u64>` in big-endian order.
 pub fn u8_slice_to_u64_be(input: &[u8]) -> Vec<u64> {
+    u8_slice_to_u64::<true>(input)
+}
+
+/// Converts a slice of `u8` to a `Vec<u64>` in little-endian order.
+pub fn u8_slice_to_u64_le(input: &[u8]) -> Vec<u64> {
+    u8_slice_to_u64::<false>(input)
+}
+
+fn u8_slice_to_u64<const BIG_ENDIAN: bool>(input: &[u8]) -> Vec<u64> {
     assert_eq!(
         input.len() % 8,
         0,
@@ -105,24 +144,29 @@ pub fn u8_slice_to_u64_be(input: &[u8]) -> Vec<u64> {
     #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
     {
         if has_avx2() {
-            return unsafe { u8_slice_to_u64_be_simd(input) };
+            return unsafe { u8_slice_to_u64_simd::<BIG_ENDIAN>(input) };
         }
     }
-    u8_slice_to_u64_be_normal(input)
+    u8_slice_to_u64_normal::<BIG_ENDIAN>(input)
 }
 
-fn u8_slice_to_u64_be_normal(input: &[u8]) -> Vec<u64> {
+fn u8_slice_to_u64_normal<const BIG_ENDIAN: bool>(input: &[u8]) -> Vec<u64> {
     input
         .chunks_exact(8)
-        .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+        .map(|chunk| {
+            let bytes = chunk.try_into().unwrap();
+            if BIG_ENDIAN {
+                u64::from_be_bytes(bytes)
+            } else {
+                u64::from_le_bytes(bytes)
+            }
+        })
         .collect()
 }
 
 #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
 #[target_feature(enable = "avx2")]
-unsafe fn u8_slice_to_u64_be_simd(input: &[u8]) -> Vec<u64> {
-    use std::mem;
-
+unsafe fn u8_slice_to_u64_simd<const BIG_ENDIAN: bool>(input: &[u8]) -> Vec<u64> {
     debug_assert_eq!(
         input.len() % 8,
         0,
@@ -130,158 +174,200 @@ unsafe fn u8_slice_to_u64_be_simd(input: &[u8]) -> Vec<u64> {
     );
 
     let num_elements = input.len() / 8;
-    let mut output = Vec::with_capacity(num_elements);
-    let mut i = 0;
+    let mut output = Vec::<u64>::with_capacity(num_elements);
+    let mask = if BIG_ENDIAN { Some(u64_shuffle_mask()) } else { None };
+    let copied = copy_simd(input.as_ptr(), output.as_mut_ptr() as *mut u8, input.len(), mask);
+    output.set_len(copied / 8);
 
-    let shuffle_mask = _mm256_setr_epi8(
-        7, 6, 5, 4, 3, 2, 1, 0, // Reverse first u64
-        15, 14, 13, 12, 11, 10, 9, 8, // Reverse second u64
-        23, 22, 21, 20, 19, 18, 17, 16, // Reverse third u64
-        31, 30, 29, 28, 27, 26, 25, 24, // Reverse fourth u64
-    );
+    output.extend(u8_slice_to_u64_normal::<BIG_ENDIAN>(&input[copied..]));
+    output
+}
 
-    while i + 32 <= input.len() {
-        let data = _mm256_loadu_si256(input.as_ptr().add(i) as *const __m256i);
-        let shuffled = _mm256_shuffle_epi8(data, shuffle_mask);
-        let mut temp = mem::MaybeUninit::<[u8; 32]>::uninit();
-        _mm256_storeu_si256(temp.as_mut_ptr() as *mut __m256i, shuffled);
-        let temp = temp.assume_init();
-
-        for j in 0..4 {
-            let bytes: [u8; 8] = temp[j * 8..(j + 1) * 8]
-                .try_into()
-                .expect("SIMD conversion failed");
-            let val = u64::from_le_bytes(bytes);
-            output.push(val);
-        }
+pub fn u8_slice_to_i64_be(input: &[u8]) -> Vec<i64> {
+    let u64s = u8_slice_to_u64_be(input);
+    unsafe { std::mem::transmute::<Vec<u64>, Vec<i64>>(u64s) }
+}
 
-        i += 32;
-    }
+pub fn u8_slice_to_i64_le(input: &[u8]) -> Vec<i64> {
+    let u64s = u8_slice_to_u64_le(input);
+    unsafe { std::mem::transmute::<Vec<u64>, Vec<i64>>(u64s) }
+}
 
-    while i + 8 <= input.len() {
-        let bytes: [u8; 8] = input[i..i + 8].try_into().expect("SIMD conversion failed");
-        let val = u64::from_be_bytes(bytes);
-        output.push(val);
-        i += 8;
-    }
+pub fn u32_slice_to_u8_be(input: &[u32]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() * 4);
+    extend_u8_from_u32_be(&mut output, input);
+    output
+}
 
+pub fn u32_slice_to_u8_le(input: &[u32]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() * 4);
+    extend_u8_from_u32_le(&mut output, input);
     output
 }
 
-pub fn u8_slice_to_i64_be(input: &[u8]) -> Vec<i64> {
-    let u64s = u8_slice_to_u64_be(input);
-    u64s.into_iter().map(|x| x as i64).collect()
+/// Appends the bytes of `input` in big-endian order to `output`, without a temporary buffer.
+pub fn extend_u8_from_u32_be(output: &mut Vec<u8>, input: &[u32]) {
+    extend_u8_from_u32::<true>(output, input)
 }
 
-pub fn u32_slice_to_u8_be(input: &[u32]) -> Vec<u8> {
+/// Appends the bytes of `input` in little-endian order to `output`, without a temporary buffer.
+pub fn extend_u8_from_u32_le(output: &mut Vec<u8>, input: &[u32]) {
+    extend_u8_from_u32::<false>(output, input)
+}
+
+fn extend_u8_from_u32<const BIG_ENDIAN: bool>(output: &mut Vec<u8>, input: &[u32]) {
     #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
     {
         if has_avx2() {
-            println!("Using SIMD");
-            return unsafe { u32_slice_to_u8_be_simd(input) };
+            return unsafe { extend_u8_from_u32_simd::<BIG_ENDIAN>(output, input) };
         }
     }
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "avx2", not(target_os = "macos"))))]
-    {
-        println!("Not using SIMD");
-        u32_slice_to_u8_be_normal(input)
-    }
+    extend_u8_from_u32_normal::<BIG_ENDIAN>(output, input)
 }
 
-fn u32_slice_to_u8_be_normal(input: &[u32]) -> Vec<u8> {
-    let mut output = Vec::with_capacity(input.len() * 4);
+fn extend_u8_from_u32_normal<const BIG_ENDIAN: bool>(output: &mut Vec<u8>, input: &[u32]) {
+    output.reserve(input.len() * 4);
     for val in input {
-        output.extend_from_slice(&val.to_be_bytes());
+        if BIG_ENDIAN {
+            output.extend_from_slice(&val.to_be_bytes());
+        } else {
+            output.extend_from_slice(&val.to_le_bytes());
+        }
     }
-    output
 }
 
 #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
 #[target_feature(enable = "avx2")]
-unsafe fn u32_slice_to_u8_be_simd(input: &[u32]) -> Vec<u8> {
-    use std::mem;
-
-    let num_elements = input.len();
-    let mut output = Vec::with_capacity(num_elements * 4);
-    let mut i = 0;
+unsafe fn extend_u8_from_u32_simd<const BIG_ENDIAN: bool>(output: &mut Vec<u8>, input: &[u32]) {
+    let len = input.len() * 4;
+    output.reserve(len);
+    let start = output.len();
+    let mask = if BIG_ENDIAN { Some(u32_shuffle_mask()) } else { None };
+    let copied = copy_simd(input.as_ptr() as *const u8, output.as_mut_ptr().add(start), len, mask);
+    output.set_len(start + copied);
+
+    extend_u8_from_u32_normal::<BIG_ENDIAN>(output, &input[copied / 4..]);
+}
 
-    let shuffle_mask = _mm256_setr_epi8(
-        3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12, 19, 18, 17, 16, 23, 22, 21, 20, 27,
-        26, 25, 24, 31, 30, 29, 28,
-    );
+pub fn u64_slice_to_u8_be(input: &[u64]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() * 8);
+    extend_u8_from_u64_be(&mut output, input);
+    output
+}
 
-    while i + 8 <= num_elements {
-        let data = _mm256_loadu_si256(input.as_ptr().add(i) as *const __m256i);
-        let shuffled = _mm256_shuffle_epi8(data, shuffle_mask);
-        let mut temp = mem::MaybeUninit::<[u8; 32]>::uninit();
-        _mm256_storeu_si256(temp.as_mut_ptr() as *mut __m256i, shuffled);
-        let temp = temp.assume_init();
-        output.extend_from_slice(&temp);
-        i += 8;
-    }
+pub fn u64_slice_to_u8_le(input: &[u64]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() * 8);
+    extend_u8_from_u64_le(&mut output, input);
+    output
+}
 
-    while i < num_elements {
-        let val = input[i].to_be_bytes();
-        output.extend_from_slice(&val);
-        i += 1;
-    }
+/// Appends the bytes of `input` in big-endian order to `output`, without a temporary buffer.
+pub fn extend_u8_from_u64_be(output: &mut Vec<u8>, input: &[u64]) {
+    extend_u8_from_u64::<true>(output, input)
+}
 
-    output
+/// Appends the bytes of `input` in little-endian order to `output`, without a temporary buffer.
+pub fn extend_u8_from_u64_le(output: &mut Vec<u8>, input: &[u64]) {
+    extend_u8_from_u64::<false>(output, input)
 }
 
-pub fn u64_slice_to_u8_be(input: &[u64]) -> Vec<u8> {
+fn extend_u8_from_u64<const BIG_ENDIAN: bool>(output: &mut Vec<u8>, input: &[u64]) {
     #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
     {
         if has_avx2() {
-            return unsafe { u64_slice_to_u8_be_simd(input) };
+            return unsafe { extend_u8_from_u64_simd::<BIG_ENDIAN>(output, input) };
         }
     }
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "avx2", not(target_os = "macos"))))]
-    {
-        u64_slice_to_u8_be_normal(input)
-    }
+    extend_u8_from_u64_normal::<BIG_ENDIAN>(output, input)
 }
 
-fn u64_slice_to_u8_be_normal(input: &[u64]) -> Vec<u8> {
-    let mut output = Vec::with_capacity(input.len() * 8);
+fn extend_u8_from_u64_normal<const BIG_ENDIAN: bool>(output: &mut Vec<u8>, input: &[u64]) {
+    output.reserve(input.len() * 8);
     for val in input {
-        output.extend_from_slice(&val.to_be_bytes());
+        if BIG_ENDIAN {
+            output.extend_from_slice(&val.to_be_bytes());
+        } else {
+            output.extend_from_slice(&val.to_le_bytes());
+        }
     }
-    output
 }
 
 #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
 #[target_feature(enable = "avx2")]
-unsafe fn u64_slice_to_u8_be_simd(input: &[u64]) -> Vec<u8> {
-    use std::mem;
-
-    let num_elements = input.len();
-    let mut output = Vec::with_capacity(num_elements * 8);
-    let mut i = 0;
+unsafe fn extend_u8_from_u64_simd<const BIG_ENDIAN: bool>(output: &mut Vec<u8>, input: &[u64]) {
+    let len = input.len() * 8;
+    output.reserve(len);
+    let start = output.len();
+    let mask = if BIG_ENDIAN { Some(u64_shuffle_mask()) } else { None };
+    let copied = copy_simd(input.as_ptr() as *const u8, output.as_mut_ptr().add(start), len, mask);
+    output.set_len(start + copied);
+
+    extend_u8_from_u64_normal::<BIG_ENDIAN>(output, &input[copied / 8..]);
+}
 
-    let shuffle_mask = _mm256_setr_epi8(
-        7, 6, 5, 4, 3, 2, 1, 0, // Reverse first u64
-        15, 14, 13, 12, 11, 10, 9, 8, // Reverse second u64
-        23, 22, 21, 20, 19, 18, 17, 16, // Reverse third u64
-        31, 30, 29, 28, 27, 26, 25, 24, // Reverse fourth u64
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    while i + 4 <= num_elements {
-        let data = _mm256_loadu_si256(input.as_ptr().add(i) as *const __m256i);
-        let shuffled = _mm256_shuffle_epi8(data, shuffle_mask);
-        let mut temp = mem::MaybeUninit::<[u8; 32]>::uninit();
-        _mm256_storeu_si256(temp.as_mut_ptr() as *mut __m256i, shuffled);
-        let temp = temp.assume_init();
-        output.extend_from_slice(&temp);
-        i += 4;
+    /// Enough bytes for a few 32 byte SIMD blocks and a tail that doesn't fill one.
+    fn bytes() -> Vec<u8> {
+        (0..8 * 13).map(|i: u32| i.wrapping_mul(151) as u8).collect()
     }
 
-    while i < num_elements {
-        let val = input[i].to_be_bytes();
-        output.extend_from_slice(&val);
-        i += 1;
+    #[test]
+    fn conversions() {
+        assert_eq!(u8_slice_to_u32_be(&[1, 2, 3, 4]), [0x0102_0304]);
+        assert_eq!(u8_slice_to_u32_le(&[1, 2, 3, 4]), [0x0403_0201]);
+        assert_eq!(u8_slice_to_u64_be(&[1, 2, 3, 4, 5, 6, 7, 8]), [0x0102_0304_0506_0708]);
+        assert_eq!(u8_slice_to_u64_le(&[1, 2, 3, 4, 5, 6, 7, 8]), [0x0807_0605_0403_0201]);
+        assert_eq!(u32_slice_to_u8_be(&[0x0102_0304]), [1, 2, 3, 4]);
+        assert_eq!(u32_slice_to_u8_le(&[0x0102_0304]), [4, 3, 2, 1]);
+        assert_eq!(u64_slice_to_u8_be(&[0x0102_0304_0506_0708]), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(u64_slice_to_u8_le(&[0x0102_0304_0506_0708]), [8, 7, 6, 5, 4, 3, 2, 1]);
+
+        let bytes = bytes();
+        assert_eq!(u32_slice_to_u8_be(&u8_slice_to_u32_be(&bytes)), bytes);
+        assert_eq!(u32_slice_to_u8_le(&u8_slice_to_u32_le(&bytes)), bytes);
+        assert_eq!(u64_slice_to_u8_be(&u8_slice_to_u64_be(&bytes)), bytes);
+        assert_eq!(u64_slice_to_u8_le(&u8_slice_to_u64_le(&bytes)), bytes);
     }
 
-    output
-}
+    #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+    #[test]
+    fn simd_paths() {
+        if !has_avx2() {
+            return;
+        }
 
+        // Every length up to a few blocks, so the tail after the last full block is covered.
+        let bytes = bytes();
+        for len in 0..=bytes.len() / 8 {
+            let input = &bytes[..len * 8];
+            unsafe {
+                assert_eq!(u8_slice_to_u32_simd::<true>(input), u8_slice_to_u32_normal::<true>(input));
+                assert_eq!(u8_slice_to_u32_simd::<false>(input), u8_slice_to_u32_normal::<false>(input));
+                assert_eq!(u8_slice_to_u64_simd::<true>(input), u8_slice_to_u64_normal::<true>(input));
+                assert_eq!(u8_slice_to_u64_simd::<false>(input), u8_slice_to_u64_normal::<false>(input));
+
+                // Appended after what's already there
+                let extend = |f: unsafe fn(&mut Vec<u8>, &[u32]), values: &[u32]| {
+                    let mut output = vec![0xAA; 3];
+                    f(&mut output, values);
+                    output
+                };
+                let u32s = u8_slice_to_u32_normal::<true>(input);
+                assert_eq!(extend(extend_u8_from_u32_simd::<true>, &u32s), extend(extend_u8_from_u32_normal::<true>, &u32s));
+                assert_eq!(extend(extend_u8_from_u32_simd::<false>, &u32s), extend(extend_u8_from_u32_normal::<false>, &u32s));
+
+                let extend = |f: unsafe fn(&mut Vec<u8>, &[u64]), values: &[u64]| {
+                    let mut output = vec![0xAA; 3];
+                    f(&mut output, values);
+                    output
+                };
+                let u64s = u8_slice_to_u64_normal::<true>(input);
+                assert_eq!(extend(extend_u8_from_u64_simd::<true>, &u64s), extend(extend_u8_from_u64_normal::<true>, &u64s));
+                assert_eq!(extend(extend_u8_from_u64_simd::<false>, &u64s), extend(extend_u8_from_u64_normal::<false>, &u64s));
+            }
+        }
+    }
+}
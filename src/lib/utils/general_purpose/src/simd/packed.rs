@@ -0,0 +1,144 @@
+//! Values packed into longs, like chunk sections store their palette indices and heightmaps their
+//! heights.
+//!
+//! Each long holds `64 / bits` values starting from the least significant bits, and values never
+//! span two longs, so the leftover high bits of every long are unused.
+
+#[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+use std::arch::x86_64::*;
+
+#[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+#[inline(always)]
+fn has_avx2() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+/// How many longs `count` values of `bits` bits take up.
+pub fn packed_len(count: usize, bits: u8) -> usize {
+    assert!((1..=32).contains(&bits), "Packed values must be between 1 and 32 bits");
+
+    count.div_ceil(64 / bits as usize)
+}
+
+/// Unpacks the first `count` values of `bits` bits from `data`.
+///
+/// Returns fewer values if `data` runs out first.
+pub fn unpack_longs(data: &[i64], bits: u8, count: usize) -> Vec<u32> {
+    assert!((1..=32).contains(&bits), "Packed values must be between 1 and 32 bits");
+
+    #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+    {
+        if has_avx2() {
+            return unsafe { unpack_longs_simd(data, bits, count) };
+        }
+    }
+    unpack_longs_normal(data, bits, count)
+}
+
+fn unpack_longs_normal(data: &[i64], bits: u8, count: usize) -> Vec<u32> {
+    let per_long = 64 / bits as usize;
+    let mask = (1u64 << bits) - 1;
+
+    data.iter()
+        .flat_map(|&long| (0..per_long).map(move |i| ((long as u64 >> (i * bits as usize)) & mask) as u32))
+        .take(count)
+        .collect()
+}
+
+#[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+#[target_feature(enable = "avx2")]
+unsafe fn unpack_longs_simd(data: &[i64], bits: u8, count: usize) -> Vec<u32> {
+    let bits = bits as usize;
+    let per_long = 64 / bits;
+    let mask = _mm256_set1_epi64x(((1u64 << bits) - 1) as i64);
+
+    let mut output = Vec::with_capacity(count);
+    let mut lanes = [0u64; 4];
+
+    for &long in data {
+        let word = _mm256_set1_epi64x(long);
+
+        // Four values at a time, shifting each lane by a different amount.
+        let mut i = 0;
+        while i < per_long && output.len() < count {
+            let shifts = _mm256_setr_epi64x(
+                (i * bits) as i64,
+                ((i + 1) * bits) as i64,
+                ((i + 2) * bits) as i64,
+                ((i + 3) * bits) as i64,
+            );
+            // Shifting by 64 or more gives 0, so lanes past the end of the long are harmless.
+            let values = _mm256_and_si256(_mm256_srlv_epi64(word, shifts), mask);
+            _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, values);
+
+            let taken = (per_long - i).min(4).min(count - output.len());
+            output.extend(lanes[..taken].iter().map(|&value| value as u32));
+            i += 4;
+        }
+
+        if output.len() == count {
+            break;
+        }
+    }
+
+    output
+}
+
+/// Packs `values` into longs with `bits` bits each. Values are truncated to fit.
+pub fn pack_longs(values: &[u32], bits: u8) -> Vec<i64> {
+    assert!((1..=32).contains(&bits), "Packed values must be between 1 and 32 bits");
+
+    let per_long = 64 / bits as usize;
+    let mask = (1u64 << bits) - 1;
+
+    values
+        .chunks(per_long)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u64, |long, (i, &value)| long | ((u64::from(value) & mask) << (i * bits as usize))) as i64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(bits: u8) -> Vec<u32> {
+        let max = if bits == 32 { u32::MAX } else { (1 << bits) - 1 };
+        (0..300u32).map(|i| i.wrapping_mul(2_654_435_761) & max).collect()
+    }
+
+    #[test]
+    fn normal_path() {
+        for bits in 1..=32 {
+            let values = values(bits);
+            let packed = pack_longs(&values, bits);
+            assert_eq!(unpack_longs_normal(&packed, bits, values.len()), values, "{bits} bits");
+            // Running out of data
+            assert_eq!(unpack_longs_normal(&packed[..1], bits, values.len()), values[..64 / bits as usize]);
+        }
+    }
+
+    #[cfg(all(target_arch = "x86_64", not(target_os = "macos")))]
+    #[test]
+    fn simd_path() {
+        if !has_avx2() {
+            return;
+        }
+
+        for bits in 1..=32 {
+            let values = values(bits);
+            let packed = pack_longs(&values, bits);
+            // Counts that stop partway through a long and partway through a group of four
+            for count in [0, 1, 3, 5, 63, 64, 65, values.len()] {
+                let simd = unsafe { unpack_longs_simd(&packed, bits, count) };
+                assert_eq!(simd, unpack_longs_normal(&packed, bits, count), "{bits} bits, {count} values");
+            }
+            let simd = unsafe { unpack_longs_simd(&packed[..1], bits, values.len()) };
+            assert_eq!(simd, values[..64 / bits as usize]);
+        }
+    }
+}
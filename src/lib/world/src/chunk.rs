@@ -0,0 +1,310 @@
+//! Chunks as Anvil stores them since 1.18.
+//!
+//! ```ignore
+//! let data = region.get_chunk(0, 0).unwrap();
+//! let mut chunk = Chunk::from_bytes(&data)?;
+//!
+//! let block = chunk.get_block(0, 64, 0);
+//! let data = chunk.to_bytes();
+//! ```
+
+use crate::errors::WorldError;
+use crate::light::LightArray;
+use crate::palette::{Biome, BlockState, PaletteKind, PalettedContainer};
+use ferrumc_general_purpose::simd::packed;
+use ferrumc_macros::{NBTDeserialize, NBTSerialize};
+use ferrumc_nbt::{FromNbt, NBTError, NBTSerializable, NBTSerializeOptions, NbtCompound, NbtEncoding, NbtTape, NbtTapeElement, NbtValue};
+
+const TAG_LONG_ARRAY: u8 = 12;
+
+/// The highest block of each column, packed as heights above the bottom of the world.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap(pub Vec<i64>);
+
+impl Heightmap {
+    /// The bits each height takes in a world `world_height` blocks tall.
+    pub fn bits_for(world_height: u32) -> u8 {
+        (u32::BITS - world_height.leading_zeros()) as u8
+    }
+
+    pub fn from_heights(heights: &[u32; 256], world_height: u32) -> Self {
+        Self(packed::pack_longs(heights, Self::bits_for(world_height)))
+    }
+
+    /// The heights of all 256 columns, indexed by `z * 16 + x`.
+    pub fn heights(&self, world_height: u32) -> Vec<u32> {
+        packed::unpack_longs(&self.0, Self::bits_for(world_height), 256)
+    }
+}
+
+impl<'a> FromNbt<'a> for Heightmap {
+    fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> ferrumc_nbt::Result<Self> {
+        Vec::<i64>::from_nbt(tapes, element).map(Heightmap)
+    }
+}
+
+impl NBTSerializable for Heightmap {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        self.0.serialize_encoded(buf, options, encoding);
+    }
+
+    fn id() -> u8 {
+        TAG_LONG_ARRAY
+    }
+}
+
+/// Only finished chunks are guaranteed to have all of these, the `_WG` ones are only used while
+/// generating.
+#[derive(Debug, Clone, PartialEq, Default, NBTSerialize, NBTDeserialize)]
+pub struct Heightmaps {
+    #[nbt(rename = "MOTION_BLOCKING")]
+    pub motion_blocking: Option<Heightmap>,
+    #[nbt(rename = "MOTION_BLOCKING_NO_LEAVES")]
+    pub motion_blocking_no_leaves: Option<Heightmap>,
+    #[nbt(rename = "OCEAN_FLOOR")]
+    pub ocean_floor: Option<Heightmap>,
+    #[nbt(rename = "OCEAN_FLOOR_WG")]
+    pub ocean_floor_wg: Option<Heightmap>,
+    #[nbt(rename = "WORLD_SURFACE")]
+    pub world_surface: Option<Heightmap>,
+    #[nbt(rename = "WORLD_SURFACE_WG")]
+    pub world_surface_wg: Option<Heightmap>,
+}
+
+/// A 16 block tall slice of a chunk.
+///
+/// Sections just above and below the world only have light, so everything else is optional.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkSection {
+    pub y: i8,
+    pub block_states: Option<PalettedContainer<BlockState>>,
+    pub biomes: Option<PalettedContainer<Biome>>,
+    pub block_light: Option<LightArray>,
+    pub sky_light: Option<LightArray>,
+    /// Entries that aren't part of the model, like ones added by server software, kept so the
+    /// section is written back as it was read.
+    pub other: NbtCompound,
+}
+
+impl ChunkSection {
+    /// A section that's only air.
+    pub fn empty(y: i8, biome: Biome) -> Self {
+        Self {
+            y,
+            block_states: Some(PalettedContainer::new(BlockState::air())),
+            biomes: Some(PalettedContainer::new(biome)),
+            block_light: None,
+            sky_light: None,
+            other: NbtCompound::new(),
+        }
+    }
+}
+
+impl<'a> FromNbt<'a> for ChunkSection {
+    fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> ferrumc_nbt::Result<Self> {
+        let entries = element.as_compound().ok_or(NBTError::TypeMismatch {
+            expected: "Compound",
+            found: element.nbt_type(),
+        })?;
+
+        let mut y = None;
+        let mut section = ChunkSection {
+            y: 0,
+            block_states: None,
+            biomes: None,
+            block_light: None,
+            sky_light: None,
+            other: NbtCompound::new(),
+        };
+
+        for (name, element) in entries {
+            match *name {
+                "Y" => y = Some(i8::from_nbt(tapes, element)?),
+                "block_states" => section.block_states = Some(FromNbt::from_nbt(tapes, element)?),
+                "biomes" => section.biomes = Some(FromNbt::from_nbt(tapes, element)?),
+                "BlockLight" => section.block_light = Some(LightArray::from_nbt(tapes, element)?),
+                "SkyLight" => section.sky_light = Some(LightArray::from_nbt(tapes, element)?),
+                _ => {
+                    section.other.insert(*name, NbtValue::from_tape_element(tapes, element)?);
+                }
+            }
+        }
+
+        section.y = y.ok_or(NBTError::ElementNotFound("Y"))?;
+        Ok(section)
+    }
+}
+
+impl NBTSerializable for ChunkSection {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        crate::write_header(crate::TAG_COMPOUND, buf, options, encoding);
+
+        self.y.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("Y"), encoding);
+        self.block_states.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("block_states"), encoding);
+        self.biomes.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("biomes"), encoding);
+        self.block_light.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("BlockLight"), encoding);
+        self.sky_light.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("SkyLight"), encoding);
+        self.other.serialize_encoded(buf, &NBTSerializeOptions::Flatten, encoding);
+
+        crate::write_end(buf, options);
+    }
+
+    fn id() -> u8 {
+        crate::TAG_COMPOUND
+    }
+}
+
+/// A 16x16 column of the world, from the bottom to the top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub data_version: i32,
+    pub x: i32,
+    pub z: i32,
+    /// The Y of the lowest section of blocks. The section below it only has light.
+    pub min_section: i32,
+    /// How far generation got, e.g. `minecraft:full`.
+    pub status: String,
+    /// The tick the chunk was last saved.
+    pub last_update: i64,
+    pub sections: Vec<ChunkSection>,
+    pub heightmaps: Heightmaps,
+    /// Entities, ticks, structures and everything else that isn't part of the model, kept so the
+    /// chunk is written back as it was read.
+    pub other: NbtCompound,
+}
+
+impl Chunk {
+    /// Parses the uncompressed NBT of a chunk, like `LoadedAnvilFile::get_chunk` returns.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, WorldError> {
+        let mut tape = NbtTape::new(data);
+        tape.parse()?;
+        let (_, root) = tape.root.as_ref().ok_or(NBTError::NoRootTag)?;
+        Ok(Self::from_nbt(&tape, root)?)
+    }
+
+    /// The chunk as uncompressed NBT, ready to be compressed into a region file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.serialize(&mut buf, &NBTSerializeOptions::WithHeader(""));
+        buf
+    }
+
+    /// The section a block at `y` is in.
+    pub fn section(&self, y: i32) -> Option<&ChunkSection> {
+        let section_y = y.div_euclid(16);
+        self.sections.iter().find(|section| i32::from(section.y) == section_y)
+    }
+
+    pub fn section_mut(&mut self, y: i32) -> Option<&mut ChunkSection> {
+        let section_y = y.div_euclid(16);
+        self.sections.iter_mut().find(|section| i32::from(section.y) == section_y)
+    }
+
+    /// The block at a position, `x` and `z` being relative to the chunk. `None` outside of it.
+    pub fn get_block(&self, x: usize, y: i32, z: usize) -> Option<&BlockState> {
+        if !in_chunk(x, z) {
+            return None;
+        }
+        let block_states = self.section(y)?.block_states.as_ref()?;
+        block_states.get(x, y.rem_euclid(16) as usize, z)
+    }
+
+    /// Sets a block, `x` and `z` being relative to the chunk. Does nothing outside of the sections
+    /// the chunk has.
+    pub fn set_block(&mut self, x: usize, y: i32, z: usize, block: BlockState) -> Result<(), WorldError> {
+        if !in_chunk(x, z) {
+            return Err(WorldError::OutOfBounds { x, z });
+        }
+        let Some(section) = self.section_mut(y) else {
+            return Ok(());
+        };
+        section
+            .block_states
+            .get_or_insert_with(|| PalettedContainer::new(BlockState::air()))
+            .set(x, y.rem_euclid(16) as usize, z, block)
+    }
+
+    /// The biome at a position, `x` and `z` being relative to the chunk. `None` outside of it.
+    pub fn get_biome(&self, x: usize, y: i32, z: usize) -> Option<&Biome> {
+        if !in_chunk(x, z) {
+            return None;
+        }
+        let biomes = self.section(y)?.biomes.as_ref()?;
+        let side = 16 / Biome::SIDE;
+        biomes.get(x / side, y.rem_euclid(16) as usize / side, z / side)
+    }
+}
+
+/// Whether a position relative to a chunk is inside of it.
+fn in_chunk(x: usize, z: usize) -> bool {
+    x < 16 && z < 16
+}
+
+impl<'a> FromNbt<'a> for Chunk {
+    fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> ferrumc_nbt::Result<Self> {
+        let entries = element.as_compound().ok_or(NBTError::TypeMismatch {
+            expected: "Compound",
+            found: element.nbt_type(),
+        })?;
+
+        let mut data_version = None;
+        let mut x = None;
+        let mut z = None;
+        let mut min_section = None;
+        let mut status = None;
+        let mut last_update = None;
+        let mut sections = None;
+        let mut heightmaps = None;
+        let mut other = NbtCompound::new();
+
+        for (name, element) in entries {
+            match *name {
+                "DataVersion" => data_version = Some(i32::from_nbt(tapes, element)?),
+                "xPos" => x = Some(i32::from_nbt(tapes, element)?),
+                "zPos" => z = Some(i32::from_nbt(tapes, element)?),
+                "yPos" => min_section = Some(i32::from_nbt(tapes, element)?),
+                "Status" => status = Some(String::from_nbt(tapes, element)?),
+                "LastUpdate" => last_update = Some(i64::from_nbt(tapes, element)?),
+                "sections" => sections = Some(Vec::<ChunkSection>::from_nbt(tapes, element)?),
+                "Heightmaps" => heightmaps = Some(Heightmaps::from_nbt(tapes, element)?),
+                _ => {
+                    other.insert(*name, NbtValue::from_tape_element(tapes, element)?);
+                }
+            }
+        }
+
+        Ok(Self {
+            data_version: data_version.ok_or(NBTError::ElementNotFound("DataVersion"))?,
+            x: x.ok_or(NBTError::ElementNotFound("xPos"))?,
+            z: z.ok_or(NBTError::ElementNotFound("zPos"))?,
+            min_section: min_section.ok_or(NBTError::ElementNotFound("yPos"))?,
+            status: status.ok_or(NBTError::ElementNotFound("Status"))?,
+            last_update: last_update.unwrap_or_default(),
+            sections: sections.unwrap_or_default(),
+            heightmaps: heightmaps.unwrap_or_default(),
+            other,
+        })
+    }
+}
+
+impl NBTSerializable for Chunk {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        crate::write_header(crate::TAG_COMPOUND, buf, options, encoding);
+
+        self.data_version.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("DataVersion"), encoding);
+        self.x.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("xPos"), encoding);
+        self.z.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("zPos"), encoding);
+        self.min_section.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("yPos"), encoding);
+        self.status.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("Status"), encoding);
+        self.last_update.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("LastUpdate"), encoding);
+        self.sections.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("sections"), encoding);
+        self.heightmaps.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("Heightmaps"), encoding);
+        self.other.serialize_encoded(buf, &NBTSerializeOptions::Flatten, encoding);
+
+        crate::write_end(buf, options);
+    }
+
+    fn id() -> u8 {
+        crate::TAG_COMPOUND
+    }
+}
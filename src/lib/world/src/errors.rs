@@ -1,7 +1,20 @@
+use ferrumc_nbt::NBTError;
 use thiserror::Error;
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Error)]
 pub enum WorldError {
-    #[error("Something failed lol")]
-    SomeError,
+    #[error("Invalid chunk NBT: {0}")]
+    Nbt(#[from] NBTError),
+    #[error("Expected {expected} longs of packed data but found {found}")]
+    InvalidDataLength { expected: usize, found: usize },
+    #[error("Packed entries must be between 1 and 32 bits, not {0}")]
+    InvalidBitsPerEntry(u8),
+    #[error("({x}, {z}) is outside of a chunk")]
+    OutOfBounds { x: usize, z: usize },
+    #[error("Direct palettes hold global ids, which can't be set from a value")]
+    DirectPalette,
+    #[error("Invalid blocks report: {0}")]
+    BlocksReport(#[from] serde_json::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
 }
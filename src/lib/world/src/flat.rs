@@ -0,0 +1,110 @@
+//! Superflat terrain, for when there's no world to load chunks from.
+
+use crate::chunk::{Chunk, ChunkSection, Heightmap, Heightmaps};
+use crate::light::LightArray;
+use crate::palette::{Biome, BlockState};
+use ferrumc_nbt::NbtCompound;
+
+/// The data version of 1.21.1, which generated chunks are saved with.
+const DATA_VERSION: i32 = 3955;
+
+/// Generates chunks of layers of blocks, bottom up, like the superflat world preset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatGenerator {
+    /// Each block and how many blocks thick its layer is.
+    pub layers: Vec<(BlockState, u32)>,
+    pub biome: Biome,
+}
+
+impl Default for FlatGenerator {
+    /// The vanilla preset: bedrock, two layers of dirt and grass.
+    fn default() -> Self {
+        Self {
+            layers: vec![
+                (BlockState::new("minecraft:bedrock"), 1),
+                (BlockState::new("minecraft:dirt"), 2),
+                (BlockState::new("minecraft:grass_block").with_property("snowy", "false"), 1),
+            ],
+            biome: Biome::new("minecraft:plains"),
+        }
+    }
+}
+
+impl FlatGenerator {
+    /// The bottom of the overworld.
+    pub const MIN_Y: i32 = -64;
+    /// The height of the overworld.
+    pub const HEIGHT: u32 = 384;
+
+    /// The Y of the first block above the layers.
+    pub fn surface(&self) -> i32 {
+        let thickness: u32 = self.layers.iter().map(|(_, thickness)| thickness).sum();
+        Self::MIN_Y + thickness.min(Self::HEIGHT) as i32
+    }
+
+    pub fn generate(&self, x: i32, z: i32) -> Chunk {
+        let min_section = Self::MIN_Y.div_euclid(16);
+        let max_section = min_section + (Self::HEIGHT / 16) as i32 - 1;
+
+        let mut sections = vec![light_section(min_section - 1, 0)];
+        sections.extend((min_section..=max_section).map(|y| ChunkSection {
+            sky_light: Some(LightArray::new(15)),
+            ..ChunkSection::empty(y as i8, self.biome.clone())
+        }));
+        sections.push(light_section(max_section + 1, 15));
+
+        let mut chunk = Chunk {
+            data_version: DATA_VERSION,
+            x,
+            z,
+            min_section,
+            status: String::from("minecraft:full"),
+            last_update: 0,
+            sections,
+            heightmaps: Heightmaps::default(),
+            other: NbtCompound::new(),
+        };
+
+        let surface = self.surface();
+        let layers = self
+            .layers
+            .iter()
+            .flat_map(|(block, thickness)| std::iter::repeat_n(block, *thickness as usize));
+        for (y, block) in (Self::MIN_Y..surface).zip(layers) {
+            let section = chunk.section_mut(y).expect("Layers are inside the world");
+            let block_y = y.rem_euclid(16) as usize;
+            for (x, z) in (0..16).flat_map(|x| (0..16).map(move |z| (x, z))) {
+                section
+                    .block_states
+                    .as_mut()
+                    .expect("Generated sections have blocks")
+                    .set(x, block_y, z, block.clone())
+                    .expect("Generated sections have indirect palettes");
+                section.sky_light.as_mut().expect("Generated sections have sky light").set(x, block_y, z, 0);
+            }
+        }
+
+        let heightmap = Heightmap::from_heights(&[(surface - Self::MIN_Y) as u32; 256], Self::HEIGHT);
+        chunk.heightmaps = Heightmaps {
+            motion_blocking: Some(heightmap.clone()),
+            motion_blocking_no_leaves: Some(heightmap.clone()),
+            ocean_floor: Some(heightmap.clone()),
+            world_surface: Some(heightmap),
+            ..Heightmaps::default()
+        };
+
+        chunk
+    }
+}
+
+/// The sections just below and above the world only have sky light.
+fn light_section(y: i32, sky_light: u8) -> ChunkSection {
+    ChunkSection {
+        y: y as i8,
+        block_states: None,
+        biomes: None,
+        block_light: None,
+        sky_light: Some(LightArray::new(sky_light)),
+        other: NbtCompound::new(),
+    }
+}
@@ -0,0 +1,194 @@
+//! The ids the protocol uses for block states and biomes, which is what direct palettes hold and
+//! what indirect palettes are translated to before a chunk is sent.
+//!
+//! Block state ids come from the blocks report of the vanilla data generator
+//! (`java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar --reports`), which the server
+//! reads from `blocks.json` next to the executable. Without it only a handful of blocks are known.
+//! Biome ids are the order of the biomes in the registry sent during configuration.
+
+use crate::errors::WorldError;
+use crate::palette::{Biome, BlockState};
+use ferrumc_general_purpose::paths::get_root_path;
+use ferrumc_nbt::NbtCompound;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+/// The file the blocks report is read from, relative to the executable.
+pub const BLOCKS_REPORT: &str = "blocks.json";
+
+/// The bits per entry of a direct block state palette, which the client derives from the number of
+/// block states it knows.
+pub const DIRECT_BLOCK_BITS: u8 = 15;
+
+/// The id unknown block states are sent as.
+const STONE: u32 = 1;
+
+/// `(name, properties, id, default)` of a block state.
+type BuiltInBlock = (&'static str, &'static [(&'static str, &'static str)], u32, bool);
+
+/// The block states a flat world needs, as of 1.21.1.
+const BUILT_IN_BLOCKS: &[BuiltInBlock] = &[
+    ("minecraft:air", &[], 0, true),
+    ("minecraft:stone", &[], 1, true),
+    ("minecraft:granite", &[], 2, true),
+    ("minecraft:polished_granite", &[], 3, true),
+    ("minecraft:diorite", &[], 4, true),
+    ("minecraft:polished_diorite", &[], 5, true),
+    ("minecraft:andesite", &[], 6, true),
+    ("minecraft:polished_andesite", &[], 7, true),
+    ("minecraft:grass_block", &[("snowy", "true")], 8, false),
+    ("minecraft:grass_block", &[("snowy", "false")], 9, true),
+    ("minecraft:dirt", &[], 10, true),
+    ("minecraft:coarse_dirt", &[], 11, true),
+    ("minecraft:podzol", &[("snowy", "true")], 12, false),
+    ("minecraft:podzol", &[("snowy", "false")], 13, true),
+    ("minecraft:cobblestone", &[], 14, true),
+    ("minecraft:oak_planks", &[], 15, true),
+    ("minecraft:bedrock", &[], 79, true),
+];
+
+#[derive(Deserialize)]
+struct ReportBlock {
+    states: Vec<ReportState>,
+}
+
+#[derive(Deserialize)]
+struct ReportState {
+    id: u32,
+    #[serde(default)]
+    default: bool,
+    properties: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GlobalPalette {
+    blocks: HashMap<BlockState, u32>,
+    /// The default state of each block, for states the report doesn't list.
+    default_blocks: HashMap<String, u32>,
+    biomes: HashMap<String, u32>,
+    biome_count: usize,
+}
+
+impl GlobalPalette {
+    /// The palette the server uses, with the blocks report if there is one.
+    pub fn get() -> &'static Self {
+        static PALETTE: OnceLock<GlobalPalette> = OnceLock::new();
+        PALETTE.get_or_init(Self::load)
+    }
+
+    fn load() -> Self {
+        let Ok(path) = get_root_path().map(|root| root.join(BLOCKS_REPORT)) else {
+            return Self::built_in();
+        };
+        if !path.exists() {
+            warn!(
+                "{} not found, chunks will only show a few blocks. Generate it with the vanilla data generator.",
+                path.display()
+            );
+            return Self::built_in();
+        }
+
+        match std::fs::read_to_string(&path)
+            .map_err(WorldError::from)
+            .and_then(|report| Self::from_blocks_report(&report))
+        {
+            Ok(palette) => {
+                info!("Loaded {} block states from {}", palette.blocks.len(), path.display());
+                palette
+            }
+            Err(e) => {
+                warn!("Could not load {}: {}", path.display(), e);
+                Self::built_in()
+            }
+        }
+    }
+
+    /// Only knows the first few block states, every other block is sent as stone.
+    pub fn built_in() -> Self {
+        let mut palette = Self::with_biomes();
+        for (name, properties, id, default) in BUILT_IN_BLOCKS {
+            let properties = (!properties.is_empty()).then(|| {
+                properties
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            });
+            palette.insert_block(name, properties, *id, *default);
+        }
+        palette
+    }
+
+    /// Reads the `blocks.json` report of the vanilla data generator.
+    pub fn from_blocks_report(report: &str) -> Result<Self, WorldError> {
+        let report: HashMap<String, ReportBlock> = serde_json::from_str(report)?;
+
+        let mut palette = Self::with_biomes();
+        for (name, block) in report {
+            for state in block.states {
+                palette.insert_block(&name, state.properties, state.id, state.default);
+            }
+        }
+        Ok(palette)
+    }
+
+    fn with_biomes() -> Self {
+        let registry = NbtCompound::from_bytes(include_bytes!("../../../../.etc/registry.nbt"))
+            .expect("The bundled registry is valid NBT");
+        let biomes = registry
+            .get("minecraft:worldgen/biome")
+            .and_then(|biomes| biomes.as_compound())
+            .map(|biomes| {
+                biomes
+                    .iter()
+                    .enumerate()
+                    .map(|(id, (name, _))| (name.to_string(), id as u32))
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default();
+
+        Self {
+            blocks: HashMap::new(),
+            default_blocks: HashMap::new(),
+            biome_count: biomes.len(),
+            biomes,
+        }
+    }
+
+    fn insert_block(&mut self, name: &str, properties: Option<BTreeMap<String, String>>, id: u32, default: bool) {
+        if default {
+            self.default_blocks.insert(name.to_string(), id);
+        }
+        self.blocks.insert(
+            BlockState {
+                name: name.to_string(),
+                properties: properties.filter(|properties| !properties.is_empty()),
+            },
+            id,
+        );
+    }
+
+    /// The id of a block state, falling back to the block's default state and then to stone.
+    pub fn block_id(&self, block: &BlockState) -> u32 {
+        if let Some(id) = self.blocks.get(block) {
+            return *id;
+        }
+        match self.default_blocks.get(&block.name) {
+            Some(id) => *id,
+            None if block.is_air() => 0,
+            None => STONE,
+        }
+    }
+
+    /// The id of a biome, or 0 if it isn't in the registry.
+    pub fn biome_id(&self, biome: &Biome) -> u32 {
+        self.biomes.get(&biome.0).copied().unwrap_or(0)
+    }
+
+    /// The bits per entry of a direct biome palette, which the client derives from the size of the
+    /// biome registry.
+    pub fn direct_biome_bits(&self) -> u8 {
+        (usize::BITS - self.biome_count.saturating_sub(1).leading_zeros()) as u8
+    }
+}
@@ -1,16 +1,35 @@
+pub mod chunk;
 pub mod errors;
+pub mod flat;
+pub mod global_palette;
+pub mod light;
+pub mod palette;
 
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+pub use chunk::{Chunk, ChunkSection, Heightmap, Heightmaps};
+pub use flat::FlatGenerator;
+pub use global_palette::GlobalPalette;
+pub use light::LightArray;
+pub use palette::{Biome, BlockState, Palette, PaletteKind, PalettedContainer};
+
+use ferrumc_nbt::{NBTSerializable, NBTSerializeOptions, NbtEncoding};
+
+pub(crate) const TAG_COMPOUND: u8 = 10;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Writes the tag and name in front of a payload, like the derived serializers do.
+pub(crate) fn write_header(id: u8, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+    match options {
+        NBTSerializeOptions::WithHeader(name) => {
+            buf.push(id);
+            name.serialize_encoded(buf, &NBTSerializeOptions::None, encoding);
+        }
+        NBTSerializeOptions::Network | NBTSerializeOptions::Flatten => buf.push(id),
+        NBTSerializeOptions::None => {}
+    }
+}
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+/// Ends a compound, unless it was flattened into the one around it.
+pub(crate) fn write_end(buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>) {
+    if options != &NBTSerializeOptions::Flatten {
+        buf.push(0);
     }
 }
@@ -0,0 +1,82 @@
+use ferrumc_nbt::{FromNbt, NBTError, NBTSerializable, NBTSerializeOptions, NbtEncoding, NbtTape, NbtTapeElement};
+
+const TAG_BYTE_ARRAY: u8 = 7;
+
+/// The light levels of a chunk section, a nibble for each block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightArray(Box<[u8; LightArray::LEN]>);
+
+impl LightArray {
+    /// The length in bytes.
+    pub const LEN: usize = 2048;
+
+    /// Every block at `level`.
+    pub fn new(level: u8) -> Self {
+        let level = level & 0xF;
+        Self(Box::new([level << 4 | level; Self::LEN]))
+    }
+
+    pub fn from_bytes(bytes: [u8; Self::LEN]) -> Self {
+        Self(Box::new(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.0
+    }
+
+    /// Whether every block is at level 0.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        let index = Self::index(x, y, z);
+        (self.0[index / 2] >> (index % 2 * 4)) & 0xF
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, level: u8) {
+        let index = Self::index(x, y, z);
+        let shift = index % 2 * 4;
+        let byte = &mut self.0[index / 2];
+        *byte = (*byte & !(0xF << shift)) | ((level & 0xF) << shift);
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        assert!(x < 16 && y < 16 && z < 16, "({x}, {y}, {z}) is outside of a chunk section");
+        (y * 16 + z) * 16 + x
+    }
+}
+
+impl<'a> FromNbt<'a> for LightArray {
+    fn from_nbt(_tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> ferrumc_nbt::Result<Self> {
+        let NbtTapeElement::ByteArray(bytes) = element else {
+            return Err(NBTError::TypeMismatch {
+                expected: "ByteArray",
+                found: element.nbt_type(),
+            });
+        };
+
+        if bytes.len() != Self::LEN {
+            return Err(NBTError::InvalidLength(bytes.len() as i32));
+        }
+
+        let mut array = [0u8; Self::LEN];
+        array
+            .iter_mut()
+            .zip(bytes.iter())
+            .for_each(|(to, from)| *to = *from as u8);
+        Ok(Self::from_bytes(array))
+    }
+}
+
+impl NBTSerializable for LightArray {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        crate::write_header(TAG_BYTE_ARRAY, buf, options, encoding);
+        (Self::LEN as i32).serialize_encoded(buf, &NBTSerializeOptions::None, encoding);
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn id() -> u8 {
+        TAG_BYTE_ARRAY
+    }
+}
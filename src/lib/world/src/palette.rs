@@ -0,0 +1,322 @@
+//! Paletted containers, which is how chunk sections store their blocks and biomes.
+//!
+//! Rather than a value per entry, a container keeps a palette of the distinct values and packs
+//! an index into it for every entry, using as few bits as the palette allows.
+
+use crate::errors::WorldError;
+use ferrumc_general_purpose::simd::packed;
+use ferrumc_macros::{NBTDeserialize, NBTSerialize};
+use ferrumc_nbt::{FromNbt, NBTError, NBTSerializable, NBTSerializeOptions, NbtEncoding, NbtTape, NbtTapeElement};
+use std::collections::BTreeMap;
+
+const TAG_STRING: u8 = 8;
+
+/// What a [PalettedContainer] can hold.
+pub trait PaletteKind: Clone + PartialEq {
+    /// The length of each side of the cube of entries.
+    const SIDE: usize;
+    /// The fewest bits an indirect palette uses for each entry.
+    const MIN_BITS: u8;
+
+    /// The number of entries in a container.
+    const ENTRIES: usize = Self::SIDE * Self::SIDE * Self::SIDE;
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        assert!(
+            x < Self::SIDE && y < Self::SIDE && z < Self::SIDE,
+            "({x}, {y}, {z}) is outside of a {0}x{0}x{0} container",
+            Self::SIDE
+        );
+        (y * Self::SIDE + z) * Self::SIDE + x
+    }
+}
+
+/// A block and its properties, like `minecraft:oak_stairs[facing=north]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, NBTSerialize, NBTDeserialize)]
+pub struct BlockState {
+    #[nbt(rename = "Name")]
+    pub name: String,
+    #[nbt(rename = "Properties")]
+    pub properties: Option<BTreeMap<String, String>>,
+}
+
+impl BlockState {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            properties: None,
+        }
+    }
+
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    pub fn air() -> Self {
+        Self::new("minecraft:air")
+    }
+
+    /// Whether this is air, cave air or void air, which don't count towards a section's blocks.
+    pub fn is_air(&self) -> bool {
+        matches!(self.name.as_str(), "minecraft:air" | "minecraft:cave_air" | "minecraft:void_air")
+    }
+}
+
+impl PaletteKind for BlockState {
+    const SIDE: usize = 16;
+    const MIN_BITS: u8 = 4;
+}
+
+/// A biome's id, like `minecraft:plains`. Biomes are stored per 4x4x4 blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Biome(pub String);
+
+impl Biome {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+impl PaletteKind for Biome {
+    const SIDE: usize = 4;
+    const MIN_BITS: u8 = 1;
+}
+
+impl<'a> FromNbt<'a> for Biome {
+    fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> ferrumc_nbt::Result<Self> {
+        String::from_nbt(tapes, element).map(Biome)
+    }
+}
+
+impl NBTSerializable for Biome {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        self.0.serialize_encoded(buf, options, encoding);
+    }
+
+    fn id() -> u8 {
+        TAG_STRING
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Palette<T> {
+    /// Every entry is this value, and there is no data.
+    Single(T),
+    /// The data holds indices into this list.
+    Indirect(Vec<T>),
+    /// The data holds global ids, for when there are too many values to list. Only the network
+    /// format uses these, so the values themselves aren't known here.
+    Direct,
+}
+
+/// The entries of a chunk section, packed into longs like Anvil and the network store them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PalettedContainer<T> {
+    palette: Palette<T>,
+    bits_per_entry: u8,
+    data: Vec<i64>,
+}
+
+impl<T: PaletteKind> PalettedContainer<T> {
+    /// A container where every entry is `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            palette: Palette::Single(value),
+            bits_per_entry: 0,
+            data: Vec::new(),
+        }
+    }
+
+    /// A container of indices into `palette`, packed like Anvil stores them.
+    pub fn indirect(palette: Vec<T>, data: Vec<i64>) -> Result<Self, WorldError> {
+        if palette.len() == 1 {
+            return Ok(Self::new(palette.into_iter().next().unwrap()));
+        }
+
+        let bits_per_entry = bits_for::<T>(palette.len());
+        check_length::<T>(bits_per_entry, &data)?;
+        Ok(Self {
+            palette: Palette::Indirect(palette),
+            bits_per_entry,
+            data,
+        })
+    }
+
+    /// A container of global ids with `bits_per_entry` bits each, which has to be from 1 to 32.
+    pub fn direct(bits_per_entry: u8, data: Vec<i64>) -> Result<Self, WorldError> {
+        check_length::<T>(bits_per_entry, &data)?;
+        Ok(Self {
+            palette: Palette::Direct,
+            bits_per_entry,
+            data,
+        })
+    }
+
+    pub fn palette(&self) -> &Palette<T> {
+        &self.palette
+    }
+
+    /// 0 for single value palettes.
+    pub fn bits_per_entry(&self) -> u8 {
+        self.bits_per_entry
+    }
+
+    /// The packed entries, empty for single value palettes.
+    pub fn data(&self) -> &[i64] {
+        &self.data
+    }
+
+    /// The value at a position, or `None` for direct palettes and indices past the palette.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<&T> {
+        let id = self.get_id(x, y, z);
+        match &self.palette {
+            Palette::Single(value) => Some(value),
+            Palette::Indirect(palette) => palette.get(id as usize),
+            Palette::Direct => None,
+        }
+    }
+
+    /// The raw entry at a position: an index into the palette, or a global id for direct palettes.
+    pub fn get_id(&self, x: usize, y: usize, z: usize) -> u32 {
+        if self.bits_per_entry == 0 {
+            return 0;
+        }
+
+        let index = T::index(x, y, z);
+        let per_long = 64 / self.bits_per_entry as usize;
+        let long = self.data[index / per_long] as u64;
+        let shift = (index % per_long) * self.bits_per_entry as usize;
+        ((long >> shift) & ((1 << self.bits_per_entry) - 1)) as u32
+    }
+
+    /// Every raw entry, in index order.
+    pub fn ids(&self) -> Vec<u32> {
+        if self.bits_per_entry == 0 {
+            return vec![0; T::ENTRIES];
+        }
+        packed::unpack_longs(&self.data, self.bits_per_entry, T::ENTRIES)
+    }
+
+    /// Sets the value at a position, adding it to the palette and widening the entries if needed.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: T) -> Result<(), WorldError> {
+        if let Palette::Single(current) = &self.palette {
+            if *current == value {
+                return Ok(());
+            }
+            self.palette = Palette::Indirect(vec![current.clone(), value]);
+            self.bits_per_entry = bits_for::<T>(2);
+            self.data = vec![0; packed::packed_len(T::ENTRIES, self.bits_per_entry)];
+            self.set_id(T::index(x, y, z), 1);
+            return Ok(());
+        }
+
+        let Palette::Indirect(palette) = &mut self.palette else {
+            return Err(WorldError::DirectPalette);
+        };
+        let id = match palette.iter().position(|existing| *existing == value) {
+            Some(id) => id,
+            None => {
+                palette.push(value);
+                let len = palette.len();
+                let bits_per_entry = bits_for::<T>(len);
+                if bits_per_entry != self.bits_per_entry {
+                    let ids = self.ids();
+                    self.data = packed::pack_longs(&ids, bits_per_entry);
+                    self.bits_per_entry = bits_per_entry;
+                }
+                len - 1
+            }
+        };
+
+        self.set_id(T::index(x, y, z), id as u32);
+        Ok(())
+    }
+
+    /// Sets every entry to `value`.
+    pub fn fill(&mut self, value: T) {
+        *self = Self::new(value);
+    }
+
+    fn set_id(&mut self, index: usize, id: u32) {
+        let per_long = 64 / self.bits_per_entry as usize;
+        let shift = (index % per_long) * self.bits_per_entry as usize;
+        let mask = ((1u64 << self.bits_per_entry) - 1) << shift;
+        let long = &mut self.data[index / per_long];
+        *long = ((*long as u64 & !mask) | (u64::from(id) << shift & mask)) as i64;
+    }
+}
+
+/// The bits per entry Anvil uses for a palette of `len` values.
+fn bits_for<T: PaletteKind>(len: usize) -> u8 {
+    if len <= 1 {
+        return 0;
+    }
+    let bits = usize::BITS - (len - 1).leading_zeros();
+    T::MIN_BITS.max(bits as u8)
+}
+
+fn check_length<T: PaletteKind>(bits_per_entry: u8, data: &[i64]) -> Result<(), WorldError> {
+    if !(1..=32).contains(&bits_per_entry) {
+        return Err(WorldError::InvalidBitsPerEntry(bits_per_entry));
+    }
+    let expected = packed::packed_len(T::ENTRIES, bits_per_entry);
+    if data.len() != expected {
+        return Err(WorldError::InvalidDataLength {
+            expected,
+            found: data.len(),
+        });
+    }
+    Ok(())
+}
+
+impl<'a, T: PaletteKind + FromNbt<'a>> FromNbt<'a> for PalettedContainer<T> {
+    fn from_nbt(tapes: &NbtTape<'a>, element: &NbtTapeElement<'a>) -> ferrumc_nbt::Result<Self> {
+        let palette: Vec<T> = element
+            .get("palette")
+            .map(|palette| tapes.unpack_list(palette))
+            .transpose()?
+            .unwrap_or_default();
+        if palette.is_empty() {
+            return Err(NBTError::ElementNotFound("palette"));
+        }
+
+        let data = element
+            .get("data")
+            .map(|data| Vec::<i64>::from_nbt(tapes, data))
+            .transpose()?
+            .unwrap_or_default();
+
+        match Self::indirect(palette, data) {
+            Ok(container) => Ok(container),
+            Err(WorldError::InvalidDataLength { found, .. }) => Err(NBTError::InvalidLength(found as i32)),
+            Err(_) => Err(NBTError::InvalidNBTData),
+        }
+    }
+}
+
+/// Written like Anvil stores it. Direct palettes can't list their values, so they're written with
+/// an empty palette and can't be read back.
+impl<T: NBTSerializable> NBTSerializable for PalettedContainer<T> {
+    fn serialize_encoded(&self, buf: &mut Vec<u8>, options: &NBTSerializeOptions<'_>, encoding: NbtEncoding) {
+        crate::write_header(crate::TAG_COMPOUND, buf, options, encoding);
+
+        let palette = match &self.palette {
+            Palette::Single(value) => std::slice::from_ref(value),
+            Palette::Indirect(palette) => palette.as_slice(),
+            Palette::Direct => &[],
+        };
+        palette.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("palette"), encoding);
+        if !self.data.is_empty() {
+            self.data.serialize_encoded(buf, &NBTSerializeOptions::WithHeader("data"), encoding);
+        }
+
+        crate::write_end(buf, options);
+    }
+
+    fn id() -> u8 {
+        crate::TAG_COMPOUND
+    }
+}
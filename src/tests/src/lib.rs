@@ -2,4 +2,5 @@
 
 mod nbt;
 mod net;
-mod ecs;
\ No newline at end of file
+mod ecs;
+mod world;
\ No newline at end of file
@@ -65,7 +65,7 @@ fn create_derive_outline() {
     let data = ferrumc_nbt::decompress_gzip(data).unwrap();
 
     let mut tape = ferrumc_nbt::de::borrow::NbtTape::new(data.as_slice());
-    tape.parse();
+    tape.parse().unwrap();
 
     mod structs {
         #![allow(dead_code)]
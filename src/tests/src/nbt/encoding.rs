@@ -0,0 +1,282 @@
+use ferrumc_macros::{NBTDeserialize, NBTSerialize};
+use ferrumc_nbt::{FromNbt, NBTError, NBTSerializable, NBTSerializeOptions, NbtCompound, NbtEncoding, NbtTape, NbtValue};
+
+const BIGTEST_LE: &[u8] = include_bytes!("../../../../.etc/tests/bigtest_le.nbt");
+const BIGTEST_BEDROCK_NETWORK: &[u8] = include_bytes!("../../../../.etc/tests/bigtest_bedrock_network.nbt");
+const LEVEL_DAT: &[u8] = include_bytes!("../../../../.etc/tests/bedrock_level.dat");
+/// Written by `make_le_fixture.py` with nothing but Python's `struct`, unlike the other samples.
+const LE_FIXTURE: &[u8] = include_bytes!("../../../../.etc/tests/le_fixture.nbt");
+
+#[derive(NBTSerialize, NBTDeserialize, Debug, PartialEq)]
+struct Abilities {
+    flying: bool,
+    #[nbt(rename = "walkSpeed")]
+    walk_speed: f32,
+}
+
+#[derive(NBTSerialize, NBTDeserialize, Debug, PartialEq)]
+struct Level {
+    #[nbt(rename = "LevelName")]
+    name: String,
+    #[nbt(rename = "SpawnX")]
+    spawn_x: i32,
+    #[nbt(rename = "RandomSeed")]
+    seed: i64,
+    #[nbt(rename = "lastOpenedWithVersion")]
+    version: Vec<i32>,
+    abilities: Abilities,
+}
+
+#[derive(NBTSerialize, Debug, PartialEq)]
+struct Nested {
+    name: String,
+}
+
+#[derive(NBTSerialize, Debug, PartialEq)]
+struct Fixture {
+    byte: i8,
+    short: i16,
+    int: i32,
+    long: i64,
+    float: f32,
+    double: f64,
+    bytes: Vec<i8>,
+    string: String,
+    list: Vec<String>,
+    nested: Nested,
+    ints: Vec<i32>,
+    longs: Vec<i64>,
+}
+
+fn bigtest() -> NbtCompound {
+    NbtCompound::from_bytes(include_bytes!("../../../../.etc/bigtest.nbt")).unwrap()
+}
+
+/// Bedrock's `level.dat` starts with its storage version and the length of the NBT after it.
+fn level_dat() -> &'static [u8] {
+    let length = i32::from_le_bytes(LEVEL_DAT[4..8].try_into().unwrap());
+    assert_eq!(length as usize, LEVEL_DAT.len() - 8);
+    &LEVEL_DAT[8..]
+}
+
+#[test]
+fn sample_files() {
+    for (data, encoding) in [
+        (BIGTEST_LE, NbtEncoding::LittleEndian),
+        (BIGTEST_BEDROCK_NETWORK, NbtEncoding::BedrockNetwork),
+    ] {
+        let compound = NbtCompound::from_encoded_bytes(data, encoding).unwrap();
+        assert_eq!(compound, bigtest(), "{encoding:?}");
+        assert_eq!(compound.to_encoded_bytes("Level", encoding), data, "{encoding:?}");
+    }
+
+    // Big-endian is what everything else reads and writes
+    assert_eq!(
+        bigtest().to_encoded_bytes("Level", NbtEncoding::BigEndian),
+        bigtest().to_bytes("Level")
+    );
+}
+
+#[test]
+fn independent_fixture() {
+    let ints = [0x0102_0304, -2, i32::MAX, i32::MIN].into_iter().chain(-8..13).collect::<Vec<_>>();
+    let longs = [0x0102_0304_0506_0708, -2, i64::MAX, i64::MIN].into_iter().chain(-3..3).collect::<Vec<_>>();
+    let fixture = Fixture {
+        byte: -7,
+        short: 0x1234,
+        int: -123_456_789,
+        long: 0x0102_0304_0506_0708,
+        float: 1.5,
+        double: -2.25,
+        bytes: vec![1, 2, -1],
+        string: "héllo".to_string(),
+        list: vec!["a".to_string(), "b".to_string()],
+        nested: Nested {
+            name: "inner".to_string(),
+        },
+        ints: ints.clone(),
+        longs: longs.clone(),
+    };
+
+    let mut bytes = Vec::new();
+    fixture.serialize_encoded(&mut bytes, &NBTSerializeOptions::WithHeader("python"), NbtEncoding::LittleEndian);
+    assert_eq!(bytes, LE_FIXTURE);
+
+    let compound = NbtCompound::from_encoded_bytes(LE_FIXTURE, NbtEncoding::LittleEndian).unwrap();
+    assert_eq!(compound.get("short"), Some(&NbtValue::Short(0x1234)));
+    assert_eq!(compound.get("long"), Some(&NbtValue::Long(0x0102_0304_0506_0708)));
+    assert_eq!(compound.get("double"), Some(&NbtValue::Double(-2.25)));
+    assert_eq!(compound.get("string"), Some(&NbtValue::String("héllo".to_string())));
+    assert_eq!(compound.get("ints"), Some(&NbtValue::IntArray(ints)));
+    assert_eq!(compound.get("longs"), Some(&NbtValue::LongArray(longs)));
+    assert_eq!(compound.to_encoded_bytes("python", NbtEncoding::LittleEndian), LE_FIXTURE);
+}
+
+#[test]
+fn level_dat_values() {
+    let data = level_dat();
+    let mut tape = NbtTape::new(data).with_encoding(NbtEncoding::LittleEndian);
+    tape.parse().unwrap();
+
+    let (_, root) = tape.root.as_ref().unwrap();
+    let level = Level::from_nbt(&tape, root).unwrap();
+    assert_eq!(
+        level,
+        Level {
+            name: "Bedrock level".to_string(),
+            spawn_x: -128,
+            seed: -6645170153374741811,
+            version: vec![1, 21, 30, 3, 0],
+            abilities: Abilities {
+                flying: false,
+                walk_speed: 0.1,
+            },
+        }
+    );
+
+    // Lazy queries go through the same reads
+    let tape = NbtTape::new(data).with_encoding(NbtEncoding::LittleEndian);
+    assert_eq!(tape.query_as::<f32>("abilities.flySpeed").unwrap(), 0.05);
+    assert_eq!(tape.query_as::<i32>("lastOpenedWithVersion[1]").unwrap(), 21);
+    assert_eq!(tape.keys("abilities").unwrap(), ["flying", "walkSpeed", "flySpeed"]);
+
+    assert_eq!(
+        NbtCompound::from_encoded_bytes(data, NbtEncoding::LittleEndian)
+            .unwrap()
+            .to_encoded_bytes("", NbtEncoding::LittleEndian),
+        data
+    );
+}
+
+#[test]
+fn derived_types() {
+    let level = Level {
+        name: "ferrumc".to_string(),
+        spawn_x: -100_000,
+        seed: i64::MIN,
+        version: vec![1, -1, i32::MAX],
+        abilities: Abilities {
+            flying: true,
+            walk_speed: 0.25,
+        },
+    };
+    let mut expected = NbtCompound::new();
+    expected.insert("LevelName", "ferrumc");
+    expected.insert("SpawnX", -100_000);
+    expected.insert("RandomSeed", i64::MIN);
+    expected.insert("lastOpenedWithVersion", vec![1, -1, i32::MAX]);
+    expected.insert(
+        "abilities",
+        [("flying", NbtValue::from(true)), ("walkSpeed", NbtValue::Float(0.25))]
+            .into_iter()
+            .collect::<NbtCompound>(),
+    );
+
+    for encoding in [NbtEncoding::BigEndian, NbtEncoding::LittleEndian, NbtEncoding::BedrockNetwork] {
+        let mut bytes = Vec::new();
+        level.serialize_encoded(&mut bytes, &NBTSerializeOptions::WithHeader("level"), encoding);
+        assert_eq!(bytes, expected.to_encoded_bytes("level", encoding), "{encoding:?}");
+
+        let mut tape = NbtTape::new(&bytes).with_encoding(encoding);
+        tape.parse().unwrap();
+        let (_, root) = tape.root.as_ref().unwrap();
+        assert_eq!(Level::from_nbt(&tape, root).unwrap(), level, "{encoding:?}");
+
+        // Without a name, and without a header at all
+        let mut network = Vec::new();
+        level.serialize_encoded(&mut network, &NBTSerializeOptions::Network, encoding);
+        assert_eq!(network[0], 10);
+        let name_length = if encoding == NbtEncoding::BedrockNetwork { 1 } else { 2 };
+        assert_eq!(network[1..], bytes[1 + name_length + 5..]);
+
+        let mut payload = Vec::new();
+        level.serialize_encoded(&mut payload, &NBTSerializeOptions::None, encoding);
+        assert_eq!(payload, network[1..]);
+    }
+}
+
+#[test]
+fn varints() {
+    let encoded = |val: i32| {
+        let mut compound = NbtCompound::new();
+        compound.insert("", val);
+        let bytes = compound.to_encoded_bytes("", NbtEncoding::BedrockNetwork);
+        // The root and entry headers, then the int, then the end tag
+        bytes[4..bytes.len() - 1].to_vec()
+    };
+
+    assert_eq!(encoded(0), [0x00]);
+    assert_eq!(encoded(-1), [0x01]);
+    assert_eq!(encoded(1), [0x02]);
+    assert_eq!(encoded(64), [0x80, 0x01]);
+    assert_eq!(encoded(i32::MAX), [0xFE, 0xFF, 0xFF, 0xFF, 0x0F]);
+    assert_eq!(encoded(i32::MIN), [0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+
+    let parse = |int: &[u8]| {
+        let mut data = vec![10, 0, 3, 0];
+        data.extend_from_slice(int);
+        data.push(0);
+        NbtCompound::from_encoded_bytes(&data, NbtEncoding::BedrockNetwork).map(|compound| compound.get("").cloned())
+    };
+
+    assert_eq!(parse(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]).unwrap(), Some(NbtValue::Int(i32::MIN)));
+    // Too many bytes, and too many bits for an int
+    assert!(matches!(parse(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00]), Err(NBTError::InvalidVarInt)));
+    assert!(matches!(parse(&[0xFF, 0xFF, 0xFF, 0xFF, 0x1F]), Err(NBTError::InvalidVarInt)));
+}
+
+#[test]
+fn truncated_data() {
+    for (data, encoding) in [
+        (BIGTEST_LE, NbtEncoding::LittleEndian),
+        (BIGTEST_BEDROCK_NETWORK, NbtEncoding::BedrockNetwork),
+        (level_dat(), NbtEncoding::LittleEndian),
+    ] {
+        for len in 0..data.len() {
+            let mut tape = NbtTape::new(&data[..len]).with_encoding(encoding);
+            assert!(tape.parse().is_err(), "parsed {len} of {} bytes as {encoding:?}", data.len());
+        }
+    }
+
+    // Little-endian data isn't valid big-endian data
+    assert!(NbtCompound::from_encoded_bytes(level_dat(), NbtEncoding::BigEndian).is_err());
+}
+
+#[test]
+fn arrays() {
+    let ints = (0..37).map(|i| i * -0x0102_0304).collect::<Vec<i32>>();
+    let longs = (0..37).map(|i| i * -0x0102_0304_0506_0708).collect::<Vec<i64>>();
+    let mut compound = NbtCompound::new();
+    compound.insert("ints", ints.clone());
+    compound.insert("longs", longs.clone());
+
+    for encoding in [NbtEncoding::BigEndian, NbtEncoding::LittleEndian, NbtEncoding::BedrockNetwork] {
+        let bytes = compound.to_encoded_bytes("", encoding);
+        let parsed = NbtCompound::from_encoded_bytes(&bytes, encoding).unwrap();
+        assert_eq!(parsed, compound, "{encoding:?}");
+    }
+
+    // Written straight after the length, in the byte order of the encoding
+    let mut single = NbtCompound::new();
+    single.insert("", vec![0x0102_0304, -2]);
+    let be = single.to_encoded_bytes("", NbtEncoding::BigEndian);
+    assert_eq!(be[6..be.len() - 1], [0, 0, 0, 2, 1, 2, 3, 4, 0xFF, 0xFF, 0xFF, 0xFE]);
+    let le = single.to_encoded_bytes("", NbtEncoding::LittleEndian);
+    assert_eq!(le[6..le.len() - 1], [2, 0, 0, 0, 4, 3, 2, 1, 0xFE, 0xFF, 0xFF, 0xFF]);
+}
+
+#[test]
+#[should_panic(expected = "longer than 65535")]
+fn strings_too_long() {
+    let mut compound = NbtCompound::new();
+    compound.insert("", "a".repeat(u16::MAX as usize + 1));
+    compound.to_encoded_bytes("", NbtEncoding::BigEndian);
+}
+
+#[test]
+fn long_bedrock_strings() {
+    let mut compound = NbtCompound::new();
+    compound.insert("", "é".repeat(u16::MAX as usize));
+    let bytes = compound.to_encoded_bytes("", NbtEncoding::BedrockNetwork);
+    assert_eq!(NbtCompound::from_encoded_bytes(&bytes, NbtEncoding::BedrockNetwork).unwrap(), compound);
+}
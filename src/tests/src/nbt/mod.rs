@@ -1,3 +1,10 @@
 mod de;
+mod encoding;
 mod mixed;
+mod network;
+mod query;
 mod ser;
+mod serde_backend;
+mod snbt;
+mod tape;
+mod value;
@@ -0,0 +1,110 @@
+use ferrumc_macros::{NBTDeserialize, NBTSerialize, NetDecode, NetEncode};
+use ferrumc_nbt::{Nbt, NbtCompound, NbtTape, NbtValue};
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use std::io::Write;
+
+#[derive(NBTSerialize, NBTDeserialize, Debug, Clone, PartialEq)]
+struct CustomData {
+    name: String,
+    level: i32,
+}
+
+#[derive(NetEncode, NetDecode, Debug, PartialEq)]
+struct ItemPacket {
+    data: Nbt<CustomData>,
+    count: VarInt,
+}
+
+fn encode<T: NetEncode>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    value.encode(&mut buf, &NetEncodeOpts::None).unwrap();
+    buf
+}
+
+fn compound() -> NbtCompound {
+    let mut compound = NbtCompound::new();
+    compound.insert("text", "hello");
+    compound.insert("extra", vec![NbtValue::from("a"), NbtValue::from("b")]);
+    compound.insert("nested", [("bold", NbtValue::from(true))].into_iter().collect::<NbtCompound>());
+    compound
+}
+
+#[test]
+fn nameless_root() {
+    let bytes = encode(&Nbt(compound()));
+    // No name after the tag id
+    assert_eq!(&bytes[..4], &[10, 8, 0, 4]);
+
+    let mut data = bytes.clone();
+    data.extend_from_slice(&[0xAA, 0xBB]);
+
+    let mut tape = NbtTape::new(&data);
+    assert_eq!(tape.parse_network().unwrap(), bytes.len());
+    assert_eq!(NbtCompound::from_tape(&tape).unwrap(), compound());
+}
+
+#[test]
+fn any_root_type() {
+    let bytes = encode(&Nbt("plain text".to_string()));
+    assert_eq!(bytes[0], 8);
+    let decoded = Nbt::<String>::decode(&mut bytes.as_slice(), &NetDecodeOpts::None).unwrap();
+    assert_eq!(decoded.0, "plain text");
+
+    let decoded = Nbt::<NbtValue>::decode(&mut encode(&Nbt(NbtValue::Int(7))).as_slice(), &NetDecodeOpts::None).unwrap();
+    assert_eq!(decoded.0, NbtValue::Int(7));
+
+    // An `End` root means there's nothing there
+    let decoded = Nbt::<Option<NbtCompound>>::decode(&mut [0u8].as_slice(), &NetDecodeOpts::None).unwrap();
+    assert_eq!(decoded.0, None);
+}
+
+#[test]
+fn packet_fields() {
+    let packet = ItemPacket {
+        data: Nbt(CustomData { name: "ferrumc".to_string(), level: 3 }),
+        count: VarInt::new(64),
+    };
+
+    let mut bytes = encode(&packet);
+    bytes.push(0xFF);
+
+    let mut reader = bytes.as_slice();
+    let decoded = ItemPacket::decode(&mut reader, &NetDecodeOpts::None).unwrap();
+    assert_eq!(decoded, packet);
+    // The reader stops right after the packet
+    assert_eq!(reader, [0xFF]);
+}
+
+#[tokio::test]
+async fn async_decode() {
+    let bytes = encode(&Nbt(compound()));
+
+    let mut reader = bytes.as_slice();
+    let decoded = Nbt::<NbtCompound>::decode_async(&mut reader, &NetDecodeOpts::None).await.unwrap();
+    assert_eq!(decoded.0, compound());
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn invalid_data() {
+    let bytes = encode(&Nbt(compound()));
+    for len in 0..bytes.len() {
+        assert!(Nbt::<NbtCompound>::decode(&mut &bytes[..len], &NetDecodeOpts::None).is_err());
+    }
+
+    // Lists nested deeper than the limit
+    let mut bomb = vec![9];
+    for _ in 0..1000 {
+        bomb.extend_from_slice(&[9, 0, 0, 0, 1]);
+    }
+    bomb.extend_from_slice(&[0, 0, 0, 0, 0]);
+    let err = Nbt::<NbtValue>::decode(&mut bomb.as_slice(), &NetDecodeOpts::None).unwrap_err();
+    assert!(err.to_string().contains("nested deeper"), "{err}");
+
+    // A byte array claiming to be larger than the size limit
+    let huge = [7, 0x7F, 0xFF, 0xFF, 0xFF];
+    let err = Nbt::<NbtValue>::decode(&mut huge.as_slice(), &NetDecodeOpts::None).unwrap_err();
+    assert!(err.to_string().contains("larger than the limit"), "{err}");
+}
@@ -0,0 +1,105 @@
+use ferrumc_macros::NBTDeserialize;
+use ferrumc_nbt::{NBTError, NbtCompound, NbtTape, NbtValue};
+
+const CHUNK: &[u8] = include_bytes!("../../../../.etc/benches/chunk_0-0.nbt");
+
+#[derive(NBTDeserialize, Debug, PartialEq)]
+struct BlockState<'a> {
+    #[nbt(rename = "Name")]
+    name: &'a str,
+}
+
+fn chunk() -> NbtCompound {
+    let mut tape = NbtTape::new(CHUNK);
+    tape.parse().unwrap();
+    NbtCompound::from_tape(&tape).unwrap()
+}
+
+fn query(tape: &NbtTape, path: &str) -> Option<NbtValue> {
+    let element = tape.query(path).unwrap()?;
+    Some(NbtValue::from_tape_element(tape, &element).unwrap())
+}
+
+#[test]
+fn same_as_parsing() {
+    let chunk = chunk();
+    // No need to parse first
+    let tape = NbtTape::new(CHUNK);
+
+    for (path, dotted) in [
+        ("xPos", "xPos"),
+        ("Heightmaps.MOTION_BLOCKING", "Heightmaps.MOTION_BLOCKING"),
+        ("sections", "sections"),
+        ("sections[3]", "sections.3"),
+        ("sections[3].block_states.palette", "sections.3.block_states.palette"),
+        ("sections[3].block_states.palette[0].Name", "sections.3.block_states.palette.0.Name"),
+    ] {
+        assert_eq!(query(&tape, path).as_ref(), chunk.get_path(dotted), "{path}");
+        assert_eq!(query(&tape, dotted), query(&tape, path), "{dotted}");
+    }
+
+    assert_eq!(query(&tape, "").as_ref(), Some(&NbtValue::Compound(chunk)));
+}
+
+#[test]
+fn typed() {
+    let tape = NbtTape::new(CHUNK);
+
+    assert_eq!(tape.query_as::<i32>("xPos").unwrap(), 0);
+    assert_eq!(tape.query_as::<i32>("zPos").unwrap(), 32);
+    assert_eq!(tape.query_as::<Vec<i64>>("Heightmaps.MOTION_BLOCKING").unwrap().len(), 37);
+
+    let palette = tape.query_as::<Vec<BlockState>>("sections[3].block_states.palette").unwrap();
+    assert!(!palette.is_empty());
+    assert_eq!(palette[0].name, tape.query_as::<&str>("sections[3].block_states.palette[0].Name").unwrap());
+
+    assert!(matches!(tape.query_as::<i32>("sections[3]"), Err(NBTError::TypeMismatch { .. })));
+    assert!(matches!(tape.query_as::<i32>("yPos2"), Err(NBTError::PathNotFound(path)) if path == "yPos2"));
+}
+
+#[test]
+fn keys() {
+    let chunk = chunk();
+    let tape = NbtTape::new(CHUNK);
+
+    assert_eq!(tape.keys("").unwrap(), chunk.iter().map(|(key, _)| key).collect::<Vec<_>>());
+
+    let NbtValue::Compound(section) = chunk.get_path("sections.3").unwrap() else {
+        panic!("sections aren't compounds");
+    };
+    assert_eq!(tape.keys("sections[3]").unwrap(), section.iter().map(|(key, _)| key).collect::<Vec<_>>());
+
+    assert!(matches!(tape.keys("xPos"), Err(NBTError::TypeMismatch { found: "Int", .. })));
+    assert!(matches!(tape.keys("nothing"), Err(NBTError::PathNotFound(_))));
+}
+
+#[test]
+fn missing() {
+    let tape = NbtTape::new(CHUNK);
+
+    for path in ["nothing", "sections[1000]", "sections.x", "xPos.y", "xPos[0]", "Heightmaps.nothing.y"] {
+        assert!(tape.query(path).unwrap().is_none(), "{path}");
+    }
+}
+
+#[test]
+fn invalid() {
+    let tape = NbtTape::new(CHUNK);
+    for path in ["a..b", ".", "sections[", "sections[x]", "sections[-1]", "sections[0]b"] {
+        assert!(matches!(tape.query(path), Err(NBTError::InvalidPath(_))), "{path}");
+    }
+
+    // The root has to be a compound
+    assert!(NbtTape::new(&[8, 0, 0]).query("").is_err());
+    // Truncated before the value is found
+    assert!(NbtTape::new(&CHUNK[..CHUNK.len() / 2]).query("nothing").is_err());
+    // Lists nested deeper than the limit on the way
+    let mut data = vec![10, 0, 0, 9, 0, 1, b'l'];
+    for _ in 0..10 {
+        data.extend_from_slice(&[9, 0, 0, 0, 1]);
+    }
+    data.extend_from_slice(&[1, 0, 0, 0, 0, 0]);
+    let tape = NbtTape::new(&data).with_max_depth(4);
+    assert!(matches!(tape.query("l[0][0][0][0][0]"), Err(NBTError::DepthLimitExceeded(4))));
+    assert!(matches!(tape.query("x"), Err(NBTError::DepthLimitExceeded(4))));
+}
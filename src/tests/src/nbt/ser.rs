@@ -31,7 +31,7 @@ fn derive_macro() {
     let buf = test.serialize_with_header();
 
     let mut parser = ferrumc_nbt::de::borrow::NbtTape::new(&buf);
-    parser.parse();
+    parser.parse().unwrap();
 
     let some_list = parser.get("some_list").unwrap();
     // let some_list : &[i32] = parser.unpack_list_sliced(some_list).unwrap();
@@ -62,7 +62,7 @@ fn derive_macro_nested() {
     let buf = test2.serialize_with_header();
 
     let mut parser = ferrumc_nbt::de::borrow::NbtTape::new(&buf);
-    parser.parse();
+    parser.parse().unwrap();
 
     let test = parser.get("test").unwrap();
     let hello = test.get("hello").unwrap();
@@ -107,7 +107,7 @@ fn derive_macro_nested_with_list() {
     let buf = test2.serialize_with_header();
 
     let mut parser = ferrumc_nbt::de::borrow::NbtTape::new(&buf);
-    parser.parse();
+    parser.parse().unwrap();
 
     let test = parser.get("test").unwrap();
     let hello = test.get("hello").unwrap();
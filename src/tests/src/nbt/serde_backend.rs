@@ -0,0 +1,188 @@
+use ferrumc_nbt::{from_slice, from_value, to_bytes, to_value, NbtCompound, NbtTape, NbtValue, SerdeOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Player {
+    name: String,
+    health: f32,
+    pos: (f64, f64, f64),
+    uuid: [i32; 4],
+    inventory: Vec<Item>,
+    nickname: Option<String>,
+    flying: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Item {
+    id: String,
+    count: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum Action {
+    Idle,
+    Jump(f32),
+    Walk { x: i32, z: i32 },
+}
+
+fn player() -> Player {
+    Player {
+        name: "ferrumc_bot".to_string(),
+        health: 20.0,
+        pos: (0.5, 64.0, -3.5),
+        uuid: [1, 2, 3, 4],
+        inventory: vec![
+            Item { id: "minecraft:stone".to_string(), count: 64 },
+            Item { id: "minecraft:dirt".to_string(), count: 200 },
+        ],
+        nickname: None,
+        flying: true,
+    }
+}
+
+fn compound(value: Option<NbtValue>) -> NbtCompound {
+    match value {
+        Some(NbtValue::Compound(compound)) => compound,
+        other => panic!("expected a compound, got {other:?}"),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let options = SerdeOptions::default();
+    let bytes = to_bytes(&player(), &options).unwrap();
+    // Network NBT, so no name after the tag id
+    assert_eq!(&bytes[..2], &[10, 8]);
+    assert_eq!(from_slice::<Player>(&bytes, &options).unwrap(), player());
+
+    let options = SerdeOptions::named("player");
+    let bytes = to_bytes(&player(), &options).unwrap();
+    assert_eq!(&bytes[..9], &[10, 0, 6, b'p', b'l', b'a', b'y', b'e', b'r']);
+    assert_eq!(from_slice::<Player>(&bytes, &options).unwrap(), player());
+
+    // The same bytes the tape and tree write and read
+    let mut tape = NbtTape::new(&bytes);
+    tape.parse().unwrap();
+    let tree = NbtCompound::from_tape(&tape).unwrap();
+    assert_eq!(tree.to_bytes("player"), bytes);
+}
+
+#[test]
+fn mapping() {
+    let player = compound(to_value(&player(), &SerdeOptions::default()).unwrap());
+
+    assert_eq!(player.get("health"), Some(&NbtValue::Float(20.0)));
+    assert_eq!(player.get("flying"), Some(&NbtValue::Byte(1)));
+    assert_eq!(player.get("uuid"), Some(&NbtValue::IntArray(vec![1, 2, 3, 4])));
+    // `None` fields are left out
+    assert_eq!(player.get("nickname"), None);
+    // Unsigned values keep their bits in the signed tag of the same width
+    assert_eq!(player.get_path("inventory.1.count"), Some(&NbtValue::Byte(200u8 as i8)));
+    assert_eq!(
+        player.get("pos"),
+        Some(&NbtValue::List(vec![NbtValue::Double(0.5), NbtValue::Double(64.0), NbtValue::Double(-3.5)]))
+    );
+}
+
+#[test]
+fn typed_arrays() {
+    let values = vec![i64::MIN, 0, i64::MAX];
+    let bytes = vec![-1i8, 0, 1];
+
+    let options = SerdeOptions::default();
+    assert_eq!(to_value(&values, &options).unwrap(), Some(NbtValue::LongArray(values.clone())));
+    assert_eq!(to_value(&bytes, &options).unwrap(), Some(NbtValue::ByteArray(bytes.clone())));
+
+    let lists = SerdeOptions {
+        typed_arrays: false,
+        ..SerdeOptions::default()
+    };
+    let list = to_value(&values, &lists).unwrap().unwrap();
+    assert_eq!(list, NbtValue::List(values.iter().copied().map(NbtValue::Long).collect()));
+
+    // Either is read back, whatever the options
+    assert_eq!(from_value::<Vec<i64>>(&list, &options).unwrap(), values);
+    assert_eq!(from_value::<Vec<i64>>(&NbtValue::LongArray(values.clone()), &lists).unwrap(), values);
+}
+
+#[test]
+fn bytes_as_bools() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Style {
+        bold: Option<bool>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Text {
+        text: String,
+        #[serde(flatten)]
+        style: Style,
+    }
+
+    let text = Text {
+        text: "hi".to_string(),
+        style: Style { bold: Some(true) },
+    };
+    let value = to_value(&text, &SerdeOptions::default()).unwrap().unwrap();
+
+    // Behind `flatten`, serde can't say it wants a bool
+    assert!(from_value::<Text>(&value, &SerdeOptions::default()).is_err());
+
+    let options = SerdeOptions {
+        bytes_as_bools: true,
+        ..SerdeOptions::default()
+    };
+    assert_eq!(from_value::<Text>(&value, &options).unwrap(), text);
+}
+
+#[test]
+fn enums() {
+    let options = SerdeOptions::default();
+    for action in [Action::Idle, Action::Jump(0.42), Action::Walk { x: 1, z: -1 }] {
+        let value = to_value(&action, &options).unwrap().unwrap();
+        assert_eq!(from_value::<Action>(&value, &options).unwrap(), action);
+    }
+
+    assert_eq!(to_value(&Action::Idle, &options).unwrap(), Some(NbtValue::from("Idle")));
+    let walk = compound(to_value(&Action::Walk { x: 1, z: -1 }, &options).unwrap());
+    assert_eq!(walk.get_path("Walk.z"), Some(&NbtValue::Int(-1)));
+}
+
+#[test]
+fn maps() {
+    let counts: HashMap<i32, String> = [(1, "one".to_string()), (-2, "minus two".to_string())].into_iter().collect();
+    let options = SerdeOptions::default();
+
+    let value = compound(to_value(&counts, &options).unwrap());
+    assert_eq!(value.get("-2"), Some(&NbtValue::from("minus two")));
+    assert_eq!(from_value::<HashMap<i32, String>>(&NbtValue::Compound(value), &options).unwrap(), counts);
+}
+
+#[test]
+fn nothing() {
+    let options = SerdeOptions::default();
+    assert_eq!(to_bytes(&None::<i32>, &options).unwrap(), [0]);
+    assert_eq!(from_slice::<Option<i32>>(&[0], &options).unwrap(), None);
+    assert_eq!(from_slice::<Option<i32>>(&to_bytes(&Some(5), &options).unwrap(), &options).unwrap(), Some(5));
+}
+
+#[test]
+fn invalid() {
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Mixed {
+        Int(i32),
+        Text(String),
+    }
+
+    let options = SerdeOptions::default();
+    // Lists can only hold one type
+    assert!(to_value(&vec![Mixed::Int(1), Mixed::Text("a".to_string())], &options).is_err());
+    // Files need a compound at the root
+    assert!(to_bytes(&5, &SerdeOptions::named("")).is_err());
+    // Wrong types
+    let bytes = to_bytes(&player(), &options).unwrap();
+    assert!(from_slice::<Vec<i32>>(&bytes, &options).is_err());
+    assert!(from_slice::<Player>(&bytes[..bytes.len() - 1], &options).is_err());
+}
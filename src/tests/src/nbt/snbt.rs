@@ -0,0 +1,160 @@
+use ferrumc_nbt::{NBTError, NbtCompound, NbtValue};
+
+fn parse(snbt: &str) -> NbtValue {
+    snbt.parse().unwrap()
+}
+
+/// The line and column of the error `snbt` fails with.
+fn error_position(snbt: &str) -> (usize, usize) {
+    match snbt.parse::<NbtValue>() {
+        Err(NBTError::InvalidSnbt { line, column, .. }) => (line, column),
+        other => panic!("Expected an SNBT error for {snbt:?}, got {other:?}"),
+    }
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(parse("1b"), NbtValue::Byte(1));
+    assert_eq!(parse("-128B"), NbtValue::Byte(-128));
+    assert_eq!(parse("300s"), NbtValue::Short(300));
+    assert_eq!(parse("-7"), NbtValue::Int(-7));
+    assert_eq!(parse("+7"), NbtValue::Int(7));
+    assert_eq!(parse("9000000000L"), NbtValue::Long(9_000_000_000));
+    assert_eq!(parse("1.5f"), NbtValue::Float(1.5));
+    assert_eq!(parse("2F"), NbtValue::Float(2.0));
+    assert_eq!(parse("1e3f"), NbtValue::Float(1000.0));
+    assert_eq!(parse("0.25"), NbtValue::Double(0.25));
+    assert_eq!(parse(".5d"), NbtValue::Double(0.5));
+    assert_eq!(parse("3d"), NbtValue::Double(3.0));
+    assert_eq!(parse("true"), NbtValue::Byte(1));
+    assert_eq!(parse("false"), NbtValue::Byte(0));
+
+    // Anything else is an unquoted string
+    assert_eq!(parse("1e3"), NbtValue::String("1e3".to_string()));
+    assert_eq!(parse("1.2.3"), NbtValue::String("1.2.3".to_string()));
+    assert_eq!(parse("minecraft.stone"), NbtValue::String("minecraft.stone".to_string()));
+
+    assert_eq!(error_position("128b"), (1, 1));
+    assert_eq!(error_position("[1, 2147483648]"), (1, 5));
+}
+
+#[test]
+fn strings_and_keys() {
+    assert_eq!(parse(r#""say \"hi\"""#), NbtValue::String(r#"say "hi""#.to_string()));
+    assert_eq!(parse(r"'it\'s \\ fine'"), NbtValue::String(r"it's \ fine".to_string()));
+    assert_eq!(parse(r#"'"quoted"'"#), NbtValue::String(r#""quoted""#.to_string()));
+    assert_eq!(parse(r#""über""#), NbtValue::String("über".to_string()));
+
+    let compound = parse(r#"{ unquoted : 1 , "quoted key": 2, 'single': 3, "": 4 }"#);
+    let compound = compound.as_compound().unwrap();
+    assert_eq!(compound.get("unquoted"), Some(&NbtValue::Int(1)));
+    assert_eq!(compound.get("quoted key"), Some(&NbtValue::Int(2)));
+    assert_eq!(compound.get("single"), Some(&NbtValue::Int(3)));
+    assert_eq!(compound.get(""), Some(&NbtValue::Int(4)));
+}
+
+#[test]
+fn lists_and_arrays() {
+    assert_eq!(parse("[]"), NbtValue::List(vec![]));
+    assert_eq!(parse("[1, 2]"), NbtValue::List(vec![NbtValue::Int(1), NbtValue::Int(2)]));
+    assert_eq!(parse("[[], [1b]]"), NbtValue::List(vec![
+        NbtValue::List(vec![]),
+        NbtValue::List(vec![NbtValue::Byte(1)]),
+    ]));
+    assert_eq!(parse("[B; 1b, -2b]"), NbtValue::ByteArray(vec![1, -2]));
+    assert_eq!(parse("[I;]"), NbtValue::IntArray(vec![]));
+    assert_eq!(parse("[I; 1, 2, 3]"), NbtValue::IntArray(vec![1, 2, 3]));
+    assert_eq!(parse("[L;1L,-1L]"), NbtValue::LongArray(vec![1, -1]));
+
+    // `B`, `I` and `L` are just strings without the semicolon
+    assert_eq!(parse("[B, I]"), NbtValue::List(vec![
+        NbtValue::String("B".to_string()),
+        NbtValue::String("I".to_string()),
+    ]));
+}
+
+#[test]
+fn error_positions() {
+    assert_eq!(error_position("[1, 2b]"), (1, 5));
+    assert_eq!(error_position("[I; 1, 2L]"), (1, 8));
+    assert_eq!(error_position("{a: 1,\n b 2}"), (2, 4));
+    assert_eq!(error_position("{a: 1, a: 2}"), (1, 8));
+    assert_eq!(error_position("{a: 1"), (1, 6));
+    assert_eq!(error_position("\"unterminated"), (1, 1));
+    assert_eq!(error_position(r#""\n""#), (1, 2));
+    assert_eq!(error_position("{a: 1} b"), (1, 8));
+    assert_eq!(error_position("{: 1}"), (1, 2));
+    assert_eq!(error_position(""), (1, 1));
+
+    assert!("[1]".parse::<NbtCompound>().is_err());
+}
+
+#[test]
+fn nesting_is_limited() {
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(nested(512).parse::<NbtValue>().is_ok());
+    assert_eq!(error_position(&nested(513)), (1, 513));
+
+    let compounds = format!("{}{}", "{a:".repeat(600), "}".repeat(600));
+    assert!(matches!(compounds.parse::<NbtCompound>(), Err(NBTError::InvalidSnbt { .. })));
+
+    // Would overflow the stack without the limit
+    assert!(matches!("[".repeat(100_000).parse::<NbtValue>(), Err(NBTError::InvalidSnbt { .. })));
+}
+
+#[test]
+fn printing() {
+    let item: NbtCompound = r#"{Count: 1b, id: "minecraft:stone", tag: {Damage: 0, Lore: ["a", "b \"c\""]}}"#
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        item.to_string(),
+        r#"{Count:1b,id:"minecraft:stone",tag:{Damage:0,Lore:["a","b \"c\""]}}"#
+    );
+    assert_eq!(
+        format!("{item:#}"),
+        r#"{
+    Count: 1b,
+    id: "minecraft:stone",
+    tag: {
+        Damage: 0,
+        Lore: [
+            "a",
+            "b \"c\""
+        ]
+    }
+}"#
+    );
+
+    let mut compound = NbtCompound::new();
+    compound.insert("has space", 1.5f32);
+    compound.insert("bytes", vec![1i8, 2]);
+    compound.insert("longs", vec![3i64]);
+    compound.insert("empty", NbtCompound::new());
+    assert_eq!(
+        compound.to_string(),
+        r#"{"has space":1.5f,bytes:[B;1b,2b],longs:[L;3L],empty:{}}"#
+    );
+    assert_eq!(NbtValue::Double(2.0).to_string(), "2d");
+}
+
+#[test]
+fn round_trip_fixtures() {
+    let fixtures: [&[u8]; 4] = [
+        include_bytes!("../../../../.etc/hello_world.nbt"),
+        include_bytes!("../../../../.etc/bigtest.nbt"),
+        include_bytes!("../../../../.etc/TheAIguy_.nbt"),
+        include_bytes!("../../../../.etc/codec.nbt"),
+    ];
+
+    for fixture in fixtures {
+        let compound = NbtCompound::from_bytes(fixture).unwrap();
+
+        let compact = compound.to_string();
+        assert_eq!(compact.parse::<NbtCompound>().unwrap(), compound);
+
+        let pretty = format!("{compound:#}");
+        assert_eq!(pretty.parse::<NbtCompound>().unwrap(), compound);
+    }
+}
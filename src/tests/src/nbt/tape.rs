@@ -0,0 +1,104 @@
+use ferrumc_nbt::{FromNbt, NBTError, NbtTape};
+
+fn parse(data: &[u8]) -> Result<(), NBTError> {
+    NbtTape::new(data).parse()
+}
+
+/// A root compound holding `depth - 1` lists nested inside each other.
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut data = vec![10, 0, 0, 9, 0, 1, b'l'];
+    for _ in 1..depth - 1 {
+        data.extend_from_slice(&[9, 0, 0, 0, 1]);
+    }
+    data.extend_from_slice(&[1, 0, 0, 0, 0]);
+    data.push(0);
+    data
+}
+
+#[test]
+fn truncated_data() {
+    let data = include_bytes!("../../../../.etc/bigtest.nbt");
+    let data = ferrumc_nbt::decompress_gzip(data).unwrap();
+
+    assert!(parse(&data).is_ok());
+    for len in 0..data.len() {
+        assert!(parse(&data[..len]).is_err(), "parsed {len} of {} bytes", data.len());
+    }
+}
+
+#[test]
+fn invalid_data() {
+    assert!(matches!(parse(&[]), Err(NBTError::ReachedEOF)));
+    assert!(matches!(parse(&[8, 0, 0, 0, 0]), Err(NBTError::InvalidRootCompound(8))));
+    assert!(matches!(parse(&[10, 0, 0, 13, 0, 0, 0]), Err(NBTError::InvalidTagType(13))));
+    // A byte array with a negative length
+    assert!(matches!(parse(&[10, 0, 0, 7, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0]), Err(NBTError::InvalidLength(-1))));
+    // A list of `End` tags that claims to hold elements
+    assert!(matches!(parse(&[10, 0, 0, 9, 0, 0, 0, 0x7F, 0xFF, 0xFF, 0xFF, 0]), Err(NBTError::InvalidTagType(0))));
+    // A key that isn't UTF-8
+    assert!(matches!(parse(&[10, 0, 0, 1, 0, 1, 0xFF, 1, 0]), Err(NBTError::InvalidUTF8(_))));
+}
+
+#[test]
+fn unknown_tag_types() {
+    use ferrumc_nbt::de::borrow::NbtTag;
+
+    for tag in 0..=12 {
+        assert_eq!(NbtTag::try_from(tag).unwrap() as u8, tag);
+    }
+    for tag in 13..=u8::MAX {
+        assert!(matches!(NbtTag::try_from(tag), Err(NBTError::InvalidTagType(invalid)) if invalid == tag));
+    }
+}
+
+#[test]
+fn depth_limit() {
+    assert!(parse(&nested_lists(512)).is_ok());
+    assert!(matches!(parse(&nested_lists(513)), Err(NBTError::DepthLimitExceeded(512))));
+
+    let data = nested_lists(10);
+    assert!(NbtTape::new(&data).with_max_depth(10).parse().is_ok());
+    assert!(matches!(NbtTape::new(&data).with_max_depth(9).parse(), Err(NBTError::DepthLimitExceeded(9))));
+}
+
+#[test]
+fn size_limit() {
+    let data = include_bytes!("../../../../.etc/hello_world.nbt");
+
+    assert!(NbtTape::new(data).with_max_size(data.len()).parse().is_ok());
+    assert!(matches!(
+        NbtTape::new(data).with_max_size(data.len() - 1).parse(),
+        Err(NBTError::SizeLimitExceeded(_))
+    ));
+}
+
+#[test]
+fn java_strings() {
+    // `𝔸` written by Java, as two surrogates
+    let data = [10, 0, 0, 8, 0, 1, b's', 0, 6, 0xED, 0xA0, 0xB5, 0xED, 0xB4, 0xB8, 0];
+    let mut tape = NbtTape::new(&data);
+    tape.parse().unwrap();
+
+    let string = tape.get("s").unwrap();
+    assert_eq!(String::from_nbt(&tape, string).unwrap(), "𝔸");
+    assert!(matches!(<&str>::from_nbt(&tape, string), Err(NBTError::UnborrowableString)));
+
+    // A lone surrogate isn't a valid string in either encoding
+    assert!(matches!(parse(&[10, 0, 0, 8, 0, 1, b's', 0, 3, 0xED, 0xA0, 0xB5, 0]), Err(NBTError::InvalidUTF8(_))));
+}
+
+#[test]
+fn wrong_list_types() {
+    // An int array can't be read as a list of longs, nor a list of ints as bytes
+    let data = [10, 0, 0, 11, 0, 1, b'a', 0, 0, 0, 1, 0, 0, 0, 7, 9, 0, 1, b'l', 3, 0, 0, 0, 1, 0, 0, 0, 7, 0];
+    let mut tape = NbtTape::new(&data);
+    tape.parse().unwrap();
+
+    let array = tape.get("a").unwrap();
+    assert_eq!(Vec::<i32>::from_nbt(&tape, array).unwrap(), vec![7]);
+    assert!(Vec::<i64>::from_nbt(&tape, array).is_err());
+
+    let list = tape.get("l").unwrap();
+    assert_eq!(Vec::<i32>::from_nbt(&tape, list).unwrap(), vec![7]);
+    assert!(Vec::<i8>::from_nbt(&tape, list).is_err());
+}
@@ -0,0 +1,123 @@
+use ferrumc_macros::{NBTDeserialize, NBTSerialize};
+use ferrumc_nbt::{NbtCompound, NbtTape, NbtValue};
+
+fn player() -> NbtCompound {
+    let inventory = vec![
+        NbtValue::from([("Slot", NbtValue::Byte(0)), ("id", "minecraft:stone".into())].into_iter().collect::<NbtCompound>()),
+        NbtValue::from([("Slot", NbtValue::Byte(1)), ("id", "minecraft:dirt".into())].into_iter().collect::<NbtCompound>()),
+    ];
+
+    let mut player = NbtCompound::new();
+    player.insert("Name", "ferrumc_bot");
+    player.insert("Health", 20.0f32);
+    player.insert("Pos", vec![NbtValue::Double(0.5), NbtValue::Double(64.0), NbtValue::Double(-3.5)]);
+    player.insert("Inventory", inventory);
+    player.insert("UUID", vec![1, 2, 3, 4]);
+    player.insert("Seeds", vec![i64::MIN, i64::MAX]);
+    player.insert("Flags", vec![-1i8, 0, 1]);
+    player.insert("Empty", Vec::<NbtValue>::new());
+    player
+}
+
+fn parse(data: &[u8]) -> NbtCompound {
+    let mut tape = NbtTape::new(data);
+    tape.parse().unwrap();
+    NbtCompound::from_tape(&tape).unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let player = player();
+
+    let bytes = player.to_bytes("player");
+    assert_eq!(&bytes[..9], &[10, 0, 6, b'p', b'l', b'a', b'y', b'e', b'r']);
+    assert_eq!(parse(&bytes), player);
+
+    // The same, but without the name
+    let network = player.to_network_bytes();
+    assert_eq!(network[0], 10);
+    assert_eq!(&network[1..], &bytes[9..]);
+
+    let gzip = player.to_gzip("player").unwrap();
+    assert_eq!(&gzip[..2], &[0x1F, 0x8B]);
+    assert_eq!(NbtCompound::from_bytes(&gzip).unwrap(), player);
+}
+
+#[test]
+fn edit() {
+    let mut player = player();
+
+    assert_eq!(player.insert("Health", 10.0f32), Some(NbtValue::Float(20.0)));
+    assert_eq!(player.get("Health").and_then(NbtValue::as_float), Some(10.0));
+    // Replaced values keep their position
+    assert_eq!(player.iter().nth(1).unwrap().0, "Health");
+
+    assert_eq!(player.remove("Name"), Some(NbtValue::String("ferrumc_bot".to_string())));
+    assert!(!player.contains_key("Name"));
+    assert_eq!(player.remove("Name"), None);
+
+    player.insert("XpLevel", 30);
+    assert_eq!(player.iter().last().unwrap().0, "XpLevel");
+    assert_eq!(player.len(), 8);
+}
+
+#[test]
+fn paths() {
+    let mut player = player();
+
+    assert_eq!(player.get_path("Pos.1").and_then(NbtValue::as_double), Some(64.0));
+    assert_eq!(
+        player.get_path("Inventory.1.id").and_then(NbtValue::as_str),
+        Some("minecraft:dirt")
+    );
+    assert!(player.get_path("Inventory.2.id").is_none());
+    assert!(player.get_path("Inventory.first").is_none());
+    assert!(player.get_path("Name.length").is_none());
+    assert!(player.get_path("Missing").is_none());
+
+    *player.get_path_mut("Inventory.0.Slot").unwrap() = NbtValue::Byte(8);
+    player
+        .get_path_mut("Inventory.1")
+        .and_then(NbtValue::as_compound_mut)
+        .unwrap()
+        .insert("Count", 64i8);
+
+    let player = parse(&player.to_bytes(""));
+    assert_eq!(player.get_path("Inventory.0.Slot"), Some(&NbtValue::Byte(8)));
+    assert_eq!(player.get_path("Inventory.1.Count"), Some(&NbtValue::Byte(64)));
+}
+
+#[test]
+fn derived_struct_fields() {
+    #[derive(NBTSerialize, NBTDeserialize, Debug, PartialEq)]
+    struct Entity {
+        id: String,
+        data: NbtCompound,
+        extra: NbtValue,
+    }
+
+    let entity = Entity {
+        id: "minecraft:pig".to_string(),
+        data: [("Saddle", true)].into_iter().collect(),
+        extra: NbtValue::IntArray(vec![1, -1]),
+    };
+
+    let bytes = entity.serialize_with_header();
+    assert_eq!(Entity::from_bytes(&bytes).unwrap(), entity);
+
+    // The generic tree reads the same data
+    let tree = parse(&bytes);
+    assert_eq!(tree.get_path("data.Saddle"), Some(&NbtValue::Byte(1)));
+    assert_eq!(tree.get("extra"), Some(&entity.extra));
+}
+
+#[test]
+fn java_strings() {
+    // Java writes `𝔸` as two surrogates and NUL as `C0 80`
+    let mut data = vec![10, 0, 0, 8, 0, 1, b's', 0, 9];
+    data.extend_from_slice(&[0xED, 0xA0, 0xB5, 0xED, 0xB4, 0xB8, b'a', 0xC0, 0x80]);
+    data.push(0);
+
+    let compound = parse(&data);
+    assert_eq!(compound.get("s").and_then(NbtValue::as_str), Some("𝔸a\0"));
+}
@@ -0,0 +1,69 @@
+use ferrumc_net::bundle::MAX_BUNDLED_PACKETS;
+use ferrumc_net::connection::{ConnectionState, StreamWriter};
+use ferrumc_net::errors::NetError;
+use ferrumc_net::packets::generated::outgoing::{ChunkBatchStartPacket, SetCenterChunkPacket};
+use ferrumc_net_codec::encode::NetEncodeOpts;
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+
+/// A writer in the play state, and the stream its packets arrive on.
+async fn connect() -> (StreamWriter, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+    let (peer, _) = listener.accept().await.unwrap();
+
+    let (_, write_half) = stream.into_split();
+    let mut writer = StreamWriter::new(write_half);
+    writer.set_state(ConnectionState::Play);
+    (writer, peer)
+}
+
+#[tokio::test]
+async fn bundle_is_wrapped_in_delimiters() {
+    let (mut writer, mut peer) = connect().await;
+
+    writer
+        .send_bundle(|bundle| {
+            bundle.add(&ChunkBatchStartPacket {}, &NetEncodeOpts::WithLength)?;
+            bundle.add(&SetCenterChunkPacket { chunk_x: 1, chunk_z: 2 }, &NetEncodeOpts::WithLength)?;
+            assert_eq!(bundle.len(), 2);
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    // An empty bundle isn't sent at all
+    writer.send_bundle(|_| Ok(())).await.unwrap();
+    drop(writer);
+
+    let mut received = Vec::new();
+    peer.read_to_end(&mut received).await.unwrap();
+    assert_eq!(received, [1, 0x00, 1, 0x0D, 3, 0x54, 1, 2, 1, 0x00]);
+}
+
+#[tokio::test]
+async fn bundle_size_is_limited() {
+    let (mut writer, _peer) = connect().await;
+
+    let result = writer
+        .send_bundle(|bundle| {
+            for _ in 0..=MAX_BUNDLED_PACKETS {
+                bundle.add(&ChunkBatchStartPacket {}, &NetEncodeOpts::WithLength)?;
+            }
+            Ok(())
+        })
+        .await;
+    assert!(matches!(result, Err(NetError::BundleTooLarge(MAX_BUNDLED_PACKETS))));
+}
+
+#[tokio::test]
+async fn bundles_can_only_be_sent_while_playing() {
+    let (mut writer, _peer) = connect().await;
+    writer.set_state(ConnectionState::Configuration);
+
+    let result = writer.send_bundle(|_| Ok(())).await;
+    assert!(matches!(
+        result,
+        Err(NetError::WrongPacketState { packet: "BundleDelimiterPacket", .. })
+    ));
+}
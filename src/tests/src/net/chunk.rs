@@ -0,0 +1,275 @@
+use ferrumc_nbt::{Nbt, NbtCompound};
+use ferrumc_net::packets::outgoing::chunk_and_light_data::{ChunkDataAndUpdateLightPacket, SECTION_COUNT};
+use ferrumc_net::utils::chunk_cache::{ChunkCache, MAX_CACHED_CHUNKS};
+use ferrumc_net::utils::chunk_sender::{chunks_around, ChunkSender};
+use ferrumc_net::utils::chunk_tracker::{view_distance, ChunkTracker};
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::net_types::bit_set::BitSet;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use ferrumc_world::{Biome, FlatGenerator, GlobalPalette};
+use std::io::Cursor;
+
+fn read<T: NetDecode>(cursor: &mut Cursor<&[u8]>) -> T {
+    T::decode(cursor, &NetDecodeOpts::None).unwrap()
+}
+
+fn read_var_int(cursor: &mut Cursor<&[u8]>) -> i32 {
+    read::<VarInt>(cursor).val
+}
+
+/// A paletted container as the client reads it: the bits per entry, the palette and the data.
+fn read_container(cursor: &mut Cursor<&[u8]>) -> (u8, Vec<i32>, Vec<i64>) {
+    let bits = read::<u8>(cursor);
+    let palette = match bits {
+        0 => vec![read_var_int(cursor)],
+        // Direct, the data holds global ids.
+        _ if bits > 8 => vec![],
+        _ => (0..read_var_int(cursor)).map(|_| read_var_int(cursor)).collect(),
+    };
+    let data = (0..read_var_int(cursor)).map(|_| read::<i64>(cursor)).collect();
+    (bits, palette, data)
+}
+
+fn encode(packet: &ChunkDataAndUpdateLightPacket) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    packet.encode(&mut buffer, &NetEncodeOpts::None).unwrap();
+    buffer
+}
+
+#[test]
+fn chunk_data() {
+    let palette = GlobalPalette::built_in();
+    let chunk = FlatGenerator::default().generate(2, -3);
+    let encoded = encode(&ChunkDataAndUpdateLightPacket::new(&chunk, &palette));
+    let mut cursor = Cursor::new(encoded.as_slice());
+
+    assert_eq!(read::<u8>(&mut cursor), 0x27);
+    assert_eq!(read::<i32>(&mut cursor), 2);
+    assert_eq!(read::<i32>(&mut cursor), -3);
+
+    let heightmaps = read::<Nbt<NbtCompound>>(&mut cursor);
+    assert_eq!(heightmaps.len(), 2);
+    assert!(heightmaps.contains_key("MOTION_BLOCKING"));
+    assert!(heightmaps.contains_key("WORLD_SURFACE"));
+
+    let size = read_var_int(&mut cursor) as usize;
+    let start = cursor.position() as usize;
+    let plains = palette.biome_id(&Biome::new("minecraft:plains")) as i32;
+
+    // bedrock, dirt and grass
+    assert_eq!(read::<i16>(&mut cursor), 16 * 16 * 4);
+    let (bits, blocks, data) = read_container(&mut cursor);
+    assert_eq!(bits, 4);
+    assert_eq!(blocks, [0, 79, 10, 9]);
+    assert_eq!(data.len(), 256);
+    // The bottom layer is all bedrock.
+    assert_eq!(data[0], 0x1111_1111_1111_1111);
+    assert_eq!(read_container(&mut cursor), (0, vec![plains], vec![]));
+
+    for _ in 1..SECTION_COUNT {
+        assert_eq!(read::<i16>(&mut cursor), 0);
+        assert_eq!(read_container(&mut cursor), (0, vec![0], vec![]));
+        assert_eq!(read_container(&mut cursor), (0, vec![plains], vec![]));
+    }
+    assert_eq!(cursor.position() as usize - start, size);
+
+    // No block entities
+    assert_eq!(read_var_int(&mut cursor), 0);
+
+    // The section below the world is dark, the 25 above it have sky light.
+    let sky_light_mask = read::<BitSet>(&mut cursor);
+    assert_eq!(sky_light_mask.data, [0b11_1111_1111_1111_1111_1111_1110]);
+    assert!(read::<BitSet>(&mut cursor).is_empty());
+    let empty_sky_light_mask = read::<BitSet>(&mut cursor);
+    assert_eq!(empty_sky_light_mask.data, [0b1]);
+    assert!(read::<BitSet>(&mut cursor).is_empty());
+
+    assert_eq!(read_var_int(&mut cursor), 25);
+    for section in 0..25 {
+        assert_eq!(read_var_int(&mut cursor), 2048);
+        let light = (0..2048).map(|_| read::<u8>(&mut cursor)).collect::<Vec<_>>();
+        // Only the first 4 layers of the bottom section are in the ground.
+        let dark = if section == 0 { 4 * 128 } else { 0 };
+        assert!(light[..dark].iter().all(|level| *level == 0));
+        assert!(light[dark..].iter().all(|level| *level == 0xFF));
+    }
+    assert_eq!(read_var_int(&mut cursor), 0);
+    assert_eq!(cursor.position() as usize, encoded.len());
+}
+
+#[test]
+fn direct_palettes() {
+    let palette = GlobalPalette::built_in();
+    let mut chunk = FlatGenerator::default().generate(0, 0);
+
+    // More biomes than an indirect biome palette can have.
+    let biomes = chunk.sections[1].biomes.as_mut().unwrap();
+    biomes.set(0, 0, 0, Biome::new("minecraft:badlands")).unwrap();
+    biomes.set(1, 0, 0, Biome::new("minecraft:bamboo_jungle")).unwrap();
+    for i in 0..16 {
+        biomes.set(i % 4, 1, i / 4, Biome::new(format!("minecraft:unknown_{i}"))).unwrap();
+    }
+    assert!(biomes.bits_per_entry() > 3);
+
+    let encoded = encode(&ChunkDataAndUpdateLightPacket::new(&chunk, &palette));
+    let mut cursor = Cursor::new(encoded.as_slice());
+    cursor.set_position(1 + 4 + 4);
+    read::<Nbt<NbtCompound>>(&mut cursor);
+    read_var_int(&mut cursor);
+
+    read::<i16>(&mut cursor);
+    read_container(&mut cursor);
+    let bits = read::<u8>(&mut cursor);
+    assert_eq!(bits, palette.direct_biome_bits());
+    let data = (0..read_var_int(&mut cursor)).map(|_| read::<i64>(&mut cursor)).collect::<Vec<_>>();
+    assert_eq!(data.len(), 64usize.div_ceil(64 / bits as usize));
+
+    // Entry 0 is (0, 0, 0), badlands.
+    assert_eq!(data[0] & 0b11_1111, 0);
+    // Entry 1 is (1, 0, 0), bamboo jungle.
+    assert_eq!((data[0] >> 6) & 0b11_1111, 1);
+}
+
+#[tokio::test]
+async fn chunk_cache() {
+    let cache = ChunkCache::default();
+    let chunks = [(0, 0), (3, -4), (0, 0)];
+
+    let packets = cache.get_or_generate(chunks).await.unwrap();
+    assert_eq!(packets.len(), 2);
+    assert_eq!(cache.len(), 2);
+    for (x, z) in [(0, 0), (3, -4)] {
+        let chunk = FlatGenerator::default().generate(x, z);
+        let mut expected = Vec::new();
+        ChunkDataAndUpdateLightPacket::new(&chunk, GlobalPalette::get())
+            .encode(&mut expected, &NetEncodeOpts::WithLength)
+            .unwrap();
+        assert_eq!(packets[&(x, z)], expected);
+    }
+
+    // Cached chunks are the same buffer, not generated again
+    let again = cache.get_or_generate([(3, -4)]).await.unwrap();
+    assert_eq!(again[&(3, -4)].as_ptr(), packets[&(3, -4)].as_ptr());
+
+    // The oldest chunks are dropped first
+    let many = (0..MAX_CACHED_CHUNKS as i32).map(|x| (x, 1));
+    cache.get_or_generate(many).await.unwrap();
+    assert_eq!(cache.len(), MAX_CACHED_CHUNKS);
+    assert!(cache.get((0, 0)).is_none());
+    assert!(cache.get((0, 1)).is_some());
+}
+
+#[test]
+fn batches() {
+    let mut sender = ChunkSender::new();
+    sender.queue(chunks_around((0, 0), 10));
+
+    // 9 chunks at first, then nothing until the client acknowledges the batch.
+    let first = sender.next_batch();
+    assert_eq!(first.len(), 9);
+    assert_eq!(first[0], (0, 0));
+    assert!(sender.next_batch().is_empty());
+
+    sender.batch_received(2.5);
+    assert_eq!(sender.chunks_per_tick(), 2.5);
+    assert_eq!(sender.next_batch().len(), 2);
+    // Now up to 10 batches may be unacknowledged.
+    assert_eq!(sender.next_batch().len(), 2);
+
+    // Slow clients get a chunk every few ticks.
+    sender.batch_received(0.25);
+    sender.batch_received(0.25);
+    let sizes = (0..8).map(|_| sender.next_batch().len()).collect::<Vec<_>>();
+    assert_eq!(sizes, [1, 0, 0, 0, 1, 0, 0, 0]);
+
+    sender.batch_received(f32::NAN);
+    assert_eq!(sender.chunks_per_tick(), 0.01);
+    sender.batch_received(1000.0);
+    assert_eq!(sender.chunks_per_tick(), 64.0);
+}
+
+#[test]
+fn batches_in_flight() {
+    let mut sender = ChunkSender::new();
+    sender.queue(chunks_around((0, 0), 10));
+
+    let first = sender.next_batch();
+    assert_eq!(sender.in_flight().copied().collect::<Vec<_>>(), first);
+
+    sender.batch_received(2.0);
+    assert_eq!(sender.in_flight().count(), 0);
+
+    // Acknowledged oldest first
+    let second = sender.next_batch();
+    let third = sender.next_batch();
+    assert_eq!(sender.in_flight().count(), 4);
+    sender.batch_received(2.0);
+    assert_eq!(sender.in_flight().copied().collect::<Vec<_>>(), third);
+    assert!(sender.in_flight().all(|chunk| !second.contains(chunk)));
+    assert!(sender.pending().all(|chunk| !third.contains(chunk)));
+}
+
+#[test]
+fn nearest_first() {
+    let chunks = chunks_around((5, -5), 2);
+
+    assert_eq!(chunks[0], (5, -5));
+    // The 3x3 square and the 4 chunks 2 away in a straight line.
+    assert_eq!(chunks.len(), 13);
+    assert!(chunks[1..5].iter().all(|(x, z)| (x - 5).abs() + (z + 5).abs() == 1));
+    assert!(chunks.contains(&(7, -5)));
+    assert!(!chunks.contains(&(7, -4)));
+}
+
+#[test]
+fn sender_follows_the_player() {
+    let mut sender = ChunkSender::new();
+    sender.queue(chunks_around((0, 0), 2));
+
+    sender.retain(|(x, _)| *x >= 0);
+    sender.queue([(5, 0)]);
+    sender.sort_nearest_first((4, 0));
+
+    let pending = sender.pending().copied().collect::<Vec<_>>();
+    assert_eq!(pending.len(), 10);
+    assert_eq!(pending[0], (5, 0));
+    assert_eq!(pending[1], (2, 0));
+    assert!(pending.iter().all(|(x, _)| *x >= 0));
+}
+
+#[test]
+fn tracker() {
+    let mut tracker = ChunkTracker::new((0, 0), 2);
+    assert_eq!(tracker.chunks().count(), 13);
+
+    // Nothing changes within the same chunk.
+    assert!(tracker.update((0, 0), 2).is_none());
+
+    let changes = tracker.update((1, 0), 2).unwrap();
+    // Nearest first
+    assert_eq!(changes.load.len(), 5);
+    assert!(changes.load[..2].contains(&(2, 1)));
+    assert!(changes.load[..2].contains(&(2, -1)));
+    assert!(changes.load.contains(&(3, 0)));
+    assert_eq!(changes.unload.len(), 5);
+    assert!(changes.unload.contains(&(-2, 0)));
+    assert!(changes.unload.iter().all(|chunk| !tracker.contains(chunk)));
+    assert_eq!(tracker.center(), (1, 0));
+
+    // A smaller view distance only unloads.
+    let changes = tracker.update((1, 0), 1).unwrap();
+    assert!(changes.load.is_empty());
+    assert_eq!(changes.unload.len(), 8);
+    assert_eq!(tracker.chunks().count(), 5);
+}
+
+#[test]
+fn view_distances() {
+    // The server's is 10 by default.
+    assert_eq!(view_distance(None), 10);
+    assert_eq!(view_distance(Some(6)), 6);
+    assert_eq!(view_distance(Some(32)), 10);
+    // The client doesn't support less than 2.
+    assert_eq!(view_distance(Some(0)), 2);
+}
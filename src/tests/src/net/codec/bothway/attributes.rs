@@ -0,0 +1,164 @@
+use ferrumc_macros::{NetDecode, NetEncode};
+use ferrumc_net::connection::ProfileProperty;
+use ferrumc_net_codec::decode::errors::NetDecodeError;
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use std::fmt::Debug;
+use std::io::Write;
+
+fn encode<T: NetEncode>(value: &T) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    value.encode(&mut buffer, &NetEncodeOpts::None).unwrap();
+    assert_eq!(value.encoded_len(), buffer.len());
+    buffer
+}
+
+/// Encodes and decodes `value` both synchronously and asynchronously, and returns the encoded bytes.
+async fn round_trip<T: NetEncode + NetDecode + PartialEq + Debug>(value: T) -> Vec<u8> {
+    let encoded = encode(&value);
+
+    let mut reader = encoded.as_slice();
+    assert_eq!(T::decode(&mut reader, &NetDecodeOpts::None).unwrap(), value);
+    assert!(reader.is_empty(), "{} bytes left after decoding {:?}", reader.len(), value);
+
+    let mut reader = encoded.as_slice();
+    assert_eq!(T::decode_async(&mut reader, &NetDecodeOpts::None).await.unwrap(), value);
+    assert!(reader.is_empty());
+
+    encoded
+}
+
+#[derive(NetEncode, NetDecode, PartialEq, Debug)]
+struct VarInts {
+    #[net(varint)]
+    int: i32,
+    #[net(varint)]
+    size: usize,
+    #[net(varint)]
+    long: i64,
+}
+
+#[tokio::test]
+async fn varint() {
+    let encoded = round_trip(VarInts { int: 300, size: 1, long: -1 }).await;
+    assert_eq!(
+        encoded,
+        [0xAC, 0x02, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]
+    );
+}
+
+#[derive(NetEncode, NetDecode, PartialEq, Debug)]
+struct LengthPrefixed {
+    #[net(length_prefixed)]
+    names: Vec<String>,
+    #[net(length_prefixed)]
+    empty: Vec<u16>,
+}
+
+#[tokio::test]
+async fn length_prefixed() {
+    let value = LengthPrefixed {
+        names: vec!["a".to_string(), "bc".to_string()],
+        empty: vec![],
+    };
+    assert_eq!(round_trip(value).await, [2, 1, b'a', 2, b'b', b'c', 0]);
+}
+
+#[derive(NetEncode)]
+struct BorrowedLengthPrefixed<'a> {
+    #[net(length_prefixed)]
+    names: &'a [&'a str],
+}
+
+#[test]
+fn length_prefixed_slice() {
+    let encoded = encode(&BorrowedLengthPrefixed { names: &["a", "bc"] });
+    assert_eq!(encoded, [2, 1, b'a', 2, b'b', b'c']);
+}
+
+#[derive(NetEncode, NetDecode, PartialEq, Debug)]
+struct OptionalIf {
+    has_value: bool,
+    #[net(optional_if = "has_value")]
+    value: Option<u8>,
+    #[net(optional_if = "!has_value", varint)]
+    fallback: Option<i32>,
+}
+
+#[tokio::test]
+async fn optional_if() {
+    let present = OptionalIf { has_value: true, value: Some(7), fallback: None };
+    assert_eq!(round_trip(present).await, [1, 7]);
+
+    let absent = OptionalIf { has_value: false, value: None, fallback: Some(128) };
+    assert_eq!(round_trip(absent).await, [0, 0x80, 0x01]);
+}
+
+#[derive(NetEncode, NetDecode, PartialEq, Debug)]
+struct RestOfPacket {
+    channel: String,
+    #[net(rest_of_packet)]
+    data: Vec<u8>,
+}
+
+#[tokio::test]
+async fn rest_of_packet() {
+    let value = RestOfPacket { channel: "a".to_string(), data: vec![1, 2, 3] };
+    assert_eq!(round_trip(value).await, [1, b'a', 1, 2, 3]);
+
+    let empty = RestOfPacket { channel: "a".to_string(), data: vec![] };
+    assert_eq!(round_trip(empty).await, [1, b'a']);
+}
+
+#[derive(NetEncode, NetDecode, PartialEq, Debug)]
+#[net(varint)]
+#[repr(i32)]
+enum Tagged {
+    Empty,
+    Tuple(u8, String),
+    Named {
+        #[net(varint)]
+        count: i32,
+        #[net(length_prefixed)]
+        values: Vec<u8>,
+    },
+    Explicit = 200,
+    AfterExplicit,
+}
+
+#[tokio::test]
+async fn varint_tagged_enum() {
+    assert_eq!(round_trip(Tagged::Empty).await, [0]);
+    assert_eq!(round_trip(Tagged::Tuple(5, "a".to_string())).await, [1, 5, 1, b'a']);
+    assert_eq!(
+        round_trip(Tagged::Named { count: 128, values: vec![9] }).await,
+        [2, 0x80, 0x01, 1, 9]
+    );
+    assert_eq!(round_trip(Tagged::Explicit).await, [0xC8, 0x01]);
+    assert_eq!(round_trip(Tagged::AfterExplicit).await, [0xC9, 0x01]);
+
+    let mut reader = [3].as_slice();
+    assert!(matches!(
+        Tagged::decode(&mut reader, &NetDecodeOpts::None),
+        Err(NetDecodeError::InvalidEnumVariant)
+    ));
+}
+
+#[tokio::test]
+async fn profile_property() {
+    let signed = ProfileProperty {
+        name: "textures".to_string(),
+        value: "value".to_string(),
+        is_signed: true,
+        signature: Some("signature".to_string()),
+    };
+    round_trip(signed).await;
+
+    let unsigned = ProfileProperty {
+        name: "textures".to_string(),
+        value: "value".to_string(),
+        is_signed: false,
+        signature: None,
+    };
+    round_trip(unsigned).await;
+}
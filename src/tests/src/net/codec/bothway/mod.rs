@@ -1,6 +1,9 @@
 use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
 use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
 
+mod attributes;
+mod net_types;
+
 #[test]
 fn hashmaps() {
     let map = maplit::hashmap! {
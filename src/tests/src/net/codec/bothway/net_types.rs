@@ -0,0 +1,188 @@
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::net_types::angle::Angle;
+use ferrumc_net_codec::net_types::bit_set::{BitSet, FixedBitSet};
+use ferrumc_net_codec::net_types::identifier::Identifier;
+use ferrumc_net_codec::net_types::network_position::NetworkPosition;
+use ferrumc_net_codec::net_types::prefixed_optional::PrefixedOptional;
+use ferrumc_net_codec::net_types::uuid::Uuid;
+use ferrumc_net_codec::net_types::var_long::VarLong;
+use std::fmt::Debug;
+
+fn encode<T: NetEncode>(value: &T) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    value.encode(&mut buffer, &NetEncodeOpts::None).unwrap();
+    buffer
+}
+
+/// Encodes and decodes `value`, and makes sure all encoded bytes were consumed.
+fn round_trip<T: NetEncode + NetDecode + PartialEq + Debug>(value: T) -> Vec<u8> {
+    let encoded = encode(&value);
+
+    let mut reader = encoded.as_slice();
+    let decoded = T::decode(&mut reader, &NetDecodeOpts::None).unwrap();
+    assert!(reader.is_empty(), "{} bytes left after decoding {:?}", reader.len(), value);
+    assert_eq!(value, decoded);
+
+    encoded
+}
+
+#[test]
+fn var_long() {
+    assert_eq!(round_trip(VarLong::new(0)), [0x00]);
+    assert_eq!(round_trip(VarLong::new(127)), [0x7F]);
+    assert_eq!(round_trip(VarLong::new(128)), [0x80, 0x01]);
+    assert_eq!(round_trip(VarLong::new(2147483647)), [0xFF, 0xFF, 0xFF, 0xFF, 0x07]);
+    assert_eq!(
+        round_trip(VarLong::new(i64::MAX)),
+        [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F]
+    );
+    assert_eq!(
+        round_trip(VarLong::new(-1)),
+        [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]
+    );
+    assert_eq!(
+        round_trip(VarLong::new(i64::MIN)),
+        [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01]
+    );
+
+    for value in [0, 1, 300, -300, i64::from(i32::MAX) + 1, i64::MAX, i64::MIN] {
+        assert_eq!(VarLong::new(value).len, encode(&VarLong::new(value)).len());
+    }
+
+    // Too many continuation bits
+    let mut reader = [0xFF; 11].as_slice();
+    assert!(VarLong::decode(&mut reader, &NetDecodeOpts::None).is_err());
+}
+
+#[test]
+fn angle() {
+    assert_eq!(round_trip(Angle(0)), [0]);
+    assert_eq!(round_trip(Angle(255)), [255]);
+
+    assert_eq!(Angle::from_degrees(90.0), Angle(64));
+    assert_eq!(Angle::from_degrees(-90.0), Angle(192));
+    assert_eq!(Angle::from_degrees(360.0), Angle(0));
+    assert_eq!(Angle(128).to_degrees(), 180.0);
+}
+
+#[test]
+fn identifier() {
+    let stone = Identifier::minecraft("stone").unwrap();
+    let mut expected = vec![15];
+    expected.extend_from_slice(b"minecraft:stone");
+    assert_eq!(round_trip(stone.clone()), expected);
+
+    assert_eq!("stone".parse::<Identifier>().unwrap(), stone);
+    assert_eq!("minecraft:stone".parse::<Identifier>().unwrap(), stone);
+
+    let custom = round_trip(Identifier::new("ferrumc", "textures/block_1.png").unwrap());
+    assert!(custom.ends_with(b"ferrumc:textures/block_1.png"));
+
+    assert!("Minecraft:stone".parse::<Identifier>().is_err());
+    assert!("ferrumc/debug:x".parse::<Identifier>().is_err());
+    assert!(":stone".parse::<Identifier>().is_err());
+    assert!("minecraft:stone:slab".parse::<Identifier>().is_err());
+
+    // Invalid identifiers are rejected while decoding as well
+    let encoded = encode(&"minecraft:Stone".to_string());
+    assert!(Identifier::decode(&mut encoded.as_slice(), &NetDecodeOpts::None).is_err());
+}
+
+#[test]
+fn bit_set() {
+    let mut set = BitSet::new();
+    set.set(0, true);
+    set.set(65, true);
+    set.set(200, false);
+
+    assert!(set.get(0));
+    assert!(!set.get(1));
+    assert!(set.get(65));
+    assert!(!set.get(1000));
+    assert_eq!(set.count_ones(), 2);
+    assert_eq!(set.data.len(), 2);
+
+    let encoded = round_trip(set);
+    assert_eq!(encoded[0], 2);
+    assert_eq!(encoded.len(), 1 + 2 * 8);
+    assert_eq!(&encoded[1..9], &1u64.to_be_bytes());
+    assert_eq!(&encoded[9..17], &2u64.to_be_bytes());
+
+    assert_eq!(round_trip(BitSet::new()), [0]);
+}
+
+#[test]
+fn fixed_bit_set() {
+    let mut set = FixedBitSet::<20>::new();
+    set.set(0, true);
+    set.set(9, true);
+    set.set(19, true);
+
+    assert!(set.get(19));
+    assert!(!set.get(18));
+    assert_eq!(round_trip(set), [0b0000_0001, 0b0000_0010, 0b0000_1000]);
+
+    assert_eq!(round_trip(FixedBitSet::<8>::new()), [0]);
+    assert!(round_trip(FixedBitSet::<0>::new()).is_empty());
+
+    // Missing bytes
+    let mut reader = [0u8; 2].as_slice();
+    assert!(FixedBitSet::<20>::decode(&mut reader, &NetDecodeOpts::None).is_err());
+}
+
+#[test]
+fn uuid() {
+    let uuid = Uuid::from_u128(0x0123456789ABCDEF0123456789ABCDEF);
+    assert_eq!(round_trip(uuid), 0x0123456789ABCDEF0123456789ABCDEFu128.to_be_bytes());
+    assert_eq!(round_trip(Uuid::nil()), [0; 16]);
+}
+
+#[test]
+fn prefixed_optional() {
+    assert_eq!(round_trip(PrefixedOptional::<u16>::none()), [0]);
+    assert_eq!(round_trip(PrefixedOptional::some(0x1234u16)), [1, 0x12, 0x34]);
+    assert_eq!(
+        round_trip(PrefixedOptional::some("hi".to_string())),
+        [1, 2, b'h', b'i']
+    );
+}
+
+#[test]
+fn network_position() {
+    for (x, y, z) in [
+        (0, 0, 0),
+        (18357644, 831, -20882616),
+        (-1, -1, -1),
+        (33554431, 2047, 33554431),
+        (-33554432, -2048, -33554432),
+    ] {
+        let position = NetworkPosition::new(x, y, z);
+        let encoded = round_trip(position.clone());
+        assert_eq!(encoded, position.as_u64().to_be_bytes());
+    }
+
+    // The example from the protocol docs
+    #[allow(clippy::unusual_byte_groupings)]
+    let encoded = 0b01000110000001110110001100_10110000010101101101001000_001100111111u64;
+    let decoded = NetworkPosition::decode(&mut encoded.to_be_bytes().as_slice(), &NetDecodeOpts::None).unwrap();
+    assert_eq!(decoded, NetworkPosition::new(18357644, 831, -20882616));
+}
+
+#[tokio::test]
+async fn decode_async() {
+    async fn check<T: NetEncode + NetDecode + PartialEq + Debug>(value: T) {
+        let encoded = encode(&value);
+        let mut reader = encoded.as_slice();
+        assert_eq!(T::decode_async(&mut reader, &NetDecodeOpts::None).await.unwrap(), value);
+        assert!(reader.is_empty());
+    }
+
+    check(VarLong::new(i64::MIN)).await;
+    check(Angle(200)).await;
+    check(Identifier::minecraft("stone").unwrap()).await;
+    check(BitSet { data: vec![1, u64::MAX] }).await;
+    check(FixedBitSet::<20>::new()).await;
+    check(Uuid::from_u128(42)).await;
+    check(PrefixedOptional::some(NetworkPosition::new(-1, 2, -3))).await;
+}
@@ -1,5 +1,6 @@
-use ferrumc_macros::NetDecode;
+use ferrumc_macros::{NetDecode, NetEncode};
 use ferrumc_net_codec::decode::NetDecode;
+use std::io::Write;
 
 #[derive(NetDecode, Debug)]
 #[allow(dead_code)]
@@ -19,4 +20,103 @@ fn test_decode() {
     ).unwrap();
     
     println!("{:?}", example);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_decode_untrusted_lengths() {
+    use ferrumc_net_codec::decode::errors::NetDecodeError;
+    use ferrumc_net_codec::decode::NetDecodeOpts;
+
+    // A string claiming to be i32::MAX bytes long with only 3 bytes of data
+    let mut reader = std::io::Cursor::new(vec![0xFF, 0xFF, 0xFF, 0xFF, 0x07, b'a', b'b', b'c']);
+    assert!(String::decode(&mut reader, &NetDecodeOpts::None).is_err());
+
+    // Negative lengths
+    let mut reader = std::io::Cursor::new(vec![0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+    assert!(matches!(
+        String::decode(&mut reader, &NetDecodeOpts::None),
+        Err(NetDecodeError::InvalidLength(-1))
+    ));
+
+    let mut reader = std::io::Cursor::new(vec![0xFF, 0xFF, 0xFF, 0xFF, 0x07, 1, 2, 3]);
+    assert!(Vec::<u64>::decode(&mut reader, &NetDecodeOpts::IsSizePrefixed).is_err());
+}
+
+#[derive(NetDecode, NetEncode, Debug, PartialEq)]
+struct SomePluginMessage {
+    pub channel: String,
+    pub position: ferrumc_net_codec::net_types::network_position::NetworkPosition,
+    pub data: Vec<u8>,
+}
+
+#[tokio::test]
+async fn test_decode_async() {
+    use ferrumc_net_codec::decode::limited_reader::LimitedReader;
+    use ferrumc_net_codec::decode::NetDecodeOpts;
+    use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+    use ferrumc_net_codec::net_types::network_position::NetworkPosition;
+    use tokio::io::AsyncReadExt;
+
+    let message = SomePluginMessage {
+        channel: "ferrumc:test".to_string(),
+        position: NetworkPosition::new(-5, 64, 1000),
+        data: vec![1, 2, 3, 4],
+    };
+    let mut encoded = Vec::new();
+    message.encode(&mut encoded, &NetEncodeOpts::None).unwrap();
+    let length = encoded.len();
+    // The next packet on the stream
+    encoded.extend_from_slice(&[0xAA, 0xBB]);
+
+    // Without a limit the trailing data ends up in the message
+    let decoded = SomePluginMessage::decode_async(&mut encoded.as_slice(), &NetDecodeOpts::None).await.unwrap();
+    assert_eq!(decoded.data, [1, 2, 3, 4, 0xAA, 0xBB]);
+
+    let (mut client, mut server) = tokio::io::duplex(16);
+    let sent = encoded.clone();
+    tokio::spawn(async move {
+        tokio::io::AsyncWriteExt::write_all(&mut client, &sent).await.unwrap();
+    });
+
+    let mut packet = LimitedReader::new(&mut server, length);
+    let decoded = SomePluginMessage::decode_async(&mut packet, &NetDecodeOpts::None).await.unwrap();
+    assert_eq!(decoded, message);
+    assert!(packet.is_exhausted());
+    assert_eq!(server.read_u16().await.unwrap(), 0xAABB);
+
+    // The sync decoder gives the same result
+    let mut reader = LimitedReader::new(encoded.as_slice(), length);
+    assert_eq!(SomePluginMessage::decode(&mut reader, &NetDecodeOpts::None).unwrap(), message);
+
+    // Skipping the rest of a packet leaves the reader at the next one
+    let mut reader = encoded.as_slice();
+    let mut packet = LimitedReader::new(&mut reader, length);
+    assert_eq!(String::decode(&mut packet, &NetDecodeOpts::None).unwrap(), "ferrumc:test");
+    packet.skip_remaining().unwrap();
+    assert_eq!(reader, [0xAA, 0xBB]);
+
+    let mut reader = encoded.as_slice();
+    let mut packet = LimitedReader::new(&mut reader, length);
+    assert_eq!(String::decode_async(&mut packet, &NetDecodeOpts::None).await.unwrap(), "ferrumc:test");
+    packet.skip_remaining_async().await.unwrap();
+    assert_eq!(reader, [0xAA, 0xBB]);
+
+    // Strings can't be read past the limit
+    let mut packet = LimitedReader::new(encoded.as_slice(), 5);
+    assert!(String::decode_async(&mut packet, &NetDecodeOpts::None).await.is_err());
+    let mut rest = Vec::new();
+    assert_eq!(std::io::Read::read_to_end(&mut LimitedReader::new(encoded.as_slice(), 5), &mut rest).unwrap(), 5);
+
+    // Skipping fails if the input ends before the limit
+    let mut packet = LimitedReader::new(&[1u8, 2, 3][..], 10);
+    assert!(packet.skip_remaining_async().await.is_err());
+}
+
+#[test]
+fn test_decode_floats() {
+    use ferrumc_net_codec::decode::NetDecodeOpts;
+
+    let mut reader = std::io::Cursor::new([1e9f64.to_be_bytes().as_slice(), &1.5f32.to_be_bytes()].concat());
+    assert_eq!(f64::decode(&mut reader, &NetDecodeOpts::None).unwrap(), 1e9);
+    assert_eq!(f32::decode(&mut reader, &NetDecodeOpts::None).unwrap(), 1.5);
+}
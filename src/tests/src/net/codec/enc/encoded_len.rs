@@ -0,0 +1,87 @@
+use ferrumc_macros::NetEncode;
+use ferrumc_net::connection::GameProfile;
+use ferrumc_net::packets::outgoing::disconnect::{DisconnectPacket, LoginDisconnect, PlayDisconnect};
+use ferrumc_net::packets::outgoing::keep_alive::KeepAlivePacket;
+use ferrumc_net::packets::outgoing::login_play::LoginPlayPacket;
+use ferrumc_net::packets::outgoing::player_info_update::{PlayerInfo, PlayerInfoUpdatePacket};
+use ferrumc_net::packets::outgoing::synchronize_player_position::SynchronizePlayerPositionPacket;
+use ferrumc_net::packets::outgoing::tick_times::TickTimes;
+use ferrumc_net::packets::outgoing::update_time::UpdateTimePacket;
+use ferrumc_net_codec::encode::pool::BufferPool;
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use ferrumc_net_codec::net_types::var_int::VarInt;
+
+#[derive(NetEncode)]
+enum SomeEnum {
+    Unit,
+    Named { id: VarInt, name: String },
+    Unnamed(i64, Option<u8>),
+}
+
+/// Checks `encoded_len` against what's actually written, with and without a length prefix.
+fn check<T: NetEncode>(value: &T) {
+    let mut plain = Vec::new();
+    value.encode(&mut plain, &NetEncodeOpts::None).unwrap();
+    assert_eq!(value.encoded_len(), plain.len());
+
+    let mut with_length = Vec::new();
+    value.encode(&mut with_length, &NetEncodeOpts::WithLength).unwrap();
+
+    let mut expected = Vec::new();
+    VarInt::from(plain.len()).write(&mut expected).unwrap();
+    expected.extend_from_slice(&plain);
+    assert_eq!(with_length, expected);
+}
+
+#[test]
+fn test_encoded_len_of_packets() {
+    check(&UpdateTimePacket::new(30000, 30000 % 24000));
+    check(&KeepAlivePacket::new(-1));
+    check(&SynchronizePlayerPositionPacket::new(0.5, 64.0, -0.5, 90.0, 0.0, 0, VarInt::new(-1)));
+    check(&LoginPlayPacket::new(7));
+    check(&TickTimes::new(vec![50_000_000; 20]).into_packet());
+    check(&PlayerInfoUpdatePacket::new(vec![PlayerInfo::from(&GameProfile::new(42, "Steve".to_string()))]).unwrap());
+    check(&DisconnectPacket::Login(LoginDisconnect::new("Bye")));
+    check(&DisconnectPacket::Play(PlayDisconnect::new("Bye")));
+
+    check(&SomeEnum::Unit);
+    check(&SomeEnum::Named { id: VarInt::new(300), name: "a".repeat(200) });
+    check(&SomeEnum::Unnamed(1, Some(2)));
+    check(&SomeEnum::Unnamed(1, None));
+}
+
+#[test]
+fn test_encoded_len_of_var_ints() {
+    for value in [0, 1, 127, 128, 16383, 16384, i32::MAX, -1, i32::MIN] {
+        let mut buffer = Vec::new();
+        VarInt::new(value).write(&mut buffer).unwrap();
+        assert_eq!(VarInt::new(value).encoded_len(), buffer.len(), "{value}");
+        assert_eq!(VarInt::new(value).len, buffer.len(), "{value}");
+    }
+}
+
+#[test]
+fn test_buffer_pool() {
+    let pool = BufferPool::new();
+    let packet = UpdateTimePacket::new(1, 1);
+
+    let encoded = pool.encode(&packet, &NetEncodeOpts::WithLength).unwrap();
+    let mut expected = Vec::new();
+    packet.encode(&mut expected, &NetEncodeOpts::WithLength).unwrap();
+    assert_eq!(encoded.as_ref(), expected.as_slice());
+
+    // Still shared, so it can't be reused yet
+    let clone = encoded.clone();
+    pool.recycle(encoded);
+    assert!(pool.is_empty());
+
+    let address = clone.as_ptr();
+    pool.recycle(clone);
+    assert_eq!(pool.len(), 1);
+
+    // The next packet is written into the same memory
+    let encoded = pool.encode(&packet, &NetEncodeOpts::WithLength).unwrap();
+    assert_eq!(encoded.as_ptr(), address);
+    assert_eq!(encoded.as_ref(), expected.as_slice());
+    assert!(pool.is_empty());
+}
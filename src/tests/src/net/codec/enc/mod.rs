@@ -2,6 +2,7 @@ use ferrumc_macros::NetEncode;
 use ferrumc_net_codec::{encode::NetEncode, net_types::var_int::VarInt};
 use std::io::Write;
 
+mod encoded_len;
 mod enums;
 
 #[derive(NetEncode)]
@@ -0,0 +1,90 @@
+use ferrumc_net::connection::{ConnectionState, StreamWriter};
+use ferrumc_net::debug_packet;
+use ferrumc_net::errors::NetError;
+use ferrumc_net::packets::generated::incoming::SetHeldItemPacket;
+use ferrumc_net::packets::generated::outgoing::{ChunkBatchStartPacket, SetCenterChunkPacket};
+use ferrumc_net::packets::generated::{CLIENTBOUND_PACKETS, PROTOCOL_VERSION};
+use ferrumc_net::packets::outgoing::login_play::LoginPlayPacket;
+use ferrumc_net::packets::{OutgoingPacket, PacketInfo};
+use ferrumc_net_codec::decode::{NetDecode, NetDecodeOpts};
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use tokio::net::{TcpListener, TcpStream};
+
+fn encode<T: NetEncode>(packet: &T) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    packet.encode(&mut buffer, &NetEncodeOpts::None).unwrap();
+    buffer
+}
+
+#[test]
+fn protocol_version() {
+    assert_eq!(PROTOCOL_VERSION, 767);
+}
+
+#[test]
+fn generated_packets_encode_their_id() {
+    assert_eq!(encode(&ChunkBatchStartPacket {}), [0x0D]);
+    assert_eq!(
+        encode(&SetCenterChunkPacket { chunk_x: -1, chunk_z: 2 }),
+        [0x54, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x02]
+    );
+}
+
+#[test]
+fn generated_packets_round_trip() {
+    let packet = SetHeldItemPacket { slot: 4 };
+    let encoded = encode(&packet);
+
+    // The packet id is read by the connection before the packet itself is decoded.
+    assert_eq!(encoded[0], 0x2F);
+    let decoded = SetHeldItemPacket::decode(&mut &encoded[1..], &NetDecodeOpts::None).unwrap();
+    assert_eq!(packet, decoded);
+}
+
+#[test]
+fn registry_contains_generated_and_hand_written_packets() {
+    let generated = debug_packet(0x2F, &ConnectionState::Play, &mut [0, 4].as_slice()).unwrap();
+    assert!(generated.unwrap().contains("SetHeldItemPacket"));
+
+    let hand_written = debug_packet(0x18, &ConnectionState::Play, &mut 1i64.to_be_bytes().as_slice()).unwrap();
+    assert!(hand_written.unwrap().contains("ServerBoundKeepAlive"));
+
+    assert!(debug_packet(0x2F, &ConnectionState::Login, &mut [0, 4].as_slice()).unwrap().is_none());
+}
+
+#[test]
+fn outgoing_packets_know_their_state() {
+    assert_eq!(<LoginPlayPacket as OutgoingPacket>::ID, 0x2B);
+    assert_eq!(<LoginPlayPacket as OutgoingPacket>::STATE, ConnectionState::Play);
+    assert_eq!(<SetCenterChunkPacket as OutgoingPacket>::NAME, "SetCenterChunkPacket");
+    assert_eq!(<SetCenterChunkPacket as OutgoingPacket>::STATE, ConnectionState::Play);
+
+    assert!(CLIENTBOUND_PACKETS.contains(&PacketInfo {
+        name: "LoginSuccessPacket",
+        id: 0x02,
+        state: ConnectionState::Login,
+    }));
+    assert!(CLIENTBOUND_PACKETS.contains(&PacketInfo {
+        name: "ChunkBatchStartPacket",
+        id: 0x0D,
+        state: ConnectionState::Play,
+    }));
+}
+
+#[tokio::test]
+async fn send_packet_checks_the_state() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+    let (_, write_half) = stream.into_split();
+    let mut writer = StreamWriter::new(write_half);
+
+    let packet = SetCenterChunkPacket { chunk_x: 0, chunk_z: 0 };
+    let result = writer.send_packet(&packet, &NetEncodeOpts::WithLength).await;
+    assert!(matches!(
+        result,
+        Err(NetError::WrongPacketState { packet: "SetCenterChunkPacket", .. })
+    ));
+
+    writer.set_state(ConnectionState::Play);
+    writer.send_packet(&packet, &NetEncodeOpts::WithLength).await.unwrap();
+}
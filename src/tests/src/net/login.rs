@@ -0,0 +1,438 @@
+use ferrumc::systems::console_system::run_command;
+use ferrumc::systems::definition::start_all_systems;
+use ferrumc_client::{Client, ReceivedPacket};
+use ferrumc_ecs::Universe;
+use ferrumc_net::capture::{start_capturing, stop_capturing, CaptureFilter, CaptureReader, PacketDirection};
+use ferrumc_net::connection::{ConnectionState, StreamWriter};
+use ferrumc_core::identity::player_identity::PlayerIdentity;
+use ferrumc_net_codec::encode::NetEncodeOpts;
+use ferrumc_net::packets::incoming::chat_message::ChatMessagePacket;
+use ferrumc_net::packets::incoming::set_player_position::SetPlayerPositionPacket;
+use ferrumc_net::packets::incoming::set_player_position_and_rotation::SetPlayerPositionAndRotationPacket;
+use ferrumc_net::packets::outgoing::synchronize_player_position::SynchronizePlayerPositionPacket;
+use ferrumc_net::packets::outgoing::keep_alive::KeepAlivePacket;
+use ferrumc_net::packets::outgoing::update_time::UpdateTimePacket;
+use ferrumc_net::packets::generated::outgoing::{ChunkBatchFinishedPacket, SetCenterChunkPacket, UnloadChunkPacket};
+use ferrumc_net::packets::incoming::chunk_batch_received::ChunkBatchReceivedPacket;
+use ferrumc_net::packets::incoming::server_bound_plugin_message::ServerBoundPluginMessage;
+use ferrumc_net::utils::chunk_sender::chunks_around;
+use ferrumc_net::errors::NetError;
+use ferrumc_net::{GlobalState, ServerState};
+use ferrumc_net_codec::net_types::bit_set::FixedBitSet;
+use ferrumc_net_codec::net_types::prefixed_optional::PrefixedOptional;
+use ferrumc_net_codec::net_types::var_int::VarInt;
+use std::collections::HashSet;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Starts a server on an ephemeral port, it runs until the test's runtime shuts down.
+async fn start_server() -> (SocketAddr, GlobalState) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let state = Arc::new(ServerState {
+        universe: Universe::new(),
+        tcp_listener: listener,
+    });
+    tokio::spawn(start_all_systems(Arc::clone(&state)));
+
+    (address, state)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_login_flow() {
+    let (address, _) = start_server().await;
+
+    let mut client = Client::connect(address).await.unwrap();
+    assert_eq!(client.state(), ConnectionState::Handshaking);
+
+    let profile = client.login("ferrumc_bot").await.unwrap();
+    assert_eq!(client.state(), ConnectionState::Play);
+    assert_eq!(profile.username, "ferrumc_bot");
+    assert_eq!(client.profile(), Some(&profile));
+
+    // The server sends a keep alive right after the player joined, the client answers it on its own.
+    let keep_alive = client.await_packet(0x26).await.unwrap();
+    assert!(keep_alive.decode::<KeepAlivePacket>().is_ok());
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_concurrent_logins() {
+    let (address, _) = start_server().await;
+
+    let clients = (0..4).map(|i| {
+        tokio::spawn(async move {
+            Client::join(address, &format!("bot_{i}")).await
+        })
+    }).collect::<Vec<_>>();
+
+    for (i, client) in clients.into_iter().enumerate() {
+        let client = client.await.unwrap().unwrap();
+        assert_eq!(client.profile().unwrap().username, format!("bot_{i}"));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_walk_and_chat() {
+    let (address, _) = start_server().await;
+    let mut client = Client::join(address, "walker").await.unwrap();
+    let spawn = client.spawn().unwrap();
+
+    client.send(&SetPlayerPositionPacket {
+        x: spawn.x + 0.2,
+        feet_y: spawn.y,
+        z: spawn.z,
+        on_ground: true,
+    }).await.unwrap();
+    client.send(&ChatMessagePacket {
+        message: "hello".to_string(),
+        timestamp: 0,
+        salt: 0,
+        signature: PrefixedOptional::none(),
+        message_count: VarInt::new(0),
+        acknowledged: FixedBitSet::new(),
+    }).await.unwrap();
+
+    // Still connected and receiving the time updates sent every tick
+    client.await_packet(0x64).await.unwrap();
+    client.await_packet(0x64).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invalid_move_with_rotation() {
+    let (address, _) = start_server().await;
+    let mut client = Client::join(address, "teleporter").await.unwrap();
+    let spawn = client.spawn().unwrap();
+    // Gives the server a tick to handle the confirmation of the spawn teleport.
+    client.await_packet(0x64).await.unwrap();
+
+    client.send(&SetPlayerPositionAndRotationPacket {
+        x: 1e9,
+        feet_y: spawn.y,
+        z: spawn.z,
+        yaw: 90.0,
+        pitch: 0.0,
+        on_ground: true,
+    }).await.unwrap();
+
+    // Moved back to where it spawned
+    let synchronize = client.await_packet(0x40).await.unwrap();
+    let synchronize = synchronize.decode::<SynchronizePlayerPositionPacket>().unwrap();
+    assert_eq!((synchronize.x, synchronize.y, synchronize.z), (spawn.x, spawn.y, spawn.z));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_initial_chunks() {
+    let (address, _) = start_server().await;
+    let mut client = Client::join(address, "explorer").await.unwrap();
+
+    let center = client.await_packet(0x54).await.unwrap().decode::<SetCenterChunkPacket>().unwrap();
+    assert_eq!((center.chunk_x, center.chunk_z), (0, 0));
+
+    // The first batch is small, the next ones are sized by what the client asks for.
+    for (chunks_per_tick, expected) in [(64.0, 9), (64.0, 64), (0.0, 64)] {
+        client.await_packet(0x0D).await.unwrap();
+        let mut chunks = 0;
+        let finished = loop {
+            let packet = client.recv().await.unwrap();
+            match packet.id {
+                0x27 => chunks += 1,
+                0x0C => break packet.decode::<ChunkBatchFinishedPacket>().unwrap(),
+                _ => {}
+            }
+        };
+        assert_eq!(chunks, expected);
+        assert_eq!(finished.batch_size, expected);
+
+        client.send(&ChunkBatchReceivedPacket { chunks_per_tick }).await.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_chunks_follow_the_player() {
+    let (address, _) = start_server().await;
+    let mut client = Client::join(address, "wanderer").await.unwrap();
+    let spawn = client.spawn().unwrap();
+
+    // Everything within the view distance of 10 has to be sent before it can be unloaded.
+    let mut chunks = 0;
+    while chunks < chunks_around((0, 0), 10).len() {
+        let packet = client.recv().await.unwrap();
+        match packet.id {
+            0x27 => chunks += 1,
+            0x0C => client.send(&ChunkBatchReceivedPacket { chunks_per_tick: 64.0 }).await.unwrap(),
+            _ => {}
+        }
+    }
+
+    // Into chunk (-1, 0)
+    client.send(&SetPlayerPositionPacket {
+        x: spawn.x - 0.5,
+        feet_y: spawn.y,
+        z: spawn.z,
+        on_ground: true,
+    }).await.unwrap();
+
+    let center = loop {
+        let packet = client.recv().await.unwrap();
+        // Acknowledges the last batch, which may still arrive.
+        if packet.id == 0x0C {
+            client.send(&ChunkBatchReceivedPacket { chunks_per_tick: 64.0 }).await.unwrap();
+        }
+        if packet.id == 0x54 {
+            break packet.decode::<SetCenterChunkPacket>().unwrap();
+        }
+    };
+    assert_eq!((center.chunk_x, center.chunk_z), (-1, 0));
+
+    // The edge at x >= 0 goes out of view, the one at x <= -2 comes into view.
+    let visible = chunks_around((-1, 0), 10);
+    let mut unloaded = Vec::new();
+    let mut loaded = Vec::new();
+    loop {
+        let packet = client.recv().await.unwrap();
+        match packet.id {
+            0x21 => {
+                let unload = packet.decode::<UnloadChunkPacket>().unwrap();
+                unloaded.push((unload.chunk_x, unload.chunk_z));
+            }
+            0x27 => {
+                let chunk_x = i32::from_be_bytes(packet.data[0..4].try_into().unwrap());
+                let chunk_z = i32::from_be_bytes(packet.data[4..8].try_into().unwrap());
+                loaded.push((chunk_x, chunk_z));
+            }
+            0x0C => break,
+            _ => {}
+        }
+    }
+
+    assert_eq!(unloaded.len(), 21);
+    assert!(unloaded.iter().all(|chunk| !visible.contains(chunk)));
+    assert_eq!(loaded.len(), 21);
+    let distance = |(x, z): &(i32, i32)| (x + 1).pow(2) + z.pow(2);
+    assert!(loaded.is_sorted_by_key(distance));
+    assert!(loaded.iter().all(|chunk| visible.contains(chunk)));
+}
+
+/// Applies the chunk packets to what the client has loaded and where its view is centered.
+fn track_chunks(packet: &ReceivedPacket, loaded: &mut HashSet<(i32, i32)>, center: &mut Option<(i32, i32)>) {
+    match packet.id {
+        0x27 => {
+            let chunk_x = i32::from_be_bytes(packet.data[0..4].try_into().unwrap());
+            let chunk_z = i32::from_be_bytes(packet.data[4..8].try_into().unwrap());
+            loaded.insert((chunk_x, chunk_z));
+        }
+        0x21 => {
+            let unload = packet.decode::<UnloadChunkPacket>().unwrap();
+            loaded.remove(&(unload.chunk_x, unload.chunk_z));
+        }
+        0x54 => {
+            let packet = packet.decode::<SetCenterChunkPacket>().unwrap();
+            *center = Some((packet.chunk_x, packet.chunk_z));
+        }
+        _ => {}
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_chunks_stay_in_order_while_moving() {
+    let (address, _) = start_server().await;
+    let mut client = Client::join(address, "pacer").await.unwrap();
+    let spawn = client.spawn().unwrap();
+
+    // Back and forth between chunk (0, 0) and (-1, 0) while the first batches are still arriving,
+    // keeping track of what the client would have loaded.
+    let mut loaded = HashSet::new();
+    let mut center = None;
+    for step in 0..8 {
+        let x = if step % 2 == 0 { spawn.x - 0.5 } else { spawn.x + 0.25 };
+        client.send(&SetPlayerPositionPacket { x, feet_y: spawn.y, z: spawn.z, on_ground: true }).await.unwrap();
+
+        // A couple of ticks, acknowledging batches as they finish.
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(120);
+        while let Ok(packet) = tokio::time::timeout_at(deadline, client.recv()).await {
+            let packet = packet.unwrap();
+            if packet.id == 0x0C {
+                client.send(&ChunkBatchReceivedPacket { chunks_per_tick: 4.0 }).await.unwrap();
+            }
+            track_chunks(&packet, &mut loaded, &mut center);
+        }
+    }
+
+    // Then standing still in (-1, 0) until everything is sent.
+    client.send(&SetPlayerPositionPacket { x: spawn.x - 0.5, feet_y: spawn.y, z: spawn.z, on_ground: true }).await.unwrap();
+    while let Ok(packet) = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            let packet = client.recv().await.unwrap();
+            if packet.id != 0x64 {
+                break packet;
+            }
+        }
+    })
+    .await
+    {
+        if packet.id == 0x0C {
+            client.send(&ChunkBatchReceivedPacket { chunks_per_tick: 64.0 }).await.unwrap();
+        }
+        track_chunks(&packet, &mut loaded, &mut center);
+    }
+
+    assert_eq!(center, Some((-1, 0)));
+    assert_eq!(loaded, chunks_around((-1, 0), 10).into_iter().collect::<HashSet<_>>());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reconfiguration() {
+    let (address, state) = start_server().await;
+    let mut client = Client::join(address, "reconfigured").await.unwrap();
+
+    let entity = state
+        .universe
+        .query::<&PlayerIdentity>()
+        .into_entities()
+        .into_iter()
+        .find(|entity| state.universe.get::<PlayerIdentity>(*entity).unwrap().username == "reconfigured")
+        .unwrap();
+
+    {
+        let mut writer = state.universe.get_mut::<StreamWriter>(entity).unwrap();
+        writer.start_reconfiguration().await.unwrap();
+
+        // No play packets until the client acknowledged it
+        assert!(!writer.is_playing());
+        let time = UpdateTimePacket::new(0, 0);
+        assert!(matches!(
+            writer.send_packet(&time, &NetEncodeOpts::WithLength).await,
+            Err(NetError::InvalidState(_))
+        ));
+        assert!(matches!(writer.start_reconfiguration().await, Err(NetError::InvalidState(_))));
+    }
+
+    client.await_reconfiguration().await.unwrap();
+    assert_eq!(client.state(), ConnectionState::Play);
+    assert_eq!(*state.universe.get::<StreamWriter>(entity).unwrap().state(), ConnectionState::Play);
+    assert!(state.universe.get::<StreamWriter>(entity).unwrap().is_playing());
+
+    // Still playing after the configuration
+    client.await_packet(0x64).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_kick_during_configuration() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+    let (mut peer, _) = listener.accept().await.unwrap();
+
+    let mut writer = StreamWriter::new(stream.into_split().1);
+    // There's no disconnect packet while handshaking
+    assert!(matches!(
+        writer.kick("bye").await,
+        Err(NetError::InvalidState(_))
+    ));
+
+    writer.set_state(ConnectionState::Configuration);
+    writer.kick("bye").await.unwrap();
+    drop(writer);
+
+    let mut received = Vec::new();
+    peer.read_to_end(&mut received).await.unwrap();
+    assert_eq!(received[0] as usize, received.len() - 1);
+    assert_eq!(received[1], 0x02);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_capture_at_runtime() {
+    let (address, state) = start_server().await;
+    let mut client = Client::join(address, "captured").await.unwrap();
+    let spawn = client.spawn().unwrap();
+
+    let entity = state
+        .universe
+        .query::<&PlayerIdentity>()
+        .into_entities()
+        .into_iter()
+        .find(|entity| state.universe.get::<PlayerIdentity>(*entity).unwrap().username == "captured")
+        .unwrap();
+
+    let paths = start_capturing(&state, CaptureFilter::Entity(entity));
+    assert_eq!(paths.len(), 1);
+    // Already captured
+    assert!(start_capturing(&state, CaptureFilter::Entity(entity)).is_empty());
+
+    client.send(&SetPlayerPositionPacket {
+        x: spawn.x,
+        feet_y: spawn.y,
+        z: spawn.z,
+        on_ground: true,
+    }).await.unwrap();
+    client.await_packet(0x64).await.unwrap();
+    client.await_packet(0x64).await.unwrap();
+
+    assert_eq!(stop_capturing(&state, CaptureFilter::Entity(entity)), paths);
+
+    // The file is written in the background
+    let mut packets = Vec::new();
+    for _ in 0..50 {
+        let reader = CaptureReader::new(BufReader::new(std::fs::File::open(&paths[0]).unwrap())).unwrap();
+        packets = reader.filter_map(Result::ok).collect::<Vec<_>>();
+        if packets.iter().any(|packet| packet.direction == PacketDirection::Inbound) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    std::fs::remove_file(&paths[0]).unwrap();
+
+    assert!(packets.iter().all(|packet| packet.state == ConnectionState::Play));
+    assert!(packets.iter().any(|packet| packet.direction == PacketDirection::Inbound && packet.packet_id == 0x1A));
+    assert!(packets.iter().any(|packet| packet.direction == PacketDirection::Outbound && packet.packet_id == 0x64));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_capture_from_console() {
+    let (address, state) = start_server().await;
+    let _client = Client::join(address, "console").await.unwrap();
+
+    let entity = state
+        .universe
+        .query::<&PlayerIdentity>()
+        .into_entities()
+        .into_iter()
+        .find(|entity| state.universe.get::<PlayerIdentity>(*entity).unwrap().username == "console")
+        .unwrap();
+
+    let started = run_command(&state, &format!("capture start {entity}"));
+    assert!(started.starts_with("Started capturing 1 connection(s)"), "{started}");
+    assert!(state.universe.get::<StreamWriter>(entity).unwrap().capture.is_some());
+
+    let stopped = run_command(&state, &format!("capture stop {entity}"));
+    assert!(stopped.starts_with("Stopped capturing 1 connection(s)"), "{stopped}");
+    assert!(state.universe.get::<StreamWriter>(entity).unwrap().capture.is_none());
+
+    let path = stopped.split('"').nth(1).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert!(run_command(&state, "capture start somebody").starts_with("Unknown connection"));
+    assert!(run_command(&state, "capture").starts_with("Commands:"));
+}
+
+#[test]
+fn test_registered_channels() {
+    let message = |data: &[u8]| ServerBoundPluginMessage {
+        channel: "minecraft:register".to_string(),
+        data: data.to_vec(),
+    };
+
+    let valid = message(b"fabric:registry/sync\0ferrumc:tick_times");
+    assert_eq!(valid.registered_channels().collect::<Vec<_>>(), ["fabric:registry/sync", "ferrumc:tick_times"]);
+
+    // A name that isn't UTF-8 doesn't hide the others
+    let invalid = message(b"mod:\xFF\xFE\0ferrumc:tick_times\0");
+    assert_eq!(invalid.registered_channels().collect::<Vec<_>>(), ["ferrumc:tick_times"]);
+    assert_eq!(message(b"").registered_channels().count(), 0);
+}
@@ -1 +1,5 @@
-mod codec;
\ No newline at end of file
+mod bundle;
+mod chunk;
+mod codec;
+mod generated;
+mod login;mod writer;
@@ -0,0 +1,91 @@
+use ferrumc_net::connection::{StreamWriter, MAX_QUEUED_BYTES};
+use ferrumc_net::errors::NetError;
+use ferrumc_net::packets::outgoing::update_time::UpdateTimePacket;
+use ferrumc_net_codec::encode::pool::BufferPool;
+use ferrumc_net_codec::encode::{NetEncode, NetEncodeOpts};
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+
+const PIECE: usize = 1024 * 1024;
+
+/// A writer, and the stream its packets arrive on.
+async fn connect() -> (StreamWriter, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+    let (peer, _) = listener.accept().await.unwrap();
+
+    let (_, write_half) = stream.into_split();
+    (StreamWriter::new(write_half), peer)
+}
+
+#[tokio::test]
+async fn slow_clients_are_disconnected() {
+    let (mut writer, mut peer) = connect().await;
+    let closed = writer.closed();
+
+    // Nothing is read, so it all stays queued.
+    let piece = vec![0u8; PIECE];
+    let mut result = Ok(());
+    for _ in 0..=MAX_QUEUED_BYTES / PIECE {
+        result = writer.send_raw(&piece, &NetEncodeOpts::None).await;
+        if result.is_err() {
+            break;
+        }
+    }
+    assert!(matches!(result, Err(NetError::SendQueueFull(queued)) if queued > MAX_QUEUED_BYTES));
+    closed.notified().await;
+
+    // The client gets what was written so far and then the end of the stream.
+    let mut received = Vec::new();
+    peer.read_to_end(&mut received).await.unwrap();
+    assert!(received.len() <= MAX_QUEUED_BYTES);
+    assert!(writer.send_raw(&piece, &NetEncodeOpts::None).await.is_err());
+}
+
+#[tokio::test]
+async fn clients_that_keep_up_stay_connected() {
+    let (mut writer, mut peer) = connect().await;
+    let reader = tokio::spawn(async move {
+        let mut received = Vec::new();
+        peer.read_to_end(&mut received).await.unwrap();
+        received.len()
+    });
+
+    let piece = vec![0u8; PIECE];
+    let pieces = 2 * MAX_QUEUED_BYTES / PIECE;
+    for _ in 0..pieces {
+        writer.send_raw(&piece, &NetEncodeOpts::None).await.unwrap();
+        while writer.queued_bytes() > MAX_QUEUED_BYTES / 2 {
+            tokio::task::yield_now().await;
+        }
+    }
+    drop(writer);
+
+    assert_eq!(reader.await.unwrap(), pieces * PIECE);
+}
+
+#[tokio::test]
+async fn broadcasts_share_one_buffer() {
+    let packet = UpdateTimePacket::new(100, 200);
+    let mut expected = Vec::new();
+    packet.encode(&mut expected, &NetEncodeOpts::WithLength).unwrap();
+
+    let encoded = BufferPool::new().encode(&packet, &NetEncodeOpts::WithLength).unwrap();
+    let mut peers = Vec::new();
+    for _ in 0..3 {
+        let (mut writer, peer) = connect().await;
+        writer.send_bytes(encoded.clone()).await.unwrap();
+        peers.push((writer, peer));
+    }
+
+    for (writer, mut peer) in peers {
+        let mut received = vec![0; expected.len()];
+        peer.read_exact(&mut received).await.unwrap();
+        assert_eq!(received, expected);
+        while writer.queued_bytes() > 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+    // Every writer let go of its clone once it was written, without copying it first.
+    assert!(encoded.is_unique());
+}
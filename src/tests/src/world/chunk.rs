@@ -0,0 +1,158 @@
+use ferrumc_nbt::{NbtCompound, NbtValue};
+use ferrumc_world::errors::WorldError;
+use ferrumc_world::{BlockState, Chunk, Heightmap, LightArray, Palette};
+
+const CHUNK: &[u8] = include_bytes!("../../../../.etc/benches/chunk_0-0.nbt");
+
+/// Compounds don't have to be in the same order to be the same.
+fn same_value(a: &NbtValue, b: &NbtValue) -> bool {
+    match (a, b) {
+        (NbtValue::Compound(a), NbtValue::Compound(b)) => same_compound(a, b),
+        (NbtValue::List(a), NbtValue::List(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_value(a, b))
+        }
+        _ => a == b,
+    }
+}
+
+fn same_compound(a: &NbtCompound, b: &NbtCompound) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(key, value)| b.get(key).is_some_and(|other| same_value(value, other)))
+}
+
+#[test]
+fn parse() {
+    let chunk = Chunk::from_bytes(CHUNK).unwrap();
+
+    assert_eq!(chunk.data_version, 3465);
+    assert_eq!((chunk.x, chunk.z), (0, 32));
+    assert_eq!(chunk.min_section, -4);
+    assert_eq!(chunk.status, "minecraft:full");
+    assert_eq!(chunk.last_update, 12420);
+    assert_eq!(chunk.sections.len(), 25);
+    assert!(chunk.other.contains_key("block_ticks"));
+    assert!(chunk.heightmaps.motion_blocking.is_some());
+    assert!(chunk.heightmaps.world_surface_wg.is_none());
+
+    // Only has light
+    let below = &chunk.sections[0];
+    assert_eq!(below.y, -5);
+    assert!(below.block_states.is_none());
+    assert!(below.other.contains_key("starlight.skylight_state"));
+
+    let bottom = &chunk.sections[1];
+    assert_eq!(bottom.y, -4);
+    assert!(bottom.block_light.is_some());
+    let block_states = bottom.block_states.as_ref().unwrap();
+    assert!(matches!(block_states.palette(), Palette::Indirect(palette) if palette.len() == 10));
+    assert_eq!(block_states.bits_per_entry(), 4);
+    assert_eq!(block_states.data().len(), 256);
+    assert!(matches!(bottom.biomes.as_ref().unwrap().palette(), Palette::Single(_)));
+}
+
+#[test]
+fn blocks_match_the_nbt() {
+    let chunk = Chunk::from_bytes(CHUNK).unwrap();
+    let nbt = NbtCompound::from_bytes(CHUNK).unwrap();
+
+    for (i, section) in chunk.sections.iter().enumerate() {
+        let Some(block_states) = &section.block_states else {
+            continue;
+        };
+        let Palette::Indirect(palette) = block_states.palette() else {
+            continue;
+        };
+
+        let Some(NbtValue::LongArray(data)) = nbt.get_path(&format!("sections.{i}.block_states.data")) else {
+            panic!("section {i} has no data");
+        };
+        let bits = block_states.bits_per_entry() as usize;
+        let per_long = 64 / bits;
+
+        let ids = block_states.ids();
+        for (index, id) in ids.iter().enumerate() {
+            let expected = (data[index / per_long] as u64 >> (index % per_long * bits)) & ((1 << bits) - 1);
+            assert_eq!(u64::from(*id), expected);
+        }
+
+        for (x, y, z) in [(0, 0, 0), (15, 0, 0), (3, 7, 11), (15, 15, 15)] {
+            let id = ids[(y * 16 + z) * 16 + x] as usize;
+            let block_y = i32::from(section.y) * 16 + y as i32;
+            assert_eq!(chunk.get_block(x, block_y, z), Some(&palette[id]));
+        }
+    }
+
+    assert_eq!(chunk.get_block(0, -80, 0), None);
+    assert!(chunk.get_biome(0, 0, 0).is_some());
+
+    // Outside of the chunk
+    assert_eq!(chunk.get_block(16, 0, 0), None);
+    assert_eq!(chunk.get_block(0, 0, 16), None);
+    assert_eq!(chunk.get_biome(0, 0, 16), None);
+}
+
+#[test]
+fn round_trip() {
+    let chunk = Chunk::from_bytes(CHUNK).unwrap();
+    let bytes = chunk.to_bytes();
+
+    assert_eq!(Chunk::from_bytes(&bytes).unwrap(), chunk);
+    assert!(same_compound(
+        &NbtCompound::from_bytes(&bytes).unwrap(),
+        &NbtCompound::from_bytes(CHUNK).unwrap()
+    ));
+}
+
+#[test]
+fn set_blocks() {
+    let mut chunk = Chunk::from_bytes(CHUNK).unwrap();
+    let stairs = BlockState::new("minecraft:oak_stairs").with_property("facing", "north");
+
+    chunk.set_block(1, 70, 2, stairs.clone()).unwrap();
+    assert_eq!(chunk.get_block(1, 70, 2), Some(&stairs));
+    assert!(matches!(
+        chunk.set_block(16, 70, 2, stairs.clone()),
+        Err(WorldError::OutOfBounds { x: 16, z: 2 })
+    ));
+
+    let chunk = Chunk::from_bytes(&chunk.to_bytes()).unwrap();
+    assert_eq!(chunk.get_block(1, 70, 2), Some(&stairs));
+}
+
+#[test]
+fn heightmaps() {
+    let chunk = Chunk::from_bytes(CHUNK).unwrap();
+    let heightmap = chunk.heightmaps.motion_blocking.as_ref().unwrap();
+
+    assert_eq!(Heightmap::bits_for(384), 9);
+    let heights = heightmap.heights(384);
+    assert_eq!(heights.len(), 256);
+    assert!(heights.iter().all(|height| *height <= 384));
+
+    let heights: [u32; 256] = heights.try_into().unwrap();
+    assert_eq!(&Heightmap::from_heights(&heights, 384), heightmap);
+}
+
+#[test]
+fn light() {
+    let mut light = LightArray::new(15);
+    assert_eq!(light.get(3, 4, 5), 15);
+
+    light.set(3, 4, 5, 7);
+    light.set(4, 4, 5, 2);
+    assert_eq!(light.get(3, 4, 5), 7);
+    assert_eq!(light.get(4, 4, 5), 2);
+    assert_eq!(light.get(5, 4, 5), 15);
+}
+
+#[test]
+fn missing_fields() {
+    let mut nbt = NbtCompound::from_bytes(CHUNK).unwrap();
+    nbt.remove("xPos");
+
+    assert!(matches!(
+        Chunk::from_bytes(&nbt.to_bytes("")),
+        Err(WorldError::Nbt(ferrumc_nbt::NBTError::ElementNotFound("xPos")))
+    ));
+}
@@ -0,0 +1,41 @@
+use ferrumc_world::{BlockState, FlatGenerator, Heightmap, Palette};
+
+#[test]
+fn layers() {
+    let generator = FlatGenerator::default();
+    let chunk = generator.generate(3, -7);
+
+    assert_eq!((chunk.x, chunk.z), (3, -7));
+    assert_eq!(chunk.min_section, -4);
+    // 24 sections of blocks and one of light above and below.
+    assert_eq!(chunk.sections.len(), 26);
+    assert_eq!(generator.surface(), -60);
+
+    assert_eq!(chunk.get_block(0, -64, 0), Some(&BlockState::new("minecraft:bedrock")));
+    assert_eq!(chunk.get_block(5, -63, 9), Some(&BlockState::new("minecraft:dirt")));
+    assert_eq!(chunk.get_block(15, -62, 15), Some(&BlockState::new("minecraft:dirt")));
+    assert_eq!(
+        chunk.get_block(7, -61, 2),
+        Some(&BlockState::new("minecraft:grass_block").with_property("snowy", "false"))
+    );
+    assert_eq!(chunk.get_block(7, -60, 2), Some(&BlockState::air()));
+    assert!(matches!(
+        chunk.section(0).unwrap().block_states.as_ref().unwrap().palette(),
+        Palette::Single(block) if block.is_air()
+    ));
+
+    let heights = chunk.heightmaps.motion_blocking.as_ref().unwrap().heights(FlatGenerator::HEIGHT);
+    assert!(heights.iter().all(|height| *height == 4));
+    assert_eq!(Heightmap::bits_for(FlatGenerator::HEIGHT), 9);
+}
+
+#[test]
+fn sky_light() {
+    let chunk = FlatGenerator::default().generate(0, 0);
+
+    let bottom = chunk.section(-64).unwrap().sky_light.as_ref().unwrap();
+    assert_eq!(bottom.get(4, 3, 4), 0);
+    assert_eq!(bottom.get(4, 4, 4), 15);
+    assert!(chunk.sections[0].sky_light.as_ref().unwrap().is_empty());
+    assert_eq!(chunk.sections[25].sky_light.as_ref().unwrap().get(0, 0, 0), 15);
+}
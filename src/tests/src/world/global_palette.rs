@@ -0,0 +1,53 @@
+use ferrumc_world::{Biome, BlockState, GlobalPalette};
+
+const REPORT: &str = r#"{
+    "minecraft:air": { "states": [{ "id": 0, "default": true }] },
+    "minecraft:oak_log": {
+        "properties": { "axis": ["x", "y", "z"] },
+        "states": [
+            { "id": 130, "properties": { "axis": "x" } },
+            { "id": 131, "default": true, "properties": { "axis": "y" } },
+            { "id": 132, "properties": { "axis": "z" } }
+        ]
+    }
+}"#;
+
+#[test]
+fn blocks_report() {
+    let palette = GlobalPalette::from_blocks_report(REPORT).unwrap();
+    let log = BlockState::new("minecraft:oak_log");
+
+    assert_eq!(palette.block_id(&BlockState::air()), 0);
+    assert_eq!(palette.block_id(&log.clone().with_property("axis", "z")), 132);
+    // Unknown properties fall back to the default state.
+    assert_eq!(palette.block_id(&log.clone().with_property("axis", "w")), 131);
+    assert_eq!(palette.block_id(&log), 131);
+    assert_eq!(palette.block_id(&BlockState::new("minecraft:cave_air")), 0);
+    assert_eq!(palette.block_id(&BlockState::new("minecraft:diamond_block")), 1);
+
+    assert!(GlobalPalette::from_blocks_report("{").is_err());
+}
+
+#[test]
+fn built_in() {
+    let palette = GlobalPalette::built_in();
+
+    assert_eq!(palette.block_id(&BlockState::new("minecraft:bedrock")), 79);
+    assert_eq!(palette.block_id(&BlockState::new("minecraft:grass_block")), 9);
+    assert_eq!(
+        palette.block_id(&BlockState::new("minecraft:grass_block").with_property("snowy", "true")),
+        8
+    );
+}
+
+#[test]
+fn biomes() {
+    let palette = GlobalPalette::built_in();
+
+    // The biomes in the registry are sorted by name.
+    assert_eq!(palette.biome_id(&Biome::new("minecraft:badlands")), 0);
+    assert_eq!(palette.biome_id(&Biome::new("minecraft:bamboo_jungle")), 1);
+    assert_eq!(palette.biome_id(&Biome::new("minecraft:not_a_biome")), 0);
+    // 64 biomes
+    assert_eq!(palette.direct_biome_bits(), 6);
+}
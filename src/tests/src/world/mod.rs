@@ -0,0 +1,4 @@
+mod chunk;
+mod flat;
+mod global_palette;
+mod palette;
@@ -0,0 +1,82 @@
+use ferrumc_general_purpose::simd::packed;
+use ferrumc_world::errors::WorldError;
+use ferrumc_world::{Biome, BlockState, Palette, PalettedContainer};
+
+#[test]
+fn packing() {
+    for bits in 1..=32u8 {
+        let max = if bits == 32 { u32::MAX } else { (1 << bits) - 1 };
+        let values = (0..4096u32).map(|i| i.wrapping_mul(2_654_435_761) & max).collect::<Vec<_>>();
+
+        let packed = packed::pack_longs(&values, bits);
+        assert_eq!(packed.len(), packed::packed_len(values.len(), bits));
+        assert_eq!(packed::unpack_longs(&packed, bits, values.len()), values);
+    }
+
+    // 5 bits leaves 4 bits of every long unused, so 4096 values take 342 longs rather than 320.
+    assert_eq!(packed::packed_len(4096, 5), 342);
+}
+
+#[test]
+fn single_to_indirect() {
+    let mut container = PalettedContainer::new(BlockState::air());
+    assert_eq!(container.bits_per_entry(), 0);
+    assert_eq!(container.get(5, 5, 5), Some(&BlockState::air()));
+
+    container.set(5, 5, 5, BlockState::air()).unwrap();
+    assert!(matches!(container.palette(), Palette::Single(_)));
+
+    let stone = BlockState::new("minecraft:stone");
+    container.set(5, 5, 5, stone.clone()).unwrap();
+    assert_eq!(container.bits_per_entry(), 4);
+    assert_eq!(container.data().len(), 256);
+    assert_eq!(container.get(5, 5, 5), Some(&stone));
+    assert_eq!(container.get(5, 5, 6), Some(&BlockState::air()));
+}
+
+#[test]
+fn widening() {
+    let mut container = PalettedContainer::new(BlockState::air());
+    for i in 0..16 {
+        container.set(i, 0, 0, BlockState::new(format!("minecraft:block_{i}"))).unwrap();
+    }
+    // Air and 16 others don't fit in 4 bits.
+    assert_eq!(container.bits_per_entry(), 5);
+
+    for i in 0..16 {
+        assert_eq!(container.get(i, 0, 0), Some(&BlockState::new(format!("minecraft:block_{i}"))));
+    }
+    assert_eq!(container.get(0, 1, 0), Some(&BlockState::air()));
+}
+
+#[test]
+fn biomes() {
+    let mut container = PalettedContainer::new(Biome::new("minecraft:plains"));
+    container.set(3, 3, 3, Biome::new("minecraft:desert")).unwrap();
+
+    // Biomes can go down to 1 bit.
+    assert_eq!(container.bits_per_entry(), 1);
+    assert_eq!(container.data().len(), 1);
+    assert_eq!(container.get(3, 3, 3), Some(&Biome::new("minecraft:desert")));
+    assert_eq!(container.get(0, 3, 3), Some(&Biome::new("minecraft:plains")));
+}
+
+#[test]
+fn invalid() {
+    assert!(matches!(
+        PalettedContainer::indirect(vec![BlockState::air(), BlockState::new("minecraft:stone")], vec![0; 10]),
+        Err(WorldError::InvalidDataLength { expected: 256, found: 10 })
+    ));
+
+    for bits in [0, 33] {
+        assert!(matches!(
+            PalettedContainer::<BlockState>::direct(bits, vec![0; 1024]),
+            Err(WorldError::InvalidBitsPerEntry(found)) if found == bits
+        ));
+    }
+
+    let mut direct = PalettedContainer::<BlockState>::direct(15, vec![0; 1024]).unwrap();
+    assert_eq!(direct.get(0, 0, 0), None);
+    assert_eq!(direct.get_id(0, 0, 0), 0);
+    assert!(matches!(direct.set(0, 0, 0, BlockState::air()), Err(WorldError::DirectPalette)));
+}